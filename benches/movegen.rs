@@ -0,0 +1,51 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use mxchess::{Board, Color, Square};
+
+// A middlegame position with all piece types still on the board.
+fn middlegame() -> Board {
+    let mut board = Board::new();
+    let mut color = Color::White;
+
+    for mv in [
+        "e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "f8c5", "c2c3", "g8f6", "d2d4", "e5d4", "c3d4",
+        "c5b4",
+    ] {
+        let from = Square::from_ascii(&mv.as_bytes()[..2]).unwrap().index();
+        let to = Square::from_ascii(&mv.as_bytes()[2..]).unwrap().index();
+        board.perform_move_unchecked(board.get_legal_move(color, from, to, None).unwrap());
+        color = color.inv();
+    }
+
+    board
+}
+
+fn moves(c: &mut Criterion) {
+    let initial = Board::new();
+    let middlegame = middlegame();
+
+    c.bench_function("moves initial", |b| {
+        b.iter(|| black_box(&initial).moves(Color::White))
+    });
+    c.bench_function("moves middlegame", |b| {
+        b.iter(|| black_box(&middlegame).moves(Color::White))
+    });
+}
+
+fn check_attack(c: &mut Criterion) {
+    let middlegame = middlegame();
+
+    c.bench_function("check_attack middlegame", |b| {
+        b.iter(|| black_box(&middlegame).check_attack(Color::Black))
+    });
+}
+
+fn perft(c: &mut Criterion) {
+    let initial = Board::new();
+
+    c.bench_function("perft 3 initial", |b| {
+        b.iter(|| black_box(&initial).perft(Color::White, 3))
+    });
+}
+
+criterion_group!(benches, moves, check_attack, perft);
+criterion_main!(benches);
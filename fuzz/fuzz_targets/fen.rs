@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mxchess::Board;
+
+fuzz_target!(|fen: &str| {
+    if let Ok((board, color)) = Board::from_fen(fen) {
+        // Whatever parses has to survive the move generation and write back the same position.
+        for mv in board.moves(color) {
+            board.to_san(color, mv);
+        }
+        let fen = board.to_fen(color);
+        let (reparsed, reparsed_color) = Board::from_fen(&fen).unwrap();
+        assert_eq!(reparsed.to_fen(reparsed_color), fen);
+    }
+});
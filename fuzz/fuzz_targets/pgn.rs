@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mxchess::pgn;
+
+fuzz_target!(|pgn: &str| {
+    if let Ok(games) = pgn::parse(pgn) {
+        for game in games {
+            game.mainline();
+            // What's written has to be read back the same.
+            assert_eq!(pgn::parse(&game.to_string()), Ok(vec![game]));
+        }
+    }
+});
@@ -0,0 +1,22 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mxchess::Board;
+
+/// Positions with castling, en passant and promotions to parse the moves in.
+const POSITIONS: [&str; 4] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 b kq - 0 1",
+];
+
+fuzz_target!(|input: (u8, &str)| {
+    let (position, san) = input;
+    let (board, color) = Board::from_fen(POSITIONS[position as usize % POSITIONS.len()]).unwrap();
+    // A parsed move has to be legal and written back as something that parses to it again.
+    if let Some(mv) = board.parse_san(color, san) {
+        assert!(board.is_legal(color, mv), "{}", san);
+        assert_eq!(board.parse_san(color, &board.to_san(color, mv)), Some(mv));
+    }
+});
@@ -0,0 +1,336 @@
+// Game analysis: every move is searched together with the alternatives, and marked by how much
+// worse than the best move it was ("?!", "?", "??"), or how much better than the second best
+// ("!", and "!!" for sacrifices). The annotated PGN also has every move's evaluation in the
+// `[%eval ..]` comments that Lichess and ChessBase read. Whole PGN databases are analyzed a game
+// per thread, with the players' average centipawn losses.
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::{
+    pgn::{PgnGame, PgnMove},
+    Board, Bot, Color, Game, Move, PieceType, Score,
+};
+
+pub const DEFAULT_DEPTH: u32 = 4;
+/// The centipawns a move has to lose to be reported by `Game::blunder_check()` by default.
+pub const DEFAULT_BLUNDER_THRESHOLD: i32 = BLUNDER;
+
+/// How many centipawns a move may lose for each judgement.
+const INACCURACY: i32 = 50;
+const MISTAKE: i32 = 100;
+const BLUNDER: i32 = 300;
+/// How much better than the second best the best move has to be to be good, or brilliant when
+/// it sacrifices material.
+const GOOD: i32 = 150;
+const BRILLIANT: i32 = 300;
+/// Mates count as this many centipawns, so that losing a mate is a blunder rather than an
+/// overflow.
+const MATE_VALUE: i32 = 10_000;
+/// A move's loss counts at most this much towards the average, so that a missed mate doesn't
+/// outweigh a whole game of moves.
+const MAX_LOSS: i32 = 1000;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Judgement {
+    Brilliant,
+    Good,
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl Judgement {
+    /// The Numeric Annotation Glyph, e.g. 4 for "??".
+    pub fn nag(self) -> u8 {
+        match self {
+            Self::Good => 1,
+            Self::Mistake => 2,
+            Self::Brilliant => 3,
+            Self::Blunder => 4,
+            Self::Inaccuracy => 6,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Brilliant => "!!",
+            Self::Good => "!",
+            Self::Inaccuracy => "?!",
+            Self::Mistake => "?",
+            Self::Blunder => "??",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MoveAnalysis {
+    pub played: Move,
+    pub best: Move,
+    /// The values of the moves for the side that played them, in centipawns.
+    pub played_value: i32,
+    pub best_value: i32,
+    /// The moves to the mate that the played move leads to, negative if the side that played it
+    /// gets mated.
+    pub mate: Option<i32>,
+    /// `None` for ordinary and forced moves.
+    pub judgement: Option<Judgement>,
+}
+
+impl MoveAnalysis {
+    /// The evaluation after the move as a PGN comment command, in pawns for White, e.g.
+    /// "[%eval 0.43]", or "[%eval #-3]" when Black mates in 3. `color` played the move.
+    pub fn eval_comment(&self, color: Color) -> String {
+        let sign = match color {
+            Color::White => 1,
+            Color::Black => -1,
+        };
+        match self.mate {
+            Some(moves) => format!("[%eval #{}]", sign * moves),
+            None => format!("[%eval {:.2}]", (sign * self.played_value) as f64 / 100.0),
+        }
+    }
+}
+
+/// A move of `Game::blunder_check()` that lost against the best move.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Blunder {
+    /// The position before the move.
+    pub fen: String,
+    /// The index of the move in `Game::moves()`.
+    pub ply: usize,
+    pub played: Move,
+    pub best: Move,
+    /// How many centipawns worse than the best move the played move is.
+    pub loss: i32,
+}
+
+/// The centipawns that a side's moves lost against the best moves.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct CentipawnLoss {
+    pub total: i64,
+    pub moves: u32,
+}
+
+impl CentipawnLoss {
+    pub fn add(&mut self, other: Self) {
+        self.total += other.total;
+        self.moves += other.moves;
+    }
+
+    /// `None` without moves.
+    pub fn average(&self) -> Option<f64> {
+        (self.moves != 0).then(|| self.total as f64 / self.moves as f64)
+    }
+}
+
+fn material(ty: PieceType) -> i32 {
+    match ty {
+        PieceType::King => 0,
+        PieceType::Queen => 9,
+        PieceType::Rook => 5,
+        PieceType::Bishop | PieceType::Knight => 3,
+        PieceType::Pawn => 1,
+    }
+}
+
+/// Whether `mv` leaves a piece where the opponent can take it for less than it's worth.
+fn is_sacrifice(board: &Board, color: Color, mv: Move) -> bool {
+    let moved = match board.get_at(1 << mv.from) {
+        Some(piece) => material(piece.ty),
+        None => return false,
+    };
+    let captured = board
+        .get_at(1 << mv.to)
+        .map_or(0, |piece| material(piece.ty));
+    let mut after = *board;
+    after.perform_move_unchecked(mv);
+    captured < moved
+        && after
+            .moves(color.inv())
+            .iter()
+            .any(|reply| reply.to == mv.to)
+}
+
+/// The moves to the mate of a search value, see `bot::mated()`, negative when the side it's for
+/// gets mated.
+fn mate_moves(value: i32) -> Option<i32> {
+    match Score::from_value(value) {
+        Score::Mate(moves) => Some(moves),
+        Score::Cp(_) => None,
+    }
+}
+
+fn judge(board: &Board, color: Color, mv: Move, values: &[(Move, i32)]) -> Option<Judgement> {
+    let best = values[0].1;
+    let played = values.iter().find(|&&(other, _)| other == mv)?.1;
+    let loss = best - played;
+    if BLUNDER <= loss {
+        Some(Judgement::Blunder)
+    } else if MISTAKE <= loss {
+        Some(Judgement::Mistake)
+    } else if INACCURACY <= loss {
+        Some(Judgement::Inaccuracy)
+    } else if mv != values[0].0 {
+        None
+    } else if BRILLIANT <= best - values[1].1 && is_sacrifice(board, color, mv) {
+        Some(Judgement::Brilliant)
+    } else if GOOD <= best - values[1].1 {
+        Some(Judgement::Good)
+    } else {
+        None
+    }
+}
+
+impl Game {
+    /// Searches every position of the game to `depth` and judges the move played in it.
+    pub fn analyze(&self, depth: u32) -> Vec<MoveAnalysis> {
+        let bot = Bot::with_depth(depth);
+        let (board, turn) = self.start();
+        let mut replay = Game::from_position(board, turn);
+
+        let mut analysis = Vec::new();
+        for &mv in self.moves() {
+            let (board, color) = (*replay.board(), replay.turn());
+            let moves = replay.legal_moves();
+            let raw_values = bot.move_values(&board, color, replay.history(), &moves, depth);
+            let mate = moves
+                .iter()
+                .zip(&raw_values)
+                .find(|&(&other, _)| other == mv)
+                .and_then(|(_, &value)| mate_moves(value));
+            let mut values: Vec<_> = moves
+                .iter()
+                .copied()
+                .zip(raw_values)
+                .map(|(mv, value)| (mv, value.clamp(-MATE_VALUE, MATE_VALUE)))
+                .collect();
+            values.sort_by_key(|&(_, value)| -value);
+
+            let played_value = values
+                .iter()
+                .find(|&&(other, _)| other == mv)
+                .map_or(values[0].1, |&(_, value)| value);
+            analysis.push(MoveAnalysis {
+                played: mv,
+                best: values[0].0,
+                played_value,
+                best_value: values[0].1,
+                mate,
+                judgement: (2 <= values.len())
+                    .then(|| judge(&board, color, mv, &values))
+                    .flatten(),
+            });
+            replay.push_move(mv);
+        }
+        analysis
+    }
+
+    /// The moves that are at least `threshold` centipawns worse than the best move, searched to
+    /// `depth`. It only searches the best and the played move, so it's faster than `analyze()`.
+    pub fn blunder_check(&self, depth: u32, threshold: i32) -> Vec<Blunder> {
+        let bot = Bot::with_depth(depth);
+        let (board, turn) = self.start();
+        let mut replay = Game::from_position(board, turn);
+
+        let mut blunders = Vec::new();
+        for (ply, &mv) in self.moves().iter().enumerate() {
+            let (board, color) = (*replay.board(), replay.turn());
+            let best = bot.choose_move_with_history(&board, color, replay.history());
+            // A forced move leaves no value and no choice.
+            if let (Some(best), Some(best_value)) = (best, bot.value()) {
+                if best != mv {
+                    let played_value =
+                        bot.move_values(&board, color, replay.history(), &[mv], depth)[0];
+                    let loss = best_value.clamp(-MATE_VALUE, MATE_VALUE)
+                        - played_value.clamp(-MATE_VALUE, MATE_VALUE);
+                    if threshold <= loss {
+                        blunders.push(Blunder {
+                            fen: board.to_fen(color),
+                            ply,
+                            played: mv,
+                            best,
+                            loss,
+                        });
+                    }
+                }
+            }
+            replay.push_move(mv);
+        }
+        blunders
+    }
+
+    /// The game in PGN with the judgements of `analyze()` as NAGs, the evaluations as `[%eval ..]`
+    /// comments, and the best move in the comment after the inaccuracies, mistakes and blunders.
+    pub fn annotate(&self, depth: u32) -> PgnGame {
+        self.annotate_analysis(&self.analyze(depth))
+    }
+
+    /// Like `annotate()`, but with the game's analysis done already.
+    pub fn annotate_analysis(&self, analysis: &[MoveAnalysis]) -> PgnGame {
+        let mut pgn = PgnGame::from_game(self);
+        let (board, turn) = self.start();
+        let mut replay = Game::from_position(board, turn);
+
+        for (pgn_move, analysis) in pgn.moves.iter_mut().zip(analysis) {
+            let mut comment = analysis.eval_comment(replay.turn());
+            if let Some(judgement) = analysis.judgement {
+                pgn_move.nags.push(judgement.nag());
+                if analysis.played != analysis.best
+                    && matches!(
+                        judgement,
+                        Judgement::Inaccuracy | Judgement::Mistake | Judgement::Blunder
+                    )
+                {
+                    let best = replay.board().to_san(replay.turn(), analysis.best);
+                    comment = format!("{} {} was best", comment, best);
+                    pgn_move.variations.push(vec![PgnMove {
+                        san: best,
+                        ..PgnMove::default()
+                    }]);
+                }
+            }
+            pgn_move.comment = Some(comment);
+            replay.push_move(analysis.played);
+        }
+        pgn
+    }
+
+    /// The losses of White's and Black's moves in `analysis`, the game's.
+    pub fn centipawn_loss(&self, analysis: &[MoveAnalysis]) -> [CentipawnLoss; 2] {
+        let mut losses = [CentipawnLoss::default(); 2];
+        let mut color = self.start().1;
+        for analysis in analysis {
+            let loss = &mut losses[color as usize];
+            loss.total += (analysis.best_value - analysis.played_value).min(MAX_LOSS) as i64;
+            loss.moves += 1;
+            color = color.inv();
+        }
+        losses
+    }
+}
+
+/// Annotates every game like `Game::annotate()`, on `jobs` threads (0 for one per core), and
+/// returns the annotated games in order with White's and Black's losses.
+pub fn analyze_games(
+    games: &[Game],
+    depth: u32,
+    jobs: usize,
+) -> Vec<(PgnGame, [CentipawnLoss; 2])> {
+    let analyze = |game: &Game| {
+        let analysis = game.analyze(depth);
+        (
+            game.annotate_analysis(&analysis),
+            game.centipawn_loss(&analysis),
+        )
+    };
+
+    #[cfg(feature = "parallel")]
+    match rayon::ThreadPoolBuilder::new().num_threads(jobs).build() {
+        Ok(pool) => return pool.install(|| games.par_iter().map(analyze).collect()),
+        Err(err) => log::warn!("Couldn't start {} threads: {}", jobs, err),
+    }
+    #[cfg(not(feature = "parallel"))]
+    let _ = jobs;
+    games.iter().map(analyze).collect()
+}
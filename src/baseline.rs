@@ -0,0 +1,122 @@
+// Bots that hardly think, as sparring partners that any change to the real bot should beat and as
+// the easiest opponents for beginners.
+
+use crate::{
+    engine::{Engine, Score, SearchLimits, SearchResult},
+    rng::Rng,
+    Board, Color, Move, PieceType,
+};
+
+fn material(ty: PieceType) -> i32 {
+    match ty {
+        PieceType::King => 0,
+        PieceType::Queen => 9,
+        PieceType::Rook => 5,
+        PieceType::Bishop | PieceType::Knight => 3,
+        PieceType::Pawn => 1,
+    }
+}
+
+/// The moves to choose from: `limits.moves`, or all the legal ones. None on an invalid board.
+fn candidates(board: &Board, color: Color, limits: &SearchLimits) -> Vec<Move> {
+    match limits.moves {
+        _ if board.validate().is_err() => Vec::new(),
+        [] => board.moves(color),
+        moves => moves.to_vec(),
+    }
+}
+
+/// `color`'s material minus its opponent's, the Crazyhouse reserves included.
+fn material_balance(board: &Board, color: Color) -> i32 {
+    let side = |color: Color| {
+        let pieces = board.get_pieces(color);
+        [
+            PieceType::Queen,
+            PieceType::Rook,
+            PieceType::Bishop,
+            PieceType::Knight,
+            PieceType::Pawn,
+        ]
+        .iter()
+        .map(|&ty| {
+            (pieces.get(ty).count_ones() as i32 + board.reserve(color, ty) as i32) * material(ty)
+        })
+        .sum::<i32>()
+    };
+    side(color) - side(color.inv())
+}
+
+/// Plays a uniformly random legal move.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RandomBot {
+    rng: Rng,
+}
+
+impl RandomBot {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: Rng::new(seed),
+        }
+    }
+}
+
+impl Engine for RandomBot {
+    /// Chooses among `limits.moves` when there are any.
+    fn best_move(&mut self, board: &Board, color: Color, limits: &SearchLimits) -> SearchResult {
+        let moves = candidates(board, color, limits);
+        let best_move = (!moves.is_empty()).then(|| moves[self.rng.below(moves.len())]);
+        SearchResult {
+            best_move,
+            pv: best_move.into_iter().collect(),
+            ..SearchResult::default()
+        }
+    }
+}
+
+/// Plays the move that leaves it the most material, without looking at the replies. The ties,
+/// most moves of most positions, are broken at random.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct GreedyBot {
+    rng: Rng,
+}
+
+impl GreedyBot {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: Rng::new(seed),
+        }
+    }
+}
+
+impl Engine for GreedyBot {
+    /// Chooses among `limits.moves` when there are any, the value is the material the move wins
+    /// in centipawns.
+    fn best_move(&mut self, board: &Board, color: Color, limits: &SearchLimits) -> SearchResult {
+        let valued: Vec<_> = candidates(board, color, limits)
+            .into_iter()
+            .map(|mv| {
+                let mut after = *board;
+                after.perform_move_unchecked(mv);
+                (mv, material_balance(&after, color))
+            })
+            .collect();
+        let best = match valued.iter().map(|&(_, value)| value).max() {
+            Some(best) => best,
+            None => return SearchResult::default(),
+        };
+        let best_moves: Vec<_> = valued
+            .iter()
+            .filter(|&&(_, value)| value == best)
+            .map(|&(mv, _)| mv)
+            .collect();
+        let best_move = best_moves[self.rng.below(best_moves.len())];
+        SearchResult {
+            best_move: Some(best_move),
+            score: Some(Score::Cp(100 * (best - material_balance(board, color)))),
+            depth: 1,
+            nodes: valued.len() as u64,
+            pv: vec![best_move],
+            ..SearchResult::default()
+        }
+    }
+}
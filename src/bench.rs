@@ -0,0 +1,98 @@
+use crate::{bot::MoveOrderer, eval::Evaluator, Board, Bot, Color, Square};
+use std::time::Instant;
+
+pub const DEFAULT_DEPTH: u32 = 4;
+
+// Every position is a sequence of moves ("<from><to>") played from the initial position.
+const POSITIONS: &[&[&str]] = &[
+    &[],
+    &["e2e4", "e7e5", "g1f3", "b8c6", "f1b5", "a7a6"],
+    &["d2d4", "g8f6", "c2c4", "e7e6", "b1c3", "f8b4"],
+    &[
+        "e2e4", "c7c5", "g1f3", "d7d6", "d2d4", "c5d4", "f3d4", "g8f6", "b1c3", "a7a6",
+    ],
+    &[
+        "e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "f8c5", "c2c3", "g8f6", "d2d4", "e5d4", "c3d4",
+        "c5b4",
+    ],
+    &[
+        "d2d4", "d7d5", "c2c4", "e7e6", "b1c3", "g8f6", "c1g5", "f8e7", "e2e3", "b8d7", "g1f3",
+        "c7c6", "f1d3", "d5c4", "d3c4",
+    ],
+    &[
+        "e2e4", "e7e6", "d2d4", "d7d5", "e4d5", "e6d5", "g1f3", "g8f6", "f1d3", "f8d6", "d1e2",
+        "d8e7", "e2e7", "e8e7", "c1g5",
+    ],
+];
+
+fn position(moves: &[&str]) -> (Board, Color) {
+    let mut board = Board::new();
+    let mut color = Color::White;
+
+    for mv in moves {
+        let from = Square::from_ascii(&mv.as_bytes()[..2]).unwrap().index();
+        let to = Square::from_ascii(&mv.as_bytes()[2..]).unwrap().index();
+        let mv = board
+            .get_legal_move(color, from, to, None)
+            .expect("Illegal bench move");
+
+        board.perform_move_unchecked(mv);
+        color = color.inv();
+    }
+
+    (board, color)
+}
+
+/// The nodes of searching the positions to the bot's depth, which tell functional changes from
+/// mere speedups: only a change to what the search does changes them, like Stockfish's bench
+/// signature. The search leaves the transposition table out, the root moves share it across
+/// threads and the count would vary from run to run.
+pub fn signature<E: Evaluator + Sync, O: MoveOrderer + Sync>(bot: &mut Bot<E, O>) -> u64
+where
+    E::State: Sync,
+{
+    let table = bot.table.take();
+    let nodes = POSITIONS
+        .iter()
+        .map(|moves| {
+            let (board, color) = position(moves);
+            bot.choose_move(&board, color);
+            bot.nodes()
+        })
+        .sum();
+    bot.table = table;
+    nodes
+}
+
+/// Searches a fixed set of positions to the bot's depth and reports the node count and speed, how
+/// full the bot's transposition table ended up if it has one, and the `signature()`.
+pub fn run<E: Evaluator + Sync, O: MoveOrderer + Sync>(bot: &mut Bot<E, O>)
+where
+    E::State: Sync,
+{
+    let mut nodes = 0;
+    let start = Instant::now();
+    for (i, moves) in POSITIONS.iter().enumerate() {
+        let (board, color) = position(moves);
+
+        println!("Position: {}/{}", i + 1, POSITIONS.len());
+        if let Some(mv) = bot.choose_move(&board, color) {
+            mv.print(&board);
+        }
+        nodes += bot.nodes();
+    }
+    let elapsed = start.elapsed();
+
+    println!();
+    println!("===========================");
+    println!("Total time (ms) : {}", elapsed.as_millis());
+    println!("Nodes searched  : {}", nodes);
+    println!(
+        "Nodes/second    : {}",
+        (nodes as f64 / elapsed.as_secs_f64().max(1e-9)) as u64
+    );
+    if let Some(table) = &bot.table {
+        println!("Hash full (‰)   : {}", table.hashfull());
+    }
+    println!("Signature       : {}", signature(bot));
+}
@@ -1,4 +1,3 @@
-
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
 pub struct BitIterator(pub u64);
 
@@ -29,4 +28,3 @@ impl Iterator for BitIterator {
 //         println!("{:>1$b}", bit, x_str.len());
 //     }
 // }
-
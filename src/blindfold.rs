@@ -0,0 +1,163 @@
+// Blindfold training: games against the bot without seeing the board, the moves only announced in
+// SAN, and the coordinate drill that comes first, naming the squares marked on an empty board.
+
+use crate::{rng::Rng, Color, Engine, Game, GameStatus, SearchLimits, Square};
+use std::io::{self, BufRead, Write};
+
+/// The squares `coordinates` asks for when no number is given.
+pub const DEFAULT_DRILL_SQUARES: u32 = 20;
+
+/// Marks the square to name.
+const MARK: char = '\u{25CF}';
+
+fn read_line(input: &mut impl BufRead, buf: &mut String) -> io::Result<Option<String>> {
+    buf.clear();
+    match input.read_line(buf)? {
+        0 => Ok(None),
+        _ => Ok(Some(buf.trim().to_owned()).filter(|line| line != "quit")),
+    }
+}
+
+/// An empty board from `color`'s side without the coordinates, `square` marked. The rows and the
+/// squares are as `Board::render()` draws them.
+pub fn render_square(square: Square, color: Color) -> String {
+    let (rows, files): (Vec<u8>, Vec<u8>) = match color {
+        Color::White => ((0..64).step_by(8).rev().collect(), (0..8).collect()),
+        Color::Black => ((0..64).step_by(8).collect(), (0..8).rev().collect()),
+    };
+    let mut s = String::new();
+    for i in rows {
+        for j in files.iter().map(|file| i + file) {
+            s.push(' ');
+            s.push(match j {
+                _ if j == square.index() => MARK,
+                _ if (j ^ j >> 3) & 1 == 0 => '\u{25FC}',
+                _ => '\u{25FB}',
+            });
+        }
+        s.push('\n');
+    }
+    s
+}
+
+/// Asks for the names of `count` squares at random, from White's or Black's side at random too,
+/// and tells the right ones and the time it took. "quit" or the end of the input ends it early.
+pub fn drill(
+    count: u32,
+    rng: &mut Rng,
+    mut input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<()> {
+    let start = std::time::Instant::now();
+    let (mut right, mut asked) = (0, 0);
+    let mut buf = String::new();
+
+    for _ in 0..count {
+        let square = Square::from_index(rng.below(64) as u8).unwrap();
+        let color = if rng.below(2) == 0 {
+            Color::White
+        } else {
+            Color::Black
+        };
+        writeln!(output)?;
+        writeln!(output, "From {:?}'s side:", color)?;
+        write!(output, "{}", render_square(square, color))?;
+        write!(output, "The square: ")?;
+        output.flush()?;
+
+        let answer = match read_line(&mut input, &mut buf)? {
+            Some(answer) => answer,
+            None => break,
+        };
+        asked += 1;
+        if answer.parse() == Ok(square) {
+            right += 1;
+            writeln!(output, "Right")?;
+        } else {
+            writeln!(output, "No, it's {}", square)?;
+        }
+    }
+
+    writeln!(output)?;
+    writeln!(
+        output,
+        "{}/{} right in {:.1}s",
+        right,
+        asked,
+        start.elapsed().as_secs_f64()
+    )
+}
+
+/// Plays a game as White against `engine` without showing the board: the moves are typed and
+/// announced in SAN. "board" shows it after all, and the number of looks is told at the end.
+/// "quit" or the end of the input resigns.
+pub fn play(
+    engine: &mut dyn Engine,
+    mut input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<()> {
+    let mut game = Game::new();
+    let mut looks = 0;
+    let mut buf = String::new();
+
+    let result = loop {
+        match game.status() {
+            GameStatus::Ongoing => {}
+            GameStatus::Checkmate(Color::White) | GameStatus::VariantWin(Color::White) => {
+                break "You win";
+            }
+            GameStatus::Checkmate(Color::Black) | GameStatus::VariantWin(Color::Black) => {
+                break "You lose";
+            }
+            GameStatus::Stalemate => break "Stalemate, a draw",
+        }
+
+        let board = *game.board();
+        let mv = match game.turn() {
+            Color::White => {
+                write!(output, "{}. ", game.moves().len() / 2 + 1)?;
+                output.flush()?;
+                let line = match read_line(&mut input, &mut buf)? {
+                    Some(line) => line,
+                    None => break "You resign",
+                };
+                if line == "board" {
+                    looks += 1;
+                    write!(output, "{}", board.render(Color::White))?;
+                    continue;
+                }
+                match board.parse_san(Color::White, &line) {
+                    Some(mv) => mv,
+                    None => {
+                        writeln!(output, "Not a legal move in SAN: {}", line)?;
+                        continue;
+                    }
+                }
+            }
+            Color::Black => {
+                let limits = SearchLimits {
+                    history: game.history(),
+                    ..SearchLimits::default()
+                };
+                let mv = engine.best_move(&board, Color::Black, &limits).best_move;
+                match mv {
+                    Some(mv) => {
+                        writeln!(
+                            output,
+                            "{}... {}",
+                            game.moves().len() / 2 + 1,
+                            board.to_san(Color::Black, mv)
+                        )?;
+                        mv
+                    }
+                    None => break "Your opponent has no move",
+                }
+            }
+        };
+        game.push_move(mv);
+    };
+
+    writeln!(output, "{}", result)?;
+    writeln!(output, "Looks at the board: {}", looks)?;
+    write!(output, "{}", game.board().render(Color::White))
+}
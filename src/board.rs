@@ -1,5 +1,14 @@
-use crate::BitIterator;
+use crate::{
+    masks::{
+        BETWEEN, FILE_A, FILE_B, FILE_G, FILE_H, LIGHT_SQUARES, LINE, RANK_1, RANK_3, RANK_4,
+        RANK_5, RANK_8,
+    },
+    variant::HORDE_PAWNS,
+    BitIterator, MxChessError, Square, Variant,
+};
+use alloc::{format, string::String, vec::Vec};
 use bitflags::bitflags;
+use core::{convert::TryFrom, fmt, ops};
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
 #[repr(u8)]
@@ -27,6 +36,14 @@ pub enum MoveType {
     PawnKnightPromotion,
 
     Castle,
+
+    /// Drops put a piece from the reserve on an empty square, their `from` is the square of the
+    /// dropping side's king.
+    DropQueen,
+    DropRook,
+    DropBishop,
+    DropKnight,
+    DropPawn,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
@@ -46,16 +63,11 @@ pub struct Piece {
     pub ty: PieceType,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
-#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
 pub struct Pieces {
     pub all: u64,
-    pub king: u64,
-    pub queens: u64,
-    pub rooks: u64,
-    pub bishops: u64,
-    pub knights: u64,
-    pub pawns: u64,
+    /// Indexed by `PieceType as usize`.
+    boards: [u64; 6],
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
@@ -65,6 +77,11 @@ pub struct Board {
     pub black_pieces: Pieces,
     pub prev_move: Move,
     pub flags: ChessFlags,
+    pub variant: Variant,
+    /// The pieces in hand in Crazyhouse, indexed by `Color` and `PieceType`.
+    pub reserves: [[u8; 6]; 2],
+    /// The pieces that were pawns in Crazyhouse, they go back to the reserve as pawns.
+    pub promoted: u64,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
@@ -100,70 +117,271 @@ impl Color {
     }
 }
 
-impl From<u8> for PieceType {
+impl MoveType {
+    /// The move types, indexed by `MoveType as usize`.
+    pub const ALL: [Self; 18] = [
+        Self::King,
+        Self::Queen,
+        Self::Rook,
+        Self::Bishop,
+        Self::Knight,
+        Self::Pawn,
+        Self::PawnLeap,
+        Self::PawnEnPassant,
+        Self::PawnQueenPromotion,
+        Self::PawnRookPromotion,
+        Self::PawnBishopPromotion,
+        Self::PawnKnightPromotion,
+        Self::Castle,
+        Self::DropQueen,
+        Self::DropRook,
+        Self::DropBishop,
+        Self::DropKnight,
+        Self::DropPawn,
+    ];
+
+    /// The piece that a drop puts on the board.
     #[inline]
-    fn from(n: u8) -> Self {
-        match n {
-            0 => Self::King,
-            1 => Self::Queen,
-            2 => Self::Rook,
-            3 => Self::Bishop,
-            4 => Self::Knight,
-            5 => Self::Pawn,
-            _ => panic!("Failed to convert u8 to PieceType because it was out of the range 0..6"),
+    pub fn dropped(self) -> Option<PieceType> {
+        match self {
+            Self::DropQueen => Some(PieceType::Queen),
+            Self::DropRook => Some(PieceType::Rook),
+            Self::DropBishop => Some(PieceType::Bishop),
+            Self::DropKnight => Some(PieceType::Knight),
+            Self::DropPawn => Some(PieceType::Pawn),
+            _ => None,
+        }
+    }
+
+    /// The type of the piece on `to` after the move, a promotion's is the piece it promotes to.
+    pub fn piece(self) -> PieceType {
+        match self {
+            Self::King | Self::Castle => PieceType::King,
+            Self::Queen | Self::DropQueen | Self::PawnQueenPromotion => PieceType::Queen,
+            Self::Rook | Self::DropRook | Self::PawnRookPromotion => PieceType::Rook,
+            Self::Bishop | Self::DropBishop | Self::PawnBishopPromotion => PieceType::Bishop,
+            Self::Knight | Self::DropKnight | Self::PawnKnightPromotion => PieceType::Knight,
+            Self::Pawn | Self::PawnLeap | Self::PawnEnPassant | Self::DropPawn => PieceType::Pawn,
+        }
+    }
+
+    /// The drop of `ty`, kings can't be dropped.
+    pub fn drop(ty: PieceType) -> Option<Self> {
+        match ty {
+            PieceType::King => None,
+            PieceType::Queen => Some(Self::DropQueen),
+            PieceType::Rook => Some(Self::DropRook),
+            PieceType::Bishop => Some(Self::DropBishop),
+            PieceType::Knight => Some(Self::DropKnight),
+            PieceType::Pawn => Some(Self::DropPawn),
         }
     }
 }
 
-impl Pieces {
-    // TODO: Improve the `get()` & `get_mut()` methods for release.
-    //       Pointer arithmetic is ugly and it depends on the field order of `Self`
-    //       and the `PieceType` enum variant values.
+impl PieceType {
+    /// The piece types, indexed by `PieceType as usize`.
+    pub const ALL: [Self; 6] = [
+        Self::King,
+        Self::Queen,
+        Self::Rook,
+        Self::Bishop,
+        Self::Knight,
+        Self::Pawn,
+    ];
+}
+
+impl TryFrom<u8> for PieceType {
+    type Error = MxChessError;
 
     #[inline]
-    pub fn get(&self, ty: PieceType) -> u64 {
-        if cfg!(debug_assertions) {
-            match ty {
-                PieceType::King => self.king,
-                PieceType::Queen => self.queens,
-                PieceType::Rook => self.rooks,
-                PieceType::Bishop => self.bishops,
-                PieceType::Knight => self.knights,
-                PieceType::Pawn => self.pawns,
-            }
-        } else {
-            unsafe { *(&self.king as *const u64).add(ty as _) }
+    fn try_from(n: u8) -> Result<Self, Self::Error> {
+        Self::ALL
+            .get(n as usize)
+            .copied()
+            .ok_or(MxChessError::PieceType(n))
+    }
+}
+
+/// The first and last ranks, where pawns can't be dropped.
+const BACK_RANKS: u64 = RANK_1 | RANK_8;
+
+/// The most pieces of a type a Crazyhouse reserve can hold: all the pawns of both sides.
+pub const MAX_RESERVE: u8 = 16;
+
+/// One of the four castles: the right it needs, the king's and the rook's moves, the squares that
+/// have to be empty, and the squares the king passes that mustn't be attacked.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Castle {
+    pub color: Color,
+    pub right: ChessFlags,
+    pub king_from: u8,
+    pub king_to: u8,
+    pub rook_from: u8,
+    pub rook_to: u8,
+    pub empty: u64,
+    pub safe: u64,
+}
+
+/// The castles, each color's short one first.
+pub const CASTLES: [Castle; 4] = [
+    Castle {
+        color: Color::White,
+        right: ChessFlags::WHITE_KINGS_CASTLE,
+        king_from: 4,
+        king_to: 6,
+        rook_from: 7,
+        rook_to: 5,
+        empty: 0x60,
+        safe: 0x70,
+    },
+    Castle {
+        color: Color::White,
+        right: ChessFlags::WHITE_QUEENS_CASTLE,
+        king_from: 4,
+        king_to: 2,
+        rook_from: 0,
+        rook_to: 3,
+        empty: 0xe,
+        safe: 0x1c,
+    },
+    Castle {
+        color: Color::Black,
+        right: ChessFlags::BLACK_KINGS_CASTLE,
+        king_from: 0o74,
+        king_to: 0o76,
+        rook_from: 0o77,
+        rook_to: 0o75,
+        empty: 0x60 << 0o70,
+        safe: 0x70 << 0o70,
+    },
+    Castle {
+        color: Color::Black,
+        right: ChessFlags::BLACK_QUEENS_CASTLE,
+        king_from: 0o74,
+        king_to: 0o72,
+        rook_from: 0o70,
+        rook_to: 0o73,
+        empty: 0xe << 0o70,
+        safe: 0x1c << 0o70,
+    },
+];
+
+impl Castle {
+    /// The castle that takes the king to `to`.
+    pub fn to(to: u8) -> Option<&'static Self> {
+        CASTLES.iter().find(|castle| castle.king_to == to)
+    }
+}
+
+/// The move with every promotion piece if it's a (queen) promotion, else just the move.
+pub(crate) fn promotion_choices(mv: Move) -> impl Iterator<Item = Move> {
+    let choices = match mv.ty {
+        MoveType::PawnQueenPromotion => 4,
+        _ => 1,
+    };
+    IntoIterator::into_iter([
+        mv.ty,
+        MoveType::PawnRookPromotion,
+        MoveType::PawnBishopPromotion,
+        MoveType::PawnKnightPromotion,
+    ])
+    .take(choices)
+    .map(move |ty| Move { ty, ..mv })
+}
+
+pub(crate) fn knight_attacks(knights: u64) -> u64 {
+    (knights << 0o21 | knights >> 0o17) & !FILE_A
+        | (knights << 0o17 | knights >> 0o21) & !FILE_H
+        | (knights << 0o12 | knights >> 6) & !(FILE_A | FILE_B)
+        | (knights << 6 | knights >> 0o12) & !(FILE_G | FILE_H)
+}
+
+/// The squares attacked by sliders on `pieces` along the files and ranks (`straight`) or the
+/// diagonals, including the first occupied square of every ray.
+pub(crate) fn ray_attacks(pieces: u64, occupied: u64, straight: bool) -> u64 {
+    let steps: [fn(u64) -> u64; 4] = if straight {
+        [
+            |ray| ray << 1 & !FILE_A,
+            |ray| ray >> 1 & !FILE_H,
+            |ray| ray << 0o10,
+            |ray| ray >> 0o10,
+        ]
+    } else {
+        [
+            |ray| ray << 0o11 & !FILE_A,
+            |ray| ray << 7 & !FILE_H,
+            |ray| ray >> 7 & !FILE_A,
+            |ray| ray >> 0o11 & !FILE_H,
+        ]
+    };
+
+    let mut attack = 0;
+    for step in steps {
+        let mut ray = step(pieces);
+        while ray != 0 {
+            attack |= ray;
+            ray = step(ray & !occupied);
         }
     }
+    attack
+}
+
+impl Pieces {
+    #[inline]
+    pub fn get(&self, ty: PieceType) -> u64 {
+        self.boards[ty as usize]
+    }
 
     #[inline]
     pub fn get_mut(&mut self, ty: PieceType) -> &mut u64 {
-        if cfg!(debug_assertions) {
-            match ty {
-                PieceType::King => &mut self.king,
-                PieceType::Queen => &mut self.queens,
-                PieceType::Rook => &mut self.rooks,
-                PieceType::Bishop => &mut self.bishops,
-                PieceType::Knight => &mut self.knights,
-                PieceType::Pawn => &mut self.pawns,
-            }
-        } else {
-            unsafe { &mut *(&mut self.king as *mut u64).add(ty as _) }
-        }
+        &mut self.boards[ty as usize]
+    }
+
+    #[inline]
+    pub fn king(&self) -> u64 {
+        self.get(PieceType::King)
+    }
+
+    #[inline]
+    pub fn queens(&self) -> u64 {
+        self.get(PieceType::Queen)
+    }
+
+    #[inline]
+    pub fn rooks(&self) -> u64 {
+        self.get(PieceType::Rook)
+    }
+
+    #[inline]
+    pub fn bishops(&self) -> u64 {
+        self.get(PieceType::Bishop)
+    }
+
+    #[inline]
+    pub fn knights(&self) -> u64 {
+        self.get(PieceType::Knight)
+    }
+
+    #[inline]
+    pub fn pawns(&self) -> u64 {
+        self.get(PieceType::Pawn)
     }
 
     pub fn get_at(&self, bit_pos: u64) -> Option<PieceType> {
         if self.all & bit_pos != 0 {
-            Some(PieceType::from(
-                PieceType::King as u8 & 0u8.wrapping_sub((self.king & bit_pos != 0) as _)
-                    | PieceType::Queen as u8 & 0u8.wrapping_sub((self.queens & bit_pos != 0) as _)
-                    | PieceType::Rook as u8 & 0u8.wrapping_sub((self.rooks & bit_pos != 0) as _)
+            Some(
+                PieceType::ALL[(PieceType::King as u8
+                    & 0u8.wrapping_sub((self.king() & bit_pos != 0) as _)
+                    | PieceType::Queen as u8
+                        & 0u8.wrapping_sub((self.queens() & bit_pos != 0) as _)
+                    | PieceType::Rook as u8 & 0u8.wrapping_sub((self.rooks() & bit_pos != 0) as _)
                     | PieceType::Bishop as u8
-                        & 0u8.wrapping_sub((self.bishops & bit_pos != 0) as _)
+                        & 0u8.wrapping_sub((self.bishops() & bit_pos != 0) as _)
                     | PieceType::Knight as u8
-                        & 0u8.wrapping_sub((self.knights & bit_pos != 0) as _)
-                    | PieceType::Pawn as u8 & 0u8.wrapping_sub((self.pawns & bit_pos != 0) as _),
-            ))
+                        & 0u8.wrapping_sub((self.knights() & bit_pos != 0) as _)
+                    | PieceType::Pawn as u8 & 0u8.wrapping_sub((self.pawns() & bit_pos != 0) as _))
+                    as usize],
+            )
         } else {
             None
         }
@@ -173,12 +391,9 @@ impl Pieces {
     /// otherwise it's slower.
     pub fn clear_unchecked(&mut self, bit_pos: u64) {
         self.all &= !bit_pos;
-        self.king &= !bit_pos;
-        self.queens &= !bit_pos;
-        self.rooks &= !bit_pos;
-        self.bishops &= !bit_pos;
-        self.knights &= !bit_pos;
-        self.pawns &= !bit_pos;
+        for board in &mut self.boards {
+            *board &= !bit_pos;
+        }
     }
 
     pub fn clear(&mut self, bit_pos: u64) -> bool {
@@ -194,23 +409,21 @@ impl Pieces {
 impl Board {
     pub fn new() -> Self {
         Self {
+            // King, queens, rooks, bishops, knights and pawns.
             white_pieces: Pieces {
-                pawns: 0xff00,
-                rooks: 0x0081,
-                knights: 0x0042,
-                bishops: 0x0024,
-                queens: 0x0008,
-                king: 0x0010,
                 all: 0xffff,
+                boards: [0x0010, 0x0008, 0x0081, 0x0024, 0x0042, 0xff00],
             },
             black_pieces: Pieces {
-                pawns: 0x00ff_0000_0000_0000,
-                rooks: 0x8100_0000_0000_0000,
-                knights: 0x4200_0000_0000_0000,
-                bishops: 0x2400_0000_0000_0000,
-                queens: 0x800_0000_0000_0000,
-                king: 0x1000_0000_0000_0000,
                 all: 0xffff_0000_0000_0000,
+                boards: [
+                    0x1000_0000_0000_0000,
+                    0x800_0000_0000_0000,
+                    0x8100_0000_0000_0000,
+                    0x2400_0000_0000_0000,
+                    0x4200_0000_0000_0000,
+                    0x00ff_0000_0000_0000,
+                ],
             },
             prev_move: Move {
                 from: 0o74,
@@ -218,9 +431,37 @@ impl Board {
                 ty: MoveType::King,
             },
             flags: ChessFlags::INIT,
+            variant: Variant::Standard,
+            reserves: [[0; 6]; 2],
+            promoted: 0,
+        }
+    }
+
+    /// The starting position of `variant`.
+    pub fn with_variant(variant: Variant) -> Self {
+        match variant {
+            Variant::Horde => Self {
+                white_pieces: Pieces {
+                    all: HORDE_PAWNS,
+                    boards: [0, 0, 0, 0, 0, HORDE_PAWNS],
+                },
+                flags: ChessFlags::BLACK_KINGS_CASTLE | ChessFlags::BLACK_QUEENS_CASTLE,
+                variant,
+                ..Self::new()
+            },
+            _ => Self {
+                variant,
+                ..Self::new()
+            },
         }
     }
 
+    /// The number of `ty` pieces in `color`'s reserve.
+    #[inline]
+    pub fn reserve(&self, color: Color, ty: PieceType) -> u8 {
+        self.reserves[color as usize][ty as usize]
+    }
+
     pub fn get_at(&self, bit_pos: u64) -> Option<Piece> {
         self.white_pieces
             .get_at(bit_pos)
@@ -244,14 +485,35 @@ impl Board {
         }
     }
 
+    /// The squares with a piece on them.
+    #[inline]
+    pub fn occupied(&self) -> u64 {
+        self.white_pieces.all | self.black_pieces.all
+    }
+
+    /// The squares without a piece on them.
+    #[inline]
+    pub fn empty(&self) -> u64 {
+        !self.occupied()
+    }
+
+    /// The squares with a piece of `color` on them.
+    #[inline]
+    pub fn occupied_by(&self, color: Color) -> u64 {
+        self.get_pieces(color).all
+    }
+
     pub fn clear(&mut self, bit_pos: u64) {
         if !self.white_pieces.clear(bit_pos) {
             self.black_pieces.clear(bit_pos);
         }
     }
 
+    /// Puts `piece` on the square, or empties it. The castling rights that the new placement
+    /// contradicts are dropped, but the board may end up invalid, see `validate()`.
     pub fn set(&mut self, bit_pos: u64, piece: Option<Piece>) {
         self.clear(bit_pos);
+        self.promoted &= !bit_pos;
 
         match piece {
             Some(Piece {
@@ -270,6 +532,20 @@ impl Board {
             }
             None => {}
         }
+        self.drop_contradicted_castling();
+    }
+
+    /// The squares whose pieces differ on `other`, as (square, the piece here, the piece there)
+    /// from a1 to h8, so a view of this board can be brought up to date with just those.
+    pub fn diff(&self, other: &Self) -> Vec<(Square, Option<Piece>, Option<Piece>)> {
+        let occupied = self.occupied() | other.occupied();
+        BitIterator(occupied)
+            .filter_map(|bit_pos| {
+                let (before, after) = (self.get_at(bit_pos), other.get_at(bit_pos));
+                let square = Square::of(bit_pos.trailing_zeros() as u8);
+                (before != after).then_some((square, before, after))
+            })
+            .collect()
     }
 
     pub fn check_attack(&self, color: Color) -> u64 {
@@ -279,33 +555,32 @@ impl Board {
 
         match color {
             Color::White => {
-                attack |= pieces.pawns << 0o11 & !0x101010101010101;
-                attack |= pieces.pawns << 7 & !0x8080808080808080;
+                attack |= pieces.pawns() << 0o11 & !FILE_A;
+                attack |= pieces.pawns() << 7 & !FILE_H;
             }
             Color::Black => {
-                attack |= pieces.pawns >> 0o11 & !0x8080808080808080;
-                attack |= pieces.pawns >> 7 & !0x101010101010101;
+                attack |= pieces.pawns() >> 0o11 & !FILE_H;
+                attack |= pieces.pawns() >> 7 & !FILE_A;
             }
         }
 
-        attack |= (pieces.king << 1 | pieces.king << 0o11 | pieces.king >> 7) & !0x101010101010101
-            | (pieces.king >> 1 | pieces.king >> 0o11 | pieces.king << 7) & !0x8080808080808080
-            | pieces.king << 0o10
-            | pieces.king >> 0o10;
+        attack |= (pieces.king() << 1 | pieces.king() << 0o11 | pieces.king() >> 7) & !FILE_A
+            | (pieces.king() >> 1 | pieces.king() >> 0o11 | pieces.king() << 7) & !FILE_H
+            | pieces.king() << 0o10
+            | pieces.king() >> 0o10;
 
         {
-            let all = (self.white_pieces.all | self.black_pieces.all)
-                & !self.get_pieces(color.inv()).king;
+            let all = self.occupied() & !self.get_pieces(color.inv()).king();
 
-            let mut move_r = (pieces.queens | pieces.rooks) << 1 & !0x101010101010101;
-            let mut move_l = (pieces.queens | pieces.rooks) >> 1 & !0x8080808080808080;
-            let mut move_u = (pieces.queens | pieces.rooks) << 0o10;
-            let mut move_d = (pieces.queens | pieces.rooks) >> 0o10;
+            let mut move_r = (pieces.queens() | pieces.rooks()) << 1 & !FILE_A;
+            let mut move_l = (pieces.queens() | pieces.rooks()) >> 1 & !FILE_H;
+            let mut move_u = (pieces.queens() | pieces.rooks()) << 0o10;
+            let mut move_d = (pieces.queens() | pieces.rooks()) >> 0o10;
 
-            let mut move_ru = (pieces.queens | pieces.bishops) << 0o11 & !0x101010101010101;
-            let mut move_lu = (pieces.queens | pieces.bishops) << 7 & !0x8080808080808080;
-            let mut move_rd = (pieces.queens | pieces.bishops) >> 7 & !0x101010101010101;
-            let mut move_ld = (pieces.queens | pieces.bishops) >> 0o11 & !0x8080808080808080;
+            let mut move_ru = (pieces.queens() | pieces.bishops()) << 0o11 & !FILE_A;
+            let mut move_lu = (pieces.queens() | pieces.bishops()) << 7 & !FILE_H;
+            let mut move_rd = (pieces.queens() | pieces.bishops()) >> 7 & !FILE_A;
+            let mut move_ld = (pieces.queens() | pieces.bishops()) >> 0o11 & !FILE_H;
 
             loop {
                 let move_all =
@@ -317,34 +592,85 @@ impl Board {
                     break;
                 }
 
-                move_r = (move_r & !all) << 1 & !0x101010101010101;
-                move_l = (move_l & !all) >> 1 & !0x8080808080808080;
+                move_r = (move_r & !all) << 1 & !FILE_A;
+                move_l = (move_l & !all) >> 1 & !FILE_H;
                 move_u = (move_u & !all) << 0o10;
                 move_d = (move_d & !all) >> 0o10;
 
-                move_ru = (move_ru & !all) << 0o11 & !0x101010101010101;
-                move_lu = (move_lu & !all) << 7 & !0x8080808080808080;
-                move_rd = (move_rd & !all) >> 7 & !0x101010101010101;
-                move_ld = (move_ld & !all) >> 0o11 & !0x8080808080808080;
+                move_ru = (move_ru & !all) << 0o11 & !FILE_A;
+                move_lu = (move_lu & !all) << 7 & !FILE_H;
+                move_rd = (move_rd & !all) >> 7 & !FILE_A;
+                move_ld = (move_ld & !all) >> 0o11 & !FILE_H;
             }
         }
 
-        attack |= (pieces.knights << 0o21 | pieces.knights >> 0o17) & !0x101010101010101
-            | (pieces.knights << 0o17 | pieces.knights >> 0o21) & !0x8080808080808080
-            | (pieces.knights << 0o12 | pieces.knights >> 6) & !0x303030303030303
-            | (pieces.knights << 6 | pieces.knights >> 0o12) & !0xc0c0c0c0c0c0c0c0;
+        attack |= (pieces.knights() << 0o21 | pieces.knights() >> 0o17) & !FILE_A
+            | (pieces.knights() << 0o17 | pieces.knights() >> 0o21) & !FILE_H
+            | (pieces.knights() << 0o12 | pieces.knights() >> 6) & !(FILE_A | FILE_B)
+            | (pieces.knights() << 6 | pieces.knights() >> 0o12) & !(FILE_G | FILE_H);
 
         attack
     }
 
+    /// Whether `by` attacks `square`. Rather than computing `check_attack()`, it looks from the
+    /// square for the pieces that could attack it, as if a piece of every type stood on it.
+    pub fn is_attacked(&self, square: Square, by: Color) -> bool {
+        self.is_attacked_through(square, by, self.occupied())
+    }
+
+    /// Like `is_attacked()`, but the sliders' rays only stop at the pieces of `all`, e.g. the
+    /// board's without the king that steps away from them.
+    fn is_attacked_through(&self, square: Square, by: Color, all: u64) -> bool {
+        let bit = square.bit();
+        let pieces = self.get_pieces(by);
+
+        // The squares a pawn of the other color on `square` would attack.
+        let pawns = match by {
+            Color::White => bit >> 0o11 & !FILE_H | bit >> 7 & !FILE_A,
+            Color::Black => bit << 0o11 & !FILE_A | bit << 7 & !FILE_H,
+        };
+        let king = (bit << 1 | bit << 0o11 | bit >> 7) & !FILE_A
+            | (bit >> 1 | bit >> 0o11 | bit << 7) & !FILE_H
+            | bit << 0o10
+            | bit >> 0o10;
+        let knights = (bit << 0o21 | bit >> 0o17) & !FILE_A
+            | (bit << 0o17 | bit >> 0o21) & !FILE_H
+            | (bit << 0o12 | bit >> 6) & !(FILE_A | FILE_B)
+            | (bit << 6 | bit >> 0o12) & !(FILE_G | FILE_H);
+        if pawns & pieces.pawns() | king & pieces.king() | knights & pieces.knights() != 0 {
+            return true;
+        }
+
+        let rooks = pieces.queens() | pieces.rooks();
+        let bishops = pieces.queens() | pieces.bishops();
+        // The straight rays first, then the diagonal ones.
+        let rays: [fn(u64) -> u64; 8] = [
+            |step| step << 1 & !FILE_A,
+            |step| step >> 1 & !FILE_H,
+            |step| step << 0o10,
+            |step| step >> 0o10,
+            |step| step << 0o11 & !FILE_A,
+            |step| step << 7 & !FILE_H,
+            |step| step >> 7 & !FILE_A,
+            |step| step >> 0o11 & !FILE_H,
+        ];
+        rays.iter().enumerate().any(|(i, shift)| {
+            let mut step = shift(bit);
+            while step & !all != 0 {
+                step = shift(step);
+            }
+            step & if i < 4 { rooks } else { bishops } != 0
+        })
+    }
+
     pub fn is_legal(&self, color: Color, mv: Move) -> bool {
-        let pieces_all = self.get_pieces(color).all;
+        let pieces_all = self.occupied_by(color);
         match mv.ty {
             MoveType::King => {
-                let king = self.get_pieces(color).king & 1 << mv.from;
+                let king = self.get_pieces(color).king() & 1 << mv.from;
 
-                if ((king << 1 | king << 0o11 | king >> 7) & !0x101010101010101
-                    | (king >> 1 | king >> 0o11 | king << 7) & !0x8080808080808080
+                if ((king << 1 | king << 0o11 | king >> 7) & !FILE_A
+                    | (king >> 1 | king >> 0o11 | king << 7) & !FILE_H
                     | king << 0o10
                     | king >> 0o10)
                     & !pieces_all
@@ -355,28 +681,28 @@ impl Board {
                 }
             }
             MoveType::Queen => {
-                let other_all = self.get_pieces(color.inv()).all;
+                let other_all = self.occupied_by(color.inv());
                 let to_square = 1 << mv.to;
 
-                let queen = self.get_pieces(color).queens & 1 << mv.from;
+                let queen = self.get_pieces(color).queens() & 1 << mv.from;
                 if queen == 0 {
                     return false;
                 }
                 'queen_block: loop {
-                    // move r: step = (step & !other_all) << 1 & !0x101010101010101 & !pieces_all;
-                    // move l: step = (step & !other_all) >> 1 & !0x8080808080808080 & !pieces_all;
+                    // move r: step = (step & !other_all) << 1 & !FILE_A & !pieces_all;
+                    // move l: step = (step & !other_all) >> 1 & !FILE_H & !pieces_all;
                     // move u: step = (step & !other_all) << 0o10 & !pieces_all;
                     // move d: step = (step & !other_all) >> 0o10 & !pieces_all;
 
-                    // move ru: step = (step & !other_all) << 0o11 & !0x101010101010101 & !pieces_all;
-                    // move lu: step = (step & !other_all) << 7 & !0x8080808080808080 & !pieces_all;
-                    // move rd: step = (step & !other_all) >> 7 & !0x101010101010101 & !pieces_all;
-                    // move ld: step = (step & !other_all) >> 0o11 & !0x8080808080808080 & !pieces_all;
+                    // move ru: step = (step & !other_all) << 0o11 & !FILE_A & !pieces_all;
+                    // move lu: step = (step & !other_all) << 7 & !FILE_H & !pieces_all;
+                    // move rd: step = (step & !other_all) >> 7 & !FILE_A & !pieces_all;
+                    // move ld: step = (step & !other_all) >> 0o11 & !FILE_H & !pieces_all;
 
                     let mut step = queen;
                     // right
                     loop {
-                        step = (step & !other_all) << 1 & !0x101010101010101 & !pieces_all;
+                        step = (step & !other_all) << 1 & !FILE_A & !pieces_all;
                         if step == 0 {
                             break;
                         }
@@ -388,7 +714,7 @@ impl Board {
                     step = queen;
                     // left
                     loop {
-                        step = (step & !other_all) >> 1 & !0x8080808080808080 & !pieces_all;
+                        step = (step & !other_all) >> 1 & !FILE_H & !pieces_all;
                         if step == 0 {
                             break;
                         }
@@ -424,7 +750,7 @@ impl Board {
                     step = queen;
                     // right up
                     loop {
-                        step = (step & !other_all) << 0o11 & !0x101010101010101 & !pieces_all;
+                        step = (step & !other_all) << 0o11 & !FILE_A & !pieces_all;
                         if step == 0 {
                             break;
                         }
@@ -436,7 +762,7 @@ impl Board {
                     step = queen;
                     // left up
                     loop {
-                        step = (step & !other_all) << 7 & !0x8080808080808080 & !pieces_all;
+                        step = (step & !other_all) << 7 & !FILE_H & !pieces_all;
                         if step == 0 {
                             break;
                         }
@@ -448,7 +774,7 @@ impl Board {
                     step = queen;
                     // right down
                     loop {
-                        step = (step & !other_all) >> 7 & !0x101010101010101 & !pieces_all;
+                        step = (step & !other_all) >> 7 & !FILE_A & !pieces_all;
                         if step == 0 {
                             break;
                         }
@@ -460,7 +786,7 @@ impl Board {
                     step = queen;
                     // left down
                     loop {
-                        step = (step & !other_all) >> 0o11 & !0x8080808080808080 & !pieces_all;
+                        step = (step & !other_all) >> 0o11 & !FILE_H & !pieces_all;
                         if step == 0 {
                             break;
                         }
@@ -473,10 +799,10 @@ impl Board {
                 }
             }
             MoveType::Rook => {
-                let other_all = self.get_pieces(color.inv()).all;
+                let other_all = self.occupied_by(color.inv());
                 let to_square = 1 << mv.to;
 
-                let rook = self.get_pieces(color).rooks & 1 << mv.from;
+                let rook = self.get_pieces(color).rooks() & 1 << mv.from;
                 if rook == 0 {
                     return false;
                 }
@@ -484,7 +810,7 @@ impl Board {
                     let mut step = rook;
                     // right
                     loop {
-                        step = (step & !other_all) << 1 & !0x101010101010101 & !pieces_all;
+                        step = (step & !other_all) << 1 & !FILE_A & !pieces_all;
                         if step == 0 {
                             break;
                         }
@@ -496,7 +822,7 @@ impl Board {
                     step = rook;
                     // left
                     loop {
-                        step = (step & !other_all) >> 1 & !0x8080808080808080 & !pieces_all;
+                        step = (step & !other_all) >> 1 & !FILE_H & !pieces_all;
                         if step == 0 {
                             break;
                         }
@@ -533,10 +859,10 @@ impl Board {
                 }
             }
             MoveType::Bishop => {
-                let other_all = self.get_pieces(color.inv()).all;
+                let other_all = self.occupied_by(color.inv());
                 let to_square = 1 << mv.to;
 
-                let bishop = self.get_pieces(color).bishops & 1 << mv.from;
+                let bishop = self.get_pieces(color).bishops() & 1 << mv.from;
                 if bishop == 0 {
                     return false;
                 }
@@ -544,7 +870,7 @@ impl Board {
                     let mut step = bishop;
                     // right up
                     loop {
-                        step = (step & !other_all) << 0o11 & !0x101010101010101 & !pieces_all;
+                        step = (step & !other_all) << 0o11 & !FILE_A & !pieces_all;
                         if step == 0 {
                             break;
                         }
@@ -556,7 +882,7 @@ impl Board {
                     step = bishop;
                     // left up
                     loop {
-                        step = (step & !other_all) << 7 & !0x8080808080808080 & !pieces_all;
+                        step = (step & !other_all) << 7 & !FILE_H & !pieces_all;
                         if step == 0 {
                             break;
                         }
@@ -568,7 +894,7 @@ impl Board {
                     step = bishop;
                     // right down
                     loop {
-                        step = (step & !other_all) >> 7 & !0x101010101010101 & !pieces_all;
+                        step = (step & !other_all) >> 7 & !FILE_A & !pieces_all;
                         if step == 0 {
                             break;
                         }
@@ -580,7 +906,7 @@ impl Board {
                     step = bishop;
                     // left down
                     loop {
-                        step = (step & !other_all) >> 0o11 & !0x8080808080808080 & !pieces_all;
+                        step = (step & !other_all) >> 0o11 & !FILE_H & !pieces_all;
                         if step == 0 {
                             break;
                         }
@@ -593,12 +919,12 @@ impl Board {
                 }
             }
             MoveType::Knight => {
-                let knight = self.get_pieces(color).knights & 1 << mv.from;
+                let knight = self.get_pieces(color).knights() & 1 << mv.from;
 
-                if ((knight << 0o21 | knight >> 0o17) & !0x101010101010101
-                    | (knight << 0o17 | knight >> 0o21) & !0x8080808080808080
-                    | (knight << 0o12 | knight >> 6) & !0x303030303030303
-                    | (knight << 6 | knight >> 0o12) & !0xc0c0c0c0c0c0c0c0)
+                if ((knight << 0o21 | knight >> 0o17) & !FILE_A
+                    | (knight << 0o17 | knight >> 0o21) & !FILE_H
+                    | (knight << 0o12 | knight >> 6) & !(FILE_A | FILE_B)
+                    | (knight << 6 | knight >> 0o12) & !(FILE_G | FILE_H))
                     & !pieces_all
                     & 1 << mv.to
                     == 0
@@ -611,60 +937,60 @@ impl Board {
             | MoveType::PawnRookPromotion
             | MoveType::PawnBishopPromotion
             | MoveType::PawnKnightPromotion => {
-                let all = self.white_pieces.all | self.black_pieces.all;
+                let all = self.occupied();
 
                 match color {
                     Color::White => {
-                        let pawn = self.white_pieces.pawns & 1 << mv.from;
+                        let pawn = self.white_pieces.pawns() & 1 << mv.from;
                         let other_all = self.black_pieces.all;
 
                         if (pawn << 0o10 & !all
-                            | (pawn << 7 & !0x8080808080808080 | pawn << 0o11 & !0x101010101010101)
-                                & other_all)
+                            | (pawn << 7 & !FILE_H | pawn << 0o11 & !FILE_A) & other_all)
                             & 1 << mv.to
                             == 0
                         {
                             return false;
                         }
 
-                        if mv.ty != MoveType::Pawn && 1u64 << mv.to & 0xff << 0o70 == 0 {
+                        if mv.ty != MoveType::Pawn && 1u64 << mv.to & RANK_8 == 0 {
                             return false;
                         }
                     }
                     Color::Black => {
-                        let pawn = self.black_pieces.pawns & 1 << mv.from;
+                        let pawn = self.black_pieces.pawns() & 1 << mv.from;
                         let other_all = self.white_pieces.all;
 
                         if (pawn >> 0o10 & !all
-                            | (pawn >> 0o11 & !0x8080808080808080 | pawn >> 7 & !0x101010101010101)
-                                & other_all)
+                            | (pawn >> 0o11 & !FILE_H | pawn >> 7 & !FILE_A) & other_all)
                             & 1 << mv.to
                             == 0
                         {
                             return false;
                         }
 
-                        if mv.ty != MoveType::Pawn && 1u64 << mv.to & 0xff == 0 {
+                        if mv.ty != MoveType::Pawn && 1u64 << mv.to & RANK_1 == 0 {
                             return false;
                         }
                     }
                 }
             }
             MoveType::PawnLeap => {
-                let all = self.white_pieces.all | self.black_pieces.all;
+                let all = self.occupied();
 
                 match color {
                     Color::White => {
-                        let pawn = self.white_pieces.pawns & 1 << mv.from;
+                        let pawn = self.white_pieces.pawns() & 1 << mv.from;
 
-                        if pawn << 0o20 & 1 << mv.to & !all & 0xff00_0000 == 0 {
+                        if (pawn << 0o10 & !all) << 0o10 & 1 << mv.to & !all & self.leap_ranks()
+                            == 0
+                        {
                             return false;
                         }
                     }
                     Color::Black => {
-                        let pawn = self.black_pieces.pawns & 1 << mv.from;
+                        let pawn = self.black_pieces.pawns() & 1 << mv.from;
 
-                        if pawn >> 0o20 & 1 << mv.to & !all & 0xff_0000_0000 == 0 {
+                        if (pawn >> 0o10 & !all) >> 0o10 & 1 << mv.to & !all & RANK_5 == 0 {
                             return false;
                         }
                     }
@@ -676,359 +1002,426 @@ impl Board {
                 }
                 match color {
                     Color::White => {
-                        let pawn = self.white_pieces.pawns & 1 << mv.from;
+                        let pawn = self.white_pieces.pawns() & 1 << mv.from;
 
                         if mv.to != self.prev_move.to + 0o10 {
                             return false;
                         }
 
-                        if (pawn << 7 & !0x8080808080808080 | pawn << 0o11 & !0x101010101010101)
-                            & 1 << mv.to
-                            == 0
-                        {
+                        if (pawn << 7 & !FILE_H | pawn << 0o11 & !FILE_A) & 1 << mv.to == 0 {
                             return false;
                         }
                     }
                     Color::Black => {
-                        let pawn = self.black_pieces.pawns & 1 << mv.from;
+                        let pawn = self.black_pieces.pawns() & 1 << mv.from;
 
                         if mv.to != self.prev_move.to - 0o10 {
                             return false;
                         }
 
-                        if (pawn >> 0o11 & !0x8080808080808080 | pawn >> 7 & !0x101010101010101)
-                            & 1 << mv.to
-                            == 0
-                        {
+                        if (pawn >> 0o11 & !FILE_H | pawn >> 7 & !FILE_A) & 1 << mv.to == 0 {
                             return false;
                         }
                     }
                 }
             }
             MoveType::Castle => {
-                let all = self.white_pieces.all | self.black_pieces.all;
-
-                match mv.to {
-                    2 => {
-                        if color == Color::Black {
-                            return false;
-                        }
-                        if all & 0xe != 0 {
-                            return false;
-                        }
-                        if !self.flags.contains(ChessFlags::WHITE_QUEENS_CASTLE) {
-                            return false;
-                        }
-                        if self.check_attack(Color::Black) & 0x1c != 0 {
-                            return false;
-                        }
-                        return true;
-                    }
-                    6 => {
-                        if color == Color::Black {
-                            return false;
-                        }
-                        if all & 0x60 != 0 {
-                            return false;
-                        }
-                        if !self.flags.contains(ChessFlags::WHITE_KINGS_CASTLE) {
-                            return false;
-                        }
-                        if self.check_attack(Color::Black) & 0x70 != 0 {
-                            return false;
-                        }
-                        return true;
-                    }
-                    0o72 => {
-                        if color == Color::White {
-                            return false;
-                        }
-                        if all & 0xe << 0o70 != 0 {
-                            return false;
-                        }
-                        if !self.flags.contains(ChessFlags::BLACK_QUEENS_CASTLE) {
-                            return false;
-                        }
-                        if self.check_attack(Color::White) & 0x1c << 0o70 != 0 {
-                            return false;
-                        }
-                        return true;
-                    }
-                    0o76 => {
-                        if color == Color::White {
-                            return false;
-                        }
-                        if all & 0x60 << 0o70 != 0 {
-                            return false;
-                        }
-                        if !self.flags.contains(ChessFlags::BLACK_KINGS_CASTLE) {
-                            return false;
-                        }
-                        if self.check_attack(Color::Black) & 0x70 << 0o70 != 0 {
-                            return false;
-                        }
-                        return true;
+                let all = self.occupied();
+
+                return match Castle::to(mv.to) {
+                    Some(castle) => {
+                        castle.color == color
+                            && mv.from == castle.king_from
+                            && all & castle.empty == 0
+                            && self.flags.contains(castle.right)
+                            && !BitIterator(castle.safe).any(|bit| {
+                                self.is_attacked(
+                                    Square::of(bit.trailing_zeros() as u8),
+                                    color.inv(),
+                                )
+                            })
                     }
-                    _ => return false,
+                    None => false,
+                };
+            }
+            MoveType::DropQueen
+            | MoveType::DropRook
+            | MoveType::DropBishop
+            | MoveType::DropKnight
+            | MoveType::DropPawn => {
+                let ty = mv.ty.dropped().unwrap();
+                if self.get_pieces(color).king() & 1 << mv.from == 0
+                    || self.occupied() & 1 << mv.to != 0
+                    || self.reserve(color, ty) == 0
+                    || ty == PieceType::Pawn && 1u64 << mv.to & BACK_RANKS != 0
+                {
+                    return false;
                 }
             }
         }
 
         let mut board = *self;
-        board.perform_move(mv);
-        board.check_attack(color.inv()) & board.get_pieces(color).king == 0
+        board.perform_move_unchecked(mv);
+        !board.in_check(color)
     }
 
-    pub fn find_pins(&self, color: Color) -> u64 {
-        let mut pins = 0;
-        let king = self.get_pieces(color).king;
-        let pieces_all = self.get_pieces(color).all;
-        let other_all = self.get_pieces(color.inv()).all;
+    /// The legal ones of `moves`, in their order. Unlike `is_legal()` on each of them, it computes
+    /// the attack map and the pins once, by generating the legal moves to check them against.
+    pub fn filter_legal(&self, color: Color, moves: &[Move]) -> Vec<Move> {
+        let legal = self.moves(color);
+        moves
+            .iter()
+            .copied()
+            .filter(|&mv| {
+                // The generator only promotes to queens, an underpromotion is legal with theirs.
+                let generated = match mv.ty {
+                    MoveType::PawnRookPromotion
+                    | MoveType::PawnBishopPromotion
+                    | MoveType::PawnKnightPromotion => Move {
+                        ty: MoveType::PawnQueenPromotion,
+                        ..mv
+                    },
+                    _ => mv,
+                };
+                legal.contains(&generated)
+            })
+            .collect()
+    }
 
-        let other_queens = self.get_pieces(color.inv()).queens;
-        let other_hor_ver_pinners = self.get_pieces(color.inv()).rooks | other_queens;
+    #[inline]
+    pub fn in_check(&self, color: Color) -> bool {
+        BitIterator(self.get_pieces(color).king())
+            .any(|king| self.is_attacked(Square::of(king.trailing_zeros() as u8), color.inv()))
+    }
 
-        {
-            // right
-            let mut pos = king;
-            let mut pin = 0;
-            loop {
-                pos = pos << 1 & !0x101010101010101;
-                if pos & pieces_all != 0 {
-                    if pin != 0 {
-                        break;
-                    } else {
-                        pin = pos;
-                    }
-                }
-                if other_hor_ver_pinners & pos != 0 {
-                    pins |= pin;
-                    break;
-                }
-                if pos == 0 || pos & other_all != 0 {
-                    break;
-                }
-            }
+    /// The ranks White's pawns can leap to: the fourth, and in Horde the third too.
+    #[inline]
+    fn leap_ranks(&self) -> u64 {
+        match self.variant {
+            Variant::Horde => RANK_3 | RANK_4,
+            _ => RANK_4,
         }
-        {
-            // left
-            let mut pos = king;
-            let mut pin = 0;
-            loop {
-                pos = pos >> 1 & !0x8080808080808080;
-                if pos & pieces_all != 0 {
-                    if pin != 0 {
-                        break;
-                    } else {
-                        pin = pos;
-                    }
-                }
-                if other_hor_ver_pinners & pos != 0 {
-                    pins |= pin;
-                    break;
-                }
-                if pos == 0 || pos & other_all != 0 {
-                    break;
-                }
-            }
+    }
+
+    /// Whether neither side can mate: only kings, a single minor piece, or bishops that are all on
+    /// squares of the same color are left. Never in Horde.
+    pub fn is_insufficient_material(&self) -> bool {
+        // Black wins Horde by taking the pieces, which needs no mating material.
+        if self.reserves != [[0; 6]; 2] || self.variant == Variant::Horde {
+            return false;
         }
-        {
-            // up
-            let mut pos = king;
-            let mut pin = 0;
-            loop {
-                pos = pos << 0o10;
-                if pos & pieces_all != 0 {
-                    if pin != 0 {
-                        break;
-                    } else {
-                        pin = pos;
-                    }
-                }
-                if other_hor_ver_pinners & pos != 0 {
-                    pins |= pin;
-                    break;
-                }
-                if pos == 0 || pos & other_all != 0 {
-                    break;
-                }
-            }
+        let [white, black] = [Color::White, Color::Black].map(|color| self.material_count(color));
+        if [white, black].iter().any(|count| {
+            count.get(PieceType::Queen) + count.get(PieceType::Rook) + count.get(PieceType::Pawn)
+                != 0
+        }) {
+            return false;
         }
-        {
-            // down
-            let mut pos = king;
-            let mut pin = 0;
-            loop {
-                pos = pos >> 0o10;
-                if pos & pieces_all != 0 {
-                    if pin != 0 {
-                        break;
-                    } else {
-                        pin = pos;
-                    }
-                }
-                if other_hor_ver_pinners & pos != 0 {
-                    pins |= pin;
-                    break;
-                }
-                if pos == 0 || pos & other_all != 0 {
-                    break;
+        let (white, black) = (&self.white_pieces, &self.black_pieces);
+        let (knights, bishops) = (
+            white.knights() | black.knights(),
+            white.bishops() | black.bishops(),
+        );
+        (knights | bishops).count_ones() <= 1
+            || knights == 0 && (bishops & LIGHT_SQUARES == 0 || bishops & !LIGHT_SQUARES == 0)
+    }
+
+    /// Whether `mv` puts the opponent of the moving piece in check. Rather than making the move,
+    /// it looks for the mover's pieces on the squares that attack the opponent's king after it.
+    pub fn gives_check(&self, mv: Move) -> bool {
+        let color = if self.white_pieces.all & 1 << mv.from != 0 {
+            Color::White
+        } else {
+            Color::Black
+        };
+        // A drop doesn't leave its `from`, the king's square.
+        let dropped = mv.ty.dropped();
+        let from = if dropped.is_some() { 0 } else { 1 << mv.from };
+        let to = 1 << mv.to;
+        let king = self.get_pieces(color.inv()).king();
+
+        let mut pieces = *self.get_pieces(color);
+        let mut occupied = self.occupied() & !from | to;
+        let ty = match mv.ty {
+            MoveType::PawnQueenPromotion => PieceType::Queen,
+            MoveType::PawnRookPromotion => PieceType::Rook,
+            MoveType::PawnBishopPromotion => PieceType::Bishop,
+            MoveType::PawnKnightPromotion => PieceType::Knight,
+            _ => match dropped.or_else(|| pieces.get_at(from)) {
+                Some(ty) => ty,
+                None => return false,
+            },
+        };
+        pieces.clear_unchecked(from);
+        *pieces.get_mut(ty) |= to;
+        match mv.ty {
+            MoveType::PawnEnPassant => {
+                occupied &= !match color {
+                    Color::White => to >> 0o10,
+                    Color::Black => to << 0o10,
                 }
             }
+            // The rook can give the check.
+            MoveType::Castle => {
+                let castle = Castle::to(mv.to).unwrap();
+                let (rook_from, rook_to) = (castle.rook_from, castle.rook_to);
+                *pieces.get_mut(PieceType::Rook) =
+                    pieces.rooks() & !(1 << rook_from) | 1 << rook_to;
+                occupied = occupied & !(1 << rook_from) | 1 << rook_to;
+            }
+            _ => {}
         }
 
-        // move_ru = (move_ru & !all) << 0o11 & !0x101010101010101;
-        // move_lu = (move_lu & !all) << 7 & !0x8080808080808080;
-        // move_rd = (move_rd & !all) >> 7 & !0x101010101010101;
-        // move_ld = (move_ld & !all) >> 0o11 & !0x8080808080808080;
+        let pawn_checks = match color {
+            Color::White => king >> 0o11 & !FILE_H | king >> 7 & !FILE_A,
+            Color::Black => king << 0o11 & !FILE_A | king << 7 & !FILE_H,
+        };
+        pawn_checks & pieces.pawns()
+            | knight_attacks(king) & pieces.knights()
+            | ray_attacks(king, occupied, true) & (pieces.rooks() | pieces.queens())
+            | ray_attacks(king, occupied, false) & (pieces.bishops() | pieces.queens())
+            != 0
+    }
 
-        let other_diagonal_pinners = self.get_pieces(color.inv()).bishops | other_queens;
-        {
-            // right up
-            let mut pos = king;
-            let mut pin = 0;
-            loop {
-                pos = pos << 0o11 & !0x101010101010101;
-                if pos & pieces_all != 0 {
-                    if pin != 0 {
-                        break;
-                    } else {
-                        pin = pos;
-                    }
-                }
-                if other_diagonal_pinners & pos != 0 {
-                    pins |= pin;
-                    break;
-                }
-                if pos == 0 || pos & other_all != 0 {
-                    break;
-                }
-            }
+    /// The pieces of `color` pinned to its king: the only piece between it and a slider of the
+    /// other color that would attack it otherwise.
+    pub fn find_pins(&self, color: Color) -> u64 {
+        let king = self.get_pieces(color).king();
+        if king == 0 {
+            return 0;
         }
-        {
-            // left up
-            let mut pos = king;
-            let mut pin = 0;
-            loop {
-                pos = pos << 7 & !0x8080808080808080;
-                if pos & pieces_all != 0 {
-                    if pin != 0 {
-                        break;
-                    } else {
-                        pin = pos;
-                    }
-                }
-                if other_diagonal_pinners & pos != 0 {
-                    pins |= pin;
-                    break;
-                }
-                if pos == 0 || pos & other_all != 0 {
-                    break;
-                }
-            }
+        let king_square = king.trailing_zeros() as u8;
+        let pieces_all = self.occupied_by(color);
+        let other = self.get_pieces(color.inv());
+        let occupied = self.occupied();
+
+        let pinners = Self::xray_rook_attacks(king_square, occupied, pieces_all)
+            & (other.rooks() | other.queens())
+            | Self::xray_bishop_attacks(king_square, occupied, pieces_all)
+                & (other.bishops() | other.queens());
+
+        let mut pins = 0;
+        for pinner in BitIterator(pinners) {
+            pins |= BETWEEN[king_square as usize][pinner.trailing_zeros() as usize] & pieces_all;
         }
-        {
-            // right down
-            let mut pos = king;
-            let mut pin = 0;
-            loop {
-                pos = pos >> 7 & !0x101010101010101;
-                if pos & pieces_all != 0 {
-                    if pin != 0 {
-                        break;
-                    } else {
-                        pin = pos;
-                    }
-                }
-                if other_diagonal_pinners & pos != 0 {
-                    pins |= pin;
-                    break;
-                }
-                if pos == 0 || pos & other_all != 0 {
-                    break;
-                }
-            }
+        pins
+    }
+
+    /// The squares a rook on `square` would attack if the pieces of `blockers` that are first on
+    /// its rays were gone, up to and including the next occupied square. Looking from a king
+    /// through its own pieces finds the sliders that pin them.
+    pub fn xray_rook_attacks(square: u8, occupied: u64, blockers: u64) -> u64 {
+        let attacks = ray_attacks(1 << square, occupied, true);
+        attacks ^ ray_attacks(1 << square, occupied & !(blockers & attacks), true)
+    }
+
+    /// Like `xray_rook_attacks()`, but along the diagonals.
+    pub fn xray_bishop_attacks(square: u8, occupied: u64, blockers: u64) -> u64 {
+        let attacks = ray_attacks(1 << square, occupied, false);
+        attacks ^ ray_attacks(1 << square, occupied & !(blockers & attacks), false)
+    }
+
+    /// The pieces of the other color that give check to `color`'s king, two of them are a double
+    /// check.
+    pub fn checkers(&self, color: Color) -> u64 {
+        let king = self.get_pieces(color).king();
+        let pieces = self.get_pieces(color.inv());
+        let occupied = self.occupied();
+
+        let pawn_checks = match color {
+            Color::White => king << 0o11 & !FILE_A | king << 7 & !FILE_H,
+            Color::Black => king >> 0o11 & !FILE_H | king >> 7 & !FILE_A,
+        };
+        pawn_checks & pieces.pawns()
+            | knight_attacks(king) & pieces.knights()
+            | ray_attacks(king, occupied, true) & (pieces.rooks() | pieces.queens())
+            | ray_attacks(king, occupied, false) & (pieces.bishops() | pieces.queens())
+    }
+
+    /// Whether two pieces give check to `color`'s king, so only the king can move.
+    #[inline]
+    pub fn in_double_check(&self, color: Color) -> bool {
+        self.checkers(color).count_ones() > 1
+    }
+
+    /// Whether `mv` gives check with a piece it doesn't move, by moving the piece that blocked a
+    /// slider (or, en passant, taking the pawn that did).
+    pub fn move_gives_discovered_check(&self, mv: Move) -> bool {
+        // A drop only blocks.
+        if mv.ty.dropped().is_some() {
+            return false;
         }
-        {
-            // left down
-            let mut pos = king;
-            let mut pin = 0;
-            loop {
-                pos = pos >> 0o11 & !0x8080808080808080;
-                if pos & pieces_all != 0 {
-                    if pin != 0 {
-                        break;
-                    } else {
-                        pin = pos;
-                    }
-                }
-                if other_diagonal_pinners & pos != 0 {
-                    pins |= pin;
-                    break;
-                }
-                if pos == 0 || pos & other_all != 0 {
-                    break;
+        let color = if self.white_pieces.all & 1 << mv.from != 0 {
+            Color::White
+        } else {
+            Color::Black
+        };
+        let king = self.get_pieces(color.inv()).king();
+        let pieces = self.get_pieces(color);
+        let from = 1 << mv.from;
+        let to = 1 << mv.to;
+
+        let mut occupied = self.occupied() & !from | to;
+        // The moved pieces don't count.
+        let mut moved = from;
+        match mv.ty {
+            MoveType::PawnEnPassant => {
+                occupied &= !match color {
+                    Color::White => to >> 0o10,
+                    Color::Black => to << 0o10,
                 }
             }
+            MoveType::Castle => {
+                let castle = Castle::to(mv.to).unwrap();
+                let (rook_from, rook_to) = (castle.rook_from, castle.rook_to);
+                occupied = occupied & !(1 << rook_from) | 1 << rook_to;
+                moved |= 1 << rook_from;
+            }
+            _ => {}
         }
 
-        pins
+        ray_attacks(king, occupied, true) & (pieces.rooks() | pieces.queens()) & !moved
+            | ray_attacks(king, occupied, false) & (pieces.bishops() | pieces.queens()) & !moved
+            != 0
     }
 
+    /// The squares a move other than the king's has to go to when `color` is in check: the
+    /// checker's square and the squares between it and the king. Nothing in double check, and
+    /// everything out of check.
+    fn evasions(&self, color: Color, check: bool) -> u64 {
+        if !check {
+            return !0;
+        }
+        let checkers = self.checkers(color);
+        if checkers.count_ones() != 1 {
+            return 0;
+        }
+        let king_square = self.get_pieces(color).king().trailing_zeros() as usize;
+        BETWEEN[king_square][checkers.trailing_zeros() as usize] | checkers
+    }
+
+    #[inline]
     pub fn moves(&self, color: Color) -> Vec<Move> {
+        self.moves_with_attack(color, self.check_attack(color.inv()))
+    }
+
+    /// Like `moves()`, but reuses an already computed `check_attack(color.inv())`.
+    #[inline]
+    pub fn moves_with_attack(&self, color: Color, other_attack: u64) -> Vec<Move> {
         let mut moves = Vec::new();
+        self.moves_into(color, other_attack, &mut moves);
+        moves
+    }
+
+    /// Whether `color` has a legal move, without allocating. `other_attack` must be
+    /// `self.check_attack(color.inv())`.
+    pub fn has_moves(&self, color: Color, other_attack: u64) -> bool {
+        struct Any(bool);
+        impl Extend<Move> for Any {
+            fn extend<I: IntoIterator<Item = Move>>(&mut self, iter: I) {
+                self.0 |= iter.into_iter().next().is_some();
+            }
+        }
 
+        let mut any = Any(false);
+        self.moves_into(color, other_attack, &mut any);
+        any.0
+    }
+
+    /// The squares of `targets` that `color`'s king can step to without being attacked there.
+    /// The king doesn't shield the squares behind it from the sliders it steps away from.
+    fn king_targets(&self, color: Color, targets: u64) -> u64 {
+        let king = self.get_pieces(color).king();
+        let steps = (king << 1 | king << 0o11 | king >> 7) & !FILE_A
+            | (king >> 1 | king >> 0o11 | king << 7) & !FILE_H
+            | king << 0o10
+            | king >> 0o10;
+        let all = self.occupied() & !king;
+        BitIterator(steps & targets)
+            .filter(|&bit| {
+                !self.is_attacked_through(Square::of(bit.trailing_zeros() as u8), color.inv(), all)
+            })
+            .fold(0, |targets, bit| targets | bit)
+    }
+
+    /// Generates the legal moves into `moves` without allocating (if `moves` doesn't),
+    /// `other_attack` must be `self.check_attack(color.inv())`. It only tells whether `color` is
+    /// in check and which moves answer it, the king's moves and castles look up the squares they
+    /// need with `is_attacked()`.
+    pub fn moves_into<E: Extend<Move>>(&self, color: Color, other_attack: u64, moves: &mut E) {
         let pieces = self.get_pieces(color);
         let pins = self.find_pins(color);
-        let other_all = self.get_pieces(color.inv()).all;
-        let other_attack = self.check_attack(color.inv());
+        let other_all = self.occupied_by(color.inv());
 
-        let check = pieces.king & other_attack != 0;
+        let check = pieces.king() & other_attack != 0;
+        let evasions = self.evasions(color, check);
+        let king_square = pieces.king().trailing_zeros() as usize;
 
         let mut push_move = |mv: Move, dont_check_king_safety: bool| {
-            if dont_check_king_safety || !check && 1 << mv.from & pins == 0 {
-                moves.push(mv);
-            } else {
+            if dont_check_king_safety {
+                moves.extend(Some(mv));
+            } else if mv.ty == MoveType::PawnEnPassant {
+                // En passant takes two pawns off a rank, which can expose the king along it even
+                // without a pin.
                 let mut board = *self;
-                board.perform_move(mv);
-                if board.check_attack(color.inv()) & board.get_pieces(color).king == 0 {
-                    moves.push(mv);
+                board.perform_move_unchecked(mv);
+                if !board.in_check(color) {
+                    moves.extend(Some(mv));
+                }
+            } else {
+                // A pinned piece can only move along the pin.
+                let pin_line = match 1 << mv.from & pins {
+                    0 => !0,
+                    _ => LINE[king_square][mv.from as usize],
+                };
+                if 1 << mv.to & evasions & pin_line != 0 {
+                    moves.extend(Some(mv));
                 }
             }
-        };
+        };
+
+        {
+            let king_moves = self.king_targets(color, !pieces.all);
+            for bit in BitIterator(king_moves) {
+                push_move(
+                    Move {
+                        from: pieces.king().trailing_zeros() as _,
+                        to: bit.trailing_zeros() as _,
+                        ty: MoveType::King,
+                    },
+                    true,
+                );
+            }
+        }
+
+        // In double check only the king can move.
+        if evasions == 0 {
+            return;
+        }
+
+        let all = self.occupied();
+        for castle in CASTLES.iter().filter(|castle| castle.color == color) {
+            if self.flags.contains(castle.right)
+                && all & castle.empty == 0
+                && !BitIterator(castle.safe).any(|bit| {
+                    self.is_attacked(Square::of(bit.trailing_zeros() as u8), color.inv())
+                })
+            {
+                push_move(
+                    Move {
+                        from: castle.king_from,
+                        to: castle.king_to,
+                        ty: MoveType::Castle,
+                    },
+                    true,
+                );
+            }
+        }
 
-        let all = self.white_pieces.all | self.black_pieces.all;
         match color {
             Color::White => {
-                if self.flags.contains(ChessFlags::WHITE_KINGS_CASTLE)
-                    && other_attack & 0x70 == 0
-                    && all & 0x60 == 0
-                {
-                    push_move(
-                        Move {
-                            from: 4,
-                            to: 6,
-                            ty: MoveType::Castle,
-                        },
-                        true,
-                    );
-                }
-                if self.flags.contains(ChessFlags::WHITE_QUEENS_CASTLE)
-                    && other_attack & 0x1c == 0
-                    && all & 0xe == 0
-                {
-                    push_move(
-                        Move {
-                            from: 4,
-                            to: 2,
-                            ty: MoveType::Castle,
-                        },
-                        true,
-                    );
-                }
-
                 if self.prev_move.ty == MoveType::PawnLeap {
-                    if 1 << (self.prev_move.to + 1) & pieces.pawns & !0x101010101010101 != 0 {
+                    if 1 << (self.prev_move.to + 1) & pieces.pawns() & !FILE_A != 0 {
                         push_move(
                             Move {
                                 from: self.prev_move.to + 1,
@@ -1038,7 +1431,7 @@ impl Board {
                             false,
                         );
                     }
-                    if 1 << (self.prev_move.to - 1) & pieces.pawns & !0x8080808080808080 != 0 {
+                    if 1 << (self.prev_move.to - 1) & pieces.pawns() & !FILE_H != 0 {
                         push_move(
                             Move {
                                 from: self.prev_move.to - 1,
@@ -1050,14 +1443,14 @@ impl Board {
                     }
                 }
 
-                let pawn_fwd = pieces.pawns << 0o10 & !all;
+                let pawn_fwd = pieces.pawns() << 0o10 & !all;
 
                 for bit in BitIterator(pawn_fwd) {
                     push_move(
                         Move {
                             from: bit.trailing_zeros() as u8 - 0o10,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit & 0xff << 0o70 == 0 {
+                            ty: if bit & RANK_8 == 0 {
                                 MoveType::Pawn
                             } else {
                                 MoveType::PawnQueenPromotion
@@ -1066,7 +1459,7 @@ impl Board {
                         false,
                     );
                 }
-                for bit in BitIterator(pawn_fwd << 0o10 & !all & 0xff00_0000) {
+                for bit in BitIterator(pawn_fwd << 0o10 & !all & self.leap_ranks()) {
                     push_move(
                         Move {
                             from: bit.trailing_zeros() as u8 - 0o20,
@@ -1076,12 +1469,12 @@ impl Board {
                         false,
                     );
                 }
-                for bit in BitIterator(pieces.pawns << 0o11 & !0x101010101010101 & other_all) {
+                for bit in BitIterator(pieces.pawns() << 0o11 & !FILE_A & other_all) {
                     push_move(
                         Move {
                             from: bit.trailing_zeros() as u8 - 0o11,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit & 0xff << 0o70 == 0 {
+                            ty: if bit & RANK_8 == 0 {
                                 MoveType::Pawn
                             } else {
                                 MoveType::PawnQueenPromotion
@@ -1090,12 +1483,12 @@ impl Board {
                         false,
                     );
                 }
-                for bit in BitIterator(pieces.pawns << 7 & !0x8080808080808080 & other_all) {
+                for bit in BitIterator(pieces.pawns() << 7 & !FILE_H & other_all) {
                     push_move(
                         Move {
                             from: bit.trailing_zeros() as u8 - 7,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit & 0xff << 0o70 == 0 {
+                            ty: if bit & RANK_8 == 0 {
                                 MoveType::Pawn
                             } else {
                                 MoveType::PawnQueenPromotion
@@ -1106,33 +1499,8 @@ impl Board {
                 }
             }
             Color::Black => {
-                if self.flags.contains(ChessFlags::BLACK_KINGS_CASTLE)
-                    && other_attack & 0x70 << 0o70 == 0
-                {
-                    push_move(
-                        Move {
-                            from: 4,
-                            to: 6,
-                            ty: MoveType::Castle,
-                        },
-                        true,
-                    );
-                }
-                if self.flags.contains(ChessFlags::BLACK_QUEENS_CASTLE)
-                    && other_attack & 0x1c << 0o70 == 0
-                {
-                    push_move(
-                        Move {
-                            from: 4,
-                            to: 2,
-                            ty: MoveType::Castle,
-                        },
-                        true,
-                    );
-                }
-
                 if self.prev_move.ty == MoveType::PawnLeap {
-                    if 1 << (self.prev_move.to + 1) & pieces.pawns & !0x101010101010101 != 0 {
+                    if 1 << (self.prev_move.to + 1) & pieces.pawns() & !FILE_A != 0 {
                         push_move(
                             Move {
                                 from: self.prev_move.to + 1,
@@ -1142,7 +1510,7 @@ impl Board {
                             false,
                         );
                     }
-                    if 1 << (self.prev_move.to - 1) & pieces.pawns & !0x8080808080808080 != 0 {
+                    if 1 << (self.prev_move.to - 1) & pieces.pawns() & !FILE_H != 0 {
                         push_move(
                             Move {
                                 from: self.prev_move.to - 1,
@@ -1154,14 +1522,14 @@ impl Board {
                     }
                 }
 
-                let pawn_fwd = pieces.pawns >> 0o10 & !all;
+                let pawn_fwd = pieces.pawns() >> 0o10 & !all;
 
                 for bit in BitIterator(pawn_fwd) {
                     push_move(
                         Move {
                             from: bit.trailing_zeros() as u8 + 0o10,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit & 0xff == 0 {
+                            ty: if bit & RANK_1 == 0 {
                                 MoveType::Pawn
                             } else {
                                 MoveType::PawnQueenPromotion
@@ -1170,7 +1538,7 @@ impl Board {
                         false,
                     );
                 }
-                for bit in BitIterator(pawn_fwd >> 0o10 & !all & 0xff_0000_0000) {
+                for bit in BitIterator(pawn_fwd >> 0o10 & !all & RANK_5) {
                     push_move(
                         Move {
                             from: bit.trailing_zeros() as u8 + 0o20,
@@ -1180,12 +1548,12 @@ impl Board {
                         false,
                     );
                 }
-                for bit in BitIterator(pieces.pawns >> 0o11 & !0x8080808080808080 & other_all) {
+                for bit in BitIterator(pieces.pawns() >> 0o11 & !FILE_H & other_all) {
                     push_move(
                         Move {
                             from: bit.trailing_zeros() as u8 + 0o11,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit & 0xff == 0 {
+                            ty: if bit & RANK_1 == 0 {
                                 MoveType::Pawn
                             } else {
                                 MoveType::PawnQueenPromotion
@@ -1194,12 +1562,12 @@ impl Board {
                         false,
                     );
                 }
-                for bit in BitIterator(pieces.pawns >> 7 & !0x101010101010101 & other_all) {
+                for bit in BitIterator(pieces.pawns() >> 7 & !FILE_A & other_all) {
                     push_move(
                         Move {
                             from: bit.trailing_zeros() as u8 + 7,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit & 0xff == 0 {
+                            ty: if bit & RANK_1 == 0 {
                                 MoveType::Pawn
                             } else {
                                 MoveType::PawnQueenPromotion
@@ -1212,47 +1580,26 @@ impl Board {
         }
 
         {
-            let king_moves = ((pieces.king << 1 | pieces.king << 0o11 | pieces.king >> 7)
-                & !0x101010101010101
-                | (pieces.king >> 1 | pieces.king >> 0o11 | pieces.king << 7)
-                    & !0x8080808080808080
-                | pieces.king << 0o10
-                | pieces.king >> 0o10)
-                & !pieces.all
-                & !other_attack;
-            for bit in BitIterator(king_moves) {
-                push_move(
-                    Move {
-                        from: pieces.king.trailing_zeros() as _,
-                        to: bit.trailing_zeros() as _,
-                        ty: MoveType::King,
-                    },
-                    true,
-                );
-            }
-        }
-
-        {
-            let mut move_r = pieces.queens | pieces.rooks;
-            let mut move_l = pieces.queens | pieces.rooks;
-            let mut move_u = pieces.queens | pieces.rooks;
-            let mut move_d = pieces.queens | pieces.rooks;
+            let mut move_r = pieces.queens() | pieces.rooks();
+            let mut move_l = pieces.queens() | pieces.rooks();
+            let mut move_u = pieces.queens() | pieces.rooks();
+            let mut move_d = pieces.queens() | pieces.rooks();
 
-            let mut move_ru = pieces.queens | pieces.bishops;
-            let mut move_lu = pieces.queens | pieces.bishops;
-            let mut move_rd = pieces.queens | pieces.bishops;
-            let mut move_ld = pieces.queens | pieces.bishops;
+            let mut move_ru = pieces.queens() | pieces.bishops();
+            let mut move_lu = pieces.queens() | pieces.bishops();
+            let mut move_rd = pieces.queens() | pieces.bishops();
+            let mut move_ld = pieces.queens() | pieces.bishops();
 
             for i in 1..8 {
-                move_r = (move_r & !other_all) << 1 & !0x101010101010101 & !pieces.all;
-                move_l = (move_l & !other_all) >> 1 & !0x8080808080808080 & !pieces.all;
+                move_r = (move_r & !other_all) << 1 & !FILE_A & !pieces.all;
+                move_l = (move_l & !other_all) >> 1 & !FILE_H & !pieces.all;
                 move_u = (move_u & !other_all) << 0o10 & !pieces.all;
                 move_d = (move_d & !other_all) >> 0o10 & !pieces.all;
 
-                move_ru = (move_ru & !other_all) << 0o11 & !0x101010101010101 & !pieces.all;
-                move_lu = (move_lu & !other_all) << 7 & !0x8080808080808080 & !pieces.all;
-                move_rd = (move_rd & !other_all) >> 7 & !0x101010101010101 & !pieces.all;
-                move_ld = (move_ld & !other_all) >> 0o11 & !0x8080808080808080 & !pieces.all;
+                move_ru = (move_ru & !other_all) << 0o11 & !FILE_A & !pieces.all;
+                move_lu = (move_lu & !other_all) << 7 & !FILE_H & !pieces.all;
+                move_rd = (move_rd & !other_all) >> 7 & !FILE_A & !pieces.all;
+                move_ld = (move_ld & !other_all) >> 0o11 & !FILE_H & !pieces.all;
 
                 if move_r | move_l | move_u | move_d | move_ru | move_lu | move_rd | move_ld == 0 {
                     break;
@@ -1263,7 +1610,7 @@ impl Board {
                         Move {
                             from: bit.trailing_zeros() as u8 - i,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit >> i & pieces.queens != 0 {
+                            ty: if bit >> i & pieces.queens() != 0 {
                                 MoveType::Queen
                             } else {
                                 MoveType::Rook
@@ -1278,7 +1625,7 @@ impl Board {
                         Move {
                             from: bit.trailing_zeros() as u8 + i,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit << i & pieces.queens != 0 {
+                            ty: if bit << i & pieces.queens() != 0 {
                                 MoveType::Queen
                             } else {
                                 MoveType::Rook
@@ -1293,7 +1640,7 @@ impl Board {
                         Move {
                             from: bit.trailing_zeros() as u8 - 0o10 * i,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit >> (0o10 * i) & pieces.queens != 0 {
+                            ty: if bit >> (0o10 * i) & pieces.queens() != 0 {
                                 MoveType::Queen
                             } else {
                                 MoveType::Rook
@@ -1308,7 +1655,7 @@ impl Board {
                         Move {
                             from: bit.trailing_zeros() as u8 + 0o10 * i,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit << (0o10 * i) & pieces.queens != 0 {
+                            ty: if bit << (0o10 * i) & pieces.queens() != 0 {
                                 MoveType::Queen
                             } else {
                                 MoveType::Rook
@@ -1323,7 +1670,7 @@ impl Board {
                         Move {
                             from: bit.trailing_zeros() as u8 - 0o11 * i,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit >> (0o11 * i) & pieces.queens != 0 {
+                            ty: if bit >> (0o11 * i) & pieces.queens() != 0 {
                                 MoveType::Queen
                             } else {
                                 MoveType::Bishop
@@ -1338,7 +1685,7 @@ impl Board {
                         Move {
                             from: bit.trailing_zeros() as u8 - 7 * i,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit >> (7 * i) & pieces.queens != 0 {
+                            ty: if bit >> (7 * i) & pieces.queens() != 0 {
                                 MoveType::Queen
                             } else {
                                 MoveType::Bishop
@@ -1353,7 +1700,7 @@ impl Board {
                         Move {
                             from: bit.trailing_zeros() as u8 + 7 * i,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit << (7 * i) & pieces.queens != 0 {
+                            ty: if bit << (7 * i) & pieces.queens() != 0 {
                                 MoveType::Queen
                             } else {
                                 MoveType::Bishop
@@ -1368,7 +1715,7 @@ impl Board {
                         Move {
                             from: bit.trailing_zeros() as u8 + 0o11 * i,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit << (0o11 * i) & pieces.queens != 0 {
+                            ty: if bit << (0o11 * i) & pieces.queens() != 0 {
                                 MoveType::Queen
                             } else {
                                 MoveType::Bishop
@@ -1381,11 +1728,11 @@ impl Board {
         }
 
         {
-            for knight in BitIterator(pieces.knights) {
-                let knight_moves = ((knight << 0o21 | knight >> 0o17) & !0x101010101010101
-                    | (knight << 0o17 | knight >> 0o21) & !0x8080808080808080
-                    | (knight << 0o12 | knight >> 6) & !0x303030303030303
-                    | (knight << 6 | knight >> 0o12) & !0xc0c0c0c0c0c0c0c0)
+            for knight in BitIterator(pieces.knights()) {
+                let knight_moves = ((knight << 0o21 | knight >> 0o17) & !FILE_A
+                    | (knight << 0o17 | knight >> 0o21) & !FILE_H
+                    | (knight << 0o12 | knight >> 6) & !(FILE_A | FILE_B)
+                    | (knight << 6 | knight >> 0o12) & !(FILE_G | FILE_H))
                     & !pieces.all;
 
                 let from = knight.trailing_zeros() as _;
@@ -1402,35 +1749,114 @@ impl Board {
             }
         }
 
-        moves
+        // Drops only block, so they can't expose the king unless it's already in check.
+        if self.reserves[color as usize] != [0; 6] {
+            let from = pieces.king().trailing_zeros() as u8;
+            let empty = self.empty();
+            for ty in [
+                PieceType::Queen,
+                PieceType::Rook,
+                PieceType::Bishop,
+                PieceType::Knight,
+                PieceType::Pawn,
+            ] {
+                if self.reserve(color, ty) == 0 {
+                    continue;
+                }
+                let targets = match ty {
+                    PieceType::Pawn => empty & !BACK_RANKS,
+                    _ => empty,
+                };
+                for bit in BitIterator(targets) {
+                    push_move(
+                        Move {
+                            from,
+                            to: bit.trailing_zeros() as _,
+                            ty: MoveType::drop(ty).unwrap(),
+                        },
+                        !check,
+                    );
+                }
+            }
+        }
     }
 
+    #[inline]
     pub fn capture_moves(&self, color: Color) -> Vec<Move> {
+        self.capture_moves_with_attack(color, self.check_attack(color.inv()))
+    }
+
+    /// Like `capture_moves()`, but reuses an already computed `check_attack(color.inv())`.
+    #[inline]
+    pub fn capture_moves_with_attack(&self, color: Color, other_attack: u64) -> Vec<Move> {
         let mut moves = Vec::new();
+        self.capture_moves_into(color, other_attack, &mut moves);
+        moves
+    }
 
+    /// The capturing counterpart of `moves_into()`. Queen promotions count as captures, they're as
+    /// forcing.
+    pub fn capture_moves_into<E: Extend<Move>>(
+        &self,
+        color: Color,
+        other_attack: u64,
+        moves: &mut E,
+    ) {
         let pieces = self.get_pieces(color);
         let pins = self.find_pins(color);
-        let other_all = self.get_pieces(color.inv()).all;
-        let other_attack = self.check_attack(color.inv());
+        let other_all = self.occupied_by(color.inv());
 
-        let check = pieces.king & other_attack != 0;
+        let check = pieces.king() & other_attack != 0;
+        let evasions = self.evasions(color, check);
+        let king_square = pieces.king().trailing_zeros() as usize;
 
         let mut push_move = |mv: Move, dont_check_king_safety: bool| {
-            if dont_check_king_safety || !check && 1 << mv.from & pins == 0 {
-                moves.push(mv);
-            } else {
+            if dont_check_king_safety {
+                moves.extend(Some(mv));
+            } else if mv.ty == MoveType::PawnEnPassant {
+                // En passant takes two pawns off a rank, which can expose the king along it even
+                // without a pin.
                 let mut board = *self;
-                board.perform_move(mv);
-                if board.check_attack(color.inv()) & board.get_pieces(color).king == 0 {
-                    moves.push(mv);
+                board.perform_move_unchecked(mv);
+                if !board.in_check(color) {
+                    moves.extend(Some(mv));
+                }
+            } else {
+                // A pinned piece can only move along the pin.
+                let pin_line = match 1 << mv.from & pins {
+                    0 => !0,
+                    _ => LINE[king_square][mv.from as usize],
+                };
+                if 1 << mv.to & evasions & pin_line != 0 {
+                    moves.extend(Some(mv));
                 }
             }
         };
 
+        {
+            let king_moves = self.king_targets(color, other_all);
+            for bit in BitIterator(king_moves) {
+                push_move(
+                    Move {
+                        from: pieces.king().trailing_zeros() as _,
+                        to: bit.trailing_zeros() as _,
+                        ty: MoveType::King,
+                    },
+                    true,
+                );
+            }
+        }
+
+        // In double check only the king can move.
+        if evasions == 0 {
+            return;
+        }
+
+        let all = self.occupied();
         match color {
             Color::White => {
                 if self.prev_move.ty == MoveType::PawnLeap {
-                    if 1 << (self.prev_move.to + 1) & pieces.pawns & !0x101010101010101 != 0 {
+                    if 1 << (self.prev_move.to + 1) & pieces.pawns() & !FILE_A != 0 {
                         push_move(
                             Move {
                                 from: self.prev_move.to + 1,
@@ -1440,7 +1866,7 @@ impl Board {
                             false,
                         );
                     }
-                    if 1 << (self.prev_move.to - 1) & pieces.pawns & !0x8080808080808080 != 0 {
+                    if 1 << (self.prev_move.to - 1) & pieces.pawns() & !FILE_H != 0 {
                         push_move(
                             Move {
                                 from: self.prev_move.to - 1,
@@ -1452,12 +1878,22 @@ impl Board {
                     }
                 }
 
-                for bit in BitIterator(pieces.pawns << 0o11 & !0x101010101010101 & other_all) {
+                for bit in BitIterator(pieces.pawns() << 0o10 & !all & RANK_8) {
+                    push_move(
+                        Move {
+                            from: bit.trailing_zeros() as u8 - 0o10,
+                            to: bit.trailing_zeros() as _,
+                            ty: MoveType::PawnQueenPromotion,
+                        },
+                        false,
+                    );
+                }
+                for bit in BitIterator(pieces.pawns() << 0o11 & !FILE_A & other_all) {
                     push_move(
                         Move {
                             from: bit.trailing_zeros() as u8 - 0o11,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit & 0xff << 0o70 == 0 {
+                            ty: if bit & RANK_8 == 0 {
                                 MoveType::Pawn
                             } else {
                                 MoveType::PawnQueenPromotion
@@ -1466,12 +1902,12 @@ impl Board {
                         false,
                     );
                 }
-                for bit in BitIterator(pieces.pawns << 7 & !0x8080808080808080 & other_all) {
+                for bit in BitIterator(pieces.pawns() << 7 & !FILE_H & other_all) {
                     push_move(
                         Move {
                             from: bit.trailing_zeros() as u8 - 7,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit & 0xff << 0o70 == 0 {
+                            ty: if bit & RANK_8 == 0 {
                                 MoveType::Pawn
                             } else {
                                 MoveType::PawnQueenPromotion
@@ -1483,7 +1919,7 @@ impl Board {
             }
             Color::Black => {
                 if self.prev_move.ty == MoveType::PawnLeap {
-                    if 1 << (self.prev_move.to + 1) & pieces.pawns & !0x101010101010101 != 0 {
+                    if 1 << (self.prev_move.to + 1) & pieces.pawns() & !FILE_A != 0 {
                         push_move(
                             Move {
                                 from: self.prev_move.to + 1,
@@ -1493,7 +1929,7 @@ impl Board {
                             false,
                         );
                     }
-                    if 1 << (self.prev_move.to - 1) & pieces.pawns & !0x8080808080808080 != 0 {
+                    if 1 << (self.prev_move.to - 1) & pieces.pawns() & !FILE_H != 0 {
                         push_move(
                             Move {
                                 from: self.prev_move.to - 1,
@@ -1505,12 +1941,22 @@ impl Board {
                     }
                 }
 
-                for bit in BitIterator(pieces.pawns >> 0o11 & !0x8080808080808080 & other_all) {
+                for bit in BitIterator(pieces.pawns() >> 0o10 & !all & RANK_1) {
+                    push_move(
+                        Move {
+                            from: bit.trailing_zeros() as u8 + 0o10,
+                            to: bit.trailing_zeros() as _,
+                            ty: MoveType::PawnQueenPromotion,
+                        },
+                        false,
+                    );
+                }
+                for bit in BitIterator(pieces.pawns() >> 0o11 & !FILE_H & other_all) {
                     push_move(
                         Move {
                             from: bit.trailing_zeros() as u8 + 0o11,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit & 0xff == 0 {
+                            ty: if bit & RANK_1 == 0 {
                                 MoveType::Pawn
                             } else {
                                 MoveType::PawnQueenPromotion
@@ -1519,12 +1965,12 @@ impl Board {
                         false,
                     );
                 }
-                for bit in BitIterator(pieces.pawns >> 7 & !0x101010101010101 & other_all) {
+                for bit in BitIterator(pieces.pawns() >> 7 & !FILE_A & other_all) {
                     push_move(
                         Move {
                             from: bit.trailing_zeros() as u8 + 7,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit & 0xff == 0 {
+                            ty: if bit & RANK_1 == 0 {
                                 MoveType::Pawn
                             } else {
                                 MoveType::PawnQueenPromotion
@@ -1537,47 +1983,26 @@ impl Board {
         }
 
         {
-            let king_moves = ((pieces.king << 1 | pieces.king << 0o11 | pieces.king >> 7)
-                & !0x101010101010101
-                | (pieces.king >> 1 | pieces.king >> 0o11 | pieces.king << 7)
-                    & !0x8080808080808080
-                | pieces.king << 0o10
-                | pieces.king >> 0o10)
-                & other_all
-                & !other_attack;
-            for bit in BitIterator(king_moves) {
-                push_move(
-                    Move {
-                        from: pieces.king.trailing_zeros() as _,
-                        to: bit.trailing_zeros() as _,
-                        ty: MoveType::King,
-                    },
-                    true,
-                );
-            }
-        }
-
-        {
-            let mut move_r = pieces.queens | pieces.rooks;
-            let mut move_l = pieces.queens | pieces.rooks;
-            let mut move_u = pieces.queens | pieces.rooks;
-            let mut move_d = pieces.queens | pieces.rooks;
+            let mut move_r = pieces.queens() | pieces.rooks();
+            let mut move_l = pieces.queens() | pieces.rooks();
+            let mut move_u = pieces.queens() | pieces.rooks();
+            let mut move_d = pieces.queens() | pieces.rooks();
 
-            let mut move_ru = pieces.queens | pieces.bishops;
-            let mut move_lu = pieces.queens | pieces.bishops;
-            let mut move_rd = pieces.queens | pieces.bishops;
-            let mut move_ld = pieces.queens | pieces.bishops;
+            let mut move_ru = pieces.queens() | pieces.bishops();
+            let mut move_lu = pieces.queens() | pieces.bishops();
+            let mut move_rd = pieces.queens() | pieces.bishops();
+            let mut move_ld = pieces.queens() | pieces.bishops();
 
             for i in 1..8 {
-                move_r = (move_r & !other_all) << 1 & !0x101010101010101 & !pieces.all;
-                move_l = (move_l & !other_all) >> 1 & !0x8080808080808080 & !pieces.all;
+                move_r = (move_r & !other_all) << 1 & !FILE_A & !pieces.all;
+                move_l = (move_l & !other_all) >> 1 & !FILE_H & !pieces.all;
                 move_u = (move_u & !other_all) << 0o10 & !pieces.all;
                 move_d = (move_d & !other_all) >> 0o10 & !pieces.all;
 
-                move_ru = (move_ru & !other_all) << 0o11 & !0x101010101010101 & !pieces.all;
-                move_lu = (move_lu & !other_all) << 7 & !0x8080808080808080 & !pieces.all;
-                move_rd = (move_rd & !other_all) >> 7 & !0x101010101010101 & !pieces.all;
-                move_ld = (move_ld & !other_all) >> 0o11 & !0x8080808080808080 & !pieces.all;
+                move_ru = (move_ru & !other_all) << 0o11 & !FILE_A & !pieces.all;
+                move_lu = (move_lu & !other_all) << 7 & !FILE_H & !pieces.all;
+                move_rd = (move_rd & !other_all) >> 7 & !FILE_A & !pieces.all;
+                move_ld = (move_ld & !other_all) >> 0o11 & !FILE_H & !pieces.all;
 
                 if move_r | move_l | move_u | move_d | move_ru | move_lu | move_rd | move_ld == 0 {
                     break;
@@ -1588,7 +2013,7 @@ impl Board {
                         Move {
                             from: bit.trailing_zeros() as u8 - i,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit >> i & pieces.queens != 0 {
+                            ty: if bit >> i & pieces.queens() != 0 {
                                 MoveType::Queen
                             } else {
                                 MoveType::Rook
@@ -1603,7 +2028,7 @@ impl Board {
                         Move {
                             from: bit.trailing_zeros() as u8 + i,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit << i & pieces.queens != 0 {
+                            ty: if bit << i & pieces.queens() != 0 {
                                 MoveType::Queen
                             } else {
                                 MoveType::Rook
@@ -1618,7 +2043,7 @@ impl Board {
                         Move {
                             from: bit.trailing_zeros() as u8 - 0o10 * i,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit >> (0o10 * i) & pieces.queens != 0 {
+                            ty: if bit >> (0o10 * i) & pieces.queens() != 0 {
                                 MoveType::Queen
                             } else {
                                 MoveType::Rook
@@ -1633,7 +2058,7 @@ impl Board {
                         Move {
                             from: bit.trailing_zeros() as u8 + 0o10 * i,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit << (0o10 * i) & pieces.queens != 0 {
+                            ty: if bit << (0o10 * i) & pieces.queens() != 0 {
                                 MoveType::Queen
                             } else {
                                 MoveType::Rook
@@ -1648,7 +2073,7 @@ impl Board {
                         Move {
                             from: bit.trailing_zeros() as u8 - 0o11 * i,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit >> (0o11 * i) & pieces.queens != 0 {
+                            ty: if bit >> (0o11 * i) & pieces.queens() != 0 {
                                 MoveType::Queen
                             } else {
                                 MoveType::Bishop
@@ -1663,7 +2088,7 @@ impl Board {
                         Move {
                             from: bit.trailing_zeros() as u8 - 7 * i,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit >> (7 * i) & pieces.queens != 0 {
+                            ty: if bit >> (7 * i) & pieces.queens() != 0 {
                                 MoveType::Queen
                             } else {
                                 MoveType::Bishop
@@ -1678,7 +2103,7 @@ impl Board {
                         Move {
                             from: bit.trailing_zeros() as u8 + 7 * i,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit << (7 * i) & pieces.queens != 0 {
+                            ty: if bit << (7 * i) & pieces.queens() != 0 {
                                 MoveType::Queen
                             } else {
                                 MoveType::Bishop
@@ -1693,7 +2118,7 @@ impl Board {
                         Move {
                             from: bit.trailing_zeros() as u8 + 0o11 * i,
                             to: bit.trailing_zeros() as _,
-                            ty: if bit << (0o11 * i) & pieces.queens != 0 {
+                            ty: if bit << (0o11 * i) & pieces.queens() != 0 {
                                 MoveType::Queen
                             } else {
                                 MoveType::Bishop
@@ -1706,11 +2131,11 @@ impl Board {
         }
 
         {
-            for knight in BitIterator(pieces.knights) {
-                let knight_moves = ((knight << 0o21 | knight >> 0o17) & !0x101010101010101
-                    | (knight << 0o17 | knight >> 0o21) & !0x8080808080808080
-                    | (knight << 0o12 | knight >> 6) & !0x303030303030303
-                    | (knight << 6 | knight >> 0o12) & !0xc0c0c0c0c0c0c0c0)
+            for knight in BitIterator(pieces.knights()) {
+                let knight_moves = ((knight << 0o21 | knight >> 0o17) & !FILE_A
+                    | (knight << 0o17 | knight >> 0o21) & !FILE_H
+                    | (knight << 0o12 | knight >> 6) & !(FILE_A | FILE_B)
+                    | (knight << 6 | knight >> 0o12) & !(FILE_G | FILE_H))
                     & other_all;
 
                 let from = knight.trailing_zeros() as _;
@@ -1726,12 +2151,62 @@ impl Board {
                 }
             }
         }
+    }
 
-        moves
+    /// Passes `color`'s turn: clears en passant and returns the side to move after the pass,
+    /// `color.inv()`, with what `undo_null_move()` takes the pass back with. `Board` stores
+    /// neither the side to move nor the hash, the key after the pass is `zobrist(color.inv())`.
+    pub fn make_null_move(&mut self, color: Color) -> (Color, NullMoveUndo) {
+        let undo = NullMoveUndo {
+            prev_move: core::mem::replace(&mut self.prev_move, Move::NULL),
+        };
+        (color.inv(), undo)
     }
 
-    pub fn perform_move(&mut self, mv: Move) {
+    /// Takes back the pass of `make_null_move()`, the side to move is the one that passed again.
+    pub fn undo_null_move(&mut self, undo: NullMoveUndo) {
+        self.prev_move = undo.prev_move;
+    }
+
+    /// Performs `mv` of `color` if it's legal, otherwise leaves the board untouched.
+    pub fn apply(&mut self, color: Color, mv: Move) -> Result<(), MoveError> {
+        if mv.from < 64 && mv.to < 64 && self.is_legal(color, mv) {
+            self.perform_move_unchecked(mv);
+            Ok(())
+        } else {
+            Err(MoveError(mv))
+        }
+    }
+
+    /// Performs `mv` without checking it, for the moves the move generation produced. An illegal
+    /// move corrupts the board, `apply()` checks moves from anywhere else.
+    pub fn perform_move_unchecked(&mut self, mv: Move) {
+        debug_assert!(
+            self.occupied() & 1 << mv.from != 0,
+            "No piece to move: {}",
+            mv
+        );
         self.prev_move = mv;
+        // Horde's leaps from the first rank can't be taken en passant.
+        if mv.ty == MoveType::PawnLeap && mv.from < 0o10 {
+            self.prev_move.ty = MoveType::Pawn;
+        }
+
+        if let Some(ty) = mv.ty.dropped() {
+            let pieces = if self.white_pieces.all & 1 << mv.from != 0 {
+                self.reserves[Color::White as usize][ty as usize] -= 1;
+                &mut self.white_pieces
+            } else {
+                self.reserves[Color::Black as usize][ty as usize] -= 1;
+                &mut self.black_pieces
+            };
+            pieces.all |= 1 << mv.to;
+            *pieces.get_mut(ty) |= 1 << mv.to;
+            return;
+        }
+        if self.variant == Variant::Crazyhouse {
+            self.pocket_capture(mv);
+        }
 
         let color = if 1 << mv.from & self.white_pieces.all != 0 {
             self.white_pieces.all &= !(1 << mv.from);
@@ -1747,245 +2222,243 @@ impl Board {
         match mv.ty {
             MoveType::King => match color {
                 Color::White => {
-                    self.white_pieces.king = 1 << mv.to;
+                    *self.white_pieces.get_mut(PieceType::King) = 1 << mv.to;
 
                     self.black_pieces.clear(1 << mv.to);
                 }
                 Color::Black => {
-                    self.black_pieces.king = 1 << mv.to;
+                    *self.black_pieces.get_mut(PieceType::King) = 1 << mv.to;
 
                     self.white_pieces.clear(1 << mv.to);
                 }
             },
             MoveType::Queen => match color {
                 Color::White => {
-                    self.white_pieces.queens &= !(1 << mv.from);
-                    self.white_pieces.queens |= 1 << mv.to;
+                    *self.white_pieces.get_mut(PieceType::Queen) &= !(1 << mv.from);
+                    *self.white_pieces.get_mut(PieceType::Queen) |= 1 << mv.to;
 
                     self.black_pieces.clear(1 << mv.to);
                 }
                 Color::Black => {
-                    self.black_pieces.queens &= !(1 << mv.from);
-                    self.black_pieces.queens |= 1 << mv.to;
+                    *self.black_pieces.get_mut(PieceType::Queen) &= !(1 << mv.from);
+                    *self.black_pieces.get_mut(PieceType::Queen) |= 1 << mv.to;
 
                     self.white_pieces.clear(1 << mv.to);
                 }
             },
             MoveType::Rook => match color {
                 Color::White => {
-                    self.white_pieces.rooks &= !(1 << mv.from);
-                    self.white_pieces.rooks |= 1 << mv.to;
+                    *self.white_pieces.get_mut(PieceType::Rook) &= !(1 << mv.from);
+                    *self.white_pieces.get_mut(PieceType::Rook) |= 1 << mv.to;
 
                     self.black_pieces.clear(1 << mv.to);
                 }
                 Color::Black => {
-                    self.black_pieces.rooks &= !(1 << mv.from);
-                    self.black_pieces.rooks |= 1 << mv.to;
+                    *self.black_pieces.get_mut(PieceType::Rook) &= !(1 << mv.from);
+                    *self.black_pieces.get_mut(PieceType::Rook) |= 1 << mv.to;
 
                     self.white_pieces.clear(1 << mv.to);
                 }
             },
             MoveType::Bishop => match color {
                 Color::White => {
-                    self.white_pieces.bishops &= !(1 << mv.from);
-                    self.white_pieces.bishops |= 1 << mv.to;
+                    *self.white_pieces.get_mut(PieceType::Bishop) &= !(1 << mv.from);
+                    *self.white_pieces.get_mut(PieceType::Bishop) |= 1 << mv.to;
 
                     self.black_pieces.clear(1 << mv.to);
                 }
                 Color::Black => {
-                    self.black_pieces.bishops &= !(1 << mv.from);
-                    self.black_pieces.bishops |= 1 << mv.to;
+                    *self.black_pieces.get_mut(PieceType::Bishop) &= !(1 << mv.from);
+                    *self.black_pieces.get_mut(PieceType::Bishop) |= 1 << mv.to;
 
                     self.white_pieces.clear(1 << mv.to);
                 }
             },
             MoveType::Knight => match color {
                 Color::White => {
-                    self.white_pieces.knights &= !(1 << mv.from);
-                    self.white_pieces.knights |= 1 << mv.to;
+                    *self.white_pieces.get_mut(PieceType::Knight) &= !(1 << mv.from);
+                    *self.white_pieces.get_mut(PieceType::Knight) |= 1 << mv.to;
 
                     self.black_pieces.clear(1 << mv.to);
                 }
                 Color::Black => {
-                    self.black_pieces.knights &= !(1 << mv.from);
-                    self.black_pieces.knights |= 1 << mv.to;
+                    *self.black_pieces.get_mut(PieceType::Knight) &= !(1 << mv.from);
+                    *self.black_pieces.get_mut(PieceType::Knight) |= 1 << mv.to;
 
                     self.white_pieces.clear(1 << mv.to);
                 }
             },
             MoveType::Pawn => match color {
                 Color::White => {
-                    self.white_pieces.pawns &= !(1 << mv.from);
-                    self.white_pieces.pawns |= 1 << mv.to;
+                    *self.white_pieces.get_mut(PieceType::Pawn) &= !(1 << mv.from);
+                    *self.white_pieces.get_mut(PieceType::Pawn) |= 1 << mv.to;
 
                     self.black_pieces.clear(1 << mv.to);
                 }
                 Color::Black => {
-                    self.black_pieces.pawns &= !(1 << mv.from);
-                    self.black_pieces.pawns |= 1 << mv.to;
+                    *self.black_pieces.get_mut(PieceType::Pawn) &= !(1 << mv.from);
+                    *self.black_pieces.get_mut(PieceType::Pawn) |= 1 << mv.to;
 
                     self.white_pieces.clear(1 << mv.to);
                 }
             },
             MoveType::PawnLeap => match color {
                 Color::White => {
-                    self.white_pieces.pawns &= !(1 << mv.from);
-                    self.white_pieces.pawns |= 1 << mv.to;
+                    *self.white_pieces.get_mut(PieceType::Pawn) &= !(1 << mv.from);
+                    *self.white_pieces.get_mut(PieceType::Pawn) |= 1 << mv.to;
                 }
                 Color::Black => {
-                    self.black_pieces.pawns &= !(1 << mv.from);
-                    self.black_pieces.pawns |= 1 << mv.to;
+                    *self.black_pieces.get_mut(PieceType::Pawn) &= !(1 << mv.from);
+                    *self.black_pieces.get_mut(PieceType::Pawn) |= 1 << mv.to;
                 }
             },
             MoveType::PawnEnPassant => match color {
                 Color::White => {
                     self.black_pieces.all &= !(1 << (mv.to - 0o10));
-                    self.black_pieces.pawns &= !(1 << (mv.to - 0o10));
+                    *self.black_pieces.get_mut(PieceType::Pawn) &= !(1 << (mv.to - 0o10));
 
-                    self.white_pieces.pawns &= !(1 << mv.from);
-                    self.white_pieces.pawns |= 1 << mv.to;
+                    *self.white_pieces.get_mut(PieceType::Pawn) &= !(1 << mv.from);
+                    *self.white_pieces.get_mut(PieceType::Pawn) |= 1 << mv.to;
                 }
                 Color::Black => {
                     self.white_pieces.all &= !(1 << (mv.to + 0o10));
-                    self.white_pieces.pawns &= !(1 << (mv.to + 0o10));
+                    *self.white_pieces.get_mut(PieceType::Pawn) &= !(1 << (mv.to + 0o10));
 
-                    self.black_pieces.pawns &= !(1 << mv.from);
-                    self.black_pieces.pawns |= 1 << mv.to;
+                    *self.black_pieces.get_mut(PieceType::Pawn) &= !(1 << mv.from);
+                    *self.black_pieces.get_mut(PieceType::Pawn) |= 1 << mv.to;
                 }
             },
             MoveType::PawnQueenPromotion => match color {
                 Color::White => {
-                    self.white_pieces.pawns &= !(1 << mv.from);
-                    self.white_pieces.queens |= 1 << mv.to;
+                    *self.white_pieces.get_mut(PieceType::Pawn) &= !(1 << mv.from);
+                    *self.white_pieces.get_mut(PieceType::Queen) |= 1 << mv.to;
 
                     self.black_pieces.clear(1 << mv.to);
                 }
                 Color::Black => {
-                    self.black_pieces.pawns &= !(1 << mv.from);
-                    self.black_pieces.queens |= 1 << mv.to;
+                    *self.black_pieces.get_mut(PieceType::Pawn) &= !(1 << mv.from);
+                    *self.black_pieces.get_mut(PieceType::Queen) |= 1 << mv.to;
 
                     self.white_pieces.clear(1 << mv.to);
                 }
             },
             MoveType::PawnRookPromotion => match color {
                 Color::White => {
-                    self.white_pieces.pawns &= !(1 << mv.from);
-                    self.white_pieces.rooks |= 1 << mv.to;
+                    *self.white_pieces.get_mut(PieceType::Pawn) &= !(1 << mv.from);
+                    *self.white_pieces.get_mut(PieceType::Rook) |= 1 << mv.to;
 
                     self.black_pieces.clear(1 << mv.to);
                 }
                 Color::Black => {
-                    self.black_pieces.pawns &= !(1 << mv.from);
-                    self.black_pieces.rooks |= 1 << mv.to;
+                    *self.black_pieces.get_mut(PieceType::Pawn) &= !(1 << mv.from);
+                    *self.black_pieces.get_mut(PieceType::Rook) |= 1 << mv.to;
 
                     self.white_pieces.clear(1 << mv.to);
                 }
             },
             MoveType::PawnBishopPromotion => match color {
                 Color::White => {
-                    self.white_pieces.pawns &= !(1 << mv.from);
-                    self.white_pieces.bishops |= 1 << mv.to;
+                    *self.white_pieces.get_mut(PieceType::Pawn) &= !(1 << mv.from);
+                    *self.white_pieces.get_mut(PieceType::Bishop) |= 1 << mv.to;
 
                     self.black_pieces.clear(1 << mv.to);
                 }
                 Color::Black => {
-                    self.black_pieces.pawns &= !(1 << mv.from);
-                    self.black_pieces.bishops |= 1 << mv.to;
+                    *self.black_pieces.get_mut(PieceType::Pawn) &= !(1 << mv.from);
+                    *self.black_pieces.get_mut(PieceType::Bishop) |= 1 << mv.to;
 
                     self.white_pieces.clear(1 << mv.to);
                 }
             },
             MoveType::PawnKnightPromotion => match color {
                 Color::White => {
-                    self.white_pieces.pawns &= !(1 << mv.from);
-                    self.white_pieces.knights |= 1 << mv.to;
+                    *self.white_pieces.get_mut(PieceType::Pawn) &= !(1 << mv.from);
+                    *self.white_pieces.get_mut(PieceType::Knight) |= 1 << mv.to;
 
                     self.black_pieces.clear(1 << mv.to);
                 }
                 Color::Black => {
-                    self.black_pieces.pawns &= !(1 << mv.from);
-                    self.black_pieces.knights |= 1 << mv.to;
+                    *self.black_pieces.get_mut(PieceType::Pawn) &= !(1 << mv.from);
+                    *self.black_pieces.get_mut(PieceType::Knight) |= 1 << mv.to;
 
                     self.white_pieces.clear(1 << mv.to);
                 }
             },
-            MoveType::Castle => match mv.to {
-                2 => {
-                    self.white_pieces.king = 4;
-
-                    self.white_pieces.all &= !1;
-                    self.white_pieces.rooks &= !1;
-
-                    self.white_pieces.all |= 8;
-                    self.white_pieces.rooks |= 8;
-                }
-                6 => {
-                    self.white_pieces.king = 0x40;
-
-                    self.white_pieces.all &= !0x80;
-                    self.white_pieces.rooks &= !0x80;
-
-                    self.white_pieces.all |= 0x20;
-                    self.white_pieces.rooks |= 0x20;
-                }
-                0o72 => {
-                    self.black_pieces.king = 1 << 0o72;
+            // A castle to a square no castle goes to moves nothing.
+            MoveType::Castle => {
+                if let Some(castle) = Castle::to(mv.to) {
+                    let pieces = match castle.color {
+                        Color::White => &mut self.white_pieces,
+                        Color::Black => &mut self.black_pieces,
+                    };
+                    *pieces.get_mut(PieceType::King) = 1 << castle.king_to;
 
-                    self.black_pieces.all &= !(1 << 0o70);
-                    self.black_pieces.rooks &= !(1 << 0o70);
+                    pieces.all &= !(1 << castle.rook_from);
+                    *pieces.get_mut(PieceType::Rook) &= !(1 << castle.rook_from);
 
-                    self.black_pieces.all |= 1 << 0o73;
-                    self.black_pieces.rooks |= 1 << 0o73;
+                    pieces.all |= 1 << castle.rook_to;
+                    *pieces.get_mut(PieceType::Rook) |= 1 << castle.rook_to;
                 }
-                0o76 => {
-                    self.black_pieces.king = 1 << 0o76;
-
-                    self.black_pieces.all &= !(1 << 0o77);
-                    self.black_pieces.rooks &= !(1 << 0o77);
+            }
+            MoveType::DropQueen
+            | MoveType::DropRook
+            | MoveType::DropBishop
+            | MoveType::DropKnight
+            | MoveType::DropPawn => unreachable!("Drops are made above"),
+        }
 
-                    self.black_pieces.all |= 1 << 0o75;
-                    self.black_pieces.rooks |= 1 << 0o75;
-                }
-                _ => panic!("Illigal castle accidentally cought"),
-            },
+        // A castle's right is gone once its king or rook has left its square.
+        for castle in &CASTLES {
+            let pieces = self.get_pieces(castle.color);
+            if pieces.king() & 1 << castle.king_from == 0
+                || pieces.rooks() & 1 << castle.rook_from == 0
+            {
+                self.flags.remove(castle.right);
+            }
         }
+    }
 
-        self.flags.remove(if self.white_pieces.king == 0x10 {
-            ChessFlags::empty()
-        } else {
-            ChessFlags::WHITE_KINGS_CASTLE | ChessFlags::WHITE_QUEENS_CASTLE
-        });
-        self.flags
-            .remove(if self.black_pieces.king == 0x10 << 0o70 {
-                ChessFlags::empty()
-            } else {
-                ChessFlags::WHITE_KINGS_CASTLE | ChessFlags::WHITE_QUEENS_CASTLE
-            });
-        self.flags.remove(if self.white_pieces.rooks & 1 != 0 {
-            ChessFlags::empty()
+    /// Puts the piece that `mv` captures into the mover's reserve, and moves the mark of a
+    /// promoted piece along.
+    fn pocket_capture(&mut self, mv: Move) {
+        let (from, to) = (1 << mv.from, 1 << mv.to);
+        let (color, other) = if self.white_pieces.all & from != 0 {
+            (Color::White, &self.black_pieces)
         } else {
-            ChessFlags::WHITE_QUEENS_CASTLE
-        });
-        self.flags.remove(if self.white_pieces.rooks & 0x80 != 0 {
-            ChessFlags::empty()
-        } else {
-            ChessFlags::WHITE_KINGS_CASTLE
-        });
-        self.flags
-            .remove(if self.white_pieces.rooks & 1 << 0o70 != 0 {
-                ChessFlags::empty()
-            } else {
-                ChessFlags::BLACK_QUEENS_CASTLE
-            });
-        self.flags
-            .remove(if self.white_pieces.rooks & 1 << 0o77 != 0 {
-                ChessFlags::empty()
-            } else {
-                ChessFlags::BLACK_KINGS_CASTLE
-            });
+            (Color::Black, &self.white_pieces)
+        };
+        let captured = match mv.ty {
+            MoveType::PawnEnPassant => Some(PieceType::Pawn),
+            _ if self.promoted & to != 0 => other.get_at(to).map(|_| PieceType::Pawn),
+            _ => other.get_at(to),
+        };
+        if let Some(ty) = captured {
+            self.reserves[color as usize][ty as usize] += 1;
+        }
+
+        let promotes = matches!(
+            mv.ty,
+            MoveType::PawnQueenPromotion
+                | MoveType::PawnRookPromotion
+                | MoveType::PawnBishopPromotion
+                | MoveType::PawnKnightPromotion
+        );
+        let moved = self.promoted & from != 0;
+        self.promoted &= !(from | to);
+        if moved || promotes {
+            self.promoted |= to;
+        }
     }
 
-    pub fn get_legal_move(&self, color: Color, from: u8, to: u8) -> Option<Move> {
+    /// The legal move from `from` to `to`. Promotions are to `promotion`, or to a queen when it's
+    /// `None`, and a `promotion` for a move that doesn't promote makes it illegal.
+    pub fn get_legal_move(
+        &self,
+        color: Color,
+        from: u8,
+        to: u8,
+        promotion: Option<PieceType>,
+    ) -> Option<Move> {
         let piece = self.get_at(1 << from)?;
         if piece.color != color {
             return None;
@@ -1996,7 +2469,7 @@ impl Board {
             to,
             ty: match piece.ty {
                 PieceType::King => {
-                    let diff = if from <= to { to - from } else { from - to };
+                    let diff = to.abs_diff(from);
 
                     if diff == 2 {
                         MoveType::Castle
@@ -2009,13 +2482,19 @@ impl Board {
                 PieceType::Bishop => MoveType::Bishop,
                 PieceType::Knight => MoveType::Knight,
                 PieceType::Pawn => {
-                    let diff = if from <= to { to - from } else { from - to };
+                    let diff = to.abs_diff(from);
 
                     if diff == 0o20 {
                         MoveType::PawnLeap
-                    } else if diff == 0o10 || self.get_pieces(color.inv()).all & 1 << to != 0 {
+                    } else if diff == 0o10 || self.occupied_by(color.inv()) & 1 << to != 0 {
                         if !(0o10..0o70).contains(&to) {
-                            MoveType::PawnQueenPromotion
+                            match promotion {
+                                None | Some(PieceType::Queen) => MoveType::PawnQueenPromotion,
+                                Some(PieceType::Rook) => MoveType::PawnRookPromotion,
+                                Some(PieceType::Bishop) => MoveType::PawnBishopPromotion,
+                                Some(PieceType::Knight) => MoveType::PawnKnightPromotion,
+                                Some(PieceType::King | PieceType::Pawn) => return None,
+                            }
                         } else {
                             MoveType::Pawn
                         }
@@ -2025,6 +2504,16 @@ impl Board {
                 }
             },
         };
+        let promotes = matches!(
+            mv.ty,
+            MoveType::PawnQueenPromotion
+                | MoveType::PawnRookPromotion
+                | MoveType::PawnBishopPromotion
+                | MoveType::PawnKnightPromotion
+        );
+        if promotion.is_some() && !promotes {
+            return None;
+        }
 
         if self.is_legal(color, mv) {
             Some(mv)
@@ -2033,57 +2522,138 @@ impl Board {
         }
     }
 
-    pub fn print(&self, color: Color) {
-        match color {
-            Color::White => {
-                for i in (0..64).step_by(8).rev() {
-                    print!("{}", 1 + i / 8);
-                    for j in i..i + 8 {
-                        print!(
-                            " {}",
-                            match self.get_at(1 << j) {
-                                None => {
-                                    if (j ^ j >> 3) & 1 == 0 {
-                                        '\u{25FC}'
-                                    } else {
-                                        '\u{25FB}'
-                                    }
-                                }
-                                Some(piece) => piece.to_char(),
-                            }
-                        );
-                    }
-                    println!();
-                }
-            }
-            Color::Black => {
-                for i in (0..64).step_by(8) {
-                    print!("{}", 1 + i / 8);
-                    for j in i..i + 8 {
-                        print!(
-                            " {}",
-                            match self.get_at(1 << j) {
-                                None => {
-                                    if (j ^ j >> 3) & 1 == 0 {
-                                        '\u{25FC}'
-                                    } else {
-                                        '\u{25FB}'
-                                    }
-                                }
-                                Some(piece) => piece.to_char(),
+    /// The legal drop of a `ty` from `color`'s reserve on `to`.
+    pub fn get_legal_drop(&self, color: Color, ty: PieceType, to: u8) -> Option<Move> {
+        let mv = Move {
+            from: self.get_pieces(color).king().trailing_zeros() as _,
+            to,
+            ty: MoveType::drop(ty)?,
+        };
+        if mv.from < 64 && self.is_legal(color, mv) {
+            Some(mv)
+        } else {
+            None
+        }
+    }
+
+    /// The bitboard of the squares the piece on `from` can legally move to, as if its side were to
+    /// move, for highlighting them. 0 for an empty square.
+    pub fn legal_destinations(&self, from: Square) -> u64 {
+        let color = match self.get_at(from.bit()) {
+            Some(piece) => piece.color,
+            None => return 0,
+        };
+        self.moves(color)
+            .iter()
+            .filter(|mv| mv.from == from.index() && mv.ty.dropped().is_none())
+            .fold(0, |destinations, mv| destinations | 1 << mv.to)
+    }
+
+    /// Counts the leaf nodes of the legal move tree of the given depth. Unlike `moves()` it
+    /// includes the underpromotions, so the counts match the usual perft tables.
+    pub fn perft(&self, color: Color, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let moves = self.moves(color);
+        if depth == 1 {
+            return moves
+                .iter()
+                .map(|&mv| promotion_choices(mv).count() as u64)
+                .sum();
+        }
+
+        moves
+            .into_iter()
+            .flat_map(promotion_choices)
+            .map(|mv| {
+                let mut board = *self;
+                board.perform_move_unchecked(mv);
+                board.perft(color.inv(), depth - 1)
+            })
+            .sum()
+    }
+
+    /// Renders the board from `color`'s side, `color` at the bottom whoever is to move, the same
+    /// way `print()` shows it. From Black's side the board is turned around: rank 1 is at the top
+    /// and the h-file on the left, and so are the rank numbers and the file letters.
+    pub fn render(&self, color: Color) -> String {
+        use fmt::Write;
+
+        let mut s = String::new();
+
+        let (rows, files): (Vec<u8>, Vec<u8>) = match color {
+            Color::White => ((0..64).step_by(8).rev().collect(), (0..8).collect()),
+            Color::Black => ((0..64).step_by(8).collect(), (0..8).rev().collect()),
+        };
+        for i in rows {
+            write!(s, "{}", 1 + i / 8).unwrap();
+            for j in files.iter().map(|file| i + file) {
+                write!(
+                    s,
+                    " {}",
+                    match self.get_at(1 << j) {
+                        None => {
+                            if (j ^ j >> 3) & 1 == 0 {
+                                '\u{25FC}'
+                            } else {
+                                '\u{25FB}'
                             }
-                        );
+                        }
+                        Some(piece) => piece.to_char(),
                     }
-                    println!();
-                }
+                )
+                .unwrap();
             }
+            s.push('\n');
+        }
+        s.push(' ');
+        for file in files {
+            write!(s, " {}", (b'a' + file) as char).unwrap();
+        }
+        s.push('\n');
+
+        s
+    }
+
+    /// Prints `render()`, from `color`'s side.
+    #[cfg(feature = "std")]
+    pub fn print(&self, color: Color) {
+        println!("{}", self.render(color));
+    }
+
+    /// Parses a move in the "<from><to>[promotion]" format, e.g. "e2e4" or "e7e8n", or a drop
+    /// like "P@e4".
+    pub fn parse_move(&self, color: Color, s: &str) -> Option<Move> {
+        let s = s.as_bytes();
+        if let [piece, b'@', to @ ..] = s {
+            let ty = match piece.to_ascii_uppercase() {
+                b'Q' => PieceType::Queen,
+                b'R' => PieceType::Rook,
+                b'B' => PieceType::Bishop,
+                b'N' => PieceType::Knight,
+                b'P' => PieceType::Pawn,
+                _ => return None,
+            };
+            return self.get_legal_drop(color, ty, Square::from_ascii(to)?.index());
         }
-        print!(" ");
-        for ch in 'a'..='h' {
-            print!(" {}", ch);
+        if s.len() != 4 && s.len() != 5 {
+            return None;
         }
-        println!();
-        println!();
+
+        let from = Square::from_ascii(&s[..2])?.index();
+        let to = Square::from_ascii(&s[2..4])?.index();
+        let promotion = match s.get(4).map(u8::to_ascii_lowercase) {
+            None => None,
+            Some(b'q') => Some(PieceType::Queen),
+            Some(b'r') => Some(PieceType::Rook),
+            Some(b'b') => Some(PieceType::Bishop),
+            Some(b'n') => Some(PieceType::Knight),
+            Some(_) => return None,
+        };
+
+        self.get_legal_move(color, from, to, promotion)
     }
 }
 
@@ -2149,16 +2719,180 @@ impl Piece {
 }
 
 impl Move {
-    pub fn print(&self, board: &Board) {
-        println!(
-            "  {} : {}->{}  // move.type={:?}",
+    /// The "move" of a side that passes, see `Board::make_null_move()`.
+    pub const NULL: Self = Self {
+        from: 0,
+        to: 0,
+        ty: MoveType::King,
+    };
+
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        self.from == self.to
+    }
+
+    /// A human readable description of the move, as shown by `print()`.
+    pub fn describe(&self, board: &Board) -> String {
+        format!(
+            "{} : {}->{}  // move.type={:?}",
             board
                 .get_at(1 << self.from)
-                .map(|p| p.to_char())
+                .map(|p| Piece {
+                    ty: self.ty.dropped().unwrap_or(p.ty),
+                    ..p
+                }
+                .to_char())
                 .unwrap_or('#'),
-            crate::to_chess_pos(self.from),
-            crate::to_chess_pos(self.to),
+            Square::of(self.from),
+            Square::of(self.to),
             self.ty,
-        );
+        )
+    }
+
+    #[cfg(feature = "std")]
+    pub fn print(&self, board: &Board) {
+        println!("  {}", self.describe(board));
+    }
+}
+
+impl fmt::Display for Move {
+    /// Formats the move as "<from><to>[promotion]", e.g. "e2e4" or "e7e8q", a drop as
+    /// "<piece>@<to>", e.g. "N@f3", and a null move as "0000".
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_null() {
+            return write!(f, "0000");
+        }
+        if let Some(ty) = self.ty.dropped() {
+            let piece = match ty {
+                PieceType::Queen => 'Q',
+                PieceType::Rook => 'R',
+                PieceType::Bishop => 'B',
+                PieceType::Knight => 'N',
+                _ => 'P',
+            };
+            return write!(
+                f,
+                "{}@{}{}",
+                piece,
+                (b'a' + (self.to & 7)) as char,
+                (b'1' + self.to / 8) as char,
+            );
+        }
+        write!(
+            f,
+            "{}{}{}{}",
+            (b'a' + (self.from & 7)) as char,
+            (b'1' + self.from / 8) as char,
+            (b'a' + (self.to & 7)) as char,
+            (b'1' + self.to / 8) as char,
+        )?;
+        match self.ty {
+            MoveType::PawnQueenPromotion => write!(f, "q"),
+            MoveType::PawnRookPromotion => write!(f, "r"),
+            MoveType::PawnBishopPromotion => write!(f, "b"),
+            MoveType::PawnKnightPromotion => write!(f, "n"),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// `Board::apply()` was given a move that isn't legal.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MoveError(pub Move);
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Illegal move: {}", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MoveError {}
+
+/// What `Board::undo_null_move()` restores: the en passant right the pass cleared.
+#[must_use]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct NullMoveUndo {
+    prev_move: Move,
+}
+
+/// A fixed capacity move list, for generating moves without an allocator. Extending a full list
+/// leaves the rest of the moves out and marks it `overflowed()`.
+#[derive(Clone, Copy)]
+pub struct MoveList {
+    moves: [Move; Self::CAPACITY],
+    len: usize,
+    overflowed: bool,
+}
+
+impl MoveList {
+    // No chess position has more than 218 legal moves. Crazyhouse positions can have hundreds of
+    // drops, the crate's own callers generate theirs into a `Vec`.
+    pub const CAPACITY: usize = 256;
+
+    pub fn new() -> Self {
+        Self {
+            moves: [Move {
+                from: 0,
+                to: 0,
+                ty: MoveType::King,
+            }; Self::CAPACITY],
+            len: 0,
+            overflowed: false,
+        }
+    }
+
+    /// Panics if the list is full.
+    #[inline]
+    pub fn push(&mut self, mv: Move) {
+        self.moves[self.len] = mv;
+        self.len += 1;
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.overflowed = false;
+    }
+
+    /// Whether moves were left out of the full list, e.g. a Crazyhouse position's drops.
+    #[inline]
+    pub fn overflowed(&self) -> bool {
+        self.overflowed
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ops::Deref for MoveList {
+    type Target = [Move];
+
+    #[inline]
+    fn deref(&self) -> &[Move] {
+        &self.moves[..self.len]
+    }
+}
+
+impl ops::DerefMut for MoveList {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [Move] {
+        &mut self.moves[..self.len]
+    }
+}
+
+impl Extend<Move> for MoveList {
+    #[inline]
+    fn extend<I: IntoIterator<Item = Move>>(&mut self, iter: I) {
+        for mv in iter {
+            if self.len == Self::CAPACITY {
+                self.overflowed = true;
+                return;
+            }
+            self.push(mv);
+        }
     }
 }
@@ -1,91 +1,416 @@
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
-use crate::{Board, Color, Move, MoveType, Piece, PieceType, Pieces};
-// use std::sync::atomic::{self, AtomicI32};
+use crate::{
+    eval::{EvalParams, Evaluator},
+    experience::Experience,
+    picker::MovePicker,
+    search_tree::{Recorder, TreeNode},
+    time::{StopToken, TimeManager},
+    tt::TranspositionTable,
+    Board, Color, Move, MoveType, Piece, PieceType,
+};
+use std::{
+    sync::{
+        atomic::{AtomicU32, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
 
-pub struct Bot;
+/// A move ordering heuristic, the better the moves are ordered the more the search prunes.
+pub trait MoveOrderer {
+    /// How promising `mv` is, higher scores are searched first. `attack` is the squares the
+    /// opponent attacks and `countermove` is the quiet move that last refuted the opponent's
+    /// previous move, or `Move::NULL`.
+    fn score(&self, mv: &Move, board: &Board, attack: u64, countermove: Move) -> i32;
+}
+
+/// The material of the piece types for move ordering, indexed by `PieceType as usize`. Kings
+/// can't be traded, so theirs doesn't count.
+const MATERIAL: [i32; 6] = [0, 9, 5, 3, 3, 1];
+
+/// Most valuable victim, least valuable attacker: the score of a capture, indexed by the captured
+/// and the capturing piece types.
+const MVV_LVA: [[i32; 6]; 6] = {
+    let mut table = [[0; 6]; 6];
+    let mut victim = 0;
+    while victim < 6 {
+        let mut attacker = 0;
+        while attacker < 6 {
+            table[victim][attacker] = 8 * MATERIAL[victim] - MATERIAL[attacker];
+            attacker += 1;
+        }
+        victim += 1;
+    }
+    table
+};
+
+/// Captures of valuable pieces by cheap ones first, then the countermove, then the other quiet
+/// moves, moves to attacked squares last. Scores every move, including king moves and captures of
+/// a king.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct DefaultOrderer;
+
+impl MoveOrderer for DefaultOrderer {
+    fn score(&self, mv: &Move, board: &Board, attack: u64, countermove: Move) -> i32 {
+        let moved = mv.ty.piece() as usize;
+        let captured = match mv.ty {
+            MoveType::PawnEnPassant => Some(PieceType::Pawn),
+            _ => board.get_at(1 << mv.to).map(|Piece { ty, .. }| ty),
+        };
+
+        // The good captures score 2 and up, the other quiet moves 0 and down. The countermove
+        // goes in between, even to an attacked square, it refuted the same move elsewhere.
+        if *mv == countermove && captured.is_none() {
+            return 1;
+        }
+
+        let mut score = captured.map_or(0, |ty| MVV_LVA[ty as usize][moved]);
+        if 1 << mv.to & attack != 0 {
+            score -= 9 * MATERIAL[moved];
+        }
+
+        2 * score
+    }
+}
+
+/// The value of being mated `ply` plies from the root, so that shorter mates are preferred and
+/// the winning side doesn't shuffle around a mate it keeps postponing.
+fn mated(ply: usize) -> i32 {
+    -(i32::MAX - ply as i32)
+}
+
+/// The value for `color`, to move, of a game that the variant's rules ended `ply` plies from the
+/// root.
+fn variant_value(board: &Board, color: Color, ply: usize) -> Option<i32> {
+    board.variant_winner().map(|winner| {
+        if winner == color {
+            -mated(ply)
+        } else {
+            mated(ply)
+        }
+    })
+}
+
+/// A move as the data of a `TranspositionTable` entry.
+fn table_data(mv: Move) -> u64 {
+    mv.from as u64 | (mv.to as u64) << 6 | (mv.ty as u64) << 12
+}
+
+/// The move of `table_data()`, which may not be legal after a collision of two keys.
+fn table_move(data: u64) -> Option<Move> {
+    Some(Move {
+        from: (data & 0o77) as u8,
+        to: (data >> 6 & 0o77) as u8,
+        ty: *MoveType::ALL.get((data >> 12) as usize)?,
+    })
+}
+
+/// See `Bot::on_root_move`.
+pub type RootMoveCallback = Box<dyn Fn(u32, Move, usize) + Send + Sync>;
+
+/// A searcher over any evaluation backend and move ordering, by default the linear `EvalParams`
+/// and `DefaultOrderer`.
+pub struct Bot<E: Evaluator = EvalParams, O: MoveOrderer = DefaultOrderer> {
+    pub depth: u32,
+    pub options: SearchOptions,
+    pub evaluator: E,
+    pub orderer: O,
+    /// Aborts the running search, `choose_move_timed()` discards the unfinished iteration.
+    pub stop: StopToken,
+    /// Called as the search of every root move starts, with the depth, the move and its number
+    /// from 1 in the iteration's order, like UCI's currmove and currmovenumber.
+    pub on_root_move: Option<RootMoveCallback>,
+    /// Past games' root positions, blended into the values of the root moves, see
+    /// `Experience::blend()`.
+    pub experience: Option<Arc<Experience>>,
+    /// Remembers the best move of every searched position, to try it first when the position comes
+    /// up again. It can be shared with other bots, e.g. a ponder search.
+    pub table: Option<Arc<TranspositionTable>>,
+    /// Caches the static evaluations by position key, which pays once the evaluation costs more
+    /// than hashing the position. Only bots with the same evaluator may share it.
+    pub eval_cache: Option<Arc<TranspositionTable>>,
+    /// The search stops itself after visiting this many nodes, like at a deadline.
+    pub node_limit: Option<u64>,
+    nodes: AtomicU64,
+    value: Mutex<Option<i32>>,
+    searched_depth: AtomicU32,
+    tree: Mutex<Option<TreeNode>>,
+}
+
+/// Tunable parameters of the search, the margins are in centipawns and indexed by `depth - 1`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SearchOptions {
+    /// Quiet moves are skipped when the static evaluation plus the margin can't reach alpha.
+    pub futility_margins: Vec<i32>,
+    /// The search drops into the quiescence search when the static evaluation plus the margin
+    /// can't reach alpha and the quiescence search confirms it.
+    pub razor_margins: Vec<i32>,
+    /// Nodes with an open window and at least this depth run a search that's `iid_reduction`
+    /// plies shallower first, to find the move to try first. 0 disables it.
+    pub iid_depth: u32,
+    pub iid_reduction: u32,
+    /// How much worse than 0 a draw is for the bot, positive values avoid draws against weaker
+    /// opponents and negative ones seek them against stronger ones.
+    pub contempt: i32,
+    /// When the best move of `choose_move_timed()`'s iteration this deep is ahead of the second
+    /// best by at least `easy_move_margin`, it's played right away. 0 disables it.
+    pub easy_move_depth: u32,
+    pub easy_move_margin: i32,
+    /// Added to the static evaluation of the side to move, being on move is worth a little.
+    pub tempo: i32,
+    /// Up to this many nodes of the search tree are recorded for debugging, see
+    /// `Bot::take_tree()`. 0 disables it.
+    pub tree_limit: usize,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            futility_margins: vec![200, 500],
+            razor_margins: vec![300],
+            iid_depth: 4,
+            iid_reduction: 2,
+            contempt: 0,
+            easy_move_depth: 2,
+            easy_move_margin: 400,
+            tempo: 10,
+            tree_limit: 0,
+        }
+    }
+}
 
-fn pieces_value(pieces: &Pieces) -> u32 {
-    pieces.pawns.count_ones()
-        + 3 * (pieces.knights.count_ones() + pieces.bishops.count_ones())
-        + 5 * pieces.rooks.count_ones()
-        + 9 * pieces.queens.count_ones()
+/// The state of a single search thread.
+struct Search {
+    /// The hashes of the positions that led to the current one, the parent's last.
+    path: Vec<u64>,
+    /// The length of the game's history at the start of `path`.
+    root: usize,
+    /// The quiet replies that last caused a cutoff, indexed by `64 * from + to` of the move they
+    /// answered. `Move::NULL` marks an empty entry.
+    countermoves: Vec<Move>,
+    /// The nominal depth of the search, which limits the check extensions.
+    depth: u32,
+    /// The search stops itself past it.
+    deadline: Option<Instant>,
+    /// The node count at which the clock is read next.
+    next_check: u64,
+    /// The side the bot plays, the contempt is from its side.
+    color: Color,
+    tree: Option<Recorder>,
+}
+
+impl Search {
+    fn new(history: &[u64], color: Color, depth: u32, deadline: Option<Instant>) -> Self {
+        Self {
+            path: history.to_vec(),
+            root: history.len(),
+            countermoves: vec![Move::NULL; 64 * 64],
+            depth,
+            deadline,
+            next_check: 0,
+            color,
+            tree: None,
+        }
+    }
+
+    fn countermove(&mut self, prev: Move) -> &mut Move {
+        &mut self.countermoves[64 * prev.from as usize + prev.to as usize]
+    }
 }
 
 impl Bot {
-    fn guess_white_win(&self, board: &Board) -> i32 {
-        100 * (pieces_value(&board.white_pieces) as i32 - pieces_value(&board.black_pieces) as i32)
+    pub const DEFAULT_DEPTH: u32 = 6;
+
+    pub fn new() -> Self {
+        Self::with_depth(Self::DEFAULT_DEPTH)
     }
 
-    fn eval_move(&self, mv: &Move, board: &Board, attack: u64) -> i32 {
-        let mut score = 0;
+    pub fn with_depth(depth: u32) -> Self {
+        Self::with_evaluator(depth, EvalParams::default())
+    }
 
-        if let Some(Piece { ty, .. }) = board.get_at(1 << mv.to) {
-            score += match ty {
-                PieceType::King => unreachable!(),
-                PieceType::Queen => 9,
-                PieceType::Rook => 5,
-                PieceType::Bishop => 3,
-                PieceType::Knight => 3,
-                PieceType::Pawn => 1,
-            };
+    /// A depth that fits the remaining time on the clock (in milliseconds).
+    pub fn depth_for_clock(time_left: u64) -> u32 {
+        match time_left {
+            0..=10_000 => 2,
+            10_001..=60_000 => 4,
+            _ => Self::DEFAULT_DEPTH,
         }
+    }
+}
 
-        if 1 << mv.to & attack != 0 {
-            score -= 9 * match mv.ty {
-                MoveType::King | MoveType::Castle => unreachable!(),
-                MoveType::Queen
-                | MoveType::PawnQueenPromotion
-                | MoveType::PawnRookPromotion
-                | MoveType::PawnBishopPromotion
-                | MoveType::PawnKnightPromotion => 9,
-                MoveType::Rook => 5,
-                MoveType::Bishop => 3,
-                MoveType::Knight => 3,
-                MoveType::Pawn | MoveType::PawnLeap | MoveType::PawnEnPassant => 1,
-            } / 8;
+impl<E: Evaluator> Bot<E> {
+    pub fn with_evaluator(depth: u32, evaluator: E) -> Self {
+        Bot::with_parts(depth, evaluator, DefaultOrderer)
+    }
+}
+
+impl<E: Evaluator, O: MoveOrderer> Bot<E, O> {
+    pub fn with_parts(depth: u32, evaluator: E, orderer: O) -> Self {
+        Self {
+            depth,
+            options: SearchOptions::default(),
+            evaluator,
+            orderer,
+            stop: StopToken::default(),
+            on_root_move: None,
+            experience: None,
+            table: None,
+            eval_cache: None,
+            node_limit: None,
+            nodes: AtomicU64::new(0),
+            value: Mutex::new(None),
+            searched_depth: AtomicU32::new(0),
+            tree: Mutex::new(None),
+        }
+    }
+
+    /// The number of nodes visited by the last `choose_move()`.
+    pub fn nodes(&self) -> u64 {
+        self.nodes.load(Ordering::Relaxed)
+    }
+
+    /// The value of the move chosen last for the side that played it, `None` if there was no
+    /// search since there was at most one legal move.
+    pub fn value(&self) -> Option<i32> {
+        *self.value.lock().unwrap()
+    }
+
+    /// The depth of the search that `value()` comes from, 0 without a search.
+    pub fn searched_depth(&self) -> u32 {
+        self.searched_depth.load(Ordering::Relaxed)
+    }
+
+    /// `mv` and the moves the transposition table has for the positions after it, the line the
+    /// search expects, up to the searched depth. Just `mv` without a table.
+    pub fn principal_variation(&self, board: &Board, color: Color, mv: Move) -> Vec<Move> {
+        let mut pv = vec![mv];
+        if let Some(table) = &self.table {
+            let (mut board, mut color) = (*board, color);
+            let mut seen = vec![board.position_key(color)];
+            board.perform_move_unchecked(mv);
+            color = color.inv();
+            while pv.len() < self.searched_depth() as usize {
+                let key = board.position_key(color);
+                // A repetition would go on forever.
+                if seen.contains(&key) {
+                    break;
+                }
+                seen.push(key);
+                match table
+                    .probe(key)
+                    .and_then(table_move)
+                    .filter(|&mv| board.is_legal(color, mv))
+                {
+                    Some(mv) => {
+                        pv.push(mv);
+                        board.perform_move_unchecked(mv);
+                        color = color.inv();
+                    }
+                    None => break,
+                }
+            }
         }
+        pv
+    }
 
-        score
+    /// The tree of the last root search when `options.tree_limit` isn't 0, the limit is split
+    /// between the root moves. The values are from the side that played every move.
+    pub fn take_tree(&self) -> Option<TreeNode> {
+        self.tree.lock().unwrap().take()
     }
 
+    /// Blends the experience into `values`, the root moves' values for the opponent like
+    /// `root_values()` returns them.
+    fn blend_experience(&self, board: &Board, color: Color, moves: &[Move], values: &mut [i32]) {
+        if let Some(experience) = &self.experience {
+            let key = board.position_key(color);
+            for (&mv, value) in moves.iter().zip(values) {
+                *value = -experience.blend(key, mv, -*value);
+            }
+        }
+    }
+
+    /// The static evaluation for `color`, to move, including the tempo bonus.
+    fn guess_win(&self, board: &Board, state: &E::State, color: Color) -> i32 {
+        let value = match &self.eval_cache {
+            Some(cache) => {
+                let key = board.position_key(color);
+                match cache.probe(key) {
+                    Some(data) => data as u32 as i32,
+                    None => {
+                        let value = self.evaluator.evaluate_for(board, state, color);
+                        cache.store(key, value as u32 as u64);
+                        value
+                    }
+                }
+            }
+            None => self.evaluator.evaluate_for(board, state, color),
+        };
+        value + self.options.tempo
+    }
+
+    /// The quiescence search: the recaptures on `pos`, and the promotions, which make their square
+    /// the one to recapture on.
+    #[allow(clippy::too_many_arguments)]
     fn eval_captures_board_rec(
         &self,
         board: &Board,
+        state: &E::State,
         pos: u8,
         color: Color,
+        ply: usize,
         mut alpha: i32,
         beta: i32,
     ) -> i32 {
+        self.nodes.fetch_add(1, Ordering::Relaxed);
+
+        if let Some(value) = variant_value(board, color, ply) {
+            return value;
+        }
+        let attack = board.check_attack(color.inv());
         let mut moves: Vec<_> = board
-            .capture_moves(color)
+            .capture_moves_with_attack(color, attack)
             .into_iter()
-            .filter(|mv| mv.to == pos)
+            .filter(|mv| mv.to == pos || mv.ty == MoveType::PawnQueenPromotion)
             .collect();
 
         if moves.is_empty() {
-            if board.check_attack(color.inv()) & board.get_pieces(color).king == 0 {
-                let val = self.guess_white_win(&board);
-                match color {
-                    Color::White => val,
-                    Color::Black => -val,
-                }
+            // A check that can't be answered by a recapture is still only mate without evasions.
+            if attack & board.get_pieces(color).king() == 0
+                || !board.moves_with_attack(color, attack).is_empty()
+            {
+                self.guess_win(board, state, color)
             } else {
-                -i32::MAX
+                mated(ply)
             }
         } else {
-            let attack = board.check_attack(color.inv());
-            moves.sort_unstable_by_key(|mv| -self.eval_move(mv, board, attack));
+            moves.sort_unstable_by_key(|mv| -self.orderer.score(mv, board, attack, Move::NULL));
 
-            let mut value = -i32::MAX;
+            // Without a recapture the side to move needn't promote either.
+            let mut value = if moves.iter().any(|mv| mv.to == pos) {
+                -i32::MAX
+            } else {
+                self.guess_win(board, state, color)
+            };
+            if beta <= value {
+                return beta;
+            }
+            alpha = alpha.max(value);
 
             for mv in moves.into_iter() {
-                let mut board = *board;
-                board.perform_move(mv);
+                let mut next = *board;
+                next.perform_move_unchecked(mv);
+                let mut next_state = state.clone();
+                self.evaluator.update(&mut next_state, board, &next);
                 value = value.max(-self.eval_captures_board_rec(
-                    &board,
-                    pos,
+                    &next,
+                    &next_state,
+                    mv.to,
                     color.inv(),
+                    ply + 1,
                     -beta,
                     -alpha,
                 ));
@@ -99,64 +424,497 @@ impl Bot {
         }
     }
 
+    /// The value of a draw for `color`, to move.
+    fn draw_value(&self, color: Color, search: &Search) -> i32 {
+        if color == search.color {
+            -self.options.contempt
+        } else {
+            self.options.contempt
+        }
+    }
+
+    /// Returns the value of the board for `color` and its best move, if it searched any.
+    #[allow(clippy::too_many_arguments)]
     fn eval_board_rec(
         &self,
         board: &Board,
+        state: &E::State,
         color: Color,
         depth: u32,
         mut alpha: i32,
         beta: i32,
-    ) -> i32 {
+        search: &mut Search,
+    ) -> (i32, Option<Move>) {
         if depth == 0 {
-            self.eval_captures_board_rec(board, board.prev_move.to, color, alpha, beta)
+            let value = self.eval_captures_board_rec(
+                board,
+                state,
+                board.prev_move.to,
+                color,
+                search.path.len() - search.root,
+                alpha,
+                beta,
+            );
+            (value, None)
         } else {
-            let mut moves = board.moves(color);
-            if moves.is_empty() {
-                if board.check_attack(color.inv()) & board.get_pieces(color).king == 0 {
-                    0
+            let nodes = self.nodes.fetch_add(1, Ordering::Relaxed);
+            // The clock isn't read at every node, it isn't free. The count includes the
+            // quiescence search's nodes, so it's read once enough of them have passed. The node
+            // limit is checked along with it.
+            if search.next_check <= nodes {
+                search.next_check = nodes + 1024;
+                if search
+                    .deadline
+                    .is_some_and(|deadline| deadline <= Instant::now())
+                    || self.node_limit.is_some_and(|limit| limit <= nodes)
+                {
+                    self.stop.stop();
+                }
+            }
+            if self.stop.is_stopped() {
+                return (0, None);
+            }
+            if let Some(value) = variant_value(board, color, search.path.len() - search.root) {
+                return (value, None);
+            }
+
+            // A repetition is a draw, since the side that repeated can keep on repeating.
+            let hash = board.position_key(color);
+            if board.is_insufficient_material()
+                || search
+                    .path
+                    .iter()
+                    .rev()
+                    .skip(1)
+                    .step_by(2)
+                    .any(|&prev| prev == hash)
+            {
+                return (self.draw_value(color, search), None);
+            }
+
+            let attack = board.check_attack(color.inv());
+            let in_check = attack & board.get_pieces(color).king() != 0;
+            let margin = |margins: &[i32]| match margins.get(depth as usize - 1) {
+                Some(&margin) if !in_check => Some(self.guess_win(board, state, color) + margin),
+                _ => None,
+            };
+
+            if let Some(bound) = margin(&self.options.razor_margins) {
+                if bound <= alpha {
+                    let value = self.eval_captures_board_rec(
+                        board,
+                        state,
+                        board.prev_move.to,
+                        color,
+                        search.path.len() - search.root,
+                        alpha,
+                        beta,
+                    );
+                    if value <= alpha {
+                        return (value, None);
+                    }
+                }
+            }
+            // Quiet moves can't reach alpha here, unless they give check.
+            let futile = margin(&self.options.futility_margins).filter(|&bound| bound <= alpha);
+
+            // The best move of an earlier search of the position, or internal iterative
+            // deepening, a shallower search finds a good first move. The picker checks that it's
+            // legal.
+            let hashed = self
+                .table
+                .as_ref()
+                .and_then(|table| table.probe(hash))
+                .and_then(table_move);
+            let first = match self.options.iid_depth {
+                _ if hashed.is_some() => hashed,
+                iid_depth if iid_depth != 0 && iid_depth <= depth && alpha + 1 < beta => {
+                    let depth = depth.saturating_sub(self.options.iid_reduction).max(1);
+                    // The shallow search isn't part of the recorded tree.
+                    let tree = search.tree.take();
+                    let first = self
+                        .eval_board_rec(board, state, color, depth, alpha, beta, search)
+                        .1;
+                    search.tree = tree;
+                    first
+                }
+                _ => None,
+            };
+            let countermove = *search.countermove(board.prev_move);
+            let mut moves =
+                MovePicker::new(board, color, attack, &self.orderer, first, countermove).peekable();
+            if moves.peek().is_none() {
+                if in_check {
+                    (mated(search.path.len() - search.root), None)
                 } else {
-                    -i32::MAX
+                    (self.draw_value(color, search), None)
                 }
             } else {
                 let mut value = -i32::MAX;
+                let mut best = None;
 
-                let attack = board.check_attack(color.inv());
-                moves.sort_unstable_by_key(|mv| -self.eval_move(mv, board, attack));
+                search.path.push(hash);
+                for mv in moves {
+                    let quiet = !matches!(
+                        mv.ty,
+                        MoveType::PawnEnPassant
+                            | MoveType::PawnQueenPromotion
+                            | MoveType::PawnRookPromotion
+                            | MoveType::PawnBishopPromotion
+                            | MoveType::PawnKnightPromotion
+                    ) && board.occupied_by(color.inv()) & 1 << mv.to == 0;
 
-                for mv in moves.into_iter() {
-                    let mut board = *board;
-                    board.perform_move(mv);
-                    value = value.max(-self.eval_board_rec(
-                        &board,
+                    let check = board.gives_check(mv);
+                    if let Some(bound) = futile {
+                        if quiet && !check {
+                            value = value.max(bound);
+                            continue;
+                        }
+                    }
+                    let mut next = *board;
+                    next.perform_move_unchecked(mv);
+
+                    // Checks are extended so forcing lines aren't cut off right before the mate,
+                    // but only up to twice the nominal depth.
+                    let ply = search.path.len() - search.root;
+                    let extension = (check && ply < 2 * search.depth as usize) as u32;
+
+                    let mut next_state = state.clone();
+                    self.evaluator.update(&mut next_state, board, &next);
+                    let recorded = search
+                        .tree
+                        .as_mut()
+                        .is_some_and(|tree| tree.enter(mv, alpha, beta));
+                    let (next_value, _) = self.eval_board_rec(
+                        &next,
+                        &next_state,
                         color.inv(),
-                        depth - 1,
+                        depth - 1 + extension,
                         -beta,
                         -alpha,
-                    ));
+                        search,
+                    );
+                    if recorded {
+                        search.tree.as_mut().unwrap().exit(-next_value);
+                    }
+                    if value < -next_value || best.is_none() {
+                        best = Some(mv);
+                    }
+                    value = value.max(-next_value);
                     if beta <= value {
-                        return beta;
+                        if quiet {
+                            *search.countermove(board.prev_move) = mv;
+                        }
+                        break;
                     }
                     alpha = alpha.max(value);
                 }
+                search.path.pop();
+                if let (Some(table), Some(best)) = (&self.table, best) {
+                    table.store(hash, table_data(best));
+                }
 
-                value
+                (value.min(beta), best)
             }
         }
     }
 
-    /// Failes if there's no legal move
-    pub fn choose_move(&self, board: &Board, color: Color) -> Option<Move> {
-        const DEPTH: u32 = 6;
+    /// The opponent's value after every root move, searched to `depth`.
+    #[allow(clippy::too_many_arguments)]
+    fn root_values(
+        &self,
+        board: &Board,
+        state: &E::State,
+        color: Color,
+        moves: &[Move],
+        depth: u32,
+        history: &[u64],
+        deadline: Option<Instant>,
+    ) -> Vec<i32>
+    where
+        E: Sync,
+        E::State: Sync,
+        O: Sync,
+    {
+        #[cfg(feature = "parallel")]
+        let moves = moves.par_iter().enumerate();
+        #[cfg(not(feature = "parallel"))]
+        let moves = moves.iter().enumerate();
+
+        let tree_limit = self.options.tree_limit / moves.len().max(1);
+        let (values, trees): (Vec<_>, Vec<_>) = moves
+            .map(|(i, &mv)| {
+                if let Some(on_root_move) = &self.on_root_move {
+                    on_root_move(depth, mv, i + 1);
+                }
+                let mut search = Search::new(history, color, depth, deadline);
+                search.path.push(board.position_key(color));
+                if tree_limit != 0 {
+                    search.tree = Some(Recorder::new(
+                        TreeNode::new(mv, -i32::MAX, i32::MAX),
+                        tree_limit,
+                    ));
+                }
+
+                let mut next = *board;
+                next.perform_move_unchecked(mv);
+                let mut next_state = state.clone();
+                self.evaluator.update(&mut next_state, board, &next);
+                let value = self
+                    .eval_board_rec(
+                        &next,
+                        &next_state,
+                        color.inv(),
+                        depth,
+                        -i32::MAX,
+                        i32::MAX,
+                        &mut search,
+                    )
+                    .0;
+
+                let tree = search.tree.map(|tree| {
+                    let mut tree = tree.finish();
+                    tree.value = -value;
+                    tree
+                });
+                (value, tree)
+            })
+            .unzip();
+
+        if self.options.tree_limit != 0 {
+            let mut root = TreeNode::new(Move::NULL, -i32::MAX, i32::MAX);
+            root.children = trees.into_iter().flatten().collect();
+            root.value = root
+                .children
+                .iter()
+                .map(|child| child.value)
+                .max()
+                .unwrap_or(0);
+            *self.tree.lock().unwrap() = Some(root);
+        }
+        values
+    }
+
+    /// The values of `moves` for `color`, searched to `depth`, in the order of `moves`.
+    pub(crate) fn move_values(
+        &self,
+        board: &Board,
+        color: Color,
+        history: &[u64],
+        moves: &[Move],
+        depth: u32,
+    ) -> Vec<i32>
+    where
+        E: Sync,
+        E::State: Sync,
+        O: Sync,
+    {
+        self.nodes.store(0, Ordering::Relaxed);
+        self.stop.reset();
+        let state = self.evaluator.init(board);
+        self.root_values(board, &state, color, moves, depth, history, None)
+            .into_iter()
+            .map(|value| -value)
+            .collect()
+    }
+
+    /// Every legal move for `color` with its value for `color`, searched to `depth`, the best
+    /// first. Empty if the board is invalid (see `Board::validate()`).
+    pub fn score_all_moves(&self, board: &Board, color: Color, depth: u32) -> Vec<(Move, i32)>
+    where
+        E: Sync,
+        E::State: Sync,
+        O: Sync,
+    {
+        if let Err(err) = board.validate() {
+            log::warn!("Not searching an invalid board: {}", err);
+            return Vec::new();
+        }
+        let moves = board.moves(color);
+        let values = self.move_values(board, color, &[], &moves, depth);
+        let mut scored: Vec<_> = moves.into_iter().zip(values).collect();
+        scored.sort_by_key(|&(_, value)| -value);
+        scored
+    }
+
+    /// Fails if there's no legal move or the board is invalid (see `Board::validate()`).
+    pub fn choose_move(&self, board: &Board, color: Color) -> Option<Move>
+    where
+        E: Sync,
+        E::State: Sync,
+        O: Sync,
+    {
+        self.choose_move_with_history(board, color, &[])
+    }
+
+    /// Like `choose_move()`, but repeating a position of `history` (see `Game::history()`) counts
+    /// as a draw.
+    pub fn choose_move_with_history(
+        &self,
+        board: &Board,
+        color: Color,
+        history: &[u64],
+    ) -> Option<Move>
+    where
+        E: Sync,
+        E::State: Sync,
+        O: Sync,
+    {
+        self.choose_move_from(board, color, history, &board.moves(color))
+    }
 
-        let mut moves = board.moves(color);
+    /// Like `choose_move_with_history()`, but only considers `moves`, which must be legal. Analysis
+    /// GUIs restrict the search this way (UCI's searchmoves).
+    pub fn choose_move_from(
+        &self,
+        board: &Board,
+        color: Color,
+        history: &[u64],
+        moves: &[Move],
+    ) -> Option<Move>
+    where
+        E: Sync,
+        E::State: Sync,
+        O: Sync,
+    {
+        self.nodes.store(0, Ordering::Relaxed);
+        *self.value.lock().unwrap() = None;
+        self.searched_depth.store(0, Ordering::Relaxed);
+        self.stop.reset();
+        if let Err(err) = board.validate() {
+            log::warn!("Not searching an invalid board: {}", err);
+            return None;
+        }
+        let state = self.evaluator.init(board);
 
         let attack = board.check_attack(color.inv());
-        moves.sort_by_key(|mv| -self.eval_move(mv, board, attack));
+        let mut moves = moves.to_vec();
+
+        // There's nothing to think about with a single legal move.
+        if moves.len() <= 1 {
+            return moves.first().copied();
+        }
+        moves.sort_by_key(|mv| -self.orderer.score(mv, board, attack, Move::NULL));
+
+        let mut values = self.root_values(board, &state, color, &moves, self.depth, history, None);
+        // The node limit cut the search short, the values are placeholders. The move ordering's
+        // favourite it is.
+        if self.stop.is_stopped() {
+            log::debug!("Depth {} aborted, {} nodes", self.depth, self.nodes());
+            return Some(moves[0]);
+        }
+        self.blend_experience(board, color, &moves, &mut values);
+        let best = moves
+            .into_iter()
+            .zip(values)
+            .min_by_key(|&(_, value)| value);
+        if let Some((mv, value)) = best {
+            log::debug!(
+                "Depth {}: {} ({}), {} nodes",
+                self.depth,
+                mv,
+                -value,
+                self.nodes()
+            );
+            *self.value.lock().unwrap() = Some(-value);
+            self.searched_depth.store(self.depth, Ordering::Relaxed);
+        }
+        best.map(|(mv, _)| mv)
+    }
+
+    /// Like `choose_move_from()`, but deepens iteratively, up to `self.depth`, within the time
+    /// manager's limits. The best move changing or failing low gets the move more time.
+    pub fn choose_move_timed(
+        &self,
+        board: &Board,
+        color: Color,
+        history: &[u64],
+        moves: &[Move],
+        time: &mut TimeManager,
+    ) -> Option<Move>
+    where
+        E: Sync,
+        E::State: Sync,
+        O: Sync,
+    {
+        // A drop of the best move's value that's worth thinking longer about.
+        const FAIL_LOW_MARGIN: i64 = 50;
+
+        self.nodes.store(0, Ordering::Relaxed);
+        *self.value.lock().unwrap() = None;
+        self.searched_depth.store(0, Ordering::Relaxed);
+        self.stop.reset();
+        if let Err(err) = board.validate() {
+            log::warn!("Not searching an invalid board: {}", err);
+            return None;
+        }
+        let state = self.evaluator.init(board);
+
+        let attack = board.check_attack(color.inv());
+        let mut moves = moves.to_vec();
+        if moves.len() <= 1 {
+            return moves.first().copied();
+        }
+        moves.sort_by_key(|mv| -self.orderer.score(mv, board, attack, Move::NULL));
+
+        let mut best: Option<(Move, i32)> = None;
+        for depth in 1..=self.depth {
+            let mut values = self.root_values(
+                board,
+                &state,
+                color,
+                &moves,
+                depth,
+                history,
+                Some(time.deadline()),
+            );
+            if self.stop.is_stopped() {
+                log::debug!("Depth {} aborted after {:?}", depth, time.elapsed());
+                break;
+            }
+            self.blend_experience(board, color, &moves, &mut values);
+
+            // The next iteration starts from this one's best moves.
+            let mut ordered: Vec<_> = moves.iter().copied().zip(values).collect();
+            ordered.sort_by_key(|&(_, value)| value);
+            let (mv, value) = ordered[0];
+            log::debug!(
+                "Depth {}: {} ({}), {} nodes, {:?}",
+                depth,
+                mv,
+                -value,
+                self.nodes(),
+                time.elapsed()
+            );
+            if let Some((prev_mv, prev_value)) = best {
+                if mv != prev_mv || prev_value as i64 + FAIL_LOW_MARGIN < value as i64 {
+                    time.extend();
+                }
+            }
+            best = Some((mv, value));
+            self.searched_depth.store(depth, Ordering::Relaxed);
+
+            let easy_move = depth == self.options.easy_move_depth
+                && ordered[0].1 as i64 + self.options.easy_move_margin as i64
+                    <= ordered[1].1 as i64;
+            if easy_move {
+                log::debug!("Easy move at depth {}", depth);
+                break;
+            }
+            if !time.should_start_iteration() {
+                break;
+            }
+            moves = ordered.into_iter().map(|(mv, _)| mv).collect();
+        }
+
+        *self.value.lock().unwrap() = best.map(|(_, value)| -value);
+        // Even the first iteration didn't finish, the move ordering's favourite it is.
+        Some(best.map_or(moves[0], |(mv, _)| mv))
+    }
+}
 
-        moves.into_par_iter().min_by_key(|&mv| {
-            let mut board = *board;
-            board.perform_move(mv);
-            self.eval_board_rec(&board, color.inv(), DEPTH, -i32::MAX, i32::MAX)
-        })
+impl Default for Bot {
+    fn default() -> Self {
+        Self::new()
     }
 }
@@ -0,0 +1,101 @@
+// A chess clock for the games played in the terminal: every side's remaining time, with an
+// increment added after each of its moves (Fischer).
+
+use crate::{time::TimeManager, Color};
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Clock {
+    /// Indexed by `Color as usize`, not counting the running clock.
+    remaining: [Duration; 2],
+    increment: Duration,
+    /// The side whose clock is running and since when.
+    running: Option<(Color, Instant)>,
+}
+
+impl Clock {
+    pub fn new(base: Duration, increment: Duration) -> Self {
+        Self {
+            remaining: [base; 2],
+            increment,
+            running: None,
+        }
+    }
+
+    /// Parses a time control like "5+3": the minutes and the increment in seconds.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (base, increment) = s.split_once('+').unwrap_or((s, "0"));
+        let base = base.parse::<f64>().ok().filter(|&base| 0.0 < base)?;
+        let increment = increment.parse::<f64>().ok().filter(|&inc| 0.0 <= inc)?;
+        Some(Self::new(
+            Duration::try_from_secs_f64(base * 60.0).ok()?,
+            Duration::try_from_secs_f64(increment).ok()?,
+        ))
+    }
+
+    /// Starts `color`'s clock, stopping the other's.
+    pub fn start(&mut self, color: Color) {
+        self.stop();
+        self.running = Some((color, Instant::now()));
+    }
+
+    /// Stops the running clock, its side gets the increment unless its flag fell.
+    pub fn stop(&mut self) {
+        if let Some((color, _)) = self.running {
+            let remaining = self.remaining(color);
+            self.remaining[color as usize] = if remaining.is_zero() {
+                remaining
+            } else {
+                remaining + self.increment
+            };
+            self.running = None;
+        }
+    }
+
+    /// `color`'s remaining time, up to now if its clock is running.
+    pub fn remaining(&self, color: Color) -> Duration {
+        let remaining = self.remaining[color as usize];
+        match self.running {
+            Some((running, since)) if running == color => remaining.saturating_sub(since.elapsed()),
+            _ => remaining,
+        }
+    }
+
+    pub fn increment(&self) -> Duration {
+        self.increment
+    }
+
+    pub fn flag_fell(&self, color: Color) -> bool {
+        self.remaining(color).is_zero()
+    }
+
+    /// The time limits of `color`'s move.
+    pub fn time_manager(&self, color: Color) -> TimeManager {
+        TimeManager::for_clock(self.remaining(color), self.increment, None)
+    }
+}
+
+/// "m:ss.s", e.g. "4:59.3".
+fn write_time(f: &mut fmt::Formatter, time: Duration) -> fmt::Result {
+    let tenths = time.as_millis() / 100;
+    write!(
+        f,
+        "{}:{:02}.{}",
+        tenths / 600,
+        tenths / 10 % 60,
+        tenths % 10
+    )
+}
+
+impl fmt::Display for Clock {
+    /// "White 4:59.3 | Black 5:00.0"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "White ")?;
+        write_time(f, self.remaining(Color::White))?;
+        write!(f, " | Black ")?;
+        write_time(f, self.remaining(Color::Black))
+    }
+}
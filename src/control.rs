@@ -0,0 +1,83 @@
+// Who controls which square: for every square the number of White's pieces that attack it minus
+// Black's, for heatmaps in the terminal and overlays in GUIs. Only direct attacks count, a rook
+// behind a rook doesn't.
+
+use crate::{
+    board::{knight_attacks, ray_attacks},
+    masks::{FILE_A, FILE_H},
+    BitIterator, Board, Color, Pieces,
+};
+use alloc::string::String;
+use core::fmt::Write;
+
+/// The squares that the piece of `pieces` on `bit` attacks.
+fn attacks(pieces: &Pieces, bit: u64, color: Color, occupied: u64) -> u64 {
+    if pieces.pawns() & bit != 0 {
+        match color {
+            Color::White => bit << 0o11 & !FILE_A | bit << 7 & !FILE_H,
+            Color::Black => bit >> 7 & !FILE_A | bit >> 0o11 & !FILE_H,
+        }
+    } else if pieces.knights() & bit != 0 {
+        knight_attacks(bit)
+    } else if pieces.king() & bit != 0 {
+        (bit << 1 | bit << 0o11 | bit >> 7) & !FILE_A
+            | (bit >> 1 | bit >> 0o11 | bit << 7) & !FILE_H
+            | bit << 0o10
+            | bit >> 0o10
+    } else {
+        let mut attack = 0;
+        if (pieces.rooks() | pieces.queens()) & bit != 0 {
+            attack |= ray_attacks(bit, occupied, true);
+        }
+        if (pieces.bishops() | pieces.queens()) & bit != 0 {
+            attack |= ray_attacks(bit, occupied, false);
+        }
+        attack
+    }
+}
+
+impl Board {
+    /// White's attackers minus Black's for every square, positive where White controls it.
+    pub fn control_map(&self) -> [i8; 64] {
+        let occupied = self.occupied();
+        let mut map = [0; 64];
+        for (color, sign) in [(Color::White, 1), (Color::Black, -1)] {
+            let pieces = self.get_pieces(color);
+            for bit in BitIterator(pieces.all) {
+                for square in BitIterator(attacks(pieces, bit, color, occupied)) {
+                    map[square.trailing_zeros() as usize] += sign;
+                }
+            }
+        }
+        map
+    }
+
+    /// Renders `control_map()` from `color`'s side like `render()` renders the board, e.g. "+2"
+    /// on a square two more White pieces attack and " ." where they're even.
+    pub fn render_control_map(&self, color: Color) -> String {
+        let map = self.control_map();
+        let mut s = String::new();
+
+        let (rows, files): (alloc::vec::Vec<usize>, alloc::vec::Vec<usize>) = match color {
+            Color::White => ((0..64).step_by(8).rev().collect(), (0..8).collect()),
+            Color::Black => ((0..64).step_by(8).collect(), (0..8).rev().collect()),
+        };
+        for i in rows {
+            write!(s, "{}", 1 + i / 8).unwrap();
+            for control in files.iter().map(|file| map[i + file]) {
+                match control {
+                    0 => s.push_str("  ."),
+                    _ => write!(s, " {:+}", control).unwrap(),
+                }
+            }
+            s.push('\n');
+        }
+        s.push(' ');
+        for file in files {
+            write!(s, "  {}", (b'a' + file as u8) as char).unwrap();
+        }
+        s.push('\n');
+
+        s
+    }
+}
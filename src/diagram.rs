@@ -0,0 +1,105 @@
+// Positions drawn as ASCII diagrams, to keep the tests of tricky positions readable:
+//
+//     . . . . . . . k
+//     . . . . . . . .
+//     . . . . . . . .
+//     K P p . . . . r
+//     . . . . . . . .
+//     . . . . . . . .
+//     . . . . . . . .
+//     . . . . . . . .
+//     w - c6
+//
+// Rank 8 is at the top unless the ranks are numbered, the line after the board holds the FEN's
+// side to move, castling rights and en passant square (white to move and no rights by default).
+// `Board::render()`'s output, with its numbers, file letters and unicode pieces, is accepted too,
+// from either side.
+
+use crate::{fen::FenError, Board, Color};
+use alloc::{format, string::String, vec::Vec};
+
+/// The FEN letter of a diagram's square, `None` if it's empty.
+fn square(ch: char) -> Result<Option<char>, FenError> {
+    match ch {
+        '.' | '-' | '_' | '\u{25FB}' | '\u{25FC}' => Ok(None),
+        'K' | 'Q' | 'R' | 'B' | 'N' | 'P' | 'k' | 'q' | 'r' | 'b' | 'n' | 'p' => Ok(Some(ch)),
+        '\u{2654}'..='\u{265F}' => Ok("KQRBNPkqrbnp".chars().nth(ch as usize - 0x2654)),
+        _ => Err(FenError("bad diagram square")),
+    }
+}
+
+impl Board {
+    /// Parses a diagram like the one at the top of `src/diagram.rs`, returns the board and the
+    /// side to move. The errors are the FEN's, since it's checked like one.
+    pub fn from_diagram(diagram: &str) -> Result<(Self, Color), FenError> {
+        // Without the blank lines and the file letters under the board, which run from h to a
+        // under a board drawn from Black's side.
+        let mut reversed = false;
+        let lines: Vec<(&str, Vec<char>)> = diagram
+            .lines()
+            .map(|line| {
+                (
+                    line,
+                    line.chars().filter(|ch| !ch.is_whitespace()).collect(),
+                )
+            })
+            .filter(|(_, chars): &(_, Vec<char>)| {
+                if chars.iter().copied().eq(('a'..='h').rev()) {
+                    reversed = true;
+                    return false;
+                }
+                !chars.is_empty() && !chars.iter().copied().eq('a'..='h')
+            })
+            .collect();
+        let annotation = match lines.len() {
+            0..=7 => return Err(FenError("too few ranks")),
+            8 => "w -",
+            9 => lines[8].0,
+            _ => return Err(FenError("too many lines")),
+        };
+
+        let mut ranks: [Option<String>; 8] = Default::default();
+        for (i, (_, line)) in lines[..8].iter().enumerate() {
+            let (rank, squares) = match line[0].to_digit(10) {
+                Some(rank @ 1..=8) => (8 - rank as usize, &line[1..]),
+                _ => (i, &line[..]),
+            };
+            if squares.len() != 8 {
+                return Err(FenError("a rank needs 8 squares"));
+            }
+            if ranks[rank].is_some() {
+                return Err(FenError("rank given twice"));
+            }
+
+            let mut squares = squares.to_vec();
+            if reversed {
+                squares.reverse();
+            }
+
+            let mut fen = String::new();
+            let mut empty = 0;
+            for ch in squares {
+                match square(ch)? {
+                    Some(piece) => {
+                        if empty != 0 {
+                            fen.push((b'0' + empty) as char);
+                            empty = 0;
+                        }
+                        fen.push(piece);
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty != 0 {
+                fen.push((b'0' + empty) as char);
+            }
+            ranks[rank] = Some(fen);
+        }
+
+        let ranks: Vec<_> = ranks.iter().flatten().map(String::as_str).collect();
+        if ranks.len() != 8 {
+            return Err(FenError("too few ranks"));
+        }
+        Board::from_fen(&format!("{} {}", ranks.join("/"), annotation))
+    }
+}
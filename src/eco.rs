@@ -0,0 +1,272 @@
+// Opening names by the Encyclopaedia of Chess Openings' codes, from a small bundled table of the
+// main lines. The positions are compared rather than the moves, so transpositions are recognized.
+
+use crate::{Board, Color, Game, Variant};
+use alloc::vec::Vec;
+use core::{fmt, str::FromStr};
+
+/// An ECO code like "C50", a volume from A to E and a number below 100.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub struct EcoCode {
+    volume: u8,
+    number: u8,
+}
+
+impl EcoCode {
+    /// `None` unless `volume` is from 'A' to 'E' and `number` is below 100.
+    pub fn new(volume: char, number: u8) -> Option<Self> {
+        match volume {
+            'A'..='E' if number < 100 => Some(Self {
+                volume: volume as u8,
+                number,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn volume(self) -> char {
+        self.volume as char
+    }
+
+    pub fn number(self) -> u8 {
+        self.number
+    }
+}
+
+impl fmt::Display for EcoCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{:02}", self.volume(), self.number)
+    }
+}
+
+/// The text isn't an ECO code.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ParseEcoError;
+
+impl fmt::Display for ParseEcoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Bad ECO code")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseEcoError {}
+
+impl FromStr for EcoCode {
+    type Err = ParseEcoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        let volume = chars.next().ok_or(ParseEcoError)?;
+        let number = chars.as_str();
+        if number.len() != 2 || !number.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseEcoError);
+        }
+        Self::new(volume, number.parse().map_err(|_| ParseEcoError)?).ok_or(ParseEcoError)
+    }
+}
+
+/// (code, name, moves in SAN from the starting position)
+pub const OPENINGS: &[(&str, &str, &str)] = &[
+    ("A00", "Polish Opening", "b4"),
+    ("A00", "Grob Opening", "g4"),
+    ("A01", "Nimzo-Larsen Attack", "b3"),
+    ("A02", "Bird's Opening", "f4"),
+    ("A04", "Reti Opening", "Nf3"),
+    ("A05", "Reti Opening", "Nf3 Nf6"),
+    ("A10", "English Opening", "c4"),
+    ("A20", "English Opening: King's English", "c4 e5"),
+    ("A30", "English Opening: Symmetrical Variation", "c4 c5"),
+    ("A40", "Queen's Pawn Game", "d4"),
+    ("A43", "Benoni Defense: Old Benoni", "d4 c5"),
+    ("A45", "Indian Defense", "d4 Nf6"),
+    ("A56", "Benoni Defense", "d4 Nf6 c4 c5"),
+    ("A57", "Benko Gambit", "d4 Nf6 c4 c5 d5 b5"),
+    ("A80", "Dutch Defense", "d4 f5"),
+    ("B00", "King's Pawn Game", "e4"),
+    ("B01", "Scandinavian Defense", "e4 d5"),
+    ("B02", "Alekhine's Defense", "e4 Nf6"),
+    ("B06", "Modern Defense", "e4 g6"),
+    ("B07", "Pirc Defense", "e4 d6 d4 Nf6"),
+    ("B10", "Caro-Kann Defense", "e4 c6"),
+    (
+        "B12",
+        "Caro-Kann Defense: Advance Variation",
+        "e4 c6 d4 d5 e5",
+    ),
+    (
+        "B13",
+        "Caro-Kann Defense: Exchange Variation",
+        "e4 c6 d4 d5 exd5",
+    ),
+    (
+        "B18",
+        "Caro-Kann Defense: Classical Variation",
+        "e4 c6 d4 d5 Nc3 dxe4 Nxe4 Bf5",
+    ),
+    ("B20", "Sicilian Defense", "e4 c5"),
+    (
+        "B21",
+        "Sicilian Defense: Smith-Morra Gambit",
+        "e4 c5 d4 cxd4 c3",
+    ),
+    ("B22", "Sicilian Defense: Alapin Variation", "e4 c5 c3"),
+    ("B23", "Sicilian Defense: Closed", "e4 c5 Nc3"),
+    ("B30", "Sicilian Defense: Old Sicilian", "e4 c5 Nf3 Nc6"),
+    (
+        "B33",
+        "Sicilian Defense: Sveshnikov Variation",
+        "e4 c5 Nf3 Nc6 d4 cxd4 Nxd4 Nf6 Nc3 e5",
+    ),
+    ("B40", "Sicilian Defense: French Variation", "e4 c5 Nf3 e6"),
+    ("B50", "Sicilian Defense", "e4 c5 Nf3 d6"),
+    ("B54", "Sicilian Defense: Open", "e4 c5 Nf3 d6 d4 cxd4 Nxd4"),
+    (
+        "B70",
+        "Sicilian Defense: Dragon Variation",
+        "e4 c5 Nf3 d6 d4 cxd4 Nxd4 Nf6 Nc3 g6",
+    ),
+    (
+        "B90",
+        "Sicilian Defense: Najdorf Variation",
+        "e4 c5 Nf3 d6 d4 cxd4 Nxd4 Nf6 Nc3 a6",
+    ),
+    ("C00", "French Defense", "e4 e6"),
+    (
+        "C01",
+        "French Defense: Exchange Variation",
+        "e4 e6 d4 d5 exd5",
+    ),
+    ("C02", "French Defense: Advance Variation", "e4 e6 d4 d5 e5"),
+    (
+        "C03",
+        "French Defense: Tarrasch Variation",
+        "e4 e6 d4 d5 Nd2",
+    ),
+    (
+        "C10",
+        "French Defense: Paulsen Variation",
+        "e4 e6 d4 d5 Nc3",
+    ),
+    (
+        "C15",
+        "French Defense: Winawer Variation",
+        "e4 e6 d4 d5 Nc3 Bb4",
+    ),
+    ("C20", "King's Pawn Game", "e4 e5"),
+    ("C21", "Center Game", "e4 e5 d4 exd4"),
+    ("C23", "Bishop's Opening", "e4 e5 Bc4"),
+    ("C25", "Vienna Game", "e4 e5 Nc3"),
+    ("C30", "King's Gambit", "e4 e5 f4"),
+    ("C33", "King's Gambit Accepted", "e4 e5 f4 exf4"),
+    ("C40", "King's Knight Opening", "e4 e5 Nf3"),
+    ("C41", "Philidor Defense", "e4 e5 Nf3 d6"),
+    ("C42", "Petrov's Defense", "e4 e5 Nf3 Nf6"),
+    (
+        "C44",
+        "King's Knight Opening: Normal Variation",
+        "e4 e5 Nf3 Nc6",
+    ),
+    ("C44", "Scotch Game", "e4 e5 Nf3 Nc6 d4"),
+    ("C46", "Three Knights Game", "e4 e5 Nf3 Nc6 Nc3"),
+    ("C47", "Four Knights Game", "e4 e5 Nf3 Nc6 Nc3 Nf6"),
+    ("C50", "Italian Game", "e4 e5 Nf3 Nc6 Bc4"),
+    ("C50", "Italian Game: Giuoco Piano", "e4 e5 Nf3 Nc6 Bc4 Bc5"),
+    (
+        "C51",
+        "Italian Game: Evans Gambit",
+        "e4 e5 Nf3 Nc6 Bc4 Bc5 b4",
+    ),
+    (
+        "C55",
+        "Italian Game: Two Knights Defense",
+        "e4 e5 Nf3 Nc6 Bc4 Nf6",
+    ),
+    ("C60", "Ruy Lopez", "e4 e5 Nf3 Nc6 Bb5"),
+    ("C65", "Ruy Lopez: Berlin Defense", "e4 e5 Nf3 Nc6 Bb5 Nf6"),
+    ("C68", "Ruy Lopez: Morphy Defense", "e4 e5 Nf3 Nc6 Bb5 a6"),
+    (
+        "C68",
+        "Ruy Lopez: Exchange Variation",
+        "e4 e5 Nf3 Nc6 Bb5 a6 Bxc6",
+    ),
+    (
+        "C84",
+        "Ruy Lopez: Closed",
+        "e4 e5 Nf3 Nc6 Bb5 a6 Ba4 Nf6 O-O Be7",
+    ),
+    ("D00", "Queen's Pawn Game", "d4 d5"),
+    (
+        "D02",
+        "Queen's Pawn Game: London System",
+        "d4 d5 Nf3 Nf6 Bf4",
+    ),
+    ("D06", "Queen's Gambit", "d4 d5 c4"),
+    (
+        "D07",
+        "Queen's Gambit Declined: Chigorin Defense",
+        "d4 d5 c4 Nc6",
+    ),
+    (
+        "D08",
+        "Queen's Gambit Declined: Albin Countergambit",
+        "d4 d5 c4 e5",
+    ),
+    ("D10", "Slav Defense", "d4 d5 c4 c6"),
+    ("D20", "Queen's Gambit Accepted", "d4 d5 c4 dxc4"),
+    ("D30", "Queen's Gambit Declined", "d4 d5 c4 e6"),
+    ("D43", "Semi-Slav Defense", "d4 d5 c4 e6 Nc3 Nf6 Nf3 c6"),
+    ("D80", "Grunfeld Defense", "d4 Nf6 c4 g6 Nc3 d5"),
+    ("E01", "Catalan Opening", "d4 Nf6 c4 e6 g3"),
+    ("E12", "Queen's Indian Defense", "d4 Nf6 c4 e6 Nf3 b6"),
+    ("E20", "Nimzo-Indian Defense", "d4 Nf6 c4 e6 Nc3 Bb4"),
+    ("E60", "King's Indian Defense", "d4 Nf6 c4 g6"),
+];
+
+/// The keys (`Board::position_key()`) of the positions at the end of `OPENINGS`' lines.
+fn opening_keys() -> Vec<(u64, EcoCode, &'static str)> {
+    OPENINGS
+        .iter()
+        .map(|&(code, name, moves)| {
+            let mut replay = Game::new();
+            for san in moves.split_whitespace() {
+                let mv = replay.board().parse_san(replay.turn(), san);
+                replay.push_move(mv.expect("an illegal move in the ECO table"));
+            }
+            let key = replay.board().position_key(replay.turn());
+            (
+                key,
+                code.parse().expect("a bad code in the ECO table"),
+                name,
+            )
+        })
+        .collect()
+}
+
+/// The opening named for exactly this position, with `color` to move. Only standard chess has
+/// opening names.
+pub fn lookup(board: &Board, color: Color) -> Option<(EcoCode, &'static str)> {
+    if board.variant != Variant::Standard {
+        return None;
+    }
+    let key = board.position_key(color);
+    opening_keys()
+        .into_iter()
+        .find(|&(opening, _, _)| opening == key)
+        .map(|(_, code, name)| (code, name))
+}
+
+impl Game {
+    /// The name of the last named position the game went through, see `eco::lookup()`.
+    pub fn opening(&self) -> Option<(EcoCode, &'static str)> {
+        if self.variant() != Variant::Standard {
+            return None;
+        }
+        let openings = opening_keys();
+        let current = self.board().position_key(self.turn());
+        core::iter::once(current)
+            .chain(self.history().iter().rev().copied())
+            .find_map(|key| openings.iter().find(|&&(opening, _, _)| opening == key))
+            .map(|&(_, code, name)| (code, name))
+    }
+}
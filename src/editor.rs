@@ -0,0 +1,171 @@
+// The board editor: sets up a position piece by piece, with the side to move and the castling
+// rights, to play or analyze it after, e.g. a position from a book or from a game over the board.
+
+use crate::{fen::piece_from_char, Board, ChessFlags, Color, Move, Square};
+use std::io::{self, BufRead, Write};
+
+/// What to do with the position set up in `edit()`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Finish {
+    Play,
+    Analyze,
+}
+
+const HELP: &str = "\
+Ke1, nf6     puts a piece on a square, White's in uppercase and Black's in lowercase
+-e4          empties a square
+clear        empties the board
+start        sets up the starting position
+fen <fen>    sets up a FEN's position
+turn w|b     sets the side to move
+castle KQkq  sets the castling rights, \"-\" for none
+play         plays the position against the bot, you're White
+analyze      searches the position
+quit         leaves the editor
+";
+
+/// Why the position can't be played, see `Board::validate()`: the side that isn't to move mustn't
+/// be in check either.
+fn playable(board: &Board, color: Color) -> Result<(), &'static str> {
+    board.validate().map_err(|err| err.0)?;
+    if board.in_check(color.inv()) {
+        return Err("the side that isn't to move is in check");
+    }
+    Ok(())
+}
+
+/// Edits `board` with `color` to move by the commands of `HELP`, a line each, and shows the board
+/// and its FEN after every change. Returns the position once it's played or analyzed, and it's
+/// only let go then if it's playable. "quit" or the end of the input leave without one.
+pub fn edit(
+    mut board: Board,
+    mut color: Color,
+    mut input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<Option<(Board, Color, Finish)>> {
+    let mut buf = String::new();
+    write!(output, "{}", HELP)?;
+    let mut changed = true;
+
+    loop {
+        if changed {
+            writeln!(output)?;
+            write!(output, "{}", board.render(Color::White))?;
+            writeln!(output, "{}", board.to_fen(color))?;
+        }
+        changed = true;
+        write!(output, "> ")?;
+        output.flush()?;
+
+        buf.clear();
+        if input.read_line(&mut buf)? == 0 {
+            return Ok(None);
+        }
+        let (command, arg) = match buf.trim().split_once(' ') {
+            Some((command, arg)) => (command, arg.trim()),
+            None => (buf.trim(), ""),
+        };
+        let finish = match command {
+            "quit" => return Ok(None),
+            "help" => {
+                write!(output, "{}", HELP)?;
+                changed = false;
+                continue;
+            }
+            "play" => Finish::Play,
+            "analyze" => Finish::Analyze,
+            "clear" => {
+                for square in 0..64 {
+                    board.set(1 << square, None);
+                }
+                board.prev_move = Move::NULL;
+                continue;
+            }
+            "start" => {
+                (board, color) = (Board::with_variant(board.variant), Color::White);
+                continue;
+            }
+            "fen" => {
+                match Board::from_fen(arg) {
+                    Ok(fen) => (board, color) = fen,
+                    Err(err) => {
+                        writeln!(output, "{}", err)?;
+                        changed = false;
+                    }
+                }
+                continue;
+            }
+            "turn" => {
+                match arg {
+                    "w" | "white" => color = Color::White,
+                    "b" | "black" => color = Color::Black,
+                    _ => {
+                        writeln!(output, "The side to move is w or b")?;
+                        changed = false;
+                    }
+                }
+                continue;
+            }
+            "castle" => {
+                let flags = arg.chars().filter(|&ch| ch != '-').try_fold(
+                    ChessFlags::empty(),
+                    |flags, ch| {
+                        Some(
+                            flags
+                                | match ch {
+                                    'K' => ChessFlags::WHITE_KINGS_CASTLE,
+                                    'Q' => ChessFlags::WHITE_QUEENS_CASTLE,
+                                    'k' => ChessFlags::BLACK_KINGS_CASTLE,
+                                    'q' => ChessFlags::BLACK_QUEENS_CASTLE,
+                                    _ => return None,
+                                },
+                        )
+                    },
+                );
+                let flags = match flags {
+                    Some(flags) if !arg.is_empty() => flags,
+                    _ => {
+                        writeln!(output, "The castling rights are like KQkq, or - for none")?;
+                        changed = false;
+                        continue;
+                    }
+                };
+                board.flags = flags;
+                board.drop_contradicted_castling();
+                if board.flags != flags {
+                    writeln!(
+                        output,
+                        "Dropped the rights of the kings and rooks that aren't on their squares"
+                    )?;
+                }
+                continue;
+            }
+            _ => {
+                let mut chars = command.chars();
+                let (first, square) = (chars.next(), chars.as_str().parse::<Square>());
+                match (first, square) {
+                    (Some('-'), Ok(square)) => board.set(square.bit(), None),
+                    (Some(ch), Ok(square)) if piece_from_char(ch).is_some() => {
+                        board.set(square.bit(), piece_from_char(ch));
+                    }
+                    _ => {
+                        writeln!(output, "Unknown command, \"help\" lists them")?;
+                        changed = false;
+                        continue;
+                    }
+                }
+                // No en passant into an edited position.
+                board.prev_move = Move::NULL;
+                continue;
+            }
+        };
+
+        match playable(&board, color) {
+            Ok(()) => return Ok(Some((board, color, finish))),
+            Err(err) => {
+                writeln!(output, "Not a playable position: {}", err)?;
+                changed = false;
+            }
+        }
+    }
+}
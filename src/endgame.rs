@@ -0,0 +1,135 @@
+// Endgame knowledge the linear evaluation can't express: drawish endgames are scaled towards 0 and
+// a lone king is driven to the edge, so the bot converts K+R vs K instead of shuffling.
+
+use crate::{
+    masks::{FILE_A, FILE_H, LIGHT_SQUARES},
+    material::MaterialCount,
+    Board, Color, PieceType, Pieces, Variant,
+};
+
+/// The scale factors are out of this.
+const SCALE_NORMAL: i32 = 64;
+
+/// The number of king moves between two squares.
+pub(crate) fn distance(a: u32, b: u32) -> u32 {
+    let files = (a & 7).abs_diff(b & 7);
+    let ranks = (a >> 3).abs_diff(b >> 3);
+    files.max(ranks)
+}
+
+/// The square the pawn on `pawn` promotes on.
+fn promotion_square(pawn: u32, color: Color) -> u32 {
+    match color {
+        Color::White => 0o70 | pawn & 7,
+        Color::Black => pawn & 7,
+    }
+}
+
+/// Whether K+P vs K is a draw by the usual rules: the defending king reaches the corner of a rook
+/// pawn, or blocks the pawn from the front while the attacking king isn't on a key square.
+fn kpk_is_draw(strong: &Pieces, weak: &Pieces, color: Color) -> bool {
+    let pawn = strong.pawns().trailing_zeros();
+    let (king, defender) = (strong.king().trailing_zeros(), weak.king().trailing_zeros());
+    let promotion = promotion_square(pawn, color);
+
+    if strong.pawns() & (FILE_A | FILE_H) != 0 {
+        return distance(defender, promotion) <= 1;
+    }
+
+    // The squares in front of the pawn, by how many ranks.
+    let (file, rank) = (pawn & 7, pawn >> 3);
+    let relative_rank = match color {
+        Color::White => rank,
+        Color::Black => 7 - rank,
+    };
+    let ranks_ahead = |square: u32| match color {
+        Color::White => (square >> 3) as i32 - rank as i32,
+        Color::Black => rank as i32 - (square >> 3) as i32,
+    };
+
+    // From these squares the attacking king wins wherever the defending king is.
+    let key_square = match ranks_ahead(king) {
+        1 => 4 <= relative_rank,
+        2 => true,
+        _ => false,
+    } && (king & 7).abs_diff(file) <= 1;
+    let blocks = defender & 7 == file && 0 < ranks_ahead(defender) && ranks_ahead(king) <= 0;
+
+    !key_square && blocks
+}
+
+/// How much of the evaluation `strong`, the side that's ahead, can realize, out of `SCALE_NORMAL`.
+fn scale(strong: &Pieces, weak: &Pieces, color: Color) -> i32 {
+    let (strong_count, weak_count) = (MaterialCount::of(strong), MaterialCount::of(weak));
+    let weak_bare = weak_count.is_bare();
+
+    if strong_count.non_pawns() == 0 && strong_count.get(PieceType::Pawn) == 1 && weak_bare {
+        return if kpk_is_draw(strong, weak, color) {
+            0
+        } else {
+            SCALE_NORMAL
+        };
+    }
+
+    // A bishop that doesn't control the promotion square can't drive the king out of the corner.
+    if strong_count.non_pawns() == 1
+        && strong_count.get(PieceType::Bishop) == 1
+        && strong.pawns() != 0
+        && (strong.pawns() & !FILE_A == 0 || strong.pawns() & !FILE_H == 0)
+        && weak_bare
+    {
+        let promotion = promotion_square(strong.pawns().trailing_zeros(), color);
+        let bishop_light = strong.bishops() & LIGHT_SQUARES != 0;
+        let promotion_light = 1 << promotion & LIGHT_SQUARES != 0;
+        if bishop_light != promotion_light && distance(weak.king().trailing_zeros(), promotion) <= 1
+        {
+            return 0;
+        }
+    }
+
+    // Opposite colored bishops hold many endgames a pawn or two down.
+    if strong_count.non_pawns() == 1
+        && weak_count.non_pawns() == 1
+        && strong_count.get(PieceType::Bishop) == 1
+        && weak_count.get(PieceType::Bishop) == 1
+        && (strong.bishops() & LIGHT_SQUARES == 0) != (weak.bishops() & LIGHT_SQUARES == 0)
+    {
+        return SCALE_NORMAL / 2;
+    }
+
+    SCALE_NORMAL
+}
+
+/// A bonus for the side mating a lone king, for pushing it to the edge and coming closer with
+/// its own king.
+fn mop_up(strong: &Pieces, weak: &Pieces) -> i32 {
+    let count = MaterialCount::of(strong);
+    let bishops = count.get(PieceType::Bishop);
+    let can_mate = count.get(PieceType::Queen) + count.get(PieceType::Rook) != 0
+        || bishops >= 2
+        || bishops != 0 && count.get(PieceType::Knight) != 0;
+    if !MaterialCount::of(weak).is_bare() || !can_mate {
+        return 0;
+    }
+
+    let (king, defender) = (strong.king().trailing_zeros(), weak.king().trailing_zeros());
+    let (file, rank) = ((defender & 7) as i32, (defender >> 3) as i32);
+    let from_center = (3 - file).max(file - 4) + (3 - rank).max(rank - 4);
+    10 * from_center + 4 * (7 - distance(king, defender) as i32)
+}
+
+/// Adjusts `value`, the linear evaluation for White, to the endgame on the board.
+pub fn adjust(board: &Board, value: i32) -> i32 {
+    // Nothing is a dead end with pieces to drop, and Horde's king-less White is out of the rules.
+    if board.reserves != [[0; 6]; 2] || board.variant == Variant::Horde {
+        return value;
+    }
+    let (white, black) = (&board.white_pieces, &board.black_pieces);
+
+    let scaled = if 0 < value {
+        value * scale(white, black, Color::White) / SCALE_NORMAL
+    } else {
+        value * scale(black, white, Color::Black) / SCALE_NORMAL
+    };
+    scaled + mop_up(white, black) - mop_up(black, white)
+}
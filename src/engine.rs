@@ -0,0 +1,171 @@
+// What the players of a game have in common: the alpha-beta `Bot`, the `MctsBot` and the
+// baselines of the `baseline` module all choose a move for a position within some limits,
+// whatever they do to find it. The frontends and the match runner only talk to an `Engine`.
+
+use crate::{
+    bot::MoveOrderer, eval::Evaluator, time::TimeManager, Board, Bot, Color, MctsBot, Move,
+};
+use core::fmt;
+use std::time::{Duration, Instant};
+
+/// `Score::centipawns()` of a mate in 0, the mates in more moves are a centipawn less per move.
+pub const MATE_CENTIPAWNS: i32 = 100_000;
+/// Search values this close to `i32::MAX` are mates, see `bot::mated()`.
+const MAX_MATE_PLIES: i32 = 1000;
+
+/// What a search may spend on a move, and what it knows of the game.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SearchLimits<'a> {
+    /// The deepest the search may go, on top of the engine's own depth.
+    pub depth: Option<u32>,
+    /// The time for the move, the search deepens within it.
+    pub time: Option<TimeManager>,
+    /// The most nodes the search may visit, as in UCI's nodes. It's checked every so many nodes,
+    /// so the search may go a little past it.
+    pub nodes: Option<u64>,
+    /// The keys of the positions before, see `Game::history()`. Repeating them is a draw.
+    pub history: &'a [u64],
+    /// The moves to choose from, which must be legal, all of them when empty (UCI's
+    /// searchmoves).
+    pub moves: &'a [Move],
+}
+
+/// The value of a move for the side that plays it, as the frontends show it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Score {
+    Cp(i32),
+    /// The moves to the mate, negative when the side gets mated.
+    Mate(i32),
+}
+
+impl Score {
+    /// The score of a value of the bot's search, whose mates are near `i32::MAX`, see
+    /// `bot::mated()`.
+    pub fn from_value(value: i32) -> Self {
+        let plies = i32::MAX - value.abs();
+        if plies < MAX_MATE_PLIES {
+            Self::Mate(value.signum() * (plies + 1) / 2)
+        } else {
+            Self::Cp(value)
+        }
+    }
+
+    /// The score in centipawns, a mate counts as `MATE_CENTIPAWNS` less the moves to it, so the
+    /// scores compare like the outcomes.
+    pub fn centipawns(self) -> i32 {
+        match self {
+            Self::Cp(cp) => cp,
+            Self::Mate(moves) if 0 < moves => MATE_CENTIPAWNS - moves,
+            Self::Mate(moves) => -MATE_CENTIPAWNS - moves,
+        }
+    }
+}
+
+/// In pawns or mates, e.g. "+1.30", "-0.25", "M4" or "-M3".
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::Cp(cp) => write!(f, "{:+.2}", cp as f64 / 100.0),
+            Self::Mate(moves) if moves < 0 => write!(f, "-M{}", -moves),
+            Self::Mate(moves) => write!(f, "M{}", moves),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct SearchResult {
+    /// `None` if there's no legal move or the board is invalid.
+    pub best_move: Option<Move>,
+    /// The score of the move for the side that plays it, `None` if the engine doesn't value
+    /// moves or didn't have to search.
+    pub score: Option<Score>,
+    /// The depth that the score comes from, 0 without a search to a depth.
+    pub depth: u32,
+    /// The positions the search visited.
+    pub nodes: u64,
+    /// The line the engine expects, from the best move on. It's empty without a best move.
+    pub pv: Vec<Move>,
+    pub time: Duration,
+    /// How full the engine's transposition table is in permille, `None` without one.
+    pub hashfull: Option<u32>,
+}
+
+impl SearchResult {
+    /// The nodes per second, 0 if the search took no measurable time.
+    pub fn nps(&self) -> u64 {
+        match self.time.as_micros() {
+            0 => 0,
+            micros => (self.nodes as u128 * 1_000_000 / micros) as u64,
+        }
+    }
+}
+
+pub trait Engine {
+    /// Searches the position with `color` to move within `limits`.
+    fn best_move(&mut self, board: &Board, color: Color, limits: &SearchLimits) -> SearchResult;
+
+    /// The move for `color` to play without limits other than the engine's own.
+    fn choose_move(&mut self, board: &Board, color: Color) -> Option<Move> {
+        self.best_move(board, color, &SearchLimits::default())
+            .best_move
+    }
+}
+
+impl<E, O> Engine for Bot<E, O>
+where
+    E: Evaluator + Sync,
+    E::State: Sync,
+    O: MoveOrderer + Sync,
+{
+    fn best_move(&mut self, board: &Board, color: Color, limits: &SearchLimits) -> SearchResult {
+        let start = Instant::now();
+        let moves = match limits.moves {
+            [] => board.moves(color),
+            moves => moves.to_vec(),
+        };
+        let own_depth = self.depth;
+        self.depth = limits.depth.map_or(own_depth, |depth| depth.min(own_depth));
+        let own_node_limit = self.node_limit;
+        self.node_limit = match (limits.nodes, own_node_limit) {
+            (Some(nodes), Some(own)) => Some(nodes.min(own)),
+            (nodes, own) => nodes.or(own),
+        };
+        // A search that the nodes can stop deepens, so that it has a move when they run out.
+        let time = limits
+            .time
+            .or_else(|| self.node_limit.map(|_| TimeManager::unlimited()));
+        let best_move = match time {
+            Some(mut time) => {
+                self.choose_move_timed(board, color, limits.history, &moves, &mut time)
+            }
+            None => self.choose_move_from(board, color, limits.history, &moves),
+        };
+        self.depth = own_depth;
+        self.node_limit = own_node_limit;
+
+        SearchResult {
+            best_move,
+            score: self.value().map(Score::from_value),
+            depth: self.searched_depth(),
+            nodes: self.nodes(),
+            pv: best_move.map_or_else(Vec::new, |mv| self.principal_variation(board, color, mv)),
+            time: start.elapsed(),
+            hashfull: self.table.as_ref().map(|table| table.hashfull()),
+        }
+    }
+}
+
+/// The iterations are its only limit, and it always chooses from all the legal moves.
+impl<E: Evaluator> Engine for MctsBot<E> {
+    fn best_move(&mut self, board: &Board, color: Color, limits: &SearchLimits) -> SearchResult {
+        let start = Instant::now();
+        let best_move = self.choose_move_with_history(board, color, limits.history);
+        SearchResult {
+            best_move,
+            nodes: self.iterations as u64,
+            pv: best_move.into_iter().collect(),
+            time: start.elapsed(),
+            ..SearchResult::default()
+        }
+    }
+}
@@ -0,0 +1,91 @@
+// One error type for the whole crate, so that an application embedding it can `?` every parser's
+// and the board's errors into a single type. The modules keep their own error types, which convert
+// into it.
+
+use crate::{fen::FenError, pgn::PgnError, Board, Color, Move, MoveError};
+use alloc::string::{String, ToString};
+use core::fmt;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum MxChessError {
+    /// A malformed FEN, or one of a position that can't be played, see `Board::validate()`.
+    Fen(FenError),
+    /// A SAN move that is malformed, illegal or ambiguous.
+    San(String),
+    /// A malformed PGN.
+    Pgn(PgnError),
+    /// A move in UCI's notation, e.g. "e2e4", that is malformed or illegal.
+    Uci(String),
+    /// A move that isn't legal in the position.
+    IllegalMove(Move),
+    /// A board the engine can't play on, e.g. one without a king, see `Board::validate()`.
+    InvalidPosition(&'static str),
+    /// A message of UCI, XBoard or an external engine that doesn't follow the protocol.
+    Protocol(String),
+    /// A piece type index outside of 0..6.
+    PieceType(u8),
+}
+
+impl fmt::Display for MxChessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Fen(err) => write!(f, "{}", err),
+            Self::San(san) => write!(f, "Bad SAN move: {}", san),
+            Self::Pgn(err) => write!(f, "{}", err),
+            Self::Uci(mv) => write!(f, "Bad UCI move: {}", mv),
+            Self::IllegalMove(mv) => write!(f, "Illegal move: {}", mv),
+            Self::InvalidPosition(msg) => write!(f, "Invalid position: {}", msg),
+            Self::Protocol(msg) => write!(f, "Protocol error: {}", msg),
+            Self::PieceType(n) => write!(f, "No piece type {}, they're 0..6", n),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MxChessError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Fen(err) => Some(err),
+            Self::Pgn(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<FenError> for MxChessError {
+    fn from(err: FenError) -> Self {
+        Self::Fen(err)
+    }
+}
+
+impl From<PgnError> for MxChessError {
+    fn from(err: PgnError) -> Self {
+        Self::Pgn(err)
+    }
+}
+
+impl From<MoveError> for MxChessError {
+    fn from(err: MoveError) -> Self {
+        Self::IllegalMove(err.0)
+    }
+}
+
+impl Board {
+    /// Like `validate()`, but with the crate's error.
+    pub fn check_valid(&self) -> Result<(), MxChessError> {
+        self.validate()
+            .map_err(|FenError(msg)| MxChessError::InvalidPosition(msg))
+    }
+
+    /// Like `parse_san()`, but with the crate's error.
+    pub fn try_parse_san(&self, color: Color, san: &str) -> Result<Move, MxChessError> {
+        self.parse_san(color, san)
+            .ok_or_else(|| MxChessError::San(san.to_string()))
+    }
+
+    /// Like `parse_move()`, but with the crate's error.
+    pub fn try_parse_move(&self, color: Color, s: &str) -> Result<Move, MxChessError> {
+        self.parse_move(color, s)
+            .ok_or_else(|| MxChessError::Uci(s.to_string()))
+    }
+}
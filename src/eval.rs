@@ -0,0 +1,608 @@
+// A linear evaluation: material, piece-square tables and a few pawn structure and king safety
+// terms, tapered between middlegame and endgame values by the material left. Being linear in its
+// parameters makes it easy to tune, see the `tune` module. The endgame knowledge that isn't
+// linear is in the `endgame` module, and the variants' goals, like King of the Hill's, aren't
+// tuned.
+
+use crate::{
+    endgame::{self, distance},
+    masks::{FILE_A, FILE_H, RANK_2, RANK_3, RANK_4, RANK_5, RANK_6, RANK_7},
+    variant::HILL,
+    BitIterator, Board, Color, PieceType, Pieces, Variant,
+};
+use alloc::{string::ToString, vec::Vec};
+use core::{fmt, str::FromStr};
+
+const PIECE_TYPES: [PieceType; 6] = [
+    PieceType::King,
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+    PieceType::Pawn,
+];
+const PIECE_NAMES: [&str; 6] = ["king", "queen", "rook", "bishop", "knight", "pawn"];
+
+/// The phase of the starting position, see `phase()`.
+const PHASE_MAX: i32 = 24;
+/// King of the Hill's bonus for a king by its distance from the hill, on it the game is won.
+const HILL_BONUS: [i32; 4] = [10_000, 250, 80, 20];
+
+/// An evaluation backend for the bot.
+/// `State` is kept along the searched line and updated on every move, since the board is
+/// copied rather than unmade, the parent's state is simply kept around to undo a move.
+pub trait Evaluator {
+    type State: Clone;
+
+    fn init(&self, board: &Board) -> Self::State;
+
+    /// Updates the state of `before` to that of `after`, which is one move away.
+    fn update(&self, state: &mut Self::State, before: &Board, after: &Board);
+
+    /// The board's value for White, in centipawns.
+    fn evaluate(&self, board: &Board, state: &Self::State) -> i32;
+
+    /// The board's value for `color`, so that callers don't have to get the sign right.
+    fn evaluate_for(&self, board: &Board, state: &Self::State, color: Color) -> i32 {
+        let value = self.evaluate(board, state);
+        match color {
+            Color::White => value,
+            Color::Black => -value,
+        }
+    }
+}
+
+/// The evaluation's parameters, the value is interpolated between the middlegame's and the
+/// endgame's by `phase()`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct EvalParams {
+    pub middlegame: PhaseParams,
+    pub endgame: PhaseParams,
+}
+
+impl Default for EvalParams {
+    /// Material and passed pawns, so that pawn endgames can be won on purpose. The kings only
+    /// escort the passed pawns in the endgame.
+    fn default() -> Self {
+        Self {
+            middlegame: PhaseParams {
+                passer_king_distance: 0,
+                ..PhaseParams::default()
+            },
+            endgame: PhaseParams::default(),
+        }
+    }
+}
+
+/// The parameters of one game phase, in centipawns and from White's side.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PhaseParams {
+    /// Indexed by `PieceType`, the king's is unused.
+    pub material: [i32; 6],
+    /// Piece-square tables indexed by `PieceType` and square, Black's squares are mirrored.
+    pub pst: [[i32; 64]; 6],
+    /// Per extra pawn on a file.
+    pub doubled_pawn: i32,
+    /// Per pawn without friendly pawns on the neighbouring files.
+    pub isolated_pawn: i32,
+    /// Per pawn right in front of the king.
+    pub king_shield: i32,
+    /// Per passed pawn, indexed by its rank from its side (the first and last are unused).
+    pub passed_pawn: [i32; 8],
+    /// Per passed pawn with no pieces in front of it.
+    pub free_passer: i32,
+    /// Per passed pawn with a friendly rook behind it.
+    pub rook_behind_passer: i32,
+    /// Per square the enemy king is further than the own king from the square in front of a
+    /// passed pawn.
+    pub passer_king_distance: i32,
+    /// For having two bishops or more.
+    pub bishop_pair: i32,
+    /// Per rook on a file without pawns.
+    pub rook_open_file: i32,
+    /// Per rook on a file with only enemy pawns.
+    pub rook_semi_open_file: i32,
+    /// Per knight on the fourth to sixth rank, protected by a pawn and out of the enemy pawns'
+    /// reach.
+    pub knight_outpost: i32,
+    /// Per rook on the seventh rank.
+    pub rook_on_seventh: i32,
+}
+
+impl Default for PhaseParams {
+    fn default() -> Self {
+        Self {
+            material: [0, 900, 500, 300, 300, 100],
+            pst: [[0; 64]; 6],
+            doubled_pawn: 0,
+            isolated_pawn: 0,
+            king_shield: 0,
+            passed_pawn: [0, 5, 10, 20, 35, 60, 100, 0],
+            free_passer: 10,
+            rook_behind_passer: 15,
+            passer_king_distance: 5,
+            bishop_pair: 0,
+            rook_open_file: 0,
+            rook_semi_open_file: 0,
+            knight_outpost: 0,
+            rook_on_seventh: 0,
+        }
+    }
+}
+
+/// How far from the endgame the board is, from 0 with only kings and pawns left to `PHASE_MAX`
+/// with all the pieces: a knight or bishop counts 1, a rook 2 and a queen 4.
+pub fn phase(board: &Board) -> i32 {
+    let (white, black) = (&board.white_pieces, &board.black_pieces);
+    let phase = (white.knights() | white.bishops() | black.knights() | black.bishops())
+        .count_ones()
+        + 2 * (white.rooks() | black.rooks()).count_ones()
+        + 4 * (white.queens() | black.queens()).count_ones();
+    (phase as i32).min(PHASE_MAX)
+}
+
+/// The kings' King of the Hill bonuses for White, 0 in the other variants.
+fn hill_bonus(board: &Board) -> i32 {
+    if board.variant != Variant::KingOfTheHill {
+        return 0;
+    }
+    let bonus = |king: u64| {
+        let king = king.trailing_zeros();
+        let steps = BitIterator(HILL)
+            .map(|square| distance(king, square.trailing_zeros()))
+            .min()
+            .unwrap();
+        HILL_BONUS[steps as usize]
+    };
+    bonus(board.white_pieces.king()) - bonus(board.black_pieces.king())
+}
+
+/// The value of every term for one side, the evaluation is the sum of the terms times their
+/// parameters. The pieces in the side's Crazyhouse reserve count as material.
+fn side_features(
+    pieces: &Pieces,
+    opponent: &Pieces,
+    reserve: &[u8; 6],
+    color: Color,
+    mut f: impl FnMut(usize, f64),
+) {
+    let mirror = match color {
+        Color::White => 0,
+        Color::Black => 0o70,
+    };
+
+    for (i, &ty) in PIECE_TYPES.iter().enumerate() {
+        let bitboard = pieces.get(ty);
+        f(
+            PhaseParams::MATERIAL + i,
+            (bitboard.count_ones() + reserve[ty as usize] as u32) as f64,
+        );
+        for bit in BitIterator(bitboard) {
+            f(
+                PhaseParams::PST + 64 * i + (bit.trailing_zeros() as usize ^ mirror),
+                1.0,
+            );
+        }
+    }
+
+    let (mut doubled, mut isolated) = (0, 0);
+    for file in 0..8 {
+        let pawns = (pieces.pawns() & FILE_A << file).count_ones();
+        let neighbours = neighbour_files(file);
+        doubled += pawns.saturating_sub(1);
+        if pieces.pawns() & neighbours == 0 {
+            isolated += pawns;
+        }
+    }
+    f(PhaseParams::DOUBLED_PAWN, doubled as f64);
+    f(PhaseParams::ISOLATED_PAWN, isolated as f64);
+
+    let king = pieces.king().trailing_zeros();
+    let front = match color {
+        Color::White if king < 0o70 => king + 0o10,
+        Color::Black if 0o10 <= king => king - 0o10,
+        _ => 64,
+    };
+    if front < 64 {
+        let file = front & 7;
+        let shield = ((0b111 << file >> 1) & 0xff) << (front - file);
+        f(
+            PhaseParams::KING_SHIELD,
+            (pieces.pawns() & shield).count_ones() as f64,
+        );
+    }
+
+    passed_pawn_features(pieces, opponent, color, &mut f);
+    piece_features(pieces, opponent, color, f);
+}
+
+/// The ranks after `rank`, from `color`'s side.
+fn ranks_ahead(rank: u32, color: Color) -> u64 {
+    match color {
+        Color::White => !0 << (8 * (rank + 1)),
+        Color::Black => (1 << (8 * rank)) - 1,
+    }
+}
+
+/// The files next to `file`.
+fn neighbour_files(file: u32) -> u64 {
+    (FILE_A << (file.max(1) - 1) | FILE_A << (file + 1).min(7)) & !(FILE_A << file)
+}
+
+/// The passed pawns' terms: pawns without enemy pawns in front of them on their own and the
+/// neighbouring files.
+fn passed_pawn_features(
+    pieces: &Pieces,
+    opponent: &Pieces,
+    color: Color,
+    mut f: impl FnMut(usize, f64),
+) {
+    let (mut free, mut rook_behind, mut king_distance) = (0, 0, 0);
+
+    for bit in BitIterator(pieces.pawns()) {
+        let square = bit.trailing_zeros();
+        let (file, rank) = (square & 7, square >> 3);
+        let (ahead, behind) = (ranks_ahead(rank, color), ranks_ahead(rank, color.inv()));
+        let (stop, relative_rank) = match color {
+            Color::White => (square + 8, rank),
+            Color::Black => (square - 8, 7 - rank),
+        };
+        if opponent.pawns() & (FILE_A << file | neighbour_files(file)) & ahead != 0 {
+            continue;
+        }
+
+        f(PhaseParams::PASSED_PAWN + relative_rank as usize, 1.0);
+        if (pieces.all | opponent.all) & FILE_A << file & ahead == 0 {
+            free += 1;
+        }
+        if pieces.rooks() & FILE_A << file & behind != 0 {
+            rook_behind += 1;
+        }
+        king_distance += distance(opponent.king().trailing_zeros(), stop) as i32
+            - distance(pieces.king().trailing_zeros(), stop) as i32;
+    }
+
+    f(PhaseParams::FREE_PASSER, free as f64);
+    f(PhaseParams::ROOK_BEHIND_PASSER, rook_behind as f64);
+    f(PhaseParams::PASSER_KING_DISTANCE, king_distance as f64);
+}
+
+/// The bishop pair, rook file and knight outpost terms.
+fn piece_features(pieces: &Pieces, opponent: &Pieces, color: Color, mut f: impl FnMut(usize, f64)) {
+    f(
+        PhaseParams::BISHOP_PAIR,
+        (2 <= pieces.bishops().count_ones()) as u8 as f64,
+    );
+
+    let (mut open, mut semi_open) = (0, 0);
+    for bit in BitIterator(pieces.rooks()) {
+        let file = FILE_A << (bit.trailing_zeros() & 7);
+        if pieces.pawns() & file == 0 {
+            if opponent.pawns() & file == 0 {
+                open += 1;
+            } else {
+                semi_open += 1;
+            }
+        }
+    }
+    f(PhaseParams::ROOK_OPEN_FILE, open as f64);
+    f(PhaseParams::ROOK_SEMI_OPEN_FILE, semi_open as f64);
+
+    let (pawn_attacks, outpost_ranks, seventh) = match color {
+        Color::White => (
+            (pieces.pawns() & !FILE_A) << 7 | (pieces.pawns() & !FILE_H) << 9,
+            RANK_4 | RANK_5 | RANK_6,
+            RANK_7,
+        ),
+        Color::Black => (
+            (pieces.pawns() & !FILE_A) >> 9 | (pieces.pawns() & !FILE_H) >> 7,
+            RANK_3 | RANK_4 | RANK_5,
+            RANK_2,
+        ),
+    };
+    let outposts = BitIterator(pieces.knights() & outpost_ranks & pawn_attacks)
+        .filter(|bit| {
+            let square = bit.trailing_zeros();
+            let span = neighbour_files(square & 7) & ranks_ahead(square >> 3, color);
+            opponent.pawns() & span == 0
+        })
+        .count();
+    f(PhaseParams::KNIGHT_OUTPOST, outposts as f64);
+    f(
+        PhaseParams::ROOK_ON_SEVENTH,
+        (pieces.rooks() & seventh).count_ones() as f64,
+    );
+}
+
+impl PhaseParams {
+    const MATERIAL: usize = 0;
+    const PST: usize = 6;
+    const DOUBLED_PAWN: usize = Self::PST + 6 * 64;
+    const ISOLATED_PAWN: usize = Self::DOUBLED_PAWN + 1;
+    const KING_SHIELD: usize = Self::ISOLATED_PAWN + 1;
+    const PASSED_PAWN: usize = Self::KING_SHIELD + 1;
+    const FREE_PASSER: usize = Self::PASSED_PAWN + 8;
+    const ROOK_BEHIND_PASSER: usize = Self::FREE_PASSER + 1;
+    const PASSER_KING_DISTANCE: usize = Self::ROOK_BEHIND_PASSER + 1;
+    const BISHOP_PAIR: usize = Self::PASSER_KING_DISTANCE + 1;
+    const ROOK_OPEN_FILE: usize = Self::BISHOP_PAIR + 1;
+    const ROOK_SEMI_OPEN_FILE: usize = Self::ROOK_OPEN_FILE + 1;
+    const KNIGHT_OUTPOST: usize = Self::ROOK_SEMI_OPEN_FILE + 1;
+    const ROOK_ON_SEVENTH: usize = Self::KNIGHT_OUTPOST + 1;
+    /// The number of parameters of a phase.
+    pub const LEN: usize = Self::ROOK_ON_SEVENTH + 1;
+
+    fn weight(&self, i: usize) -> i32 {
+        match i {
+            Self::DOUBLED_PAWN => self.doubled_pawn,
+            Self::ISOLATED_PAWN => self.isolated_pawn,
+            Self::KING_SHIELD => self.king_shield,
+            Self::FREE_PASSER => self.free_passer,
+            Self::ROOK_BEHIND_PASSER => self.rook_behind_passer,
+            Self::PASSER_KING_DISTANCE => self.passer_king_distance,
+            Self::BISHOP_PAIR => self.bishop_pair,
+            Self::ROOK_OPEN_FILE => self.rook_open_file,
+            Self::ROOK_SEMI_OPEN_FILE => self.rook_semi_open_file,
+            Self::KNIGHT_OUTPOST => self.knight_outpost,
+            Self::ROOK_ON_SEVENTH => self.rook_on_seventh,
+            i if Self::PASSED_PAWN <= i => self.passed_pawn[i - Self::PASSED_PAWN],
+            i if Self::PST <= i => self.pst[(i - Self::PST) / 64][(i - Self::PST) % 64],
+            i => self.material[i],
+        }
+    }
+
+    fn weight_mut(&mut self, i: usize) -> &mut i32 {
+        match i {
+            Self::DOUBLED_PAWN => &mut self.doubled_pawn,
+            Self::ISOLATED_PAWN => &mut self.isolated_pawn,
+            Self::KING_SHIELD => &mut self.king_shield,
+            Self::FREE_PASSER => &mut self.free_passer,
+            Self::ROOK_BEHIND_PASSER => &mut self.rook_behind_passer,
+            Self::PASSER_KING_DISTANCE => &mut self.passer_king_distance,
+            Self::BISHOP_PAIR => &mut self.bishop_pair,
+            Self::ROOK_OPEN_FILE => &mut self.rook_open_file,
+            Self::ROOK_SEMI_OPEN_FILE => &mut self.rook_semi_open_file,
+            Self::KNIGHT_OUTPOST => &mut self.knight_outpost,
+            Self::ROOK_ON_SEVENTH => &mut self.rook_on_seventh,
+            i if Self::PASSED_PAWN <= i => &mut self.passed_pawn[i - Self::PASSED_PAWN],
+            i if Self::PST <= i => &mut self.pst[(i - Self::PST) / 64][(i - Self::PST) % 64],
+            i => &mut self.material[i],
+        }
+    }
+
+    /// Parses a line of the `Display` format, split into words.
+    fn parse_line<'a>(
+        &mut self,
+        mut words: impl Iterator<Item = &'a str>,
+        err: ParseParamsError,
+    ) -> Result<(), ParseParamsError> {
+        let values: &mut [i32] = match words.next() {
+            None => return Ok(()),
+            Some("material") => &mut self.material,
+            Some("pst") => {
+                let name = words.next().ok_or(err)?;
+                let ty = PIECE_NAMES.iter().position(|&n| n == name).ok_or(err)?;
+                &mut self.pst[ty]
+            }
+            Some("doubled_pawn") => core::slice::from_mut(&mut self.doubled_pawn),
+            Some("isolated_pawn") => core::slice::from_mut(&mut self.isolated_pawn),
+            Some("king_shield") => core::slice::from_mut(&mut self.king_shield),
+            Some("passed_pawn") => &mut self.passed_pawn,
+            Some("free_passer") => core::slice::from_mut(&mut self.free_passer),
+            Some("rook_behind_passer") => core::slice::from_mut(&mut self.rook_behind_passer),
+            Some("passer_king_distance") => core::slice::from_mut(&mut self.passer_king_distance),
+            Some("bishop_pair") => core::slice::from_mut(&mut self.bishop_pair),
+            Some("rook_open_file") => core::slice::from_mut(&mut self.rook_open_file),
+            Some("rook_semi_open_file") => core::slice::from_mut(&mut self.rook_semi_open_file),
+            Some("knight_outpost") => core::slice::from_mut(&mut self.knight_outpost),
+            Some("rook_on_seventh") => core::slice::from_mut(&mut self.rook_on_seventh),
+            Some(_) => return Err(err),
+        };
+
+        let mut len = 0;
+        for word in words {
+            *values.get_mut(len).ok_or(err)? = word.parse().map_err(|_| err)?;
+            len += 1;
+        }
+        if len == values.len() {
+            Ok(())
+        } else {
+            Err(err)
+        }
+    }
+}
+
+impl EvalParams {
+    /// The number of parameters, see `to_weights()`.
+    pub const LEN: usize = 2 * PhaseParams::LEN;
+
+    /// The board's value for White, in centipawns.
+    pub fn evaluate(&self, board: &Board) -> i32 {
+        let (mut middlegame, mut endgame) = (0, 0);
+        side_features(
+            &board.white_pieces,
+            &board.black_pieces,
+            &board.reserves[Color::White as usize],
+            Color::White,
+            |i, n| {
+                middlegame += self.middlegame.weight(i) * n as i32;
+                endgame += self.endgame.weight(i) * n as i32;
+            },
+        );
+        side_features(
+            &board.black_pieces,
+            &board.white_pieces,
+            &board.reserves[Color::Black as usize],
+            Color::Black,
+            |i, n| {
+                middlegame -= self.middlegame.weight(i) * n as i32;
+                endgame -= self.endgame.weight(i) * n as i32;
+            },
+        );
+
+        let phase = phase(board);
+        let value = (middlegame * phase + endgame * (PHASE_MAX - phase)) / PHASE_MAX;
+        endgame::adjust(board, value) + hill_bonus(board)
+    }
+
+    /// The board's terms as sparse `(parameter index, value)` pairs, so that `evaluate()` is the
+    /// dot product of them with `to_weights()`, but for the endgame adjustments, the variants'
+    /// bonuses and rounding.
+    pub fn features(board: &Board) -> Vec<(usize, f64)> {
+        let middlegame = phase(board) as f64 / PHASE_MAX as f64;
+        let mut features = Vec::new();
+        let mut push = |i, n: f64| {
+            features.push((i, n * middlegame));
+            features.push((PhaseParams::LEN + i, n * (1.0 - middlegame)));
+        };
+        side_features(
+            &board.white_pieces,
+            &board.black_pieces,
+            &board.reserves[Color::White as usize],
+            Color::White,
+            &mut push,
+        );
+        side_features(
+            &board.black_pieces,
+            &board.white_pieces,
+            &board.reserves[Color::Black as usize],
+            Color::Black,
+            |i, n| push(i, -n),
+        );
+        features
+    }
+
+    fn weight(&self, i: usize) -> i32 {
+        match i.checked_sub(PhaseParams::LEN) {
+            None => self.middlegame.weight(i),
+            Some(i) => self.endgame.weight(i),
+        }
+    }
+
+    fn weight_mut(&mut self, i: usize) -> &mut i32 {
+        match i.checked_sub(PhaseParams::LEN) {
+            None => self.middlegame.weight_mut(i),
+            Some(i) => self.endgame.weight_mut(i),
+        }
+    }
+
+    /// All the parameters as a flat vector of `LEN` weights.
+    pub fn to_weights(&self) -> Vec<f64> {
+        (0..Self::LEN).map(|i| self.weight(i) as f64).collect()
+    }
+
+    /// The inverse of `to_weights()`, rounding to whole centipawns.
+    pub fn from_weights(weights: &[f64]) -> Self {
+        let mut params = Self::default();
+        for (i, &weight) in weights.iter().enumerate().take(Self::LEN) {
+            *params.weight_mut(i) = if weight < 0.0 {
+                (weight - 0.5) as i32
+            } else {
+                (weight + 0.5) as i32
+            };
+        }
+        params
+    }
+}
+
+impl Evaluator for EvalParams {
+    type State = ();
+
+    fn init(&self, _board: &Board) {}
+
+    fn update(&self, _state: &mut (), _before: &Board, _after: &Board) {}
+
+    #[inline]
+    fn evaluate(&self, board: &Board, _state: &()) -> i32 {
+        EvalParams::evaluate(self, board)
+    }
+}
+
+impl fmt::Display for EvalParams {
+    /// The phases' terms one per line, e.g. "middlegame material 0 900 500 300 300 100" or
+    /// "endgame pst knight <64 values>".
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, params) in [("middlegame", &self.middlegame), ("endgame", &self.endgame)] {
+            for line in params.to_string().lines() {
+                writeln!(f, "{} {}", name, line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for PhaseParams {
+    /// One line per term, e.g. "material 0 900 500 300 300 100" or "pst knight <64 values>".
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "material")?;
+        for value in &self.material {
+            write!(f, " {}", value)?;
+        }
+        writeln!(f)?;
+
+        for (name, pst) in PIECE_NAMES.iter().zip(&self.pst) {
+            write!(f, "pst {}", name)?;
+            for value in pst {
+                write!(f, " {}", value)?;
+            }
+            writeln!(f)?;
+        }
+
+        writeln!(f, "doubled_pawn {}", self.doubled_pawn)?;
+        writeln!(f, "isolated_pawn {}", self.isolated_pawn)?;
+        writeln!(f, "king_shield {}", self.king_shield)?;
+
+        write!(f, "passed_pawn")?;
+        for value in &self.passed_pawn {
+            write!(f, " {}", value)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "free_passer {}", self.free_passer)?;
+        writeln!(f, "rook_behind_passer {}", self.rook_behind_passer)?;
+        writeln!(f, "passer_king_distance {}", self.passer_king_distance)?;
+        writeln!(f, "bishop_pair {}", self.bishop_pair)?;
+        writeln!(f, "rook_open_file {}", self.rook_open_file)?;
+        writeln!(f, "rook_semi_open_file {}", self.rook_semi_open_file)?;
+        writeln!(f, "knight_outpost {}", self.knight_outpost)?;
+        writeln!(f, "rook_on_seventh {}", self.rook_on_seventh)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ParseParamsError {
+    pub line: usize,
+}
+
+impl fmt::Display for ParseParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Bad evaluation parameters at line {}", self.line)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseParamsError {}
+
+impl FromStr for EvalParams {
+    type Err = ParseParamsError;
+
+    /// Parses the `Display` format, missing terms keep their default. A term without a phase
+    /// sets both.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut params = Self::default();
+
+        for (i, line) in s.lines().enumerate() {
+            let err = ParseParamsError { line: i + 1 };
+            let mut words = line.split_whitespace().peekable();
+            match words.peek() {
+                Some(&"middlegame") => params.middlegame.parse_line(words.skip(1), err)?,
+                Some(&"endgame") => params.endgame.parse_line(words.skip(1), err)?,
+                _ => {
+                    params.middlegame.parse_line(words.clone(), err)?;
+                    params.endgame.parse_line(words, err)?;
+                }
+            }
+        }
+
+        Ok(params)
+    }
+}
@@ -0,0 +1,167 @@
+// Learning from the bot's own games: the root positions it searched, the values it found and how
+// the games went on from them, kept in a file between runs. Later searches of the same positions
+// blend them in and favour the moves that won, see `Bot::experience`.
+
+use crate::{Color, Move};
+use core::{fmt, str::FromStr};
+use std::collections::BTreeMap;
+
+/// What winning after a move, on average, adds to its value in centipawns, and losing takes off.
+pub const RESULT_BONUS: i32 = 40;
+/// Values beyond this are mates or variant wins, which the experience doesn't change.
+const DECISIVE: i32 = 10_000;
+
+/// What was learned about a move in a position.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Entry {
+    /// The move in the "<from><to>[promotion]" format, see `Board::parse_move()`.
+    pub mv: String,
+    /// The value the search last found for the side that played it, in centipawns.
+    pub value: i32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl Entry {
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+}
+
+/// The searched root positions of past games by their `Board::position_key()`.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Experience {
+    positions: BTreeMap<u64, Vec<Entry>>,
+}
+
+impl Experience {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of positions learned.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// What was learned in the position with the key `key`.
+    pub fn entries(&self, key: u64) -> &[Entry] {
+        self.positions.get(&key).map_or(&[], Vec::as_slice)
+    }
+
+    /// Learns a finished game: every searched position's key, the side to move, the move it
+    /// played and the value the search gave it. `winner` is `None` for a draw.
+    pub fn learn(&mut self, searched: &[(u64, Color, Move, i32)], winner: Option<Color>) {
+        for &(key, color, mv, value) in searched {
+            let entries = self.positions.entry(key).or_default();
+            let mv = mv.to_string();
+            let entry = match entries.iter().position(|entry| entry.mv == mv) {
+                Some(i) => &mut entries[i],
+                None => {
+                    entries.push(Entry {
+                        mv,
+                        value,
+                        wins: 0,
+                        draws: 0,
+                        losses: 0,
+                    });
+                    entries.last_mut().unwrap()
+                }
+            };
+            entry.value = value;
+            match winner {
+                Some(winner) if winner == color => entry.wins += 1,
+                Some(_) => entry.losses += 1,
+                None => entry.draws += 1,
+            }
+        }
+    }
+
+    /// `value`, a searched value of `mv` in the position with the key `key` for the side that
+    /// plays it, blended with the experience: a quarter of it is the value found before, and the
+    /// move's average result adds up to `RESULT_BONUS`. Decisive values stay as they are.
+    pub fn blend(&self, key: u64, mv: Move, value: i32) -> i32 {
+        if DECISIVE < value.abs() {
+            return value;
+        }
+        let mv = mv.to_string();
+        let entry = match self.entries(key).iter().find(|entry| entry.mv == mv) {
+            Some(entry) if entry.games() != 0 => entry,
+            _ => return value,
+        };
+        let value = match entry.value {
+            old if DECISIVE < old.abs() => value,
+            old => (3 * value + old) / 4,
+        };
+        value + RESULT_BONUS * (entry.wins as i32 - entry.losses as i32) / entry.games() as i32
+    }
+}
+
+impl fmt::Display for Experience {
+    /// One move per line, "<key in hex> <move> <value> <wins> <draws> <losses>", e.g.
+    /// "463b96181691fc9c e2e4 25 3 1 0".
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (key, entries) in &self.positions {
+            for entry in entries {
+                writeln!(
+                    f,
+                    "{:016x} {} {} {} {} {}",
+                    key, entry.mv, entry.value, entry.wins, entry.draws, entry.losses
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ParseExperienceError {
+    pub line: usize,
+}
+
+impl fmt::Display for ParseExperienceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Bad experience at line {}", self.line)
+    }
+}
+
+impl std::error::Error for ParseExperienceError {}
+
+impl FromStr for Experience {
+    type Err = ParseExperienceError;
+
+    /// Parses the `Display` format, blank lines are skipped.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut experience = Self::new();
+
+        for (i, line) in s.lines().enumerate() {
+            let err = ParseExperienceError { line: i + 1 };
+            let words: Vec<_> = line.split_whitespace().collect();
+            let (key, mv, numbers) = match words.as_slice() {
+                [] => continue,
+                [key, mv, numbers @ ..] if numbers.len() == 4 => (key, mv, numbers),
+                _ => return Err(err),
+            };
+            let key = u64::from_str_radix(key, 16).map_err(|_| err)?;
+            let value = numbers[0].parse().map_err(|_| err)?;
+            let mut results = [0; 3];
+            for (result, word) in results.iter_mut().zip(&numbers[1..]) {
+                *result = word.parse().map_err(|_| err)?;
+            }
+            experience.positions.entry(key).or_default().push(Entry {
+                mv: mv.to_string(),
+                value,
+                wins: results[0],
+                draws: results[1],
+                losses: results[2],
+            });
+        }
+
+        Ok(experience)
+    }
+}
@@ -0,0 +1,198 @@
+// Engines in other processes, spoken to over UCI, so that tournaments can pit the bot against
+// other programs. The position is sent as a FEN, so the engine doesn't see the positions before
+// it and can't avoid or seek repetitions.
+
+use crate::{
+    engine::{Engine, Score, SearchLimits, SearchResult},
+    Board, Color,
+};
+use std::{
+    fmt::Write as _,
+    io::{self, BufRead, BufReader, Write},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    time::Duration,
+};
+
+/// The time for a move without limits.
+pub const DEFAULT_MOVE_TIME: Duration = Duration::from_secs(1);
+
+pub struct UciEngine {
+    /// The engine's `id name`, or the command without one.
+    name: String,
+    child: Child,
+    input: ChildStdin,
+    output: BufReader<ChildStdout>,
+}
+
+impl UciEngine {
+    /// Starts `command`, the program and its arguments separated by whitespace, and waits for
+    /// its "uciok".
+    pub fn spawn(command: &str) -> io::Result<Self> {
+        let mut args = command.split_whitespace();
+        let program = args
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "No engine command"))?;
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let input = child.stdin.take().unwrap();
+        let output = BufReader::new(child.stdout.take().unwrap());
+
+        let mut engine = Self {
+            name: command.to_owned(),
+            child,
+            input,
+            output,
+        };
+        engine.send("uci")?;
+        loop {
+            let line = engine.read_line()?;
+            if let Some(name) = line.strip_prefix("id name ") {
+                engine.name = name.trim().to_owned();
+            } else if line.trim() == "uciok" {
+                break;
+            }
+        }
+        engine.new_game()?;
+        Ok(engine)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Tells the engine that the next search is of another game.
+    pub fn new_game(&mut self) -> io::Result<()> {
+        self.send("ucinewgame")?;
+        self.send("isready")?;
+        while self.read_line()?.trim() != "readyok" {}
+        Ok(())
+    }
+
+    fn send(&mut self, line: &str) -> io::Result<()> {
+        log::trace!("{} < {}", self.name, line);
+        writeln!(self.input, "{}", line)?;
+        self.input.flush()
+    }
+
+    fn read_line(&mut self) -> io::Result<String> {
+        let mut line = String::new();
+        if self.output.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("{} quit", self.name),
+            ));
+        }
+        log::trace!("{} > {}", self.name, line.trim_end());
+        Ok(line)
+    }
+
+    fn search(
+        &mut self,
+        board: &Board,
+        color: Color,
+        limits: &SearchLimits,
+    ) -> io::Result<SearchResult> {
+        self.send(&format!("position fen {}", board.to_fen(color)))?;
+        let mut go = String::from("go");
+        match (limits.time, limits.depth, limits.nodes) {
+            (Some(time), _, _) => write!(go, " movetime {}", time.soft_limit().as_millis()),
+            (None, Some(depth), _) => write!(go, " depth {}", depth),
+            (None, None, Some(_)) => Ok(()),
+            (None, None, None) => write!(go, " movetime {}", DEFAULT_MOVE_TIME.as_millis()),
+        }
+        .unwrap();
+        if let Some(nodes) = limits.nodes {
+            write!(go, " nodes {}", nodes).unwrap();
+        }
+        if !limits.moves.is_empty() {
+            go.push_str(" searchmoves");
+            for mv in limits.moves {
+                write!(go, " {}", mv).unwrap();
+            }
+        }
+        self.send(&go)?;
+
+        let mut result = SearchResult::default();
+        loop {
+            let line = self.read_line()?;
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("info") => {
+                    while let Some(word) = words.next() {
+                        let number =
+                            |word: Option<&str>| word.and_then(|word| word.parse::<i64>().ok());
+                        match word {
+                            "depth" => {
+                                result.depth = number(words.next()).map_or(0, |depth| depth as u32)
+                            }
+                            "nodes" => {
+                                result.nodes =
+                                    number(words.next()).map_or(result.nodes, |nodes| nodes as u64)
+                            }
+                            "hashfull" => {
+                                result.hashfull = number(words.next())
+                                    .map_or(result.hashfull, |permille| Some(permille as u32))
+                            }
+                            "time" => {
+                                result.time = number(words.next())
+                                    .map_or(result.time, |ms| Duration::from_millis(ms as u64))
+                            }
+                            "score" => {
+                                result.score = match (words.next(), number(words.next())) {
+                                    (Some("cp"), Some(cp)) => Some(Score::Cp(cp as i32)),
+                                    (Some("mate"), Some(moves)) => Some(Score::Mate(moves as i32)),
+                                    _ => result.score,
+                                }
+                            }
+                            // The rest of the line is the line, as far as it's legal.
+                            "pv" => {
+                                result.pv.clear();
+                                let (mut board, mut color) = (*board, color);
+                                for mv in words.by_ref() {
+                                    match board.parse_move(color, mv) {
+                                        Some(mv) => {
+                                            result.pv.push(mv);
+                                            board.perform_move_unchecked(mv);
+                                            color = color.inv();
+                                        }
+                                        None => break,
+                                    }
+                                }
+                            }
+                            // The rest of the line is the string.
+                            "string" => break,
+                            _ => {}
+                        }
+                    }
+                }
+                Some("bestmove") => {
+                    result.best_move = words.next().and_then(|mv| board.parse_move(color, mv));
+                    return Ok(result);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Engine for UciEngine {
+    /// Searches for the clock's soft limit, or to the depth, or for `DEFAULT_MOVE_TIME` unless the
+    /// nodes limit the search. Doesn't move if the engine failed.
+    fn best_move(&mut self, board: &Board, color: Color, limits: &SearchLimits) -> SearchResult {
+        self.search(board, color, limits).unwrap_or_else(|err| {
+            log::warn!("{}: {}", self.name, err);
+            SearchResult::default()
+        })
+    }
+}
+
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        if self.send("quit").is_err() || self.child.wait().is_err() {
+            let _ = self.child.kill();
+        }
+    }
+}
@@ -0,0 +1,310 @@
+// Forsyth–Edwards Notation, e.g. "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1".
+// The move counters aren't tracked by `Board`, they're accepted but ignored. Crazyhouse FENs have
+// the reserves after the placement, e.g. ".../RNBQKBNR[Qp]" (or as a ninth rank), and "~" after
+// the promoted pieces. A FEN without a white king is Horde's, e.g.
+// "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1".
+
+use crate::{
+    board::MAX_RESERVE,
+    masks::{RANK_1, RANK_8},
+    Board, ChessFlags, Color, Move, MoveType, Piece, PieceType, Square, Variant,
+};
+use alloc::string::{String, ToString};
+use core::fmt;
+
+pub const STARTPOS: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FenError(pub &'static str);
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Bad FEN: {}", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FenError {}
+
+pub(crate) fn piece_from_char(ch: char) -> Option<Piece> {
+    let ty = match ch.to_ascii_lowercase() {
+        'k' => PieceType::King,
+        'q' => PieceType::Queen,
+        'r' => PieceType::Rook,
+        'b' => PieceType::Bishop,
+        'n' => PieceType::Knight,
+        'p' => PieceType::Pawn,
+        _ => return None,
+    };
+    let color = if ch.is_ascii_uppercase() {
+        Color::White
+    } else {
+        Color::Black
+    };
+    Some(Piece { color, ty })
+}
+
+fn piece_char(piece: Piece) -> char {
+    let ch = match piece.ty {
+        PieceType::King => 'k',
+        PieceType::Queen => 'q',
+        PieceType::Rook => 'r',
+        PieceType::Bishop => 'b',
+        PieceType::Knight => 'n',
+        PieceType::Pawn => 'p',
+    };
+    match piece.color {
+        Color::White => ch.to_ascii_uppercase(),
+        Color::Black => ch,
+    }
+}
+
+impl Board {
+    /// Checks what the search and the evaluation take for granted: a king for each side and no
+    /// pawns on the first or last rank. In Horde White has no king instead, and its pawns may be
+    /// on the first rank. `from_fen()` only returns valid boards, but `set()` can make any.
+    pub fn validate(&self) -> Result<(), FenError> {
+        let (white_kings, white_first_rank) = match self.variant {
+            Variant::Horde => (0, RANK_1),
+            _ => (1, 0),
+        };
+        if self.white_pieces.king().count_ones() != white_kings
+            || self.black_pieces.king().count_ones() != 1
+        {
+            return Err(FenError("each side needs exactly one king"));
+        }
+        if (self.white_pieces.pawns() & !white_first_rank | self.black_pieces.pawns())
+            & (RANK_1 | RANK_8)
+            != 0
+        {
+            return Err(FenError("pawn on the first or last rank"));
+        }
+        Ok(())
+    }
+
+    /// Drops the castling rights that the king and rooks' placement contradicts.
+    pub(crate) fn drop_contradicted_castling(&mut self) {
+        let (white, black) = (self.white_pieces, self.black_pieces);
+        for (flag, king, rook, pieces) in [
+            (ChessFlags::WHITE_KINGS_CASTLE, 0o04, 0o07, &white),
+            (ChessFlags::WHITE_QUEENS_CASTLE, 0o04, 0o00, &white),
+            (ChessFlags::BLACK_KINGS_CASTLE, 0o74, 0o77, &black),
+            (ChessFlags::BLACK_QUEENS_CASTLE, 0o74, 0o70, &black),
+        ] {
+            if pieces.king() & 1 << king == 0 || pieces.rooks() & 1 << rook == 0 {
+                self.flags.remove(flag);
+            }
+        }
+    }
+
+    /// Parses a FEN, returns the board and the side to move. The board is Crazyhouse's if the FEN
+    /// has reserves, and Horde's if White has no king.
+    pub fn from_fen(fen: &str) -> Result<(Self, Color), FenError> {
+        let mut fields = fen.split_whitespace();
+        let placement = fields.next().ok_or(FenError("empty"))?;
+        let (placement, reserves) = match placement.split_once('[') {
+            Some((placement, reserves)) => (
+                placement,
+                Some(reserves.strip_suffix(']').ok_or(FenError("bad reserves"))?),
+            ),
+            None => match placement.rsplit_once('/') {
+                Some((ranks, reserves)) if ranks.matches('/').count() == 7 => {
+                    (ranks, Some(reserves))
+                }
+                _ => (placement, None),
+            },
+        };
+
+        let mut board = Self {
+            white_pieces: Default::default(),
+            black_pieces: Default::default(),
+            flags: ChessFlags::empty(),
+            ..Board::with_variant(match reserves {
+                Some(_) => Variant::Crazyhouse,
+                None => Variant::Standard,
+            })
+        };
+        for ch in reserves.unwrap_or_default().chars() {
+            let count = match piece_from_char(ch) {
+                Some(piece) if piece.ty != PieceType::King => {
+                    &mut board.reserves[piece.color as usize][piece.ty as usize]
+                }
+                _ => return Err(FenError("bad reserves")),
+            };
+            if *count == MAX_RESERVE {
+                return Err(FenError("bad reserves"));
+            }
+            *count += 1;
+        }
+
+        let mut ranks = 0;
+        for (i, rank) in placement.split('/').enumerate() {
+            if 8 <= i {
+                return Err(FenError("too many ranks"));
+            }
+            ranks += 1;
+
+            let mut file = 0;
+            for ch in rank.chars() {
+                if let Some(n) = ch.to_digit(10) {
+                    file += n as u8;
+                } else if ch == '~' && reserves.is_some() && file != 0 {
+                    board.promoted |= 1 << ((7 - i as u8) * 8 + file - 1);
+                } else {
+                    let piece = piece_from_char(ch).ok_or(FenError("bad piece"))?;
+                    if 8 <= file {
+                        return Err(FenError("rank too long"));
+                    }
+                    board.set(1 << ((7 - i as u8) * 8 + file), Some(piece));
+                    file += 1;
+                }
+                if 8 < file {
+                    return Err(FenError("rank too long"));
+                }
+            }
+            if file != 8 {
+                return Err(FenError("rank too short"));
+            }
+        }
+        if ranks != 8 {
+            return Err(FenError("too few ranks"));
+        }
+
+        if board.white_pieces.king() == 0 && reserves.is_none() {
+            board.variant = Variant::Horde;
+        }
+        board.validate()?;
+
+        let color = match fields.next() {
+            Some("w") | None => Color::White,
+            Some("b") => Color::Black,
+            Some(_) => return Err(FenError("bad side to move")),
+        };
+
+        match fields.next() {
+            Some("-") | None => {}
+            Some(castling) => {
+                for ch in castling.chars() {
+                    board.flags |= match ch {
+                        'K' => ChessFlags::WHITE_KINGS_CASTLE,
+                        'Q' => ChessFlags::WHITE_QUEENS_CASTLE,
+                        'k' => ChessFlags::BLACK_KINGS_CASTLE,
+                        'q' => ChessFlags::BLACK_QUEENS_CASTLE,
+                        _ => return Err(FenError("bad castling rights")),
+                    };
+                }
+            }
+        }
+        board.drop_contradicted_castling();
+
+        match fields.next() {
+            Some("-") | None => {}
+            Some(ep) => {
+                let square = Square::from_ascii(ep.as_bytes())
+                    .ok_or(FenError("bad en passant square"))?
+                    .index();
+                // The en passant square is behind the pawn that just leaped.
+                let (from, to) = match (color, square / 8) {
+                    (Color::White, 5) => (square + 0o10, square - 0o10),
+                    (Color::Black, 2) => (square - 0o10, square + 0o10),
+                    _ => return Err(FenError("bad en passant square")),
+                };
+                let leaped = board.get_pieces(color.inv()).pawns() & 1 << to != 0;
+                // Some writers always emit the square, even when no capture is possible.
+                if leaped && board.occupied() & 1 << from == 0 {
+                    board.prev_move = Move {
+                        from,
+                        to,
+                        ty: MoveType::PawnLeap,
+                    };
+                }
+            }
+        }
+
+        for counter in fields.take(2) {
+            counter
+                .parse::<u32>()
+                .map_err(|_| FenError("bad move counter"))?;
+        }
+
+        Ok((board, color))
+    }
+
+    /// Formats the board in FEN, with `color` to move.
+    /// The move counters are always "0 1" since the board doesn't track them.
+    pub fn to_fen(&self, color: Color) -> String {
+        let mut fen = String::new();
+
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+            for file in 0..8 {
+                match self.get_at(1 << (rank * 8 + file)) {
+                    Some(piece) => {
+                        if empty != 0 {
+                            fen.push((b'0' + empty) as char);
+                            empty = 0;
+                        }
+                        fen.push(piece_char(piece));
+                        if self.promoted & 1 << (rank * 8 + file) != 0 {
+                            fen.push('~');
+                        }
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty != 0 {
+                fen.push((b'0' + empty) as char);
+            }
+            if rank != 0 {
+                fen.push('/');
+            }
+        }
+
+        if self.variant == Variant::Crazyhouse {
+            fen.push('[');
+            for color in [Color::White, Color::Black] {
+                for ty in [
+                    PieceType::Queen,
+                    PieceType::Rook,
+                    PieceType::Bishop,
+                    PieceType::Knight,
+                    PieceType::Pawn,
+                ] {
+                    for _ in 0..self.reserve(color, ty) {
+                        fen.push(piece_char(Piece { color, ty }));
+                    }
+                }
+            }
+            fen.push(']');
+        }
+
+        fen.push_str(match color {
+            Color::White => " w ",
+            Color::Black => " b ",
+        });
+
+        if self.flags.is_empty() {
+            fen.push('-');
+        }
+        for (flag, ch) in [
+            (ChessFlags::WHITE_KINGS_CASTLE, 'K'),
+            (ChessFlags::WHITE_QUEENS_CASTLE, 'Q'),
+            (ChessFlags::BLACK_KINGS_CASTLE, 'k'),
+            (ChessFlags::BLACK_QUEENS_CASTLE, 'q'),
+        ] {
+            if self.flags.contains(flag) {
+                fen.push(ch);
+            }
+        }
+
+        fen.push(' ');
+        if self.prev_move.ty == MoveType::PawnLeap {
+            fen.push_str(&Square::of((self.prev_move.from + self.prev_move.to) / 2).to_string());
+        } else {
+            fen.push('-');
+        }
+        fen.push_str(" 0 1");
+
+        fen
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{Board, Color, Move, Odds, Variant};
+use alloc::vec::Vec;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum GameStatus {
+    Ongoing,
+    /// The color is the winner's.
+    Checkmate(Color),
+    Stalemate,
+    /// The color is the winner's, by a rule of the variant, see `Board::variant_winner()`.
+    VariantWin(Color),
+}
+
+/// A board together with the side to move and the moves that were played.
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct Game {
+    /// The position before the first move.
+    start: (Board, Color),
+    board: Board,
+    turn: Color,
+    moves: Vec<Move>,
+    /// The hashes of the positions before every move, for detecting repetitions.
+    history: Vec<u64>,
+}
+
+impl Game {
+    pub fn new() -> Self {
+        Self::from_position(Board::new(), Color::White)
+    }
+
+    /// A game of `variant` from its starting position.
+    pub fn with_variant(variant: Variant) -> Self {
+        Self::from_position(Board::with_variant(variant), Color::White)
+    }
+
+    /// A handicap game from the starting position without the piece `odds` takes off Black.
+    pub fn with_odds(odds: Odds) -> Self {
+        Self::from_position(Board::with_odds(odds), Color::White)
+    }
+
+    /// A game starting from an arbitrary position, e.g. from `Board::from_fen()`.
+    pub fn from_position(board: Board, turn: Color) -> Self {
+        Self {
+            start: (board, turn),
+            board,
+            turn,
+            moves: Vec::new(),
+            history: Vec::new(),
+        }
+    }
+
+    /// The position the game started from, and the side to move in it.
+    #[inline]
+    pub fn start(&self) -> (Board, Color) {
+        self.start
+    }
+
+    /// The handicap the game started with, if it's the starting position of one.
+    pub fn odds(&self) -> Option<Odds> {
+        Odds::ALL
+            .iter()
+            .copied()
+            .find(|&odds| self.start == (Board::with_odds(odds), Color::White))
+    }
+
+    #[inline]
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    #[inline]
+    pub fn variant(&self) -> Variant {
+        self.board.variant
+    }
+
+    #[inline]
+    pub fn turn(&self) -> Color {
+        self.turn
+    }
+
+    /// The moves that were played so far.
+    #[inline]
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    /// The keys (`Board::position_key()`) of the positions before the current one, oldest first.
+    #[inline]
+    pub fn history(&self) -> &[u64] {
+        &self.history
+    }
+
+    pub fn legal_moves(&self) -> Vec<Move> {
+        self.board.moves(self.turn)
+    }
+
+    /// Plays `mv` if it's legal, returns whether it was played.
+    pub fn push_move(&mut self, mv: Move) -> bool {
+        let hash = self.board.position_key(self.turn);
+        if self.board.apply(self.turn, mv).is_err() {
+            return false;
+        }
+
+        self.history.push(hash);
+        self.turn = self.turn.inv();
+        self.moves.push(mv);
+
+        true
+    }
+
+    pub fn status(&self) -> GameStatus {
+        if let Some(winner) = self.board.variant_winner() {
+            return GameStatus::VariantWin(winner);
+        }
+        let attack = self.board.check_attack(self.turn.inv());
+        if self.board.has_moves(self.turn, attack) {
+            GameStatus::Ongoing
+        } else if attack & self.board.get_pieces(self.turn).king() == 0 {
+            GameStatus::Stalemate
+        } else {
+            GameStatus::Checkmate(self.turn.inv())
+        }
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,130 @@
+// A weakened bot that plays more like a human than a bot with a lower depth: it doesn't always
+// play its best move but one of the good ones at random, and now and then it only glances at some
+// of the moves, so it misses what they'd win or lose.
+
+use crate::{
+    engine::{Engine, Score, SearchLimits, SearchResult},
+    rng::Rng,
+    Board, Bot, Color, Move,
+};
+use std::time::Instant;
+
+/// How much worse than the best move the moves it plays may be, in centipawns.
+pub const DEFAULT_MARGIN: i32 = 100;
+/// How likely the worse moves within the margin are, in centipawns, see `HumanBot::temperature`.
+pub const DEFAULT_TEMPERATURE: i32 = 40;
+/// How often a move is only glanced at, in percent.
+pub const DEFAULT_OVERSIGHT: u32 = 15;
+
+/// Values a move only glanced at by searching it this shallowly.
+const GLANCE_DEPTH: u32 = 1;
+
+pub struct HumanBot {
+    /// Searches the moves, to its depth. The clock doesn't stop it.
+    pub bot: Bot,
+    /// Only the moves within `margin` of the best are played.
+    pub margin: i32,
+    /// A move is played with a chance proportional to `exp(-loss / temperature)`, where the loss
+    /// is how much worse than the best it is. 0 always plays the best move.
+    pub temperature: i32,
+    /// The chance in percent that a move is searched only to `GLANCE_DEPTH`, 0 searches all of
+    /// them fully.
+    pub oversight: u32,
+    rng: Rng,
+}
+
+impl HumanBot {
+    pub fn new(bot: Bot, seed: u64) -> Self {
+        Self {
+            bot,
+            margin: DEFAULT_MARGIN,
+            temperature: DEFAULT_TEMPERATURE,
+            oversight: DEFAULT_OVERSIGHT,
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// A move of `valued` at random by the margin and the temperature, the best if there's none.
+    fn sample(&mut self, valued: &[(Move, i32)]) -> Option<(Move, i32)> {
+        let best = valued.iter().map(|&(_, value)| value).max()?;
+        let weighted: Vec<_> = valued
+            .iter()
+            .filter_map(|&(mv, value)| {
+                let loss = best.saturating_sub(value);
+                let weight = match self.temperature {
+                    _ if loss > self.margin => return None,
+                    0 if loss == 0 => 1.0,
+                    0 => return None,
+                    temperature => (-loss as f64 / temperature as f64).exp(),
+                };
+                Some(((mv, value), weight))
+            })
+            .collect();
+        let total: f64 = weighted.iter().map(|&(_, weight)| weight).sum();
+        let mut left = self.rng.unit() * total;
+        for &(valued, weight) in &weighted {
+            if left < weight {
+                return Some(valued);
+            }
+            left -= weight;
+        }
+        weighted.last().map(|&(valued, _)| valued)
+    }
+}
+
+/// Chooses among `limits.moves` when there are any. `limits.depth` caps the depth, the time and
+/// the nodes don't.
+impl Engine for HumanBot {
+    fn best_move(&mut self, board: &Board, color: Color, limits: &SearchLimits) -> SearchResult {
+        let start = Instant::now();
+        if let Err(err) = board.validate() {
+            log::warn!("Not searching an invalid board: {}", err);
+            return SearchResult::default();
+        }
+        let moves = match limits.moves {
+            [] => board.moves(color),
+            moves => moves.to_vec(),
+        };
+        if moves.len() <= 1 {
+            return SearchResult {
+                best_move: moves.first().copied(),
+                pv: moves,
+                time: start.elapsed(),
+                ..SearchResult::default()
+            };
+        }
+
+        let depth = limits
+            .depth
+            .map_or(self.bot.depth, |depth| depth.min(self.bot.depth));
+        let (glanced, searched): (Vec<_>, Vec<_>) = moves
+            .into_iter()
+            .partition(|_| self.rng.below(100) < self.oversight as usize);
+        let mut valued = Vec::new();
+        let mut nodes = 0;
+        for (moves, depth) in [(searched, depth), (glanced, GLANCE_DEPTH.min(depth))] {
+            if moves.is_empty() {
+                continue;
+            }
+            let values = self
+                .bot
+                .move_values(board, color, limits.history, &moves, depth);
+            nodes += self.bot.nodes();
+            valued.extend(moves.into_iter().zip(values));
+        }
+
+        let (best_move, value) = match self.sample(&valued) {
+            Some((mv, value)) => (Some(mv), Some(value)),
+            None => (None, None),
+        };
+        SearchResult {
+            best_move,
+            score: value.map(Score::from_value),
+            depth,
+            nodes,
+            pv: best_move.into_iter().collect(),
+            time: start.elapsed(),
+            hashfull: self.bot.table.as_ref().map(|table| table.hashfull()),
+        }
+    }
+}
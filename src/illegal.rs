@@ -0,0 +1,152 @@
+// Why a move isn't legal, for telling a player what's wrong with the move they typed rather than
+// just that it's illegal: "no piece on e3", "that knight is pinned".
+
+use crate::{board::Castle, Board, Color, Move, PieceType, Square};
+use core::fmt;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum IllegalMoveReason {
+    /// The square the move starts from is empty.
+    NoPiece(Square),
+    /// The piece on the square the move starts from is the opponent's.
+    OpponentsPiece(Square),
+    /// One of the mover's own pieces is on the square the move goes to.
+    OwnPiece(Square),
+    /// The piece doesn't move that way, or another piece is in the way.
+    CantReach(PieceType, Square, Square),
+    /// None of the mover's pieces of the type can go to the square, e.g. "Nf5" without a knight
+    /// that reaches it.
+    NoneCanReach(PieceType, Square),
+    /// The castling rights are gone, or there's a piece between the king and the rook.
+    CantCastle,
+    /// The king would castle out of, through or into check.
+    CastlesThroughCheck,
+    /// The king would move into check on the square.
+    IntoCheck(Square),
+    /// The piece is pinned to its king and the move leaves the line of the pin.
+    Pinned(PieceType),
+    /// The king is in check and the move doesn't answer it.
+    InCheck,
+    /// The move would leave the king in check, e.g. an en passant capture that takes both pawns
+    /// off the king's rank.
+    ExposesKing,
+    /// A promotion piece for a move that doesn't promote, or a king or a pawn to promote to.
+    BadPromotion,
+}
+
+fn name(ty: PieceType) -> &'static str {
+    match ty {
+        PieceType::King => "king",
+        PieceType::Queen => "queen",
+        PieceType::Rook => "rook",
+        PieceType::Bishop => "bishop",
+        PieceType::Knight => "knight",
+        PieceType::Pawn => "pawn",
+    }
+}
+
+/// Lowercase, like "no piece on e3", to follow e.g. "Illegal move: ".
+impl fmt::Display for IllegalMoveReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::NoPiece(square) => write!(f, "no piece on {}", square),
+            Self::OpponentsPiece(square) => {
+                write!(f, "the piece on {} is your opponent's", square)
+            }
+            Self::OwnPiece(square) => write!(f, "your own piece is on {}", square),
+            Self::CantReach(ty, from, to) => {
+                write!(f, "the {} on {} can't move to {}", name(ty), from, to)
+            }
+            Self::NoneCanReach(ty, to) => write!(f, "no {} of yours can move to {}", name(ty), to),
+            Self::CantCastle => write!(f, "you can't castle that way"),
+            Self::CastlesThroughCheck => {
+                write!(f, "the king can't castle out of, through or into check")
+            }
+            Self::IntoCheck(square) => write!(f, "the king would be in check on {}", square),
+            Self::Pinned(ty) => write!(f, "that {} is pinned", name(ty)),
+            Self::InCheck => write!(f, "that doesn't get your king out of check"),
+            Self::ExposesKing => write!(f, "that would leave your king in check"),
+            Self::BadPromotion => write!(
+                f,
+                "that's not a promotion to a queen, rook, bishop or knight"
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for IllegalMoveReason {}
+
+impl Board {
+    /// Like `get_legal_move()`, but tells why there's no such legal move for `color`.
+    pub fn checked_move(
+        &self,
+        color: Color,
+        from: Square,
+        to: Square,
+        promotion: Option<PieceType>,
+    ) -> Result<Move, IllegalMoveReason> {
+        if let Some(mv) = self.get_legal_move(color, from.index(), to.index(), promotion) {
+            return Ok(mv);
+        }
+
+        let piece = self
+            .get_at(1 << from.index())
+            .ok_or(IllegalMoveReason::NoPiece(from))?;
+        if piece.color != color {
+            return Err(IllegalMoveReason::OpponentsPiece(from));
+        }
+        if self.occupied_by(color) & 1 << to.index() != 0 {
+            return Err(IllegalMoveReason::OwnPiece(to));
+        }
+        let last_rank = matches!(
+            (color, to.index() / 8),
+            (Color::White, 7) | (Color::Black, 0)
+        );
+        let promotes = piece.ty == PieceType::Pawn && last_rank;
+        if promotion.is_some_and(|ty| !promotes || matches!(ty, PieceType::King | PieceType::Pawn))
+        {
+            return Err(IllegalMoveReason::BadPromotion);
+        }
+
+        // Whether the piece could make the move if its king weren't in danger: the king's steps
+        // and castles with the rights and the squares between free, the other pieces' moves on a
+        // board without their king, which also frees them from their pins.
+        let castles = piece.ty == PieceType::King && from.index().abs_diff(to.index()) == 2;
+        let reachable = |board: &Board| {
+            board.moves_with_attack(color, 0).iter().any(|mv| {
+                (mv.from, mv.to) == (from.index(), to.index()) && mv.ty.dropped().is_none()
+            })
+        };
+        let reachable = match piece.ty {
+            PieceType::King if castles => Castle::to(to.index()).is_some_and(|castle| {
+                castle.color == color
+                    && castle.king_from == from.index()
+                    && self.flags.contains(castle.right)
+                    && self.occupied() & castle.empty == 0
+            }),
+            PieceType::King => from.distance(to) == 1,
+            _ => {
+                let mut board = *self;
+                board.set(self.get_pieces(color).king(), None);
+                reachable(&board)
+            }
+        };
+        if !reachable {
+            return Err(match castles {
+                true => IllegalMoveReason::CantCastle,
+                false => IllegalMoveReason::CantReach(piece.ty, from, to),
+            });
+        }
+
+        Err(match piece.ty {
+            PieceType::King if castles => IllegalMoveReason::CastlesThroughCheck,
+            PieceType::King => IllegalMoveReason::IntoCheck(to),
+            _ if self.find_pins(color) & 1 << from.index() != 0 => {
+                IllegalMoveReason::Pinned(piece.ty)
+            }
+            _ if self.in_check(color) => IllegalMoveReason::InCheck,
+            _ => IllegalMoveReason::ExposesKing,
+        })
+    }
+}
@@ -0,0 +1,330 @@
+// Where the interactive games get the player's moves from: the terminal, a script, or a TCP
+// connection that e.g. an electronic board's bridge writes the moves to.
+
+use crate::{BitIterator, Board, Color, IllegalMoveReason, Move, MoveType, PieceType, Square};
+use std::{
+    fmt,
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+};
+
+/// Why a typed move wasn't taken, see `parse_move()`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MoveInputError {
+    /// Neither squares nor SAN.
+    Unreadable,
+    /// SAN that fits more than one piece's move, e.g. "Nd2" with knights on b1 and f3.
+    Ambiguous,
+    /// A drop that isn't legal, e.g. of a piece that isn't in the reserve.
+    IllegalDrop,
+    Illegal(IllegalMoveReason),
+}
+
+impl fmt::Display for MoveInputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unreadable => write!(f, "not a move, type it like e2e4, e2 e4, e2-e4 or Nf3"),
+            Self::Ambiguous => write!(
+                f,
+                "more than one piece can make that move, add its file or rank like Nbd2"
+            ),
+            Self::IllegalDrop => write!(f, "that drop is illegal"),
+            Self::Illegal(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl std::error::Error for MoveInputError {}
+
+fn promotion(ch: char) -> Option<PieceType> {
+    match ch.to_ascii_lowercase() {
+        'q' => Some(PieceType::Queen),
+        'r' => Some(PieceType::Rook),
+        'b' => Some(PieceType::Bishop),
+        'n' => Some(PieceType::Knight),
+        _ => None,
+    }
+}
+
+/// The squares and the promotion of a move typed as squares, "e2e4", "e2 e4", "e2-e4" or "e2xe4",
+/// the promotion after them like "e7e8q", "e7-e8=Q" or "e7 e8 q".
+pub fn parse_squares(line: &str) -> Option<(Square, Square, Option<PieceType>)> {
+    let chars: Vec<char> = line
+        .chars()
+        .filter(|ch| !matches!(ch, ' ' | '-' | 'x' | ':' | '='))
+        .collect();
+    let square = |chars: &[char]| chars.iter().collect::<String>().parse().ok();
+    let (from, to) = (square(chars.get(..2)?)?, square(chars.get(2..4)?)?);
+    match chars[4..] {
+        [] => Some((from, to, None)),
+        [ch] => Some((from, to, Some(promotion(ch)?))),
+        _ => None,
+    }
+}
+
+/// Explains why the SAN move `san`, which `Board::parse_san()` didn't take, isn't legal: the most
+/// telling reason of the pieces it may name, e.g. the pin of the only knight that reaches the
+/// square. A pawn's promotion without its piece is to a queen.
+fn explain_san(board: &Board, color: Color, san: &str) -> Result<Move, MoveInputError> {
+    let san = san.trim_end_matches(['+', '#', '!', '?']);
+    let king = Square::from_index(board.get_pieces(color).king().trailing_zeros() as u8);
+    let rank = match color {
+        Color::White => 0,
+        Color::Black => 7,
+    };
+    let castle = match san {
+        "O-O" | "0-0" => Square::new(6, rank),
+        "O-O-O" | "0-0-0" => Square::new(2, rank),
+        _ => None,
+    };
+    if let Some(to) = castle {
+        let king = king.ok_or(MoveInputError::Illegal(IllegalMoveReason::CantCastle))?;
+        return board
+            .checked_move(color, king, to, None)
+            .map_err(MoveInputError::Illegal);
+    }
+
+    let mut chars: Vec<char> = san.chars().collect();
+    let ty = match chars.first() {
+        Some('K') => PieceType::King,
+        Some('Q') => PieceType::Queen,
+        Some('R') => PieceType::Rook,
+        Some('B') => PieceType::Bishop,
+        Some('N') => PieceType::Knight,
+        Some('a'..='h') => PieceType::Pawn,
+        _ => return Err(MoveInputError::Unreadable),
+    };
+    if ty != PieceType::Pawn {
+        chars.remove(0);
+    }
+    let mut promote = None;
+    if ty == PieceType::Pawn {
+        if let Some(ty) = chars.last().copied().and_then(promotion) {
+            promote = Some(ty);
+            chars.pop();
+            if chars.last() == Some(&'=') {
+                chars.pop();
+            }
+        }
+    }
+    if chars.len() < 2 {
+        return Err(MoveInputError::Unreadable);
+    }
+    let to: Square = chars
+        .split_off(chars.len() - 2)
+        .into_iter()
+        .collect::<String>()
+        .parse()
+        .map_err(|_| MoveInputError::Unreadable)?;
+    if chars.last() == Some(&'x') {
+        chars.pop();
+    }
+    let (mut file, mut rank) = (None, None);
+    for ch in chars {
+        match ch {
+            'a'..='h' if file.is_none() => file = Some(ch as u8 - b'a'),
+            '1'..='8' if rank.is_none() => rank = Some(ch as u8 - b'1'),
+            _ => return Err(MoveInputError::Unreadable),
+        }
+    }
+
+    let promotion = match promote {
+        None if ty == PieceType::Pawn && matches!(to.rank(), 0 | 7) => Some(PieceType::Queen),
+        promote => promote,
+    };
+    let mut legal = Vec::new();
+    let mut reason = IllegalMoveReason::NoneCanReach(ty, to);
+    for from in BitIterator(board.get_pieces(color).get(ty)) {
+        let from = Square::from_index(from.trailing_zeros() as u8).unwrap();
+        if file.is_some_and(|file| from.file() != file)
+            || rank.is_some_and(|rank| from.rank() != rank)
+        {
+            continue;
+        }
+        match board.checked_move(color, from, to, promotion) {
+            Ok(mv) => legal.push(mv),
+            Err(
+                err @ (IllegalMoveReason::OwnPiece(_)
+                | IllegalMoveReason::BadPromotion
+                | IllegalMoveReason::CastlesThroughCheck
+                | IllegalMoveReason::IntoCheck(_)
+                | IllegalMoveReason::Pinned(_)
+                | IllegalMoveReason::InCheck
+                | IllegalMoveReason::ExposesKing),
+            ) => reason = err,
+            Err(_) => {}
+        }
+    }
+    match legal[..] {
+        [mv] => Ok(mv),
+        [] => Err(MoveInputError::Illegal(reason)),
+        _ => Err(MoveInputError::Ambiguous),
+    }
+}
+
+/// Reads a move the way players type them: as squares, see `parse_squares()`, in SAN like "Nf3",
+/// "exd5" or "O-O", or a drop like "P@e4". A promotion without its piece is to a queen.
+pub fn parse_move(board: &Board, color: Color, line: &str) -> Result<Move, MoveInputError> {
+    let line = line.trim();
+    if let Some((from, to, promotion)) = parse_squares(line) {
+        return board
+            .checked_move(color, from, to, promotion)
+            .map_err(MoveInputError::Illegal);
+    }
+    if line.contains('@') {
+        return board
+            .parse_san(color, line)
+            .or_else(|| board.parse_move(color, line))
+            .ok_or(MoveInputError::IllegalDrop);
+    }
+    match board.parse_san(color, line) {
+        Some(mv) => Ok(mv),
+        None => explain_san(board, color, line),
+    }
+}
+
+pub trait MoveSource {
+    /// The player's legal move for `color`, `None` once they quit or the source ran out.
+    fn next_move(&mut self, board: &Board, color: Color) -> io::Result<Option<Move>>;
+
+    /// Whether the player offered a draw with their last move, asking clears the offer.
+    fn offers_draw(&mut self) -> bool {
+        false
+    }
+
+    /// Whether the player accepts the opponent's draw offer.
+    fn accepts_draw(&mut self) -> io::Result<bool> {
+        Ok(false)
+    }
+}
+
+/// Moves in lines like "g8 f6", "g8g6", "g8-f6" or SAN like "Nf6", see `parse_move()`, castling
+/// by the king's move or "O-O", the promotion piece is asked for on the next line when only the
+/// squares were given. Crazyhouse's drops are like "P@e4". The prompts and the reasons a move
+/// isn't legal go to `output`, "quit" or the end of the input quit.
+/// "draw" before a move offers a draw with it.
+pub struct LineSource<R, W> {
+    input: R,
+    output: W,
+    buf: String,
+    draw_offered: bool,
+}
+
+impl<R: BufRead, W: Write> LineSource<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        Self {
+            input,
+            output,
+            buf: String::new(),
+            draw_offered: false,
+        }
+    }
+
+    /// The next line, trimmed, `None` at the end of the input or "quit".
+    fn line(&mut self, prompt: &str) -> io::Result<Option<&str>> {
+        write!(self.output, "{}", prompt)?;
+        self.output.flush()?;
+        self.buf.clear();
+        if self.input.read_line(&mut self.buf)? == 0 || self.buf.trim() == "quit" {
+            return Ok(None);
+        }
+        Ok(Some(self.buf.trim()))
+    }
+}
+
+impl<R: BufRead, W: Write> MoveSource for LineSource<R, W> {
+    fn next_move(&mut self, board: &Board, color: Color) -> io::Result<Option<Move>> {
+        loop {
+            let line = match self.line("Your move: ")? {
+                Some("draw") => {
+                    self.draw_offered = true;
+                    writeln!(self.output, "The draw is offered with your move")?;
+                    continue;
+                }
+                Some(line) => line.to_owned(),
+                None => return Ok(None),
+            };
+            let mv = match parse_move(board, color, &line) {
+                Ok(mv) => mv,
+                Err(err) => {
+                    writeln!(self.output, "Illegal move: {}", err)?;
+                    continue;
+                }
+            };
+            // Asked for when the squares were typed without it.
+            let asks = matches!(parse_squares(&line), Some((_, _, None)));
+            if mv.ty != MoveType::PawnQueenPromotion || !asks {
+                return Ok(Some(mv));
+            }
+
+            let promotion = match self.line("Choose pawn promotion (q,r,b,n): ")? {
+                Some(line) => match line.to_ascii_lowercase().as_str() {
+                    "q" | "queen" => PieceType::Queen,
+                    "r" | "rook" => PieceType::Rook,
+                    "b" | "bishop" => PieceType::Bishop,
+                    "n" | "knight" => PieceType::Knight,
+                    _ => {
+                        writeln!(self.output, "Bad promotion path")?;
+                        continue;
+                    }
+                },
+                None => return Ok(None),
+            };
+            match board.get_legal_move(color, mv.from, mv.to, Some(promotion)) {
+                Some(mv) => return Ok(Some(mv)),
+                None => writeln!(self.output, "This move is illegal")?,
+            }
+        }
+    }
+
+    fn offers_draw(&mut self) -> bool {
+        core::mem::take(&mut self.draw_offered)
+    }
+
+    fn accepts_draw(&mut self) -> io::Result<bool> {
+        Ok(matches!(
+            self.line("Your opponent offers a draw, accept it? (y/n): ")?,
+            Some("y" | "yes")
+        ))
+    }
+}
+
+/// The player at the terminal.
+pub fn stdin() -> LineSource<io::StdinLock<'static>, io::Stdout> {
+    LineSource::new(io::stdin().lock(), io::stdout())
+}
+
+/// Waits for a connection on `addr` and reads the moves from it, the prompts are sent back.
+pub fn tcp(addr: impl ToSocketAddrs) -> io::Result<LineSource<BufReader<TcpStream>, TcpStream>> {
+    let (stream, peer) = TcpListener::bind(addr)?.accept()?;
+    log::info!("Reading the moves from {}", peer);
+    Ok(LineSource::new(BufReader::new(stream.try_clone()?), stream))
+}
+
+/// A fixed list of moves, e.g. to replay a game or drive the loop in tests.
+pub struct ScriptedSource {
+    moves: std::vec::IntoIter<Move>,
+}
+
+impl ScriptedSource {
+    pub fn new(moves: Vec<Move>) -> Self {
+        Self {
+            moves: moves.into_iter(),
+        }
+    }
+}
+
+impl MoveSource for ScriptedSource {
+    /// Fails on an illegal move.
+    fn next_move(&mut self, board: &Board, color: Color) -> io::Result<Option<Move>> {
+        match self.moves.next() {
+            Some(mv) if mv.from >= 64 || mv.to >= 64 || !board.is_legal(color, mv) => {
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Illegal scripted move: {}", mv),
+                ))
+            }
+            mv => Ok(mv),
+        }
+    }
+}
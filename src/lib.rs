@@ -0,0 +1,132 @@
+// The Octal Chess Board:
+//
+// 8 | 70  71  72  73  74  75  76  77
+//   |
+// 7 | 60  61  62  63  64  65  66  67
+//   |
+// 6 | 50  51  52  53  54  55  56  57
+//   |
+// 5 | 40  41  42  43  44  45  46  47
+//   |
+// 4 | 30  31  32  33  34  35  36  37
+//   |
+// 3 | 20  21  22  23  24  25  26  27
+//   |
+// 2 | 10  11  12  13  14  15  16  17
+//   |
+// 1 | 00  01  02  03  04  05  06  07
+//   +-------------------------------
+//      a   b   c   d   e   f   g   h
+
+#![cfg_attr(not(feature = "std"), no_std)]
+// This is because labled block are still unreleased and are immitated with never looping loops.
+#![allow(clippy::never_loop)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod annotate;
+#[cfg(feature = "std")]
+pub mod baseline;
+#[cfg(feature = "std")]
+pub mod bench;
+pub mod bit_iter;
+#[cfg(feature = "std")]
+pub mod blindfold;
+pub mod board;
+#[cfg(feature = "std")]
+pub mod bot;
+#[cfg(feature = "std")]
+pub mod clock;
+pub mod control;
+pub mod diagram;
+pub mod eco;
+#[cfg(feature = "std")]
+pub mod editor;
+pub mod endgame;
+#[cfg(feature = "std")]
+pub mod engine;
+pub mod error;
+pub mod eval;
+#[cfg(feature = "std")]
+pub mod experience;
+#[cfg(feature = "std")]
+pub mod external;
+pub mod fen;
+pub mod game;
+#[cfg(feature = "std")]
+pub mod human;
+pub mod illegal;
+#[cfg(feature = "std")]
+pub mod input;
+#[cfg(feature = "lichess")]
+pub mod lichess;
+#[cfg(feature = "std")]
+pub mod logger;
+pub mod masks;
+pub mod material;
+#[cfg(feature = "std")]
+pub mod mcts;
+pub mod nnue;
+pub mod odds;
+#[cfg(feature = "std")]
+pub mod openings;
+#[cfg(feature = "std")]
+pub mod options;
+#[cfg(feature = "std")]
+pub mod perft;
+pub mod pgn;
+#[cfg(feature = "std")]
+pub mod picker;
+#[cfg(feature = "std")]
+pub mod profile;
+#[cfg(feature = "std")]
+pub mod puzzle;
+pub mod render;
+pub mod rng;
+pub mod san;
+#[cfg(feature = "std")]
+pub mod search_tree;
+#[cfg(feature = "std")]
+pub mod selfplay;
+#[cfg(feature = "std")]
+pub mod server;
+pub mod square;
+#[cfg(feature = "std")]
+pub mod time;
+#[cfg(feature = "std")]
+pub mod tournament;
+#[cfg(feature = "std")]
+pub mod train;
+#[cfg(feature = "std")]
+pub mod tt;
+#[cfg(feature = "tui")]
+pub mod tui;
+#[cfg(feature = "std")]
+pub mod tune;
+#[cfg(feature = "std")]
+pub mod uci;
+pub mod variant;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "std")]
+pub mod xboard;
+pub mod zobrist;
+
+pub use bit_iter::BitIterator;
+pub use board::{
+    Board, ChessFlags, Color, Move, MoveError, MoveList, MoveType, NullMoveUndo, Piece, PieceType,
+    Pieces,
+};
+#[cfg(feature = "std")]
+pub use bot::{Bot, SearchOptions};
+#[cfg(feature = "std")]
+pub use engine::{Engine, Score, SearchLimits, SearchResult};
+pub use error::MxChessError;
+pub use game::{Game, GameStatus};
+pub use illegal::IllegalMoveReason;
+#[cfg(feature = "std")]
+pub use mcts::MctsBot;
+pub use odds::Odds;
+pub use square::Square;
+pub use variant::Variant;
@@ -0,0 +1,182 @@
+// A bot for lichess.org, speaking the Lichess Bot API (https://lichess.org/api#tag/Bot).
+// It accepts standard challenges and plays every game on its own thread.
+
+use crate::{Bot, Color, Game};
+use serde_json::Value;
+use std::{
+    io::{self, BufRead, BufReader},
+    thread,
+    time::Duration,
+};
+
+const API: &str = "https://lichess.org/api";
+
+#[derive(Clone)]
+pub struct Lichess {
+    agent: ureq::Agent,
+    token: String,
+    id: String,
+}
+
+fn io_error(err: impl std::fmt::Display) -> io::Error {
+    io::Error::other(err.to_string())
+}
+
+impl Lichess {
+    /// Connects with a bot account's API token.
+    pub fn connect(token: String) -> io::Result<Self> {
+        let agent = ureq::AgentBuilder::new()
+            .timeout_connect(Duration::from_secs(10))
+            .build();
+
+        let mut lichess = Self {
+            agent,
+            token,
+            id: String::new(),
+        };
+        let account = lichess.get_json("/account")?;
+        lichess.id = account["id"]
+            .as_str()
+            .ok_or_else(|| io_error("No account id"))?
+            .to_owned();
+
+        Ok(lichess)
+    }
+
+    fn get(&self, path: &str) -> io::Result<ureq::Response> {
+        self.agent
+            .get(&format!("{}{}", API, path))
+            .set("Authorization", &format!("Bearer {}", self.token))
+            .call()
+            .map_err(io_error)
+    }
+
+    fn get_json(&self, path: &str) -> io::Result<Value> {
+        self.get(path)?.into_json()
+    }
+
+    fn post(&self, path: &str) -> io::Result<()> {
+        self.agent
+            .post(&format!("{}{}", API, path))
+            .set("Authorization", &format!("Bearer {}", self.token))
+            .call()
+            .map_err(io_error)?;
+        Ok(())
+    }
+
+    /// Calls `f` for every JSON line of a ndjson stream, until the stream ends or `f` returns
+    /// `false`. Empty lines are keep-alive messages and are skipped.
+    fn stream(&self, path: &str, mut f: impl FnMut(Value) -> io::Result<bool>) -> io::Result<()> {
+        let reader = BufReader::new(self.get(path)?.into_reader());
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if !f(serde_json::from_str(&line)?)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handles incoming events forever.
+    pub fn run(&self) -> io::Result<()> {
+        log::info!("Connected as {}", self.id);
+
+        self.stream("/stream/event", |event| {
+            match event["type"].as_str() {
+                Some("challenge") => {
+                    let challenge = &event["challenge"];
+                    let id = challenge["id"].as_str().unwrap_or_default();
+                    if challenge["challenger"]["id"].as_str() == Some(&self.id) {
+                        return Ok(true);
+                    }
+
+                    if challenge["variant"]["key"].as_str() == Some("standard") {
+                        log::info!("Accepting challenge {}", id);
+                        self.post(&format!("/challenge/{}/accept", id))?;
+                    } else {
+                        log::info!("Declining challenge {}", id);
+                        self.post(&format!("/challenge/{}/decline", id))?;
+                    }
+                }
+                Some("gameStart") => {
+                    let id = event["game"]["gameId"]
+                        .as_str()
+                        .or_else(|| event["game"]["id"].as_str())
+                        .unwrap_or_default()
+                        .to_owned();
+                    log::info!("Game {} started", id);
+
+                    let lichess = self.clone();
+                    thread::spawn(move || {
+                        if let Err(err) = lichess.play_game(&id) {
+                            log::error!("Game {} failed: {}", id, err);
+                        }
+                    });
+                }
+                Some("gameFinish") => {
+                    log::info!("Game {} finished", event["game"]["id"]);
+                }
+                _ => {}
+            }
+            Ok(true)
+        })
+    }
+
+    fn play_game(&self, id: &str) -> io::Result<()> {
+        let mut color = Color::White;
+
+        self.stream(&format!("/bot/game/stream/{}", id), |event| {
+            let state = match event["type"].as_str() {
+                Some("gameFull") => {
+                    if event["initialFen"].as_str().unwrap_or("startpos") != "startpos" {
+                        return Err(io_error(
+                            "Only games from the initial position are supported",
+                        ));
+                    }
+                    color = if event["white"]["id"].as_str() == Some(&self.id) {
+                        Color::White
+                    } else {
+                        Color::Black
+                    };
+                    &event["state"]
+                }
+                Some("gameState") => &event,
+                _ => return Ok(true),
+            };
+
+            if state["status"].as_str() != Some("started") {
+                return Ok(false);
+            }
+
+            let mut game = Game::new();
+            for mv in state["moves"]
+                .as_str()
+                .unwrap_or_default()
+                .split_whitespace()
+            {
+                let mv = game
+                    .board()
+                    .parse_move(game.turn(), mv)
+                    .ok_or_else(|| io_error(format!("Bad move from lichess: {}", mv)))?;
+                game.push_move(mv);
+            }
+            if game.turn() != color {
+                return Ok(true);
+            }
+
+            let time = match color {
+                Color::White => &state["wtime"],
+                Color::Black => &state["btime"],
+            };
+            let bot = Bot::with_depth(Bot::depth_for_clock(time.as_u64().unwrap_or(u64::MAX)));
+            if let Some(mv) = bot.choose_move_with_history(game.board(), color, game.history()) {
+                self.post(&format!("/bot/game/{}/move/{}", id, mv))?;
+            }
+
+            Ok(true)
+        })
+    }
+}
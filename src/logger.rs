@@ -0,0 +1,86 @@
+// A small `log` backend for the binary: the messages go to stderr, filtered per module by a spec
+// like "info,mxchess::bot=debug" from `--log` or the MXCHESS_LOG environment variable.
+
+use log::{LevelFilter, Log, Metadata, Record};
+use std::{error, fmt};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FilterError(&'static str);
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Bad log filter: {}", self.0)
+    }
+}
+
+impl error::Error for FilterError {}
+
+struct Logger {
+    default: LevelFilter,
+    /// Module paths with their own level.
+    modules: Vec<(String, LevelFilter)>,
+}
+
+impl Logger {
+    fn parse(spec: &str) -> Result<Self, FilterError> {
+        let mut logger = Self {
+            default: LevelFilter::Info,
+            modules: Vec::new(),
+        };
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            let level = |level: &str| {
+                level.parse().map_err(|_| {
+                    FilterError("unknown level, use off, error, warn, info, debug or trace")
+                })
+            };
+            match directive.split_once('=') {
+                Some((module, level_str)) => {
+                    logger.modules.push((module.to_owned(), level(level_str)?));
+                }
+                None => logger.default = level(directive)?,
+            }
+        }
+        Ok(logger)
+    }
+
+    /// The level of the most specific module `target` is in.
+    fn level(&self, target: &str) -> LevelFilter {
+        self.modules
+            .iter()
+            .filter(|(module, _)| {
+                target
+                    .strip_prefix(module.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .max_by_key(|(module, _)| module.len())
+            .map_or(self.default, |&(_, level)| level)
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("[{} {}] {}", record.level(), record.target(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Installs the logger with a comma separated list of a default level and `module=level`
+/// overrides. Fails on a bad spec or when a logger is already installed.
+pub fn init(spec: &str) -> Result<(), Box<dyn error::Error>> {
+    let logger = Logger::parse(spec)?;
+    let max = logger
+        .modules
+        .iter()
+        .map(|&(_, level)| level)
+        .fold(logger.default, Ord::max);
+    log::set_boxed_logger(Box::new(logger))?;
+    log::set_max_level(max);
+    Ok(())
+}
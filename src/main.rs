@@ -1,103 +1,120 @@
-// The Octal Chess Board:
-//
-// 8 | 70  71  72  73  74  75  76  77
-//   |
-// 7 | 60  61  62  63  64  65  66  67
-//   |
-// 6 | 50  51  52  53  54  55  56  57
-//   |
-// 5 | 40  41  42  43  44  45  46  47
-//   |
-// 4 | 30  31  32  33  34  35  36  37
-//   |
-// 3 | 20  21  22  23  24  25  26  27
-//   |
-// 2 | 10  11  12  13  14  15  16  17
-//   |
-// 1 | 00  01  02  03  04  05  06  07
-//   +-------------------------------
-//      a   b   c   d   e   f   g   h
-
-// This is because labled block are still unreleased and are immitated with never looping loops.
-#![allow(clippy::never_loop)]
-
-pub mod bit_iter;
-pub mod board;
-pub mod bot;
-
-pub use bit_iter::BitIterator;
-pub use board::{Board, Color, Move, MoveType, Piece, PieceType, Pieces};
-pub use bot::Bot;
-
-use std::io::{self, Write};
-
-pub fn chess_pos(chs: &[u8]) -> Option<u8> {
-    if chs.len() != 2 || !(b'a'..=b'h').contains(&chs[0]) || !(b'1'..=b'8').contains(&chs[1]) {
-        None
-    } else {
-        Some(8 * (chs[1] - b'1') + (chs[0] - b'a'))
-    }
+use mxchess::{
+    baseline::{GreedyBot, RandomBot},
+    clock::Clock,
+    eco,
+    editor::Finish,
+    external::UciEngine,
+    human::HumanBot,
+    input::{self, MoveSource},
+    nnue::Nnue,
+    options::EngineOptions,
+    profile::Profile,
+    render::{Annotations, Brush, Perspective},
+    rng::Rng,
+    selfplay::{Adjudication, PlayerAdjudication, Sprt},
+    tournament::{Format, Player},
+    tt::TranspositionTable,
+    Board, Bot, Color, Engine, Game, MctsBot, Move, MoveType, Odds, Score, SearchLimits, Square,
+};
+
+use std::{io, sync::Arc};
+
+/// The nodes `mxchess tree` records.
+const TREE_LIMIT: usize = 100_000;
+/// How deep the bot may deepen on the clock, the clock stops it long before.
+const CLOCK_DEPTH: u32 = 64;
+/// Who Black is in `play`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Opponent {
+    Bot,
+    /// Monte Carlo tree search.
+    Mcts,
+    /// The weakened bot of `HumanBot`.
+    Human,
 }
 
-fn to_chess_pos(x: u8) -> String {
-    String::from_utf8([b'a' + (x & 7), b'1' + x / 8].to_vec()).unwrap()
+/// Where `play` starts from.
+#[derive(Clone, Copy, Debug)]
+pub enum Start {
+    /// The variant's starting position.
+    Standard,
+    /// Without the bot's piece, see `Board::with_odds()`.
+    Odds(Odds),
+    /// A position set up e.g. in the board editor, the side to move moves first.
+    Position(Board, Color),
 }
 
+/// The flags that set the engine options and the options' UCI names.
+const ENGINE_FLAGS: [(&str, &str); 12] = [
+    ("--hash", "Hash"),
+    ("--threads", "Threads"),
+    ("--contempt", "Contempt"),
+    ("--skill", "Skill Level"),
+    ("--book", "BookFile"),
+    ("--experience", "ExperienceFile"),
+    ("--variant", "UCI_Variant"),
+    ("--queen-value", "QueenValue"),
+    ("--rook-value", "RookValue"),
+    ("--bishop-value", "BishopValue"),
+    ("--knight-value", "KnightValue"),
+    ("--pawn-value", "PawnValue"),
+];
+
 pub fn demo() {
     let moves: &[_] = &[
         Move {
-            from: chess_pos(b"e2").unwrap(),
-            to: chess_pos(b"e4").unwrap(),
+            from: Square::E2.index(),
+            to: Square::E4.index(),
             ty: MoveType::PawnLeap,
         },
         Move {
-            from: chess_pos(b"d7").unwrap(),
-            to: chess_pos(b"d5").unwrap(),
+            from: Square::D7.index(),
+            to: Square::D5.index(),
             ty: MoveType::PawnLeap,
         },
         Move {
-            from: chess_pos(b"e4").unwrap(),
-            to: chess_pos(b"e5").unwrap(),
+            from: Square::E4.index(),
+            to: Square::E5.index(),
             ty: MoveType::Pawn,
         },
         Move {
-            from: chess_pos(b"f7").unwrap(),
-            to: chess_pos(b"f5").unwrap(),
+            from: Square::F7.index(),
+            to: Square::F5.index(),
             ty: MoveType::PawnLeap,
         },
         Move {
-            from: chess_pos(b"e5").unwrap(),
-            to: chess_pos(b"f6").unwrap(),
+            from: Square::E5.index(),
+            to: Square::F6.index(),
             ty: MoveType::PawnEnPassant,
         },
         Move {
-            from: chess_pos(b"g8").unwrap(),
-            to: chess_pos(b"f6").unwrap(),
+            from: Square::G8.index(),
+            to: Square::F6.index(),
             ty: MoveType::Knight,
         },
         Move {
-            from: chess_pos(b"f1").unwrap(),
-            to: chess_pos(b"b5").unwrap(),
+            from: Square::F1.index(),
+            to: Square::B5.index(),
             ty: MoveType::Bishop,
         },
         Move {
-            from: chess_pos(b"c7").unwrap(),
-            to: chess_pos(b"c6").unwrap(),
+            from: Square::C7.index(),
+            to: Square::C6.index(),
             ty: MoveType::Pawn,
         },
         Move {
-            from: chess_pos(b"g1").unwrap(),
-            to: chess_pos(b"h3").unwrap(),
+            from: Square::G1.index(),
+            to: Square::H3.index(),
             ty: MoveType::Knight,
         },
         Move {
-            from: chess_pos(b"c6").unwrap(),
-            to: chess_pos(b"b5").unwrap(),
+            from: Square::C6.index(),
+            to: Square::B5.index(),
             ty: MoveType::Pawn,
         },
         Move {
-            from: chess_pos(b"e1").unwrap(),
-            to: chess_pos(b"g1").unwrap(),
+            from: Square::E1.index(),
+            to: Square::G1.index(),
             ty: MoveType::Castle,
         },
     ];
@@ -117,7 +134,7 @@ pub fn demo() {
             .moves(color)
             .iter()
             .all(|&mv| board.is_legal(color, mv)));
-        board.perform_move(mv);
+        board.perform_move_unchecked(mv);
 
         color = color.inv();
     }
@@ -131,9 +148,7 @@ pub fn demo() {
     println!();
 }
 
-pub fn two_player_mode() -> io::Result<()> {
-    let mut buf = String::new();
-
+pub fn two_player_mode(source: &mut dyn MoveSource) -> io::Result<()> {
     let mut board = Board::new();
 
     println!("Move format: \"<Initial chess position> <Target chess position>\"");
@@ -158,58 +173,62 @@ pub fn two_player_mode() -> io::Result<()> {
         }
         println!();
 
-        buf.clear();
-        print!("Your move: ");
-        io::stdout().flush()?;
-        io::stdin().read_line(&mut buf)?;
-
-        let (from, to) = {
-            let mut iter = buf.trim().split(' ');
-            let from = chess_pos(iter.next().expect("Bad input").as_bytes()).unwrap();
-            let to = chess_pos(iter.next().expect("Bad input").as_bytes()).unwrap();
-            assert!(iter.next().is_none(), "Bad input");
-
-            (from, to)
+        let mv = match source.next_move(&board, color)? {
+            Some(mv) => mv,
+            None => return Ok(()),
         };
-
-        let mut mv = board
-            .get_legal_move(color, from, to)
-            .expect("This move is illegal");
-        if mv.ty == MoveType::PawnQueenPromotion {
-            print!("Choose pawn promotion (q,r,b,n): ");
-            io::stdout().flush()?;
-
-            buf.clear();
-            io::stdin().read_line(&mut buf)?;
-            buf.make_ascii_lowercase();
-
-            mv.ty = match buf.as_str().trim() {
-                "q" | "queen" => MoveType::PawnQueenPromotion,
-                "r" | "rook" => MoveType::PawnRookPromotion,
-                "b" | "bishop" => MoveType::PawnBishopPromotion,
-                "n" | "knight" => MoveType::PawnKnightPromotion,
-                _ => panic!("Bad promotion path"),
-            };
-        }
-        board.perform_move(mv);
+        board.perform_move_unchecked(mv);
 
         color = color.inv();
     }
-
-    // Ok(())
 }
 
-fn main() -> io::Result<()> {
-    let mut buf = String::new();
-
-    let mut board = Board::new();
-    let bot = Bot;
+/// Plays against the bot, Monte Carlo tree search or the human-like bot, on `clock` if given.
+/// The bot is configured by `options`, and plays the book's moves while it has any. The game
+/// starts from `start`, e.g. without the bot's piece for odds. White's moves come from `source`.
+/// The opening is named once the game leaves the named lines, and a finished game goes into the
+/// experience file, if there's one, and into the player's profile.
+pub fn play(
+    opponent: Opponent,
+    seed: u64,
+    mut clock: Option<Clock>,
+    start: Start,
+    adjudication: Adjudication,
+    options: &mut EngineOptions,
+    source: &mut dyn MoveSource,
+) -> io::Result<()> {
+    let (mut board, mut color) = match start {
+        Start::Standard => (Board::with_variant(options.variant), Color::White),
+        Start::Odds(odds) => (Board::with_odds(odds), Color::White),
+        Start::Position(board, color) => (board, color),
+    };
+    let mut engine: Box<dyn Engine> = match opponent {
+        Opponent::Bot => Box::new(options.bot(if clock.is_some() {
+            CLOCK_DEPTH
+        } else {
+            Bot::DEFAULT_DEPTH
+        })),
+        Opponent::Mcts => {
+            let mut mcts_bot = MctsBot::new();
+            mcts_bot.seed = seed;
+            Box::new(mcts_bot)
+        }
+        // The clock doesn't stop its search, it always searches to the default depth.
+        Opponent::Human => Box::new(HumanBot::new(options.bot(Bot::DEFAULT_DEPTH), seed)),
+    };
+    let mut rng = Rng::new(seed);
+    let mut game = Game::from_position(board, color);
+    let mut opening_named = false;
+    // The positions the bot searched, for the experience file.
+    let mut searched = Vec::new();
+    // The bot resigns and offers draws by its values, a draw only once while it stays drawish.
+    let mut verdicts = PlayerAdjudication::new(adjudication);
+    let mut draw_offered = false;
 
     println!("Move format: \"<Initial chess position> <Target chess position>\"");
     println!("  castling will be inferred from the king's move");
     println!("  for example: g8 f6");
-    let mut color = Color::White;
-    loop {
+    let winner = loop {
         println!();
         match color {
             Color::White => println!("White's move:"),
@@ -217,101 +236,957 @@ fn main() -> io::Result<()> {
         }
         println!("------------");
 
-        let moves = board.moves(color);
+        if let Some(winner) = board.variant_winner() {
+            board.print(color);
+            println!("{:?} wins by the {} rules", winner, board.variant);
+            break Some(winner);
+        }
+        let attack = board.check_attack(color.inv());
+        let moves = board.moves_with_attack(color, attack);
         if moves.is_empty() {
-            let attack = board.check_attack(color.inv());
-
-            if attack & board.get_pieces(color).king == 0 {
+            let winner = if attack & board.get_pieces(color).king() == 0 {
                 println!("STALE MATE");
+                None
             } else {
                 println!("CHECK MATE, {:?} wins", color.inv());
-            }
+                Some(color.inv())
+            };
+            board.print(color);
+            break winner;
         }
         board.print(color);
         for mv in moves.iter() {
             mv.print(&board);
         }
         println!();
+        if let Some(clock) = &mut clock {
+            println!("{}", clock);
+            clock.start(color);
+        }
 
+        let book_moves = options.book_moves(&game);
         let mv = match color {
-            Color::White => loop {
-                print!("Your move: ");
-                io::stdout().flush()?;
-                buf.clear();
-                io::stdin().read_line(&mut buf)?;
-                if buf.trim() == "quit" {
+            Color::White => match source.next_move(&board, color)? {
+                Some(mv) => mv,
+                None => {
                     println!("Goodbye LOSER!!!");
                     return Ok(());
                 }
+            },
+            Color::Black if !book_moves.is_empty() => book_moves[rng.below(book_moves.len())],
+            Color::Black => {
+                let limits = SearchLimits {
+                    time: clock.map(|clock| clock.time_manager(Color::Black)),
+                    moves: &moves,
+                    ..SearchLimits::default()
+                };
+                let result = engine.best_move(&board, Color::Black, &limits);
+                let mv = result.best_move.unwrap();
+                let value = result.score.map(Score::centipawns);
+                if let Some(value) = value {
+                    searched.push((board.position_key(Color::Black), Color::Black, mv, value));
+                }
+                verdicts.record(value);
+                if verdicts.resigns() {
+                    println!("Black resigns");
+                    break Some(Color::White);
+                }
+                mv
+            }
+        };
+        let move_number = game.moves().len() / 2 + 1;
+        match color {
+            Color::White if source.offers_draw() => {
+                if verdicts.wants_draw(move_number) {
+                    println!("Black accepts the draw");
+                    break None;
+                }
+                println!("Black declines the draw");
+            }
+            Color::White => {}
+            Color::Black if verdicts.wants_draw(move_number) => {
+                if !draw_offered {
+                    draw_offered = true;
+                    println!("Black offers a draw");
+                    if source.accepts_draw()? {
+                        println!("Draw agreed");
+                        break None;
+                    }
+                }
+            }
+            Color::Black => draw_offered = false,
+        }
+        if let Some(clock) = &mut clock {
+            clock.stop();
+            if clock.flag_fell(color) {
+                println!("{:?} lost on time", color);
+                break Some(color.inv());
+            }
+        }
 
-                let (from, to) = {
-                    let mut iter = buf.trim().split(' ');
-                    let from = chess_pos(match iter.next() {
-                        Some(s) => s.as_bytes(),
-                        None => {
-                            println!("Bad input");
-                            continue;
-                        }
-                    });
-                    let to = chess_pos(match iter.next() {
-                        Some(s) => s.as_bytes(),
-                        None => {
-                            println!("Bad input");
-                            continue;
-                        }
-                    });
+        println!();
+        mv.print(&board);
+        println!();
 
-                    if iter.next().is_some() {
-                        println!("Bad input");
-                        continue;
-                    }
+        board.perform_move_unchecked(mv);
+        game.push_move(mv);
+        if !opening_named && eco::lookup(&board, color.inv()).is_none() {
+            if let Some((code, name)) = game.opening() {
+                println!("Opening: {} {}", code, name);
+            }
+            opening_named = true;
+        }
 
-                    match (from, to) {
-                        (Some(from), Some(to)) => (from, to),
-                        _ => {
-                            println!("Bad input");
-                            continue;
+        color = color.inv();
+    };
+    options.learn(&searched, winner)?;
+
+    // Only the games against the alpha-beta bot from the starting position go into the rating.
+    if let (Opponent::Bot, Start::Standard, Some(path)) = (opponent, start, Profile::default_path())
+    {
+        let mut profile = Profile::load(&path)?;
+        let score = match winner {
+            Some(Color::White) => 1.0,
+            Some(Color::Black) => 0.0,
+            None => 0.5,
+        };
+        profile.record(options.skill, score);
+        profile.save(&path)?;
+        println!("Your rating: {:.0}", profile.rating);
+    }
+    Ok(())
+}
+
+fn main() -> io::Result<()> {
+    let mut args: Vec<_> = std::env::args().skip(1).collect();
+
+    // `--seed <n>` reproduces a run, otherwise the seed is random.
+    let seed = match args.iter().position(|arg| arg == "--seed") {
+        Some(i) => match args.get(i + 1).map(|seed| seed.parse()) {
+            Some(Ok(seed)) => {
+                args.drain(i..i + 2);
+                seed
+            }
+            _ => {
+                eprintln!("Usage: --seed <number>");
+                std::process::exit(2);
+            }
+        },
+        None => Rng::entropy_seed(),
+    };
+
+    // `--log <spec>` or MXCHESS_LOG set the log levels, e.g. "info,mxchess::bot=debug".
+    let log = match args.iter().position(|arg| arg == "--log") {
+        Some(i) if i + 1 < args.len() => args.drain(i..i + 2).nth(1).unwrap(),
+        Some(_) => {
+            eprintln!("Usage: --log <level>[,<module>=<level>...]");
+            std::process::exit(2);
+        }
+        None => std::env::var("MXCHESS_LOG").unwrap_or_default(),
+    };
+    if let Err(err) = mxchess::logger::init(&log) {
+        eprintln!("{}", err);
+        std::process::exit(2);
+    }
+
+    // The engine options by their UCI names, e.g. `--hash 64` or `--skill 10`.
+    let mut options = EngineOptions::default();
+    for (flag, name) in ENGINE_FLAGS {
+        if let Some(i) = args.iter().position(|arg| arg == flag) {
+            let set = match args.get(i + 1) {
+                Some(value) => options.set(name, value),
+                None => Err(io::Error::new(io::ErrorKind::InvalidInput, "Missing value")),
+            };
+            if let Err(err) = set {
+                eprintln!("{}: {}", flag, err);
+                std::process::exit(2);
+            }
+            args.drain(i..i + 2);
+        }
+    }
+    #[cfg(feature = "parallel")]
+    if options.threads != 0 {
+        if let Err(err) = rayon::ThreadPoolBuilder::new()
+            .num_threads(options.threads)
+            .build_global()
+        {
+            eprintln!("--threads: {}", err);
+            std::process::exit(2);
+        }
+    }
+
+    match args.first().map(|s| s.as_str()) {
+        None => play(
+            Opponent::Bot,
+            seed,
+            None,
+            Start::Standard,
+            Adjudication::NONE,
+            &mut options,
+            &mut input::stdin(),
+        ),
+        Some("play") => {
+            // `mcts` or `human`, a time control like "5+3", `odds=<piece>`, `listen=<addr>` and the bot's
+            // `resign=<cp>/<moves>` and `draw=<cp>/<moves>`, in any order.
+            let mut opponent = Opponent::Bot;
+            let mut clock = None;
+            let mut start = Start::Standard;
+            let mut listen = None;
+            let mut adjudication = Adjudication::NONE;
+            for arg in &args[1..] {
+                let rule = arg
+                    .split_once('=')
+                    .and_then(|(name, rule)| Some(name).zip(Adjudication::parse_rule(rule)));
+                match (arg.as_str(), Clock::parse(arg), rule) {
+                    ("mcts", _, _) => opponent = Opponent::Mcts,
+                    ("human", _, _) => opponent = Opponent::Human,
+                    (_, Some(parsed), _) => clock = Some(parsed),
+                    (_, None, Some(("resign", (score, moves)))) => {
+                        adjudication.resign_score = score;
+                        adjudication.resign_moves = moves;
+                    }
+                    (_, None, Some(("draw", (score, moves)))) => {
+                        adjudication.draw_score = score;
+                        adjudication.draw_moves = moves;
+                    }
+                    (arg, None, _) if arg.starts_with("listen=") => listen = Some(&arg[7..]),
+                    (arg, None, _)
+                        if arg.starts_with("odds=") && arg[5..].parse::<Odds>().is_ok() =>
+                    {
+                        start = Start::Odds(arg[5..].parse().unwrap())
+                    }
+                    _ => {
+                        eprintln!(
+                            "Usage: mxchess play [mcts|human] [<minutes>+<increment>] \
+                            [odds=pawn|knight|rook|queen] [listen=<addr>] \
+                            [resign=<cp>/<moves>] [draw=<cp>/<moves>]"
+                        );
+                        std::process::exit(2);
+                    }
+                }
+            }
+            match listen {
+                Some(addr) => {
+                    println!("Waiting for the moves' connection on {}", addr);
+                    play(
+                        opponent,
+                        seed,
+                        clock,
+                        start,
+                        adjudication,
+                        &mut options,
+                        &mut input::tcp(addr)?,
+                    )
+                }
+                None => play(
+                    opponent,
+                    seed,
+                    clock,
+                    start,
+                    adjudication,
+                    &mut options,
+                    &mut input::stdin(),
+                ),
+            }
+        }
+        Some("selfplay") => {
+            const USAGE: &str = "Usage: mxchess selfplay [games] [<minutes>+<increment>] \
+                [resign=<cp>/<moves>] [draw=<cp>/<moves>] [params=<file>] [sprt=<elo0>/<elo1>] [book=<file>] \
+                [base=bot|mcts|human|random|greedy]";
+            let mut games = mxchess::selfplay::DEFAULT_GAMES;
+            let mut clock = Clock::new(
+                mxchess::selfplay::DEFAULT_BASE,
+                mxchess::selfplay::DEFAULT_INCREMENT,
+            );
+            let mut adjudication = Adjudication::default();
+            let mut params: Option<mxchess::eval::EvalParams> = None;
+            let mut sprt = None;
+            let mut openings = vec![Game::new()];
+            // Without parameters to test the bot plays itself, or the base engine.
+            let mut base: Box<dyn Engine> = Box::new(Bot::with_depth(mxchess::selfplay::MAX_DEPTH));
+            for (i, arg) in args.iter().enumerate().skip(1) {
+                let parsed = match arg.split_once('=') {
+                    Some(("resign", rule)) => {
+                        Adjudication::parse_rule(rule).map(|(score, moves)| {
+                            adjudication.resign_score = score;
+                            adjudication.resign_moves = moves;
+                        })
+                    }
+                    Some(("draw", rule)) => Adjudication::parse_rule(rule).map(|(score, moves)| {
+                        adjudication.draw_score = score;
+                        adjudication.draw_moves = moves;
+                    }),
+                    Some(("params", path)) => {
+                        let text = std::fs::read_to_string(path)?;
+                        params = Some(
+                            text.parse()
+                                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?,
+                        );
+                        Some(())
+                    }
+                    Some(("book", path)) => {
+                        openings = mxchess::openings::load(&std::fs::read_to_string(path)?)?;
+                        Some(()).filter(|_| !openings.is_empty())
+                    }
+                    Some(("sprt", test)) => Sprt::parse(test).map(|test| sprt = Some(test)),
+                    Some(("base", name)) => {
+                        let engine: Option<Box<dyn Engine>> = match name {
+                            "bot" => Some(Box::new(Bot::with_depth(mxchess::selfplay::MAX_DEPTH))),
+                            "mcts" => Some(Box::new(MctsBot::new())),
+                            "human" => Some(Box::new(HumanBot::new(Bot::new(), seed))),
+                            "random" => Some(Box::new(RandomBot::new(seed))),
+                            "greedy" => Some(Box::new(GreedyBot::new(seed))),
+                            _ => None,
+                        };
+                        engine.map(|engine| base = engine)
+                    }
+                    Some(_) => None,
+                    None if i == 1 => arg.parse().ok().map(|n| games = n),
+                    None if i == 2 => Clock::parse(arg).map(|c| clock = c),
+                    None => None,
+                };
+                if parsed.is_none() {
+                    eprintln!("{}", USAGE);
+                    std::process::exit(2);
+                }
+            }
+            let mut test =
+                Bot::with_evaluator(mxchess::selfplay::MAX_DEPTH, params.unwrap_or_default());
+            mxchess::selfplay::run(
+                [&mut *base, &mut test],
+                &openings,
+                games,
+                clock,
+                &adjudication,
+                sprt.as_ref(),
+            );
+            Ok(())
+        }
+        Some("tournament") => {
+            const USAGE: &str =
+                "Usage: mxchess tournament <bot|mcts|human|random|greedy|uci:<command>>... \
+                [<minutes>+<increment>] [games=<n>] [gauntlet] [resign=<cp>/<moves>] \
+                [draw=<cp>/<moves>] [book=<file>] [pgn=<file>]";
+            let mut players = Vec::new();
+            let mut games = mxchess::selfplay::DEFAULT_GAMES;
+            let mut clock = Clock::new(
+                mxchess::selfplay::DEFAULT_BASE,
+                mxchess::selfplay::DEFAULT_INCREMENT,
+            );
+            let mut format = Format::RoundRobin;
+            let mut adjudication = Adjudication::default();
+            let mut openings = vec![Game::new()];
+            let mut pgn_path = None;
+            for arg in &args[1..] {
+                let parsed = match arg.split_once('=') {
+                    Some(("games", n)) => n.parse().ok().map(|n| games = n),
+                    Some(("resign", rule)) => {
+                        Adjudication::parse_rule(rule).map(|(score, moves)| {
+                            adjudication.resign_score = score;
+                            adjudication.resign_moves = moves;
+                        })
+                    }
+                    Some(("draw", rule)) => Adjudication::parse_rule(rule).map(|(score, moves)| {
+                        adjudication.draw_score = score;
+                        adjudication.draw_moves = moves;
+                    }),
+                    Some(("book", path)) => {
+                        openings = mxchess::openings::load(&std::fs::read_to_string(path)?)?;
+                        Some(()).filter(|_| !openings.is_empty())
+                    }
+                    Some(("pgn", path)) => {
+                        pgn_path = Some(path.to_owned());
+                        Some(())
+                    }
+                    Some(_) => None,
+                    None if arg == "gauntlet" => {
+                        format = Format::Gauntlet;
+                        Some(())
+                    }
+                    None => {
+                        let engine: Option<Box<dyn Engine>> = match arg.as_str() {
+                            "bot" => Some(Box::new(Bot::with_depth(mxchess::selfplay::MAX_DEPTH))),
+                            "mcts" => Some(Box::new(MctsBot::new())),
+                            "human" => Some(Box::new(HumanBot::new(Bot::new(), seed))),
+                            "random" => Some(Box::new(RandomBot::new(seed))),
+                            "greedy" => Some(Box::new(GreedyBot::new(seed))),
+                            _ => None,
+                        };
+                        match (engine, arg.strip_prefix("uci:")) {
+                            (Some(engine), _) => {
+                                players.push(Player::new(arg.as_str(), engine));
+                                Some(())
+                            }
+                            (None, Some(command)) => {
+                                let engine = UciEngine::spawn(command)?;
+                                let name = engine.name().to_owned();
+                                players.push(Player::new(name, Box::new(engine)));
+                                Some(())
+                            }
+                            (None, None) => Clock::parse(arg).map(|c| clock = c),
                         }
                     }
                 };
+                if parsed.is_none() {
+                    eprintln!("{}", USAGE);
+                    std::process::exit(2);
+                }
+            }
+            if players.len() < 2 {
+                eprintln!("{}", USAGE);
+                std::process::exit(2);
+            }
+            let tournament = mxchess::tournament::run(
+                &mut players,
+                format,
+                games,
+                &openings,
+                clock,
+                &adjudication,
+            );
+            print!("{}", tournament);
+            if let Some(path) = pgn_path {
+                std::fs::write(path, tournament.pgn())?;
+            }
+            Ok(())
+        }
+        Some("two-player") => two_player_mode(&mut input::stdin()),
+        Some("demo") => {
+            demo();
+            Ok(())
+        }
+        Some("bench") => {
+            let depth = match args.get(1) {
+                Some(depth) => match depth.parse() {
+                    Ok(depth) => depth,
+                    Err(_) => {
+                        eprintln!("Bad depth: {}", depth);
+                        std::process::exit(2);
+                    }
+                },
+                None => mxchess::bench::DEFAULT_DEPTH,
+            };
+            // Comparing the evaluation backends, given a network.
+            match args.get(2) {
+                Some(path) => {
+                    let nnue = Nnue::from_bytes(&std::fs::read(path)?)
+                        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                    let mut bot = Bot::with_evaluator(depth, nnue);
+                    bot.table = Some(Arc::new(TranspositionTable::new(options.hash_mb)));
+                    mxchess::bench::run(&mut bot);
+                }
+                None => {
+                    let mut bot = Bot::with_depth(depth);
+                    bot.table = Some(Arc::new(TranspositionTable::new(options.hash_mb)));
+                    mxchess::bench::run(&mut bot);
+                }
+            }
+            Ok(())
+        }
+        Some("perft") => {
+            let depth = match args.get(1).map(|depth| depth.parse()) {
+                Some(Ok(depth)) => depth,
+                _ => {
+                    eprintln!("Usage: mxchess perft <depth> [fen]");
+                    std::process::exit(2);
+                }
+            };
+            let (board, color) = match args.get(2) {
+                Some(fen) => Board::from_fen(fen)
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?,
+                None => (Board::new(), Color::White),
+            };
 
-                let mut mv = match board.get_legal_move(color, from, to) {
-                    Some(mv) => mv,
+            let start = std::time::Instant::now();
+            let nodes = board.perft_fast_with_hash(color, depth, options.hash_mb);
+            println!("Nodes: {}", nodes);
+            println!("Time (ms): {}", start.elapsed().as_millis());
+            Ok(())
+        }
+        Some("annotate") => {
+            let (path, depth) = match (args.get(1), args.get(2).map(|depth| depth.parse())) {
+                (Some(path), None) => (path, mxchess::annotate::DEFAULT_DEPTH),
+                (Some(path), Some(Ok(depth))) => (path, depth),
+                _ => {
+                    eprintln!("Usage: mxchess annotate <pgn> [depth]");
+                    std::process::exit(2);
+                }
+            };
+            let pgn = std::fs::read_to_string(path)?;
+            let games = mxchess::pgn::parse(&pgn)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            for (i, game) in games.iter().enumerate() {
+                let replay = match game.mainline() {
+                    Some(replay) => replay,
                     None => {
-                        println!("This move is illegal");
+                        eprintln!("Game {} has an illegal move, skipping it", i + 1);
                         continue;
                     }
                 };
-                if mv.ty == MoveType::PawnQueenPromotion {
-                    print!("Choose pawn promotion (q,r,b,n): ");
-                    io::stdout().flush()?;
-
-                    buf.clear();
-                    io::stdin().read_line(&mut buf)?;
-                    buf.make_ascii_lowercase();
-
-                    mv.ty = match buf.as_str().trim() {
-                        "q" | "queen" => MoveType::PawnQueenPromotion,
-                        "r" | "rook" => MoveType::PawnRookPromotion,
-                        "b" | "bishop" => MoveType::PawnBishopPromotion,
-                        "n" | "knight" => MoveType::PawnKnightPromotion,
-                        _ => {
-                            println!("Bad promotion path");
-                            continue;
-                        }
+                let mut annotated = replay.annotate(depth);
+                annotated.tags = game.tags.clone();
+                annotated.result = game.result.clone();
+                println!("{}", annotated);
+            }
+            Ok(())
+        }
+        Some("blundercheck") => {
+            let parsed = (
+                args.get(1),
+                args.get(2)
+                    .map_or(Ok(mxchess::annotate::DEFAULT_DEPTH), |depth| depth.parse()),
+                args.get(3)
+                    .map_or(Ok(mxchess::annotate::DEFAULT_BLUNDER_THRESHOLD), |cp| {
+                        cp.parse()
+                    }),
+            );
+            let (path, depth, threshold) = match parsed {
+                (Some(path), Ok(depth), Ok(threshold)) => (path, depth, threshold),
+                _ => {
+                    eprintln!("Usage: mxchess blundercheck <pgn> [depth] [centipawns]");
+                    std::process::exit(2);
+                }
+            };
+            let pgn = std::fs::read_to_string(path)?;
+            let games = mxchess::pgn::parse(&pgn)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            for (i, game) in games.iter().enumerate() {
+                let replay = match game.mainline() {
+                    Some(replay) => replay,
+                    None => {
+                        eprintln!("Game {} has an illegal move, skipping it", i + 1);
+                        continue;
+                    }
+                };
+                let black_first = (replay.start().1 == Color::Black) as usize;
+                for blunder in replay.blunder_check(depth, threshold) {
+                    let (board, color) = Board::from_fen(&blunder.fen)
+                        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                    let dots = match color {
+                        Color::White => ".",
+                        Color::Black => "...",
                     };
+                    println!(
+                        "Game {}, {}{} {}: {} was better by {:.2}, {}",
+                        i + 1,
+                        (blunder.ply + black_first) / 2 + 1,
+                        dots,
+                        board.to_san(color, blunder.played),
+                        board.to_san(color, blunder.best),
+                        blunder.loss as f64 / 100.0,
+                        blunder.fen
+                    );
                 }
-                break mv;
-            },
-            Color::Black => bot.choose_move(&board, Color::Black).unwrap(),
-        };
+            }
+            Ok(())
+        }
+        Some("analyze-db") => {
+            const USAGE: &str = "Usage: mxchess analyze-db <pgn> [--depth <n>] [--jobs <n>]";
+            let path = match args.get(1) {
+                Some(path) => path,
+                None => {
+                    eprintln!("{}", USAGE);
+                    std::process::exit(2);
+                }
+            };
+            let (mut depth, mut jobs) = (mxchess::annotate::DEFAULT_DEPTH, 0);
+            for pair in args[2..].chunks(2) {
+                let parsed = match (pair[0].as_str(), pair.get(1)) {
+                    ("--depth", Some(n)) => n.parse().ok().map(|n| depth = n),
+                    ("--jobs", Some(n)) => n.parse().ok().map(|n| jobs = n),
+                    _ => None,
+                };
+                if parsed.is_none() {
+                    eprintln!("{}", USAGE);
+                    std::process::exit(2);
+                }
+            }
 
-        println!();
-        mv.print(&board);
-        println!();
+            let pgn = std::fs::read_to_string(path)?;
+            let pgn_games = mxchess::pgn::parse(&pgn)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            let mut sources = Vec::new();
+            let mut games = Vec::new();
+            for (i, game) in pgn_games.iter().enumerate() {
+                match game.mainline() {
+                    Some(replay) => {
+                        sources.push(game);
+                        games.push(replay);
+                    }
+                    None => eprintln!("Game {} has an illegal move, skipping it", i + 1),
+                }
+            }
+
+            // The annotated games go to the output and the summary to the errors, so that the
+            // output is a PGN file.
+            let mut losses = std::collections::BTreeMap::new();
+            let analyzed = mxchess::annotate::analyze_games(&games, depth, jobs);
+            for (source, (mut annotated, loss)) in sources.into_iter().zip(analyzed) {
+                for (tag, loss) in ["White", "Black"].iter().zip(loss) {
+                    let player = source.tag(tag).unwrap_or("?").to_owned();
+                    losses
+                        .entry(player)
+                        .or_insert_with(mxchess::annotate::CentipawnLoss::default)
+                        .add(loss);
+                }
+                annotated.tags = source.tags.clone();
+                annotated.result = source.result.clone();
+                println!("{}", annotated);
+            }
+            eprintln!("Average centipawn loss:");
+            for (player, loss) in losses {
+                if let Some(average) = loss.average() {
+                    eprintln!("{}: {:.1} in {} moves", player, average, loss.moves);
+                }
+            }
+            Ok(())
+        }
+        Some("control") => {
+            let (board, color) = match args.get(1) {
+                Some(fen) => Board::from_fen(fen)
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?,
+                None => (Board::new(), Color::White),
+            };
+            board.print(color);
+            print!("{}", board.render_control_map(color));
+            Ok(())
+        }
+        Some("edit") => {
+            let (board, color) = match args.get(1) {
+                Some(fen) => Board::from_fen(fen)
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?,
+                None => (Board::with_variant(options.variant), Color::White),
+            };
+            match mxchess::editor::edit(board, color, io::stdin().lock(), io::stdout())? {
+                Some((board, color, Finish::Play)) => play(
+                    Opponent::Bot,
+                    seed,
+                    None,
+                    Start::Position(board, color),
+                    Adjudication::NONE,
+                    &mut options,
+                    &mut input::stdin(),
+                ),
+                Some((board, color, Finish::Analyze)) => {
+                    let limits = SearchLimits::default();
+                    let result = options
+                        .bot(Bot::DEFAULT_DEPTH)
+                        .best_move(&board, color, &limits);
+                    let mut line = Vec::new();
+                    let (mut board, mut turn) = (board, color);
+                    for &mv in &result.pv {
+                        line.push(board.to_san(turn, mv));
+                        board.perform_move_unchecked(mv);
+                        turn = turn.inv();
+                    }
+                    match result.score {
+                        Some(score) => println!("Score: {} for {:?}", score, color),
+                        None => println!("No legal move"),
+                    }
+                    if !line.is_empty() {
+                        println!("Line: {}", line.join(" "));
+                    }
+                    Ok(())
+                }
+                None => Ok(()),
+            }
+        }
+        Some("render") => {
+            const USAGE: &str =
+                "Usage: mxchess render [fen] [out=<file.svg|file.png>] [flip|auto] \
+                [last=<from><to>] [arrow=<from><to>]... [circle=<square>]... [insights]";
+            let squares = |s: &str| {
+                let (from, to) = (s.get(..2)?, s.get(2..)?);
+                Some((from.parse().ok()?, to.parse().ok()?))
+            };
+            let (mut board, mut color) = (Board::new(), Color::White);
+            let mut insights = false;
+            let mut svg = mxchess::render::SvgOptions::default();
+            let mut out = None;
+            for arg in &args[1..] {
+                let parsed = match arg.split_once('=') {
+                    Some(("out", path)) => {
+                        out = Some(path);
+                        Some(())
+                    }
+                    Some(("last", mv)) => squares(mv).map(|mv| svg.last_move = Some(mv)),
+                    Some(("arrow", mv)) => {
+                        squares(mv).map(|(from, to)| svg.annotations.arrow(from, to, Brush::Green))
+                    }
+                    Some(("circle", square)) => square
+                        .parse()
+                        .ok()
+                        .map(|square| svg.annotations.circle(square, Brush::Blue)),
+                    Some(_) => None,
+                    None if arg == "flip" => {
+                        svg.perspective = Perspective::Black;
+                        Some(())
+                    }
+                    None if arg == "auto" => {
+                        svg.perspective = Perspective::Auto;
+                        Some(())
+                    }
+                    None if arg == "insights" => {
+                        insights = true;
+                        Some(())
+                    }
+                    None => Board::from_fen(arg).ok().map(|fen| (board, color) = fen),
+                };
+                if parsed.is_none() {
+                    eprintln!("{}", USAGE);
+                    std::process::exit(2);
+                }
+            }
+            svg.to_move = color;
+            if insights {
+                let best_move = options.bot(Bot::DEFAULT_DEPTH).choose_move(&board, color);
+                let analysis = Annotations::from_analysis(&board, color, best_move);
+                svg.annotations.marks.extend(analysis.marks);
+            }
+            match out {
+                #[cfg(feature = "png")]
+                Some(path) if path.ends_with(".png") => std::fs::write(
+                    path,
+                    mxchess::render::to_png(&board, &svg)
+                        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?,
+                ),
+                Some(path) => std::fs::write(path, mxchess::render::to_svg(&board, &svg)),
+                None => {
+                    println!("{}", mxchess::render::to_svg(&board, &svg));
+                    Ok(())
+                }
+            }
+        }
+        #[cfg(feature = "animation")]
+        Some("animate") => {
+            const USAGE: &str = "Usage: mxchess animate <pgn> <gif> [game=<n>] [delay=<ms>] [flip]";
+            let (pgn_path, gif_path) = match (args.get(1), args.get(2)) {
+                (Some(pgn), Some(gif)) => (pgn, gif),
+                _ => {
+                    eprintln!("{}", USAGE);
+                    std::process::exit(2);
+                }
+            };
+            let mut options = mxchess::render::AnimationOptions::default();
+            let mut number = 1;
+            for arg in &args[3..] {
+                let parsed = match arg.split_once('=') {
+                    Some(("game", n)) => n.parse().ok().filter(|&n| n != 0).map(|n| number = n),
+                    Some(("delay", ms)) => ms
+                        .parse()
+                        .ok()
+                        .map(|ms| options.delay = std::time::Duration::from_millis(ms)),
+                    Some(_) => None,
+                    None if arg == "flip" => {
+                        options.board.perspective = Perspective::Black;
+                        Some(())
+                    }
+                    None => None,
+                };
+                if parsed.is_none() {
+                    eprintln!("{}", USAGE);
+                    std::process::exit(2);
+                }
+            }
+            let games = mxchess::pgn::parse(&std::fs::read_to_string(pgn_path)?)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            let game = match games.get(number - 1).map(|game| game.mainline()) {
+                Some(Some(game)) => game,
+                Some(None) => {
+                    eprintln!("Game {} has an illegal move", number);
+                    std::process::exit(1);
+                }
+                None => {
+                    eprintln!("There are only {} games", games.len());
+                    std::process::exit(2);
+                }
+            };
+            game.to_animation(gif_path, &options)
+        }
+        Some("tree") => {
+            let depth = match args.get(1).map(|depth| depth.parse()) {
+                Some(Ok(depth)) => depth,
+                _ => {
+                    eprintln!("Usage: mxchess tree <depth> [fen] [json]");
+                    std::process::exit(2);
+                }
+            };
+            let (board, color) = match args.get(2).filter(|&fen| fen != "json") {
+                Some(fen) => Board::from_fen(fen)
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?,
+                None => (Board::new(), Color::White),
+            };
 
-        board.perform_move(mv);
+            let mut bot = Bot::with_depth(depth);
+            bot.options.tree_limit = TREE_LIMIT;
+            bot.choose_move(&board, color);
+            if let Some(tree) = bot.take_tree() {
+                if args.last().is_some_and(|arg| arg == "json") {
+                    println!("{}", tree.to_json());
+                } else {
+                    print!("{}", tree);
+                }
+            }
+            Ok(())
+        }
+        Some("train") => {
+            let path = match args.get(1) {
+                Some(path) => path,
+                None => {
+                    eprintln!("Usage: mxchess train <repertoire.pgn> [white | black]");
+                    std::process::exit(2);
+                }
+            };
+            let side = match args.get(2).map(|s| s.as_str()) {
+                None | Some("white") => Color::White,
+                Some("black") => Color::Black,
+                Some(side) => {
+                    eprintln!("Bad side: {}", side);
+                    std::process::exit(2);
+                }
+            };
 
-        color = color.inv();
+            let repertoire = mxchess::train::Repertoire::from_pgn(&std::fs::read_to_string(path)?)?;
+            println!("Seed: {}", seed);
+            mxchess::train::run(
+                &repertoire,
+                side,
+                &mut Rng::new(seed),
+                io::stdin().lock(),
+                io::stdout(),
+            )
+        }
+        Some("stats") => match Profile::default_path() {
+            Some(path) => {
+                print!("{}", Profile::load(&path)?);
+                Ok(())
+            }
+            None => {
+                eprintln!("No config directory for the profile");
+                std::process::exit(1);
+            }
+        },
+        Some("puzzle") => {
+            let path = match args.get(1) {
+                Some(path) => path,
+                None => {
+                    eprintln!("Usage: mxchess puzzle <puzzles.epd | puzzles.pgn | puzzles.csv>");
+                    std::process::exit(2);
+                }
+            };
+
+            let puzzles = mxchess::puzzle::load(&std::fs::read_to_string(path)?)?;
+            mxchess::puzzle::run(&puzzles, io::stdin().lock(), io::stdout())
+        }
+        Some("blindfold") => {
+            let mut bot = options.bot(Bot::DEFAULT_DEPTH);
+            mxchess::blindfold::play(&mut bot, io::stdin().lock(), io::stdout())
+        }
+        Some("coordinates") => {
+            let count = match args.get(1).map(|n| n.parse()) {
+                None => mxchess::blindfold::DEFAULT_DRILL_SQUARES,
+                Some(Ok(count)) => count,
+                Some(Err(_)) => {
+                    eprintln!("Usage: mxchess coordinates [squares]");
+                    std::process::exit(2);
+                }
+            };
+            let mut rng = Rng::new(seed);
+            mxchess::blindfold::drill(count, &mut rng, io::stdin().lock(), io::stdout())
+        }
+        Some("tune") => {
+            let (positions, params_path) = match (args.get(1), args.get(2)) {
+                (Some(positions), Some(params)) => (positions, params),
+                _ => {
+                    eprintln!(
+                        "Usage: mxchess tune <positions.epd | games.pgn> <params.txt> [iterations]"
+                    );
+                    std::process::exit(2);
+                }
+            };
+            let iterations = match args.get(3).map(|n| n.parse()) {
+                None => mxchess::tune::DEFAULT_ITERATIONS,
+                Some(Ok(iterations)) => iterations,
+                Some(Err(_)) => {
+                    eprintln!("Bad iteration count");
+                    std::process::exit(2);
+                }
+            };
+
+            // Continue from the previous run's parameters, if there was one.
+            let params = match std::fs::read_to_string(params_path) {
+                Ok(params) => params
+                    .parse()
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => Default::default(),
+                Err(err) => return Err(err),
+            };
+
+            let text = std::fs::read_to_string(positions)?;
+            let params = mxchess::tune::tune(&text, &params, iterations, io::stdout())?;
+            std::fs::write(params_path, params.to_string())?;
+            println!("Wrote the tuned parameters to {}", params_path);
+            Ok(())
+        }
+        Some("uci") => mxchess::uci::run(io::stdin().lock(), io::stdout(), options),
+        Some("xboard") => mxchess::xboard::run(io::stdin().lock(), io::stdout()),
+        Some("serve") => mxchess::server::run(
+            args.get(1)
+                .map_or(mxchess::server::DEFAULT_ADDR, |addr| addr.as_str()),
+        ),
+        #[cfg(feature = "tui")]
+        Some("tui") => {
+            use mxchess::tui::Mode;
+
+            // `tui [<minutes>+<increment>]` plays the bot, `tui analyze [fen]` analyzes.
+            let (mode, game, clock) = match args.get(1).map(|arg| arg.as_str()) {
+                Some("analyze") => {
+                    let game = match args.get(2) {
+                        Some(fen) => match Board::from_fen(fen) {
+                            Ok((board, color)) => Game::from_position(board, color),
+                            Err(err) => {
+                                eprintln!("{}", err);
+                                std::process::exit(2);
+                            }
+                        },
+                        None => Game::with_variant(options.variant),
+                    };
+                    (Mode::Analyze, game, None)
+                }
+                arg => match arg.map(Clock::parse) {
+                    None | Some(Some(_)) => (
+                        Mode::Play,
+                        Game::with_variant(options.variant),
+                        arg.and_then(Clock::parse),
+                    ),
+                    Some(None) => {
+                        eprintln!("Usage: mxchess tui [<minutes>+<increment> | analyze [fen]]");
+                        std::process::exit(2);
+                    }
+                },
+            };
+            let mut bot = options.bot(if clock.is_some() {
+                CLOCK_DEPTH
+            } else {
+                Bot::DEFAULT_DEPTH
+            });
+            mxchess::tui::run(mode, game, clock, &mut bot)
+        }
+        #[cfg(feature = "lichess")]
+        Some("lichess") => {
+            let token = match std::env::var("LICHESS_TOKEN") {
+                Ok(token) => token,
+                Err(_) => {
+                    eprintln!("Set LICHESS_TOKEN to the bot account's API token");
+                    std::process::exit(2);
+                }
+            };
+            mxchess::lichess::Lichess::connect(token)?.run()
+        }
+        Some(cmd) => {
+            eprintln!("Unknown command: {}", cmd);
+            eprintln!("Usage: mxchess [--seed <n>] [--log <spec>] [--hash <mb>] [--threads <n>] [--contempt <cp>] [--skill <0-20>] [--book <pgn>] [--experience <file>] [--variant <name>] [--<piece>-value <cp>] [play [mcts|human] [time] [odds=..] [listen=..] [resign=..] [draw=..] | two-player | demo | selfplay [games] [time] [resign=..] [draw=..] [params=..] [sprt=..] [book=..] [base=..] | tournament <engine>... [time] [games=..] [gauntlet] [resign=..] [draw=..] [book=..] [pgn=..] | bench [depth] [net] | perft <depth> [fen] | control [fen] | edit [fen] | render [fen] [out=..] [flip|auto] [last=..] [arrow=..] [circle=..] [insights] | annotate <pgn> [depth] | analyze-db <pgn> [--depth <n>] [--jobs <n>] | blundercheck <pgn> [depth] [centipawns] | tree <depth> [fen] [json] | serve [addr] | uci | xboard | train <pgn> [side] | puzzle <file> | blindfold | coordinates [squares] | stats | tune <positions> <params>]");
+            std::process::exit(2);
+        }
     }
 }
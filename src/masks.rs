@@ -0,0 +1,123 @@
+// Bitboards of the board's fixed regions: the files, the ranks, the square colors, the center and
+// the wings, and the squares between and along two squares, also as tables of every pair. The move
+// generation masks the shifted bitboards with them so that pieces don't wrap around the edges, e.g.
+// a step to the right is `bits << 1 & !FILE_A`, and resolves pins and checks with the tables.
+
+pub const FILE_A: u64 = 0x0101_0101_0101_0101;
+pub const FILE_B: u64 = FILE_A << 1;
+pub const FILE_C: u64 = FILE_A << 2;
+pub const FILE_D: u64 = FILE_A << 3;
+pub const FILE_E: u64 = FILE_A << 4;
+pub const FILE_F: u64 = FILE_A << 5;
+pub const FILE_G: u64 = FILE_A << 6;
+pub const FILE_H: u64 = FILE_A << 7;
+/// The files from a to h, indexed like `square & 7`.
+pub const FILES: [u64; 8] = [
+    FILE_A, FILE_B, FILE_C, FILE_D, FILE_E, FILE_F, FILE_G, FILE_H,
+];
+
+pub const RANK_1: u64 = 0xff;
+pub const RANK_2: u64 = RANK_1 << 0o10;
+pub const RANK_3: u64 = RANK_1 << 0o20;
+pub const RANK_4: u64 = RANK_1 << 0o30;
+pub const RANK_5: u64 = RANK_1 << 0o40;
+pub const RANK_6: u64 = RANK_1 << 0o50;
+pub const RANK_7: u64 = RANK_1 << 0o60;
+pub const RANK_8: u64 = RANK_1 << 0o70;
+/// The ranks from 1 to 8, indexed like `square / 8`.
+pub const RANKS: [u64; 8] = [
+    RANK_1, RANK_2, RANK_3, RANK_4, RANK_5, RANK_6, RANK_7, RANK_8,
+];
+
+/// h1 is a light square.
+pub const LIGHT_SQUARES: u64 = 0x55aa_55aa_55aa_55aa;
+pub const DARK_SQUARES: u64 = !LIGHT_SQUARES;
+/// d4, e4, d5 and e5.
+pub const CENTER: u64 = (FILE_D | FILE_E) & (RANK_4 | RANK_5);
+/// The files from e to h, where the king castles short.
+pub const KING_SIDE: u64 = FILE_E | FILE_F | FILE_G | FILE_H;
+/// The files from a to d, where the king castles long.
+pub const QUEEN_SIDE: u64 = !KING_SIDE;
+
+/// The file and rank steps from `a` towards `b`, `None` unless they share a file, a rank or a
+/// diagonal.
+const fn direction(a: u8, b: u8) -> Option<(i8, i8)> {
+    let files = (b & 7) as i8 - (a & 7) as i8;
+    let ranks = (b / 8) as i8 - (a / 8) as i8;
+    if a == b || (files != 0 && ranks != 0 && files.abs() != ranks.abs()) {
+        None
+    } else {
+        Some((files.signum(), ranks.signum()))
+    }
+}
+
+/// The squares strictly between `a` and `b` on their file, rank or diagonal, empty if they
+/// aren't on one. Blocking them blocks a slider on `a` from `b`.
+pub const fn between(a: u8, b: u8) -> u64 {
+    let (file_step, rank_step) = match direction(a, b) {
+        Some(step) => step,
+        None => return 0,
+    };
+    let mut mask = 0;
+    let (mut file, mut rank) = ((a & 7) as i8 + file_step, (a / 8) as i8 + rank_step);
+    while 8 * rank + file != b as i8 {
+        mask |= 1 << (8 * rank + file);
+        file += file_step;
+        rank += rank_step;
+    }
+    mask
+}
+
+/// The whole file, rank or diagonal through `a` and `b`, from edge to edge, empty if they aren't
+/// on one. A piece pinned on `a` along it can only move on it.
+pub const fn line(a: u8, b: u8) -> u64 {
+    let (file_step, rank_step) = match direction(a, b) {
+        Some(step) => step,
+        None => return 0,
+    };
+    let mut mask = 1 << a;
+    let mut sign = -1;
+    while sign <= 1 {
+        let (mut file, mut rank) = ((a & 7) as i8, (a / 8) as i8);
+        loop {
+            file += sign * file_step;
+            rank += sign * rank_step;
+            if file < 0 || 8 <= file || rank < 0 || 8 <= rank {
+                break;
+            }
+            mask |= 1 << (8 * rank + file);
+        }
+        sign += 2;
+    }
+    mask
+}
+
+/// `between(a, b)` for every pair of squares, indexed like `BETWEEN[a][b]`.
+pub static BETWEEN: [[u64; 64]; 64] = {
+    let mut table = [[0; 64]; 64];
+    let mut a = 0;
+    while a < 64 {
+        let mut b = 0;
+        while b < 64 {
+            table[a][b] = between(a as u8, b as u8);
+            b += 1;
+        }
+        a += 1;
+    }
+    table
+};
+
+/// `line(a, b)` for every pair of squares, indexed like `LINE[a][b]`.
+pub static LINE: [[u64; 64]; 64] = {
+    let mut table = [[0; 64]; 64];
+    let mut a = 0;
+    while a < 64 {
+        let mut b = 0;
+        while b < 64 {
+            table[a][b] = line(a as u8, b as u8);
+            b += 1;
+        }
+        a += 1;
+    }
+    table
+};
@@ -0,0 +1,112 @@
+// Material counting: how many pieces of every type the sides have, and the material's signature,
+// e.g. "KRPvKR", which classifies endgames and tells whether a tablebase covers the position.
+
+use crate::{Board, Color, PieceType, Pieces};
+use core::fmt;
+
+const LETTERS: [char; 6] = ['K', 'Q', 'R', 'B', 'N', 'P'];
+
+/// The pieces of every type a side has on the board, indexed by `PieceType as usize`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct MaterialCount(pub [u8; 6]);
+
+impl MaterialCount {
+    pub fn of(pieces: &Pieces) -> Self {
+        let mut count = [0; 6];
+        for (count, &ty) in count.iter_mut().zip(&PieceType::ALL) {
+            *count = pieces.get(ty).count_ones() as u8;
+        }
+        Self(count)
+    }
+
+    #[inline]
+    pub fn get(&self, ty: PieceType) -> u8 {
+        self.0[ty as usize]
+    }
+
+    /// All the pieces, the king included.
+    pub fn total(&self) -> u32 {
+        self.0.iter().map(|&count| count as u32).sum()
+    }
+
+    /// The queens, rooks, bishops and knights.
+    pub fn non_pawns(&self) -> u8 {
+        self.get(PieceType::Queen)
+            + self.get(PieceType::Rook)
+            + self.get(PieceType::Bishop)
+            + self.get(PieceType::Knight)
+    }
+
+    /// Whether the side has nothing but its king.
+    pub fn is_bare(&self) -> bool {
+        self.non_pawns() + self.get(PieceType::Pawn) == 0
+    }
+
+    /// Four bits per piece type but the king, from the queens' in the lowest bits up. The counts
+    /// saturate at 15, which only Horde's pawns reach.
+    pub fn key(&self) -> u32 {
+        self.0[1..].iter().enumerate().fold(0, |key, (i, &count)| {
+            key | (count.min(15) as u32) << (4 * i)
+        })
+    }
+}
+
+/// The pieces' letters from the king to the pawns, e.g. "KRP".
+impl fmt::Display for MaterialCount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (&letter, &count) in LETTERS.iter().zip(&self.0) {
+            for _ in 0..count {
+                write!(f, "{}", letter)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Both sides' material, displayed White's first, e.g. "KRPvKR".
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct MaterialSignature {
+    pub white: MaterialCount,
+    pub black: MaterialCount,
+}
+
+impl MaterialSignature {
+    /// White's key in the low half and Black's in the high half, see `MaterialCount::key()`.
+    pub fn key(&self) -> u64 {
+        self.white.key() as u64 | (self.black.key() as u64) << 32
+    }
+
+    /// All the pieces on the board, the kings included, e.g. to check that a tablebase of so many
+    /// pieces covers the position.
+    pub fn pieces(&self) -> u32 {
+        self.white.total() + self.black.total()
+    }
+
+    /// The signature with the colors swapped, "KRvKRP" for "KRPvKR".
+    pub fn mirror(&self) -> Self {
+        Self {
+            white: self.black,
+            black: self.white,
+        }
+    }
+}
+
+impl fmt::Display for MaterialSignature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}v{}", self.white, self.black)
+    }
+}
+
+impl Board {
+    /// The pieces of every type `color` has on the board, the Crazyhouse reserves aside.
+    pub fn material_count(&self, color: Color) -> MaterialCount {
+        MaterialCount::of(self.get_pieces(color))
+    }
+
+    pub fn material_signature(&self) -> MaterialSignature {
+        MaterialSignature {
+            white: self.material_count(Color::White),
+            black: self.material_count(Color::Black),
+        }
+    }
+}
@@ -0,0 +1,231 @@
+// Monte Carlo tree search (UCT), an alternative to the alpha-beta `Bot` for comparing search
+// paradigms on the same move generation. Every iteration walks down the tree by the UCB1 formula,
+// expands the leaf it reaches, estimates the leaf's value by a rollout and adds it to the nodes on
+// the way back up.
+
+use crate::{
+    eval::{EvalParams, Evaluator},
+    rng::Rng,
+    Board, Color, Move,
+};
+use std::ops::Range;
+
+pub const DEFAULT_ITERATIONS: u32 = 20_000;
+
+/// How a newly expanded leaf is valued.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rollout {
+    /// Random moves until the game ends or `plies` moves were played, then the evaluator.
+    Playout { plies: u32 },
+    /// The evaluator right away, which with `nnue::Nnue` makes it a network guided search.
+    Evaluation,
+}
+
+pub struct MctsBot<E: Evaluator = EvalParams> {
+    pub iterations: u32,
+    /// The UCB1 exploration constant, higher values favour the less visited moves.
+    pub exploration: f64,
+    pub rollout: Rollout,
+    pub evaluator: E,
+    /// Seeds the playouts' random moves, together with the position.
+    pub seed: u64,
+}
+
+struct Node {
+    mv: Move,
+    visits: u32,
+    /// The sum of the results for the side that played `mv`, 1 for a win and 0.5 for a draw.
+    score: f64,
+    /// The indices of the children, `None` until the node is expanded.
+    children: Option<Range<usize>>,
+}
+
+impl Node {
+    fn new(mv: Move) -> Self {
+        Self {
+            mv,
+            visits: 0,
+            score: 0.0,
+            children: None,
+        }
+    }
+}
+
+impl MctsBot {
+    pub fn new() -> Self {
+        Self::with_evaluator(EvalParams::default())
+    }
+}
+
+impl<E: Evaluator> MctsBot<E> {
+    pub fn with_evaluator(evaluator: E) -> Self {
+        Self {
+            iterations: DEFAULT_ITERATIONS,
+            exploration: 1.4,
+            rollout: Rollout::Evaluation,
+            evaluator,
+            seed: 0,
+        }
+    }
+
+    /// The evaluation as the probability that `color` wins.
+    fn win_probability(&self, board: &Board, color: Color) -> f64 {
+        let state = self.evaluator.init(board);
+        let value = self.evaluator.evaluate_for(board, &state, color) as f64;
+        1.0 / (1.0 + 10f64.powf(-value / 400.0))
+    }
+
+    /// The value of a leaf for `color`, to move.
+    fn rollout(&self, board: &Board, color: Color, rng: &mut Rng) -> f64 {
+        let plies = match self.rollout {
+            Rollout::Playout { plies } => plies,
+            Rollout::Evaluation => 0,
+        };
+
+        let (mut board, mut turn) = (*board, color);
+        for _ in 0..plies {
+            if let Some(winner) = board.variant_winner() {
+                return if winner == color { 1.0 } else { 0.0 };
+            }
+            let moves = board.moves(turn);
+            if moves.is_empty() {
+                return if !board.in_check(turn) {
+                    0.5
+                } else if turn == color {
+                    0.0
+                } else {
+                    1.0
+                };
+            }
+            board.perform_move_unchecked(moves[rng.below(moves.len())]);
+            turn = turn.inv();
+        }
+        self.win_probability(&board, color)
+    }
+
+    /// The child with the highest UCB1 value, unvisited children first.
+    fn select(&self, nodes: &[Node], parent: usize, children: Range<usize>) -> usize {
+        let log_visits = (nodes[parent].visits.max(1) as f64).ln();
+        let ucb = |node: &Node| match node.visits {
+            0 => f64::INFINITY,
+            visits => {
+                node.score / visits as f64 + self.exploration * (log_visits / visits as f64).sqrt()
+            }
+        };
+        children
+            .reduce(|best, i| {
+                if ucb(&nodes[best]) < ucb(&nodes[i]) {
+                    i
+                } else {
+                    best
+                }
+            })
+            .unwrap()
+    }
+
+    /// Runs one iteration from the root, `path` holds the hashes of the positions before the
+    /// root.
+    fn iterate(
+        &self,
+        nodes: &mut Vec<Node>,
+        board: &Board,
+        color: Color,
+        path: &mut Vec<u64>,
+        rng: &mut Rng,
+    ) {
+        let (mut board, mut color) = (*board, color);
+        let mut visited = vec![0];
+        let root = path.len();
+
+        // The result for `color`, to move at the last visited node.
+        let result = loop {
+            let node = *visited.last().unwrap();
+
+            // A repetition is a draw, like in `Bot`.
+            let hash = board.position_key(color);
+            if node != 0
+                && path
+                    .iter()
+                    .rev()
+                    .skip(1)
+                    .step_by(2)
+                    .any(|&prev| prev == hash)
+            {
+                break 0.5;
+            }
+            path.push(hash);
+            if let Some(winner) = board.variant_winner() {
+                break if winner == color { 1.0 } else { 0.0 };
+            }
+
+            let children = match nodes[node].children.clone() {
+                Some(children) => children,
+                None => {
+                    let start = nodes.len();
+                    nodes.extend(board.moves(color).into_iter().map(Node::new));
+                    nodes[node].children = Some(start..nodes.len());
+                    if node != 0 && start < nodes.len() {
+                        break self.rollout(&board, color, rng);
+                    }
+                    start..nodes.len()
+                }
+            };
+            if children.is_empty() {
+                break if board.in_check(color) { 0.0 } else { 0.5 };
+            }
+
+            let child = self.select(nodes, node, children);
+            board.perform_move_unchecked(nodes[child].mv);
+            color = color.inv();
+            visited.push(child);
+        };
+        path.truncate(root);
+
+        let mut reward = 1.0 - result;
+        for &node in visited.iter().rev() {
+            nodes[node].visits += 1;
+            nodes[node].score += reward;
+            reward = 1.0 - reward;
+        }
+    }
+
+    /// Fails if there's no legal move or the board is invalid (see `Board::validate()`).
+    pub fn choose_move(&self, board: &Board, color: Color) -> Option<Move> {
+        self.choose_move_with_history(board, color, &[])
+    }
+
+    /// Like `choose_move()`, but repeating a position of `history` (see `Game::history()`) counts
+    /// as a draw.
+    pub fn choose_move_with_history(
+        &self,
+        board: &Board,
+        color: Color,
+        history: &[u64],
+    ) -> Option<Move> {
+        if let Err(err) = board.validate() {
+            log::warn!("Not searching an invalid board: {}", err);
+            return None;
+        }
+        let mut nodes = vec![Node::new(Move::NULL)];
+        let mut path = history.to_vec();
+        // Seeded by the position too, so the same position gets the same move.
+        let mut rng = Rng::new(self.seed ^ board.zobrist(color));
+
+        for _ in 0..self.iterations {
+            self.iterate(&mut nodes, board, color, &mut path, &mut rng);
+        }
+
+        // The most visited move is the most robust choice.
+        let children = nodes[0].children.clone()?;
+        nodes[children]
+            .iter()
+            .max_by_key(|node| node.visits)
+            .map(|node| node.mv)
+    }
+}
+
+impl Default for MctsBot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,204 @@
+// A small NNUE-style network: 768 one-hot piece-square inputs (from White's side), one
+// ClippedReLU hidden layer that's kept as an accumulator and updated incrementally, and a linear
+// output. The network file is little-endian:
+//
+//   b"MXNN"
+//   u32               hidden size H, which must be `HIDDEN`
+//   i16[768 * H]      input weights, input-major, input = color * 384 + piece type * 64 + square
+//   i16[H]            hidden biases
+//   i16[H]            output weights
+//   i32               output bias
+//
+// The hidden layer is quantized by `QA` and the output weights by `QB`. Its size is fixed, so
+// that the accumulator is an array the search copies from node to node rather than allocates.
+
+use crate::{board::Castle, eval::Evaluator, BitIterator, Board, Color, MoveType, PieceType};
+use alloc::vec::Vec;
+use core::fmt;
+
+const INPUTS: usize = 768;
+/// The size of the hidden layer.
+pub const HIDDEN: usize = 256;
+const QA: i32 = 255;
+const QB: i32 = 64;
+/// Converts the output to centipawns.
+const SCALE: i32 = 400;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct NnueError(pub &'static str);
+
+impl fmt::Display for NnueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Bad network: {}", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for NnueError {}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Nnue {
+    input_weights: Vec<i16>,
+    hidden_biases: Vec<i16>,
+    output_weights: Vec<i16>,
+    output_bias: i32,
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], NnueError> {
+        if self.0.len() < n {
+            return Err(NnueError("truncated"));
+        }
+        let (taken, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn i16s(&mut self, n: usize) -> Result<Vec<i16>, NnueError> {
+        let bytes = self.take(2 * n)?;
+        Ok(bytes
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]))
+            .collect())
+    }
+
+    fn u32(&mut self) -> Result<u32, NnueError> {
+        let b = self.take(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+}
+
+impl Nnue {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, NnueError> {
+        let mut reader = Reader(bytes);
+        if reader.take(4)? != b"MXNN" {
+            return Err(NnueError("bad magic"));
+        }
+        if reader.u32()? as usize != HIDDEN {
+            return Err(NnueError("bad hidden size"));
+        }
+
+        let nnue = Self {
+            input_weights: reader.i16s(INPUTS * HIDDEN)?,
+            hidden_biases: reader.i16s(HIDDEN)?,
+            output_weights: reader.i16s(HIDDEN)?,
+            output_bias: reader.u32()? as i32,
+        };
+        if !reader.0.is_empty() {
+            return Err(NnueError("trailing data"));
+        }
+        Ok(nnue)
+    }
+
+    /// Writes the network in the format `from_bytes()` reads.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"MXNN");
+        bytes.extend_from_slice(&(HIDDEN as u32).to_le_bytes());
+        for weights in [
+            &self.input_weights,
+            &self.hidden_biases,
+            &self.output_weights,
+        ] {
+            for weight in weights.iter() {
+                bytes.extend_from_slice(&weight.to_le_bytes());
+            }
+        }
+        bytes.extend_from_slice(&self.output_bias.to_le_bytes());
+        bytes
+    }
+
+    fn add_input(&self, accumulator: &mut [i32; HIDDEN], input: usize, sign: i32) {
+        let weights = &self.input_weights[input * HIDDEN..(input + 1) * HIDDEN];
+        for (acc, &weight) in accumulator.iter_mut().zip(weights) {
+            *acc += sign * weight as i32;
+        }
+    }
+}
+
+/// The input of a piece of `color` and type `ty` on `square`.
+fn input(color: Color, ty: PieceType, square: u8) -> usize {
+    color as usize * 384 + ty as usize * 64 + square as usize
+}
+
+impl Evaluator for Nnue {
+    /// The hidden layer before the activation.
+    type State = [i32; HIDDEN];
+
+    fn init(&self, board: &Board) -> [i32; HIDDEN] {
+        let mut accumulator = [0; HIDDEN];
+        for (acc, &bias) in accumulator.iter_mut().zip(&self.hidden_biases) {
+            *acc = bias as i32;
+        }
+        for color in [Color::White, Color::Black] {
+            let pieces = board.get_pieces(color);
+            for ty in PieceType::ALL {
+                for bit in BitIterator(pieces.get(ty)) {
+                    let input = input(color, ty, bit.trailing_zeros() as u8);
+                    self.add_input(&mut accumulator, input, 1);
+                }
+            }
+        }
+        accumulator
+    }
+
+    /// Touches only the inputs of the move's pieces: the moved one, the captured one and, when
+    /// castling, the rook.
+    fn update(&self, accumulator: &mut [i32; HIDDEN], before: &Board, after: &Board) {
+        let mv = after.prev_move;
+        let color = match before.white_pieces.all & 1 << mv.from != 0 {
+            true => Color::White,
+            false => Color::Black,
+        };
+        if let Some(ty) = mv.ty.dropped() {
+            self.add_input(accumulator, input(color, ty, mv.to), 1);
+            return;
+        }
+
+        let moved = match before.get_at(1 << mv.from) {
+            Some(moved) => moved,
+            None => return,
+        };
+        self.add_input(accumulator, input(color, moved.ty, mv.from), -1);
+        self.add_input(accumulator, input(color, mv.ty.piece(), mv.to), 1);
+        match mv.ty {
+            MoveType::PawnEnPassant => {
+                let square = match color {
+                    Color::White => mv.to - 0o10,
+                    Color::Black => mv.to + 0o10,
+                };
+                self.add_input(accumulator, input(color.inv(), PieceType::Pawn, square), -1);
+            }
+            MoveType::Castle => {
+                if let Some(castle) = Castle::to(mv.to) {
+                    self.add_input(
+                        accumulator,
+                        input(color, PieceType::Rook, castle.rook_from),
+                        -1,
+                    );
+                    self.add_input(
+                        accumulator,
+                        input(color, PieceType::Rook, castle.rook_to),
+                        1,
+                    );
+                }
+            }
+            _ => {
+                if let Some(captured) = before.get_at(1 << mv.to) {
+                    self.add_input(accumulator, input(captured.color, captured.ty, mv.to), -1);
+                }
+            }
+        }
+    }
+
+    fn evaluate(&self, _board: &Board, accumulator: &[i32; HIDDEN]) -> i32 {
+        let output: i64 = accumulator
+            .iter()
+            .zip(&self.output_weights)
+            .map(|(&acc, &weight)| acc.clamp(0, QA) as i64 * weight as i64)
+            .sum();
+        ((output + self.output_bias as i64) * SCALE as i64 / (QA * QB) as i64) as i32
+    }
+}
@@ -0,0 +1,90 @@
+// Material odds: handicap games from the standard starting position with one of Black's pieces
+// taken off, so that a human playing White can face the bot at its full strength. They're named
+// like in the PGN `Odds` tag, e.g. "queen".
+
+use crate::Board;
+use core::{fmt, str::FromStr};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum Odds {
+    /// Black plays without the f7 pawn.
+    PawnOdds,
+    /// Black plays without the b8 knight.
+    KnightOdds,
+    /// Black plays without the a8 rook, and so without castling queenside.
+    RookOdds,
+    /// Black plays without the queen.
+    QueenOdds,
+}
+
+impl Odds {
+    pub const ALL: [Self; 4] = [
+        Self::PawnOdds,
+        Self::KnightOdds,
+        Self::RookOdds,
+        Self::QueenOdds,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::PawnOdds => "pawn",
+            Self::KnightOdds => "knight",
+            Self::RookOdds => "rook",
+            Self::QueenOdds => "queen",
+        }
+    }
+
+    /// The square of the piece Black plays without.
+    pub fn square(self) -> u8 {
+        match self {
+            Self::PawnOdds => 0o65,
+            Self::KnightOdds => 0o71,
+            Self::RookOdds => 0o70,
+            Self::QueenOdds => 0o73,
+        }
+    }
+}
+
+impl fmt::Display for Odds {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The name isn't a known handicap's.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct UnknownOdds;
+
+impl fmt::Display for UnknownOdds {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unknown odds")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnknownOdds {}
+
+impl FromStr for Odds {
+    type Err = UnknownOdds;
+
+    /// Case insensitive, the piece's name or its letter.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "pawn" | "p" => Ok(Self::PawnOdds),
+            "knight" | "n" => Ok(Self::KnightOdds),
+            "rook" | "r" => Ok(Self::RookOdds),
+            "queen" | "q" => Ok(Self::QueenOdds),
+            _ => Err(UnknownOdds),
+        }
+    }
+}
+
+impl Board {
+    /// The standard starting position without the piece `odds` takes off.
+    pub fn with_odds(odds: Odds) -> Self {
+        let mut board = Self::new();
+        board.set(1 << odds.square(), None);
+        debug_assert_eq!(board.validate(), Ok(()));
+        board
+    }
+}
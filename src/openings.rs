@@ -0,0 +1,54 @@
+// Opening suites for matches: start positions from EPD lines (`<board> <side> <castling> <ep>`,
+// further fields are ignored) or from PGN games, whose main line is played from the start or from
+// the `FEN` tag.
+
+use crate::{pgn, Board, Game};
+use std::io;
+
+fn invalid_data(msg: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+/// Replays a PGN game's main line, from its `FEN` tag if it has one.
+fn from_pgn(game: &pgn::PgnGame) -> io::Result<Game> {
+    let mut replay = match game.tag("FEN") {
+        Some(fen) => {
+            let (board, turn) = Board::from_fen(fen).map_err(invalid_data)?;
+            Game::from_position(board, turn)
+        }
+        None => Game::new(),
+    };
+    for mv in &game.moves {
+        let parsed = replay
+            .board()
+            .parse_san(replay.turn(), &mv.san)
+            .ok_or_else(|| invalid_data(format!("Illegal opening move: {}", mv.san)))?;
+        replay.push_move(parsed);
+    }
+    Ok(replay)
+}
+
+/// Loads the openings of an EPD or PGN file.
+pub fn load(text: &str) -> io::Result<Vec<Game>> {
+    if text.trim_start().starts_with('[') {
+        return pgn::parse(text)
+            .map_err(invalid_data)?
+            .iter()
+            .map(from_pgn)
+            .collect();
+    }
+
+    let mut openings = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<_> = line.split_whitespace().collect();
+        let (board, turn) = Board::from_fen(&fields[..4.min(fields.len())].join(" "))
+            .map_err(|err| invalid_data(format!("Line {}: {}", i + 1, err)))?;
+        openings.push(Game::from_position(board, turn));
+    }
+    Ok(openings)
+}
@@ -0,0 +1,275 @@
+// The engine's runtime options, shared by UCI's setoption and the command line's flags. They're
+// set by their UCI names, e.g. `options.set("Hash", "64")`.
+
+use crate::{
+    eval::EvalParams, experience::Experience, train::Repertoire, tt::TranspositionTable, Bot,
+    Color, Game, Move, PieceType, SearchOptions, Variant,
+};
+use std::{io, path::PathBuf, sync::Arc};
+
+/// The size of the bots' transposition table in MiB when none is set.
+pub const DEFAULT_HASH_MB: usize = 16;
+/// The largest hash size the options take, in MiB.
+const MAX_HASH_MB: usize = 65536;
+
+/// Full strength, lower skill levels limit the search's depth.
+pub const MAX_SKILL: u32 = 20;
+
+/// The size of the evaluation cache in MiB, a small one holds the evaluations of the positions
+/// that come up again and again in a search.
+const EVAL_CACHE_MB: usize = 1;
+
+/// The options that set the evaluation's piece values in centipawns, in both game phases.
+const PIECE_VALUES: [(&str, PieceType); 5] = [
+    ("QueenValue", PieceType::Queen),
+    ("RookValue", PieceType::Rook),
+    ("BishopValue", PieceType::Bishop),
+    ("KnightValue", PieceType::Knight),
+    ("PawnValue", PieceType::Pawn),
+];
+/// The largest piece value the options take.
+const MAX_PIECE_VALUE: i32 = 10_000;
+
+#[derive(Clone)]
+pub struct EngineOptions {
+    /// The size of the hash tables in MiB, the bots' shared transposition table's and
+    /// `perft_fast()`'s. Setting it through `set()` makes a new table.
+    pub hash_mb: usize,
+    table: Arc<TranspositionTable>,
+    /// The bots' evaluation, the piece values can be set through `set()`.
+    eval: EvalParams,
+    /// The bots' shared cache of static evaluations, see `Bot::eval_cache`. Setting a piece value
+    /// makes a new one.
+    eval_cache: Arc<TranspositionTable>,
+    /// The searches' threads, 0 for one per core. Setting it through `set()` starts a thread pool
+    /// of that many for `install()`.
+    pub threads: usize,
+    #[cfg(feature = "parallel")]
+    pool: Option<Arc<rayon::ThreadPool>>,
+    /// See `SearchOptions::contempt`.
+    pub contempt: i32,
+    /// From 0 to `MAX_SKILL`.
+    pub skill: u32,
+    book_path: Option<PathBuf>,
+    book: Option<Repertoire>,
+    experience_path: Option<PathBuf>,
+    experience: Option<Arc<Experience>>,
+    /// The rules of the games that start from "startpos".
+    pub variant: Variant,
+}
+
+impl Default for EngineOptions {
+    fn default() -> Self {
+        Self {
+            hash_mb: DEFAULT_HASH_MB,
+            table: Arc::new(TranspositionTable::new(DEFAULT_HASH_MB)),
+            eval: EvalParams::default(),
+            eval_cache: Arc::new(TranspositionTable::new(EVAL_CACHE_MB)),
+            threads: 0,
+            #[cfg(feature = "parallel")]
+            pool: None,
+            contempt: 0,
+            skill: MAX_SKILL,
+            book_path: None,
+            book: None,
+            experience_path: None,
+            experience: None,
+            variant: Variant::Standard,
+        }
+    }
+}
+
+fn invalid_input(msg: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg.to_string())
+}
+
+fn parse<T: std::str::FromStr>(name: &str, value: &str) -> io::Result<T> {
+    value
+        .parse()
+        .map_err(|_| invalid_input(format!("Bad value for {}: {}", name, value)))
+}
+
+impl EngineOptions {
+    /// The UCI declarations of the options, the lines of the answer to "uci".
+    pub fn uci_options(&self) -> Vec<String> {
+        let mut options = vec![
+            format!(
+                "option name Hash type spin default {} min 1 max {}",
+                self.hash_mb, MAX_HASH_MB
+            ),
+            format!(
+                "option name Threads type spin default {} min 0 max 1024",
+                self.threads
+            ),
+            format!(
+                "option name Contempt type spin default {} min -1000 max 1000",
+                self.contempt
+            ),
+            format!(
+                "option name Skill Level type spin default {} min 0 max {}",
+                self.skill, MAX_SKILL
+            ),
+            format!(
+                "option name BookFile type string default {}",
+                self.book_path
+                    .as_ref()
+                    .map_or("<empty>".into(), |path| path.display().to_string())
+            ),
+            format!(
+                "option name ExperienceFile type string default {}",
+                self.experience_path
+                    .as_ref()
+                    .map_or("<empty>".into(), |path| path.display().to_string())
+            ),
+            Variant::ALL.iter().fold(
+                format!(
+                    "option name UCI_Variant type combo default {}",
+                    self.variant
+                ),
+                |option, variant| option + " var " + variant.name(),
+            ),
+        ];
+        options.extend(PIECE_VALUES.iter().map(|&(name, ty)| {
+            format!(
+                "option name {} type spin default {} min 0 max {}",
+                name, self.eval.middlegame.material[ty as usize], MAX_PIECE_VALUE
+            )
+        }));
+        options
+    }
+
+    /// Sets the option called `name` in UCI, "<empty>" or "" clear the paths. Setting the book
+    /// loads it, and so does setting the experience file unless it doesn't exist yet.
+    pub fn set(&mut self, name: &str, value: &str) -> io::Result<()> {
+        let path = match value {
+            "" | "<empty>" => None,
+            path => Some(PathBuf::from(path)),
+        };
+        match name {
+            "Hash" => match parse(name, value)? {
+                hash_mb @ 1..=MAX_HASH_MB => {
+                    self.hash_mb = hash_mb;
+                    self.table = Arc::new(TranspositionTable::new(hash_mb));
+                }
+                _ => return Err(invalid_input(format!("The hash is 1-{} MiB", MAX_HASH_MB))),
+            },
+            "Threads" => {
+                self.threads = parse(name, value)?;
+                #[cfg(feature = "parallel")]
+                {
+                    self.pool = match self.threads {
+                        0 => None,
+                        threads => {
+                            match rayon::ThreadPoolBuilder::new().num_threads(threads).build() {
+                                Ok(pool) => Some(Arc::new(pool)),
+                                Err(err) => return Err(invalid_input(err)),
+                            }
+                        }
+                    };
+                }
+            }
+            "Contempt" => self.contempt = parse(name, value)?,
+            "Skill Level" => match parse(name, value)? {
+                skill @ 0..=MAX_SKILL => self.skill = skill,
+                _ => return Err(invalid_input(format!("The skill level is 0-{}", MAX_SKILL))),
+            },
+            "BookFile" => {
+                self.book = match &path {
+                    Some(path) => Some(Repertoire::from_pgn(&std::fs::read_to_string(path)?)?),
+                    None => None,
+                };
+                self.book_path = path;
+            }
+            "ExperienceFile" => {
+                self.experience = match &path {
+                    Some(path) if path.exists() => Some(Arc::new(
+                        std::fs::read_to_string(path)?
+                            .parse()
+                            .map_err(invalid_input)?,
+                    )),
+                    Some(_) => Some(Arc::default()),
+                    None => None,
+                };
+                self.experience_path = path;
+            }
+            "UCI_Variant" => self.variant = parse(name, value)?,
+            _ => match PIECE_VALUES.iter().find(|&&(option, _)| option == name) {
+                Some(&(_, ty)) => match parse(name, value)? {
+                    value @ 0..=MAX_PIECE_VALUE => {
+                        self.eval.middlegame.material[ty as usize] = value;
+                        self.eval.endgame.material[ty as usize] = value;
+                        self.eval_cache = Arc::new(TranspositionTable::new(EVAL_CACHE_MB));
+                    }
+                    _ => {
+                        return Err(invalid_input(format!(
+                            "The piece values are 0-{}",
+                            MAX_PIECE_VALUE
+                        )))
+                    }
+                },
+                None => return Err(invalid_input(format!("No such option: {}", name))),
+            },
+        }
+        Ok(())
+    }
+
+    /// The depth the skill level allows, `None` at full strength.
+    pub fn max_depth(&self) -> Option<u32> {
+        (self.skill < MAX_SKILL).then(|| 1 + self.skill / 4)
+    }
+
+    /// The transposition table the bots share.
+    pub fn table(&self) -> &TranspositionTable {
+        &self.table
+    }
+
+    /// The bots' evaluation.
+    pub fn eval(&self) -> &EvalParams {
+        &self.eval
+    }
+
+    /// A bot searching to `depth`, or less if the skill level says so.
+    pub fn bot(&self, depth: u32) -> Bot {
+        let mut bot = Bot::with_evaluator(
+            self.max_depth().map_or(depth, |max| max.min(depth)),
+            self.eval.clone(),
+        );
+        bot.options = SearchOptions {
+            contempt: self.contempt,
+            ..SearchOptions::default()
+        };
+        bot.experience = self.experience.clone();
+        bot.table = Some(self.table.clone());
+        bot.eval_cache = Some(self.eval_cache.clone());
+        bot
+    }
+
+    /// The book's moves in the game's position.
+    pub fn book_moves(&self, game: &Game) -> &[Move] {
+        self.book.as_ref().map_or(&[], |book| book.moves(game))
+    }
+
+    /// Learns a finished game, see `Experience::learn()`, and saves the experience file. Does
+    /// nothing without one.
+    pub fn learn(
+        &mut self,
+        searched: &[(u64, Color, Move, i32)],
+        winner: Option<Color>,
+    ) -> io::Result<()> {
+        if let (Some(path), Some(experience)) = (&self.experience_path, &mut self.experience) {
+            let experience = Arc::make_mut(experience);
+            experience.learn(searched, winner);
+            std::fs::write(path, experience.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Runs `f` on the configured number of threads.
+    pub fn install<R: Send>(&self, f: impl FnOnce() -> R + Send) -> R {
+        #[cfg(feature = "parallel")]
+        if let Some(pool) = &self.pool {
+            return pool.install(f);
+        }
+        f()
+    }
+}
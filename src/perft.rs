@@ -0,0 +1,83 @@
+// A faster perft for validating the move generation at depths 7-8: the root moves are spread over
+// the threads and the subtrees' counts are shared through the lockless transposition table.
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::{board::promotion_choices, tt::TranspositionTable, Board, Color};
+
+/// The table's size in MiB when none is given.
+pub const DEFAULT_HASH_MB: usize = 16;
+
+/// The data of the table's entries is the count shifted left by 8 and the depth.
+fn probe(table: &TranspositionTable, hash: u64, depth: u32) -> Option<u64> {
+    table
+        .probe(hash)
+        .filter(|data| data & 0xff == depth as u64)
+        .map(|data| data >> 8)
+}
+
+fn perft_rec(board: &Board, color: Color, depth: u32, table: &TranspositionTable) -> u64 {
+    match depth {
+        0 => return 1,
+        1 => {
+            return board
+                .moves(color)
+                .into_iter()
+                .map(|mv| promotion_choices(mv).count() as u64)
+                .sum()
+        }
+        _ => {}
+    }
+
+    let hash = board.position_key(color);
+    if let Some(count) = probe(table, hash, depth) {
+        return count;
+    }
+
+    let count = board
+        .moves(color)
+        .into_iter()
+        .flat_map(promotion_choices)
+        .map(|mv| {
+            let mut board = *board;
+            board.perform_move_unchecked(mv);
+            perft_rec(&board, color.inv(), depth - 1, table)
+        })
+        .sum();
+    table.store(hash, count << 8 | depth as u64);
+    count
+}
+
+impl Board {
+    /// The same as `perft()`, but parallel and with a transposition table.
+    pub fn perft_fast(&self, color: Color, depth: u32) -> u64 {
+        self.perft_fast_with_hash(color, depth, DEFAULT_HASH_MB)
+    }
+
+    /// Like `perft_fast()`, with a `hash_mb` MiB transposition table.
+    pub fn perft_fast_with_hash(&self, color: Color, depth: u32, hash_mb: usize) -> u64 {
+        if depth <= 1 {
+            return self.perft(color, depth);
+        }
+        let table = TranspositionTable::new(hash_mb);
+
+        let moves: Vec<_> = self
+            .moves(color)
+            .into_iter()
+            .flat_map(promotion_choices)
+            .collect();
+        #[cfg(feature = "parallel")]
+        let moves = moves.into_par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let moves = moves.into_iter();
+
+        moves
+            .map(|mv| {
+                let mut board = *self;
+                board.perform_move_unchecked(mv);
+                perft_rec(&board, color.inv(), depth - 1, &table)
+            })
+            .sum()
+    }
+}
@@ -0,0 +1,392 @@
+// Portable Game Notation, with comments, NAGs and (recursive) variations.
+
+use crate::{Board, Color, Game, Variant};
+use alloc::{
+    borrow::ToOwned,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct PgnMove {
+    pub san: String,
+    /// Numeric Annotation Glyphs, e.g. 2 for "?".
+    pub nags: Vec<u8>,
+    /// The comment after the move.
+    pub comment: Option<String>,
+    /// Alternatives to this move.
+    pub variations: Vec<Vec<PgnMove>>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<PgnMove>,
+    /// "1-0", "0-1", "1/2-1/2" or "*".
+    pub result: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PgnError {
+    pub line: usize,
+    pub msg: &'static str,
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PGN error at line {}: {}", self.line, self.msg)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PgnError {}
+
+/// Deeper variations are rejected rather than overflowing the stack.
+const MAX_VARIATION_DEPTH: usize = 64;
+
+struct Parser<'a> {
+    s: &'a str,
+    line: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, msg: &'static str) -> PgnError {
+        PgnError {
+            line: self.line,
+            msg,
+        }
+    }
+
+    fn advance(&mut self, n: usize) -> &'a str {
+        let (taken, rest) = self.s.split_at(n);
+        self.line += taken.matches('\n').count();
+        self.s = rest;
+        taken
+    }
+
+    fn skip_whitespace(&mut self) {
+        let n = self.s.len() - self.s.trim_start().len();
+        self.advance(n);
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.s.chars().next()
+    }
+
+    /// Takes everything up to `end` (exclusive), consuming `end`.
+    fn take_until(&mut self, end: char, msg: &'static str) -> Result<&'a str, PgnError> {
+        let n = self.s.find(end).ok_or_else(|| self.error(msg))?;
+        let taken = self.advance(n);
+        self.advance(end.len_utf8());
+        Ok(taken)
+    }
+
+    fn token(&mut self) -> &'a str {
+        let n = self
+            .s
+            .find(|ch: char| ch.is_whitespace() || "{}()[];$".contains(ch))
+            .unwrap_or(self.s.len());
+        self.advance(n)
+    }
+
+    fn tags(&mut self) -> Result<Vec<(String, String)>, PgnError> {
+        let mut tags = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('[') {
+                return Ok(tags);
+            }
+            self.advance(1);
+
+            let tag = self.take_until(']', "unterminated tag")?.trim();
+            let (name, value) = tag
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| self.error("tag without a value"))?;
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .ok_or_else(|| self.error("unquoted tag value"))?;
+
+            tags.push((name.to_owned(), value.replace("\\\"", "\"")));
+        }
+    }
+
+    /// Parses moves until the end of a variation or the game's result, `depth` is the number of
+    /// variations it's nested in.
+    fn moves(&mut self, depth: usize) -> Result<(Vec<PgnMove>, String), PgnError> {
+        let in_variation = depth != 0;
+        let mut moves: Vec<PgnMove> = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None if in_variation => return Err(self.error("unterminated variation")),
+                None => return Ok((moves, "*".to_owned())),
+                Some('{') => {
+                    self.advance(1);
+                    let comment = self.take_until('}', "unterminated comment")?.trim();
+                    // Comments before the first move and empty ones are dropped.
+                    if let Some(mv) = moves.last_mut().filter(|_| !comment.is_empty()) {
+                        match &mut mv.comment {
+                            Some(prev) => {
+                                prev.push(' ');
+                                prev.push_str(comment);
+                            }
+                            None => mv.comment = Some(comment.to_owned()),
+                        }
+                    }
+                }
+                Some(';') => {
+                    let n = self.s.find('\n').unwrap_or(self.s.len());
+                    self.advance(n);
+                }
+                Some('(') => {
+                    if MAX_VARIATION_DEPTH <= depth {
+                        return Err(self.error("variations nested too deep"));
+                    }
+                    self.advance(1);
+                    let (variation, _) = self.moves(depth + 1)?;
+                    moves
+                        .last_mut()
+                        .ok_or_else(|| self.error("variation before the first move"))?
+                        .variations
+                        .push(variation);
+                }
+                Some(')') if in_variation => {
+                    self.advance(1);
+                    return Ok((moves, String::new()));
+                }
+                Some('$') => {
+                    self.advance(1);
+                    let nag = self.token().parse().map_err(|_| self.error("bad NAG"))?;
+                    moves
+                        .last_mut()
+                        .ok_or_else(|| self.error("NAG before the first move"))?
+                        .nags
+                        .push(nag);
+                }
+                Some(_) => {
+                    let token = self.token();
+                    if token.is_empty() {
+                        return Err(self.error("unexpected character"));
+                    }
+                    // Skip move numbers: "12." or "12..."
+                    let token = match token.rfind('.') {
+                        Some(i) => &token[i + 1..],
+                        None => token,
+                    };
+                    match token {
+                        "" => {}
+                        "1-0" | "0-1" | "1/2-1/2" | "*" if !in_variation => {
+                            return Ok((moves, token.to_owned()));
+                        }
+                        "1-0" | "0-1" | "1/2-1/2" | "*" => {}
+                        _ => {
+                            let (san, nag) = split_suffix_nag(token);
+                            if san.is_empty() {
+                                // A detached annotation, e.g. "e4 !?".
+                                moves
+                                    .last_mut()
+                                    .ok_or_else(|| self.error("NAG before the first move"))?
+                                    .nags
+                                    .extend(nag);
+                                continue;
+                            }
+                            // A piece, a file, castling or a null move ("--").
+                            if !(san.starts_with(|ch: char| ch.is_ascii_alphabetic())
+                                || san.starts_with("0-0")
+                                || san == "--")
+                            {
+                                return Err(self.error("bad move"));
+                            }
+                            moves.push(PgnMove {
+                                san: san.to_owned(),
+                                nags: nag.into_iter().collect(),
+                                ..PgnMove::default()
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Splits "!?"-style suffixes into a NAG.
+fn split_suffix_nag(san: &str) -> (&str, Option<u8>) {
+    let trimmed = san.trim_end_matches(['!', '?']);
+    let nag = match &san[trimmed.len()..] {
+        "!" => Some(1),
+        "?" => Some(2),
+        "!!" => Some(3),
+        "??" => Some(4),
+        "!?" => Some(5),
+        "?!" => Some(6),
+        _ => None,
+    };
+    (trimmed, nag)
+}
+
+/// Parses all the games in `pgn`.
+pub fn parse(pgn: &str) -> Result<Vec<PgnGame>, PgnError> {
+    let mut parser = Parser { s: pgn, line: 1 };
+    let mut games = Vec::new();
+
+    loop {
+        let tags = parser.tags()?;
+        parser.skip_whitespace();
+        if tags.is_empty() && parser.s.is_empty() {
+            return Ok(games);
+        }
+
+        let (moves, result) = parser.moves(0)?;
+        games.push(PgnGame {
+            tags,
+            moves,
+            result,
+        });
+    }
+}
+
+impl PgnGame {
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Replays the main line from the `FEN` tag or the starting position of the `Variant` tag's
+    /// variant, fails at the first illegal move or an unknown variant.
+    pub fn mainline(&self) -> Option<Game> {
+        let variant = match self.tag("Variant") {
+            Some(variant) => variant.parse().ok()?,
+            None => Variant::Standard,
+        };
+        let mut game = match self.tag("FEN") {
+            Some(fen) => {
+                let (mut board, turn) = Board::from_fen(fen).ok()?;
+                if variant != Variant::Standard {
+                    board.variant = variant;
+                }
+                Game::from_position(board, turn)
+            }
+            None => Game::with_variant(variant),
+        };
+        for mv in &self.moves {
+            let parsed = game.board().parse_san(game.turn(), &mv.san)?;
+            game.push_move(parsed);
+        }
+        Some(game)
+    }
+
+    /// Builds a game record from the moves of `game`, with a `Variant` tag unless it's standard
+    /// chess, `SetUp` and `FEN` tags if it didn't start from the starting position, an `Odds` tag
+    /// if it started from a handicap's (see `Game::with_odds()`) and `ECO` and `Opening` tags if
+    /// it went through a named opening (see `Game::opening()`).
+    pub fn from_game(game: &Game) -> Self {
+        let (board, turn) = game.start();
+        let mut tags = Vec::new();
+        if board.variant != Variant::Standard {
+            tags.push(("Variant".to_owned(), board.variant.to_string()));
+        }
+        if (board, turn) != (Board::with_variant(board.variant), Color::White) {
+            tags.extend([
+                ("SetUp".to_owned(), "1".to_owned()),
+                ("FEN".to_owned(), board.to_fen(turn)),
+            ]);
+        }
+        if let Some(odds) = game.odds() {
+            tags.push(("Odds".to_owned(), odds.to_string()));
+        }
+        if let Some((code, name)) = game.opening() {
+            tags.extend([
+                ("ECO".to_owned(), code.to_string()),
+                ("Opening".to_owned(), name.to_owned()),
+            ]);
+        }
+
+        let mut replay = Game::from_position(board, turn);
+        let moves = game
+            .moves()
+            .iter()
+            .map(|&mv| {
+                let san = replay.board().to_san(replay.turn(), mv);
+                replay.push_move(mv);
+                PgnMove {
+                    san,
+                    ..PgnMove::default()
+                }
+            })
+            .collect();
+
+        Self {
+            tags,
+            moves,
+            result: "*".to_owned(),
+        }
+    }
+}
+
+fn write_moves(
+    f: &mut fmt::Formatter,
+    moves: &[PgnMove],
+    mut ply: usize,
+    mut first: bool,
+) -> fmt::Result {
+    for (i, mv) in moves.iter().enumerate() {
+        if i != 0 {
+            write!(f, " ")?;
+        }
+        match (ply.is_multiple_of(2), first) {
+            (true, _) => write!(f, "{}. ", ply / 2 + 1)?,
+            (false, true) => write!(f, "{}... ", ply / 2 + 1)?,
+            (false, false) => {}
+        }
+        write!(f, "{}", mv.san)?;
+        for nag in &mv.nags {
+            write!(f, " ${}", nag)?;
+        }
+        if let Some(comment) = &mv.comment {
+            write!(f, " {{{}}}", comment)?;
+        }
+        first = mv.comment.is_some();
+
+        for variation in &mv.variations {
+            write!(f, " (")?;
+            write_moves(f, variation, ply, true)?;
+            write!(f, ")")?;
+            first = true;
+        }
+
+        ply += 1;
+    }
+    Ok(())
+}
+
+impl fmt::Display for PgnGame {
+    /// Writes the game in PGN, without wrapping the move text.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, value) in &self.tags {
+            writeln!(f, "[{} \"{}\"]", name, value.replace('"', "\\\""))?;
+        }
+        if !self.tags.is_empty() {
+            writeln!(f)?;
+        }
+
+        // A game set up with black to move starts at its first ply.
+        let ply = self.tag("FEN").map_or(0, |fen| {
+            let mut fields = fen.split_whitespace().skip(1);
+            let black = fields.next() == Some("b");
+            let fullmove: usize = fields.nth(3).and_then(|n| n.parse().ok()).unwrap_or(1);
+            2 * fullmove.saturating_sub(1) + black as usize
+        });
+        write_moves(f, &self.moves, ply, true)?;
+        if !self.moves.is_empty() {
+            write!(f, " ")?;
+        }
+        writeln!(f, "{}", self.result)
+    }
+}
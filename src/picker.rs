@@ -0,0 +1,127 @@
+// Staged move generation for the search: the moves most likely to cut a node off come first and
+// are generated first, so a node that cuts off after the hash move or a capture never generates
+// and sorts its quiet moves.
+
+use crate::{bot::MoveOrderer, Board, Color, Move};
+
+/// The stages of `MovePicker`, in the order it goes through them.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Stage {
+    First,
+    Captures,
+    Countermove,
+    Rest,
+    Done,
+}
+
+/// Yields the legal moves of a position lazily, in stages: the move to try first (e.g. the hash
+/// move), the captures the orderer scores as good, the countermove, and then all the other moves
+/// by the orderer's scores, bad captures among them.
+pub struct MovePicker<'a, O: MoveOrderer> {
+    board: &'a Board,
+    color: Color,
+    attack: u64,
+    orderer: &'a O,
+    first: Option<Move>,
+    countermove: Move,
+    stage: Stage,
+    /// The current stage's moves, the best last.
+    moves: Vec<Move>,
+    /// The moves yielded by the stages before the last, which it skips.
+    yielded: Vec<Move>,
+}
+
+impl<'a, O: MoveOrderer> MovePicker<'a, O> {
+    /// `attack` must be `board.check_attack(color.inv())`. `first` and `countermove` are only
+    /// yielded if they're legal, `Move::NULL` is no countermove.
+    pub fn new(
+        board: &'a Board,
+        color: Color,
+        attack: u64,
+        orderer: &'a O,
+        first: Option<Move>,
+        countermove: Move,
+    ) -> Self {
+        Self {
+            board,
+            color,
+            attack,
+            orderer,
+            first,
+            countermove,
+            stage: Stage::First,
+            moves: Vec::new(),
+            yielded: Vec::new(),
+        }
+    }
+
+    fn score(&self, mv: &Move) -> i32 {
+        self.orderer
+            .score(mv, self.board, self.attack, self.countermove)
+    }
+
+    /// Whether `mv` is a legal move that no stage yielded yet.
+    fn is_new(&self, mv: Move) -> bool {
+        mv != Move::NULL
+            && mv.from < 64
+            && mv.to < 64
+            && !self.yielded.contains(&mv)
+            && self.board.is_legal(self.color, mv)
+    }
+}
+
+impl<O: MoveOrderer> Iterator for MovePicker<'_, O> {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Move> {
+        loop {
+            if let Some(mv) = self.moves.pop() {
+                // The last stage's moves can't come up again.
+                if self.stage != Stage::Done {
+                    self.yielded.push(mv);
+                }
+                return Some(mv);
+            }
+            match self.stage {
+                Stage::First => {
+                    self.stage = Stage::Captures;
+                    if let Some(first) = self.first.filter(|&first| self.is_new(first)) {
+                        self.moves.push(first);
+                    }
+                }
+                Stage::Captures => {
+                    self.stage = Stage::Countermove;
+                    let mut captures: Vec<_> = self
+                        .board
+                        .capture_moves_with_attack(self.color, self.attack)
+                        .into_iter()
+                        .filter(|mv| !self.yielded.contains(mv))
+                        .map(|mv| (self.score(&mv), mv))
+                        .filter(|&(score, _)| 0 <= score)
+                        .collect();
+                    captures.sort_unstable_by_key(|&(score, _)| score);
+                    self.moves = captures.into_iter().map(|(_, mv)| mv).collect();
+                }
+                Stage::Countermove => {
+                    self.stage = Stage::Rest;
+                    if self.is_new(self.countermove) {
+                        self.moves.push(self.countermove);
+                    }
+                }
+                Stage::Rest => {
+                    self.stage = Stage::Done;
+                    let mut rest: Vec<_> = self
+                        .board
+                        .moves_with_attack(self.color, self.attack)
+                        .into_iter()
+                        .filter(|mv| !self.yielded.contains(mv))
+                        .map(|mv| (self.score(&mv), mv))
+                        .collect();
+                    rest.sort_unstable_by_key(|&(score, _)| score);
+                    self.moves = rest.into_iter().map(|(_, mv)| mv).collect();
+                }
+                Stage::Done => return None,
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+// The human player's record against the bot's skill levels, kept as JSON in the config directory
+// between runs, and a rating estimated from it: every game moves it by the Elo formula towards what
+// the result says, against a nominal rating of the level.
+
+use crate::options::MAX_SKILL;
+use std::{collections::BTreeMap, fmt, io, path::PathBuf};
+
+/// The rating of a player without games.
+pub const START_RATING: f64 = 1200.0;
+/// How far a single game can move the rating.
+const K: f64 = 32.0;
+
+/// A guess at the bot's strength at a skill level, from 800 at level 0 to 2000 at full strength.
+pub fn level_rating(skill: u32) -> f64 {
+    800.0 + 1200.0 * skill.min(MAX_SKILL) as f64 / MAX_SKILL as f64
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Record {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl Record {
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Profile {
+    pub rating: f64,
+    /// The record against every skill level played.
+    pub levels: BTreeMap<u32, Record>,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            rating: START_RATING,
+            levels: BTreeMap::new(),
+        }
+    }
+}
+
+fn invalid_data(msg: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+// Just enough JSON to read the numeric fields of a flat object, e.g. `{"wins": 3}`.
+fn json_number_field(json: &str, name: &str) -> Option<f64> {
+    let key = format!("\"{}\"", name);
+    let rest = json[json.find(&key)? + key.len()..].trim_start();
+    let rest = rest.strip_prefix(':')?.trim_start();
+    let end = rest
+        .find(|c: char| !matches!(c, '0'..='9' | '-' | '+' | '.' | 'e' | 'E'))
+        .unwrap_or(rest.len());
+    rest[..end].parse().ok()
+}
+
+impl Profile {
+    /// `$XDG_CONFIG_HOME/mxchess/profile.json`, or under `~/.config` or `%APPDATA%`. `None` if
+    /// there's no config directory.
+    pub fn default_path() -> Option<PathBuf> {
+        let config = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))?;
+        Some(config.join("mxchess").join("profile.json"))
+    }
+
+    /// Loads the profile at `path`, a new one if there's no file yet.
+    pub fn load(path: &std::path::Path) -> io::Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(json) => Self::from_json(&json)
+                .ok_or_else(|| invalid_data(format!("Not a profile: {}", path.display()))),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Saves the profile to `path`, creating its directory.
+    pub fn save(&self, path: &std::path::Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, self.to_json())
+    }
+
+    /// Records a game against the bot at `skill`, `score` is 1 for a win, 0.5 for a draw and 0
+    /// for a loss.
+    pub fn record(&mut self, skill: u32, score: f64) {
+        let record = self.levels.entry(skill).or_default();
+        match score {
+            score if 0.75 <= score => record.wins += 1,
+            score if 0.25 <= score => record.draws += 1,
+            _ => record.losses += 1,
+        }
+        let expected = 1.0 / (1.0 + 10f64.powf((level_rating(skill) - self.rating) / 400.0));
+        self.rating += K * (score - expected);
+    }
+
+    /// All the levels' records together.
+    pub fn total(&self) -> Record {
+        self.levels
+            .values()
+            .fold(Record::default(), |total, record| Record {
+                wins: total.wins + record.wins,
+                draws: total.draws + record.draws,
+                losses: total.losses + record.losses,
+            })
+    }
+
+    /// `{"rating": 1216.0, "levels": [{"skill": 20, "wins": 1, "draws": 0, "losses": 0}]}`
+    pub fn to_json(&self) -> String {
+        let levels: Vec<_> = self
+            .levels
+            .iter()
+            .map(|(skill, record)| {
+                format!(
+                    "{{\"skill\": {}, \"wins\": {}, \"draws\": {}, \"losses\": {}}}",
+                    skill, record.wins, record.draws, record.losses
+                )
+            })
+            .collect();
+        format!(
+            "{{\"rating\": {:.1}, \"levels\": [{}]}}\n",
+            self.rating,
+            levels.join(", ")
+        )
+    }
+
+    /// Reads `to_json()`'s format.
+    pub fn from_json(json: &str) -> Option<Self> {
+        let (head, levels) = json.split_once("\"levels\"")?;
+        let rating = json_number_field(head, "rating")?;
+        let levels = levels.trim_start().strip_prefix(':')?.trim_start();
+        let levels = levels.strip_prefix('[')?;
+        let levels = &levels[..levels.find(']')?];
+
+        let mut profile = Self {
+            rating,
+            levels: BTreeMap::new(),
+        };
+        for level in levels.split('}').filter(|level| level.contains('{')) {
+            let field = |name| json_number_field(level, name).map(|n| n as u32);
+            profile.levels.insert(
+                field("skill")?,
+                Record {
+                    wins: field("wins")?,
+                    draws: field("draws")?,
+                    losses: field("losses")?,
+                },
+            );
+        }
+        Some(profile)
+    }
+}
+
+impl fmt::Display for Profile {
+    /// The rating and a line per skill level, for `mxchess stats`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let total = self.total();
+        writeln!(
+            f,
+            "Rating: {:.0} after {} games (+{} ={} -{})",
+            self.rating,
+            total.games(),
+            total.wins,
+            total.draws,
+            total.losses
+        )?;
+        for (skill, record) in &self.levels {
+            writeln!(
+                f,
+                "  skill {:2} (~{:.0}): +{} ={} -{}",
+                skill,
+                level_rating(*skill),
+                record.wins,
+                record.draws,
+                record.losses
+            )?;
+        }
+        Ok(())
+    }
+}
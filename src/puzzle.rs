@@ -0,0 +1,322 @@
+// Tactics puzzles, loaded from EPD (`bm` opcodes), PGN (with a `FEN` tag, the main line is the
+// solution) or the Lichess puzzle database CSV (https://database.lichess.org/#puzzles).
+
+use crate::{pgn, Board, Bot, Color, Game, GameStatus, Move};
+use std::io::{self, BufRead, Write};
+
+// Alternate mates are verified by a full-width search, which gets slow quickly.
+const MAX_MATE_SEARCH: u32 = 3;
+
+#[derive(Clone, Debug)]
+pub struct Puzzle {
+    pub id: String,
+    pub board: Board,
+    pub turn: Color,
+    /// The opponent's move that led to the puzzle, in SAN.
+    pub last_move: Option<String>,
+    /// Alternative solutions, each alternates between the solver's and the opponent's moves.
+    pub solutions: Vec<Vec<Move>>,
+}
+
+enum Outcome {
+    Solved,
+    Failed,
+    Quit,
+}
+
+fn invalid_data(msg: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+impl Puzzle {
+    /// Parses an EPD line, e.g. `6k1/5ppp/8/8/8/8/5PPP/3R2K1 w - - bm Rd8#; id "back rank";`.
+    /// Every move of `bm` is an alternative solution.
+    pub fn from_epd(line: &str) -> io::Result<Self> {
+        let fields: Vec<_> = line.split_whitespace().collect();
+        if fields.len() < 4 {
+            return Err(invalid_data("EPD line is too short"));
+        }
+        let (board, turn) = Board::from_fen(&fields[..4].join(" ")).map_err(invalid_data)?;
+
+        let mut puzzle = Self {
+            id: String::new(),
+            board,
+            turn,
+            last_move: None,
+            solutions: Vec::new(),
+        };
+        for op in fields[4..].join(" ").split(';') {
+            let op = op.trim();
+            let (opcode, operand) = op.split_once(' ').unwrap_or((op, ""));
+            match opcode {
+                "bm" => {
+                    for san in operand.split_whitespace() {
+                        let mv = board
+                            .parse_san(turn, san)
+                            .ok_or_else(|| invalid_data(format!("Illegal best move: {}", san)))?;
+                        puzzle.solutions.push(vec![mv]);
+                    }
+                }
+                "id" => puzzle.id = operand.trim_matches('"').to_owned(),
+                _ => {}
+            }
+        }
+
+        if puzzle.solutions.is_empty() {
+            return Err(invalid_data("EPD line without a best move"));
+        }
+        Ok(puzzle)
+    }
+
+    /// Parses a line of the Lichess puzzle CSV, `PuzzleId,FEN,Moves,...`.
+    /// The first move is the opponent's, the rest are the solution.
+    pub fn from_lichess_csv(line: &str) -> io::Result<Self> {
+        let fields: Vec<_> = line.split(',').collect();
+        if fields.len() < 3 {
+            return Err(invalid_data("Lichess puzzle line is too short"));
+        }
+        let (board, turn) = Board::from_fen(fields[1]).map_err(invalid_data)?;
+
+        let mut game = Game::from_position(board, turn);
+        let mut last_move = None;
+        for (i, mv) in fields[2].split_whitespace().enumerate() {
+            let parsed = game
+                .board()
+                .parse_move(game.turn(), mv)
+                .ok_or_else(|| invalid_data(format!("Illegal puzzle move: {}", mv)))?;
+            if i == 0 {
+                last_move = Some(game.board().to_san(game.turn(), parsed));
+            }
+            game.push_move(parsed);
+        }
+        let (first, solution) = game
+            .moves()
+            .split_first()
+            .ok_or_else(|| invalid_data("Lichess puzzle without moves"))?;
+
+        let mut board = board;
+        board.perform_move_unchecked(*first);
+        Ok(Self {
+            id: fields[0].to_owned(),
+            board,
+            turn: turn.inv(),
+            last_move,
+            solutions: vec![solution.to_vec()],
+        })
+    }
+
+    /// Builds a puzzle from a PGN game with a `FEN` tag, its main line is the solution.
+    pub fn from_pgn(game: &pgn::PgnGame) -> io::Result<Self> {
+        let fen = game
+            .tag("FEN")
+            .ok_or_else(|| invalid_data("PGN puzzle without a FEN tag"))?;
+        let (board, turn) = Board::from_fen(fen).map_err(invalid_data)?;
+
+        let mut replay = Game::from_position(board, turn);
+        for mv in &game.moves {
+            let parsed = replay
+                .board()
+                .parse_san(replay.turn(), &mv.san)
+                .ok_or_else(|| invalid_data(format!("Illegal puzzle move: {}", mv.san)))?;
+            replay.push_move(parsed);
+        }
+        if replay.moves().is_empty() {
+            return Err(invalid_data("PGN puzzle without moves"));
+        }
+
+        Ok(Self {
+            id: game.tag("Event").unwrap_or_default().to_owned(),
+            board,
+            turn,
+            last_move: None,
+            solutions: vec![replay.moves().to_vec()],
+        })
+    }
+
+    /// The number of the solver's moves in the longest solution that ends in mate.
+    pub fn mate_in(&self) -> Option<u32> {
+        self.solutions
+            .iter()
+            .filter(|solution| {
+                let mut game = Game::from_position(self.board, self.turn);
+                for &mv in solution.iter() {
+                    game.push_move(mv);
+                }
+                matches!(game.status(), GameStatus::Checkmate(_))
+            })
+            .map(|solution| (solution.len() as u32).div_ceil(2))
+            .max()
+    }
+}
+
+/// Loads the puzzles of an EPD, PGN or Lichess CSV file.
+pub fn load(text: &str) -> io::Result<Vec<Puzzle>> {
+    if text.trim_start().starts_with('[') {
+        return pgn::parse(text)
+            .map_err(invalid_data)?
+            .iter()
+            .map(Puzzle::from_pgn)
+            .collect();
+    }
+
+    let mut puzzles = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with("PuzzleId,") {
+            continue;
+        }
+
+        // EPD lines start with the board, which is followed by a space.
+        let is_csv = matches!(line.split_once(','), Some((id, _)) if !id.contains(' '));
+        let puzzle = if is_csv {
+            Puzzle::from_lichess_csv(line)
+        } else {
+            Puzzle::from_epd(line)
+        };
+        puzzles.push(puzzle.map_err(|err| invalid_data(format!("Line {}: {}", i + 1, err)))?);
+    }
+    Ok(puzzles)
+}
+
+/// Whether `color`, to move, can mate within `depth` of its moves.
+fn mates_within(board: &Board, color: Color, depth: u32) -> bool {
+    depth != 0
+        && board.moves(color).into_iter().any(|mv| {
+            let mut board = *board;
+            board.perform_move_unchecked(mv);
+            is_mated_within(&board, color.inv(), depth - 1)
+        })
+}
+
+/// Whether `color`, to move, gets mated with the opponent having `depth` more moves.
+fn is_mated_within(board: &Board, color: Color, depth: u32) -> bool {
+    let attack = board.check_attack(color.inv());
+    let moves = board.moves_with_attack(color, attack);
+    if moves.is_empty() {
+        return attack & board.get_pieces(color).king() != 0;
+    }
+
+    moves.into_iter().all(|mv| {
+        let mut board = *board;
+        board.perform_move_unchecked(mv);
+        mates_within(&board, color.inv(), depth)
+    })
+}
+
+fn solve(
+    puzzle: &Puzzle,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> io::Result<Outcome> {
+    let side = puzzle.turn;
+    let mut game = Game::from_position(puzzle.board, side);
+    // The solutions that match the moves played so far.
+    let mut solutions: Vec<&[Move]> = puzzle.solutions.iter().map(|s| s.as_slice()).collect();
+    let mut mate_in = puzzle.mate_in().filter(|&n| n <= MAX_MATE_SEARCH);
+    // Whether an alternate mate left the given solutions, the search picks the replies then.
+    let mut searching = false;
+    let mut buf = String::new();
+
+    if let Some(san) = &puzzle.last_move {
+        writeln!(output, "Your opponent played {}", san)?;
+    }
+
+    loop {
+        writeln!(output)?;
+        write!(output, "{}", game.board().render(side))?;
+        write!(output, "Your move: ")?;
+        output.flush()?;
+
+        buf.clear();
+        if input.read_line(&mut buf)? == 0 || buf.trim() == "quit" {
+            return Ok(Outcome::Quit);
+        }
+        let board = *game.board();
+        let mv = match crate::input::parse_move(&board, side, &buf) {
+            Ok(mv) => mv,
+            Err(err) => {
+                writeln!(output, "Illegal move: {}", err)?;
+                continue;
+            }
+        };
+
+        let ply = game.moves().len();
+        let mates = |n: u32| {
+            let mut board = board;
+            board.perform_move_unchecked(mv);
+            is_mated_within(&board, side.inv(), n - 1)
+        };
+        if !searching && solutions.iter().any(|s| s.get(ply) == Some(&mv)) {
+            solutions.retain(|s| s.get(ply) == Some(&mv));
+        } else if mate_in.is_some_and(mates) {
+            searching = true;
+        } else {
+            match solutions.first().and_then(|s| s.get(ply)) {
+                Some(&expected) => writeln!(
+                    output,
+                    "Wrong, the solution is {}",
+                    board.to_san(side, expected)
+                )?,
+                None => writeln!(output, "Wrong")?,
+            }
+            return Ok(Outcome::Failed);
+        }
+
+        game.push_move(mv);
+        mate_in = mate_in.map(|n| n - 1);
+        if matches!(game.status(), GameStatus::Checkmate(_))
+            || !searching && solutions.iter().any(|s| s.len() == ply + 1)
+        {
+            return Ok(Outcome::Solved);
+        }
+
+        let reply = if searching {
+            Bot::with_depth(2).choose_move_with_history(game.board(), game.turn(), game.history())
+        } else {
+            solutions.first().and_then(|s| s.get(ply + 1)).copied()
+        };
+        let reply = match reply {
+            Some(reply) => reply,
+            None => return Ok(Outcome::Solved),
+        };
+        solutions.retain(|s| s.get(ply + 1) == Some(&reply));
+
+        writeln!(
+            output,
+            "Your opponent played {}",
+            game.board().to_san(game.turn(), reply)
+        )?;
+        game.push_move(reply);
+    }
+}
+
+/// Presents the puzzles one by one until they run out or the user quits.
+pub fn run(puzzles: &[Puzzle], mut input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    let (mut solved, mut tried) = (0, 0);
+
+    for (i, puzzle) in puzzles.iter().enumerate() {
+        writeln!(output)?;
+        writeln!(output, "Puzzle {}/{} {}", i + 1, puzzles.len(), puzzle.id)?;
+        writeln!(
+            output,
+            "{} to move",
+            match puzzle.turn {
+                Color::White => "White",
+                Color::Black => "Black",
+            }
+        )?;
+
+        match solve(puzzle, &mut input, &mut output)? {
+            Outcome::Solved => {
+                solved += 1;
+                writeln!(output, "Solved!")?;
+            }
+            Outcome::Failed => {}
+            Outcome::Quit => break,
+        }
+        tried += 1;
+    }
+
+    writeln!(output)?;
+    writeln!(output, "Solved {}/{} puzzles", solved, tried)
+}
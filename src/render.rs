@@ -0,0 +1,470 @@
+// Board images for blogs, chat bots and reports: SVG, and PNG rasterized from it with the `png`
+// feature. The pieces are the Unicode chess symbols, so the PNG needs a font that has them, e.g.
+// DejaVu Sans.
+
+use crate::{Board, Color, Move, PieceType, Square};
+use alloc::{format, string::String, vec::Vec};
+use core::fmt::Write;
+
+const LIGHT_SQUARE: &str = "#f0d9b5";
+const DARK_SQUARE: &str = "#b58863";
+const LAST_MOVE: &str = "#cdd26a";
+const FONT: &str = "DejaVu Sans, Segoe UI Symbol, Noto Sans Symbols2, sans-serif";
+
+/// The side a board is drawn from, the one at the bottom, apart from the side to move.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum Perspective {
+    #[default]
+    White,
+    Black,
+    /// The side to move's, e.g. to show a puzzle from the solver's side.
+    Auto,
+}
+
+impl Perspective {
+    /// The side at the bottom with `to_move` to move.
+    pub fn side(self, to_move: Color) -> Color {
+        match self {
+            Self::White => Color::White,
+            Self::Black => Color::Black,
+            Self::Auto => to_move,
+        }
+    }
+}
+
+impl From<Color> for Perspective {
+    fn from(color: Color) -> Self {
+        match color {
+            Color::White => Self::White,
+            Color::Black => Self::Black,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SvgOptions {
+    /// The width of a square in pixels, the board is 8 times as wide.
+    pub square_size: u32,
+    /// The side at the bottom.
+    pub perspective: Perspective,
+    /// The side to move, at the bottom with `Perspective::Auto`.
+    pub to_move: Color,
+    /// The file letters and the rank numbers inside the edge squares.
+    pub coordinates: bool,
+    /// Highlights the squares of the last move, from and to.
+    pub last_move: Option<(Square, Square)>,
+    /// The arrows and the circled squares drawn over the pieces.
+    pub annotations: Annotations,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            square_size: 45,
+            perspective: Perspective::White,
+            to_move: Color::White,
+            coordinates: true,
+            last_move: None,
+            annotations: Annotations::default(),
+        }
+    }
+}
+
+/// The colors of the annotations, those of lichess's analysis board.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Brush {
+    Green,
+    Red,
+    Blue,
+    Yellow,
+}
+
+impl Brush {
+    pub const ALL: [Self; 4] = [Self::Green, Self::Red, Self::Blue, Self::Yellow];
+
+    pub fn rgb(self) -> (u8, u8, u8) {
+        match self {
+            Self::Green => (0x15, 0x78, 0x1b),
+            Self::Red => (0x88, 0x20, 0x20),
+            Self::Blue => (0x00, 0x30, 0x88),
+            Self::Yellow => (0xe6, 0x8f, 0x00),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Green => "green",
+            Self::Red => "red",
+            Self::Blue => "blue",
+            Self::Yellow => "yellow",
+        }
+    }
+}
+
+/// An arrow of the annotation layer, or a circled square when `from` and `to` are the same.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Mark {
+    pub from: Square,
+    pub to: Square,
+    pub brush: Brush,
+}
+
+impl Mark {
+    pub fn is_circle(&self) -> bool {
+        self.from == self.to
+    }
+}
+
+/// What the analysis has to show on the board, drawn over the pieces by `to_svg()` and as the
+/// squares' colors by the terminal UI.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Annotations {
+    pub marks: Vec<Mark>,
+}
+
+/// The exchange values of the pieces, to tell the captures that win material.
+fn value(ty: PieceType) -> u32 {
+    match ty {
+        PieceType::King => 100,
+        PieceType::Queen => 9,
+        PieceType::Rook => 5,
+        PieceType::Bishop | PieceType::Knight => 3,
+        PieceType::Pawn => 1,
+    }
+}
+
+impl Annotations {
+    pub fn arrow(&mut self, from: Square, to: Square, brush: Brush) {
+        self.marks.push(Mark { from, to, brush });
+    }
+
+    pub fn circle(&mut self, square: Square, brush: Brush) {
+        self.arrow(square, square, brush);
+    }
+
+    /// The insights of an analysis of the position with `color` to move: the best move in green,
+    /// the threats in red, the opponent's captures of `color`'s pieces that are undefended or
+    /// worth more than the capturer, and the pinned pieces of both sides circled in blue.
+    pub fn from_analysis(board: &Board, color: Color, best_move: Option<Move>) -> Self {
+        let mut annotations = Self::default();
+        let square = |index: u8| Square::from_index(index).unwrap();
+
+        for mv in board.capture_moves(color.inv()) {
+            let victim = match board.get_at(1 << mv.to) {
+                Some(victim) if victim.color == color => victim,
+                _ => continue,
+            };
+            let attacker = mv.ty.piece();
+            if !board.is_attacked(square(mv.to), color) || value(victim.ty) > value(attacker) {
+                annotations.arrow(square(mv.from), square(mv.to), Brush::Red);
+            }
+        }
+        for pinned in crate::BitIterator(board.find_pins(color) | board.find_pins(color.inv())) {
+            annotations.circle(square(pinned.trailing_zeros() as u8), Brush::Blue);
+        }
+        if let Some(mv) = best_move {
+            annotations.arrow(square(mv.from), square(mv.to), Brush::Green);
+        }
+        annotations.marks.dedup();
+        annotations
+    }
+}
+
+impl SvgOptions {
+    /// Whether the board is drawn from Black's side, turned around: rank 1 at the top and the
+    /// h-file on the left.
+    pub fn flipped(&self) -> bool {
+        self.perspective.side(self.to_move) == Color::Black
+    }
+
+    /// The top left corner of the square at `index`.
+    fn corner(&self, index: u8) -> (u32, u32) {
+        let (file, rank) = (index as u32 % 8, index as u32 / 8);
+        let (column, row) = match self.flipped() {
+            false => (file, 7 - rank),
+            true => (7 - file, rank),
+        };
+        (column * self.square_size, row * self.square_size)
+    }
+
+    fn center(&self, square: Square) -> (f64, f64) {
+        let (x, y) = self.corner(square.index());
+        let half = self.square_size as f64 / 2.0;
+        (x as f64 + half, y as f64 + half)
+    }
+}
+
+/// The board as an SVG document, `options.square_size * 8` pixels wide and high.
+pub fn to_svg(board: &Board, options: &SvgOptions) -> String {
+    let size = options.square_size;
+    let mut svg = String::new();
+    write!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{0}\" viewBox=\"0 0 {0} {0}\">",
+        8 * size
+    ).unwrap();
+    svg.push_str("<defs>");
+    for brush in Brush::ALL {
+        write!(
+            svg,
+            "<marker id=\"arrowhead-{}\" viewBox=\"0 0 10 10\" refX=\"5\" refY=\"5\" \
+             markerWidth=\"3\" markerHeight=\"3\" orient=\"auto\">\
+             <path d=\"M0,0 L10,5 L0,10 z\" fill=\"{}\"/></marker>",
+            brush.name(),
+            hex(brush)
+        )
+        .unwrap();
+    }
+    svg.push_str("</defs>");
+
+    let highlighted = |index: u8| {
+        options
+            .last_move
+            .is_some_and(|(from, to)| from.index() == index || to.index() == index)
+    };
+    for index in 0..64 {
+        let (x, y) = options.corner(index);
+        let fill = match () {
+            _ if highlighted(index) => LAST_MOVE,
+            _ if (index ^ index >> 3) & 1 == 0 => DARK_SQUARE,
+            _ => LIGHT_SQUARE,
+        };
+        write!(
+            svg,
+            "<rect x=\"{}\" y=\"{}\" width=\"{2}\" height=\"{2}\" fill=\"{3}\"/>",
+            x, y, size, fill
+        )
+        .unwrap();
+    }
+
+    if options.coordinates {
+        let font_size = size / 5;
+        // In the other color of the square they're on.
+        let color = |index: u8| match (index ^ index >> 3) & 1 {
+            0 => LIGHT_SQUARE,
+            _ => DARK_SQUARE,
+        };
+        for i in 0..8u8 {
+            let (bottom, left) = match options.flipped() {
+                false => (i, 8 * i),
+                true => (56 + i, 8 * i + 7),
+            };
+            let (x, y) = options.corner(bottom);
+            write!(
+                svg,
+                "<text x=\"{}\" y=\"{}\" font-size=\"{}\" font-family=\"{}\" fill=\"{}\" \
+                 text-anchor=\"end\">{}</text>",
+                x + size - 2,
+                y + size - 2,
+                font_size,
+                FONT,
+                color(bottom),
+                (b'a' + i) as char
+            )
+            .unwrap();
+            let (x, y) = options.corner(left);
+            write!(
+                svg,
+                "<text x=\"{}\" y=\"{}\" font-size=\"{}\" font-family=\"{}\" fill=\"{}\">{}</text>",
+                x + 2,
+                y + font_size + 1,
+                font_size,
+                FONT,
+                color(left),
+                i + 1
+            )
+            .unwrap();
+        }
+    }
+
+    for index in 0..64 {
+        let piece = match board.get_at(1 << index) {
+            Some(piece) => piece,
+            None => continue,
+        };
+        // The filled symbols for both colors, outlined, read best at any size.
+        let symbol = "\u{265A}\u{265B}\u{265C}\u{265D}\u{265E}\u{265F}"
+            .chars()
+            .nth(piece.ty as usize)
+            .unwrap();
+        let (fill, stroke) = match piece.color {
+            Color::White => ("#ffffff", "#000000"),
+            Color::Black => ("#000000", "#000000"),
+        };
+        let (x, y) = options.corner(index);
+        write!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" font-size=\"{}\" font-family=\"{}\" fill=\"{}\" \
+             stroke=\"{}\" stroke-width=\"1\" text-anchor=\"middle\">{}</text>",
+            x + size / 2,
+            y + size * 4 / 5,
+            size * 4 / 5,
+            FONT,
+            fill,
+            stroke,
+            symbol
+        )
+        .unwrap();
+    }
+
+    for mark in &options.annotations.marks {
+        let ((x1, y1), (x2, y2)) = (options.center(mark.from), options.center(mark.to));
+        if mark.is_circle() {
+            write!(
+                svg,
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.1}\" fill=\"none\" stroke=\"{}\" \
+                 stroke-width=\"{:.1}\" stroke-opacity=\"0.8\"/>",
+                x1,
+                y1,
+                size as f64 * 0.45,
+                hex(mark.brush),
+                size as f64 / 15.0
+            )
+            .unwrap();
+            continue;
+        }
+        // Stops short of the center, so that the head doesn't cover the piece.
+        let (dx, dy) = (x2 - x1, y2 - y1);
+        let short = size as f64 * 0.3 / hypot(dx, dy);
+        write!(
+            svg,
+            "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"{}\" \
+             stroke-width=\"{:.1}\" stroke-opacity=\"0.8\" marker-end=\"url(#arrowhead-{})\"/>",
+            x1,
+            y1,
+            x2 - dx * short,
+            y2 - dy * short,
+            hex(mark.brush),
+            size as f64 / 5.0,
+            mark.brush.name()
+        )
+        .unwrap();
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+/// "#rrggbb"
+fn hex(brush: Brush) -> String {
+    let (r, g, b) = brush.rgb();
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// `sqrt(dx² + dy²)` without `std`, by Newton's method, the arrows don't need more precision.
+fn hypot(dx: f64, dy: f64) -> f64 {
+    let square = dx * dx + dy * dy;
+    let mut root = square.max(1.0);
+    for _ in 0..32 {
+        root = (root + square / root) / 2.0;
+    }
+    root
+}
+
+/// The usvg options with the system's fonts, for the pieces.
+#[cfg(feature = "png")]
+fn usvg_options() -> resvg::usvg::Options<'static> {
+    let mut options = resvg::usvg::Options::default();
+    options.fontdb_mut().load_system_fonts();
+    options
+}
+
+#[cfg(feature = "png")]
+fn rasterize(
+    svg: &str,
+    options: &resvg::usvg::Options,
+) -> Result<resvg::tiny_skia::Pixmap, String> {
+    let tree = resvg::usvg::Tree::from_str(svg, options).map_err(|err| err.to_string())?;
+    let size = tree.size().to_int_size();
+    let mut pixmap =
+        resvg::tiny_skia::Pixmap::new(size.width(), size.height()).ok_or("The image is empty")?;
+    resvg::render(
+        &tree,
+        resvg::tiny_skia::Transform::default(),
+        &mut pixmap.as_mut(),
+    );
+    Ok(pixmap)
+}
+
+/// The board as a PNG image, see `to_svg()`.
+#[cfg(feature = "png")]
+pub fn to_png(board: &Board, options: &SvgOptions) -> Result<Vec<u8>, String> {
+    rasterize(&to_svg(board, options), &usvg_options())?
+        .encode_png()
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(feature = "animation")]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AnimationOptions {
+    /// How every position is drawn, the last move is highlighted on top.
+    pub board: SvgOptions,
+    /// How long every position shows, and the final one before the animation starts over.
+    pub delay: std::time::Duration,
+    pub final_delay: std::time::Duration,
+}
+
+#[cfg(feature = "animation")]
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        Self {
+            board: SvgOptions::default(),
+            delay: std::time::Duration::from_secs(1),
+            final_delay: std::time::Duration::from_secs(3),
+        }
+    }
+}
+
+#[cfg(feature = "animation")]
+impl crate::Game {
+    /// Writes the game to `path` as an animated GIF, a frame for the starting position and one
+    /// after every move.
+    pub fn to_animation(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        options: &AnimationOptions,
+    ) -> std::io::Result<()> {
+        use std::io;
+
+        let invalid_data = |err: String| io::Error::new(io::ErrorKind::InvalidData, err);
+        let usvg_options = usvg_options();
+        let (mut board, mut color) = self.start();
+        let mut frames = Vec::new();
+        let mut board_options = options.board.clone();
+        for i in 0..=self.moves().len() {
+            if i != 0 {
+                let mv = self.moves()[i - 1];
+                board.perform_move_unchecked(mv);
+                color = color.inv();
+                board_options.last_move =
+                    Square::from_index(mv.from).zip(Square::from_index(mv.to));
+            }
+            board_options.to_move = color;
+            frames.push(
+                rasterize(&to_svg(&board, &board_options), &usvg_options).map_err(invalid_data)?,
+            );
+        }
+
+        let (width, height) = (frames[0].width() as u16, frames[0].height() as u16);
+        let mut encoder = gif::Encoder::new(std::fs::File::create(path)?, width, height, &[])
+            .map_err(|err| invalid_data(err.to_string()))?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(|err| invalid_data(err.to_string()))?;
+        let last = frames.len() - 1;
+        for (i, pixmap) in frames.into_iter().enumerate() {
+            let mut pixels = pixmap.take();
+            let mut frame = gif::Frame::from_rgba_speed(width, height, &mut pixels, 10);
+            let delay = if i == last {
+                options.final_delay
+            } else {
+                options.delay
+            };
+            frame.delay = (delay.as_millis() / 10).min(u16::MAX as u128) as u16;
+            encoder
+                .write_frame(&frame)
+                .map_err(|err| invalid_data(err.to_string()))?;
+        }
+        Ok(())
+    }
+}
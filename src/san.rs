@@ -0,0 +1,205 @@
+// Standard Algebraic Notation, e.g. "e4", "Nbd7", "exd5", "e8=Q+", "O-O-O#", and Crazyhouse's drops
+// like "N@f3".
+
+use crate::{Board, Color, Move, MoveType, PieceType, Square};
+use alloc::string::{String, ToString};
+
+fn piece_letter(ty: PieceType) -> Option<char> {
+    match ty {
+        PieceType::King => Some('K'),
+        PieceType::Queen => Some('Q'),
+        PieceType::Rook => Some('R'),
+        PieceType::Bishop => Some('B'),
+        PieceType::Knight => Some('N'),
+        PieceType::Pawn => None,
+    }
+}
+
+fn promotion(ch: u8) -> Option<MoveType> {
+    match ch {
+        b'Q' => Some(MoveType::PawnQueenPromotion),
+        b'R' => Some(MoveType::PawnRookPromotion),
+        b'B' => Some(MoveType::PawnBishopPromotion),
+        b'N' => Some(MoveType::PawnKnightPromotion),
+        _ => None,
+    }
+}
+
+impl Board {
+    /// Formats a legal move of `color` in SAN.
+    pub fn to_san(&self, color: Color, mv: Move) -> String {
+        let mut san = String::new();
+
+        if let Some(ty) = mv.ty.dropped() {
+            san.push(piece_letter(ty).unwrap_or('P'));
+            san.push('@');
+            san.push_str(&Square::of(mv.to).to_string());
+        } else if mv.ty == MoveType::Castle {
+            san.push_str(if mv.to & 7 == 6 { "O-O" } else { "O-O-O" });
+        } else {
+            let ty = match self.get_at(1 << mv.from) {
+                Some(piece) => piece.ty,
+                None => return san,
+            };
+            let capture =
+                mv.ty == MoveType::PawnEnPassant || self.occupied_by(color.inv()) & 1 << mv.to != 0;
+
+            match piece_letter(ty) {
+                Some(letter) => {
+                    san.push(letter);
+
+                    // Disambiguate between pieces of the same type that can reach the same square.
+                    let others = self.moves(color).into_iter().filter(|other| {
+                        other.to == mv.to
+                            && other.from != mv.from
+                            && self.get_pieces(color).get(ty) & 1 << other.from != 0
+                    });
+                    let (mut any, mut same_file, mut same_rank) = (false, false, false);
+                    for other in others {
+                        any = true;
+                        same_file |= other.from & 7 == mv.from & 7;
+                        same_rank |= other.from / 8 == mv.from / 8;
+                    }
+                    if any && (!same_file || same_rank) {
+                        san.push((b'a' + (mv.from & 7)) as char);
+                    }
+                    if same_file {
+                        san.push((b'1' + mv.from / 8) as char);
+                    }
+                }
+                None if capture => san.push((b'a' + (mv.from & 7)) as char),
+                None => {}
+            }
+
+            if capture {
+                san.push('x');
+            }
+            san.push_str(&Square::of(mv.to).to_string());
+
+            match mv.ty {
+                MoveType::PawnQueenPromotion => san.push_str("=Q"),
+                MoveType::PawnRookPromotion => san.push_str("=R"),
+                MoveType::PawnBishopPromotion => san.push_str("=B"),
+                MoveType::PawnKnightPromotion => san.push_str("=N"),
+                _ => {}
+            }
+        }
+
+        if self.gives_check(mv) {
+            let mut board = *self;
+            board.perform_move_unchecked(mv);
+            let attack = board.check_attack(color);
+            san.push(if board.has_moves(color.inv(), attack) {
+                '+'
+            } else {
+                '#'
+            });
+        }
+
+        san
+    }
+
+    /// Parses a SAN move of `color`, returns `None` if it's malformed, illegal or ambiguous.
+    pub fn parse_san(&self, color: Color, san: &str) -> Option<Move> {
+        let san = san.trim_end_matches(['+', '#', '!', '?']);
+
+        let castle_to = match san {
+            "O-O" | "0-0" => Some(6),
+            "O-O-O" | "0-0-0" => Some(2),
+            _ => None,
+        };
+        if let Some(to) = castle_to {
+            let rank = match color {
+                Color::White => 0,
+                Color::Black => 0o70,
+            };
+            let mv = Move {
+                from: rank + 4,
+                to: rank + to,
+                ty: MoveType::Castle,
+            };
+            return if self.is_legal(color, mv) {
+                Some(mv)
+            } else {
+                None
+            };
+        }
+
+        if let Some((piece, to)) = san.split_once('@') {
+            let ty = match piece {
+                "" | "P" => PieceType::Pawn,
+                "Q" => PieceType::Queen,
+                "R" => PieceType::Rook,
+                "B" => PieceType::Bishop,
+                "N" => PieceType::Knight,
+                _ => return None,
+            };
+            return self.get_legal_drop(color, ty, Square::from_ascii(to.as_bytes())?.index());
+        }
+
+        let mut s = san.as_bytes();
+
+        let ty = match s.first()? {
+            b'K' => PieceType::King,
+            b'Q' => PieceType::Queen,
+            b'R' => PieceType::Rook,
+            b'B' => PieceType::Bishop,
+            b'N' => PieceType::Knight,
+            _ => PieceType::Pawn,
+        };
+        if ty != PieceType::Pawn {
+            s = &s[1..];
+        }
+
+        let mut promote = None;
+        if let Some((&last, rest)) = s.split_last() {
+            if let Some(ty) = promotion(last) {
+                promote = Some(ty);
+                s = rest.strip_suffix(b"=").unwrap_or(rest);
+            }
+        }
+
+        if s.len() < 2 {
+            return None;
+        }
+        let (rest, to) = s.split_at(s.len() - 2);
+        let to = Square::from_ascii(to)?.index();
+        let rest = rest.strip_suffix(b"x").unwrap_or(rest);
+
+        let (mut file, mut rank) = (None, None);
+        for &ch in rest {
+            match ch {
+                b'a'..=b'h' if file.is_none() => file = Some(ch - b'a'),
+                b'1'..=b'8' if rank.is_none() => rank = Some(ch - b'1'),
+                _ => return None,
+            }
+        }
+
+        let pieces = self.get_pieces(color).get(ty);
+        let mut found = None;
+        for mv in self.moves(color) {
+            if mv.to != to
+                || mv.ty == MoveType::Castle
+                || mv.ty.dropped().is_some()
+                || pieces & 1 << mv.from == 0
+                || file.is_some_and(|file| mv.from & 7 != file)
+                || rank.is_some_and(|rank| mv.from / 8 != rank)
+            {
+                continue;
+            }
+            if found.is_some() {
+                return None;
+            }
+            found = Some(mv);
+        }
+
+        let mut mv = found?;
+        match (mv.ty, promote) {
+            (MoveType::PawnQueenPromotion, Some(ty)) => mv.ty = ty,
+            (MoveType::PawnQueenPromotion, None) | (_, Some(_)) => return None,
+            _ => {}
+        }
+
+        Some(mv)
+    }
+}
@@ -0,0 +1,132 @@
+// A record of the search tree for diagnosing the search and the evaluation, e.g. why a free rook
+// wasn't taken: every move tried, the window it was searched with, its value and whether it cut
+// the search off. It's only recorded when `SearchOptions::tree_limit` isn't 0.
+
+use crate::Move;
+use std::fmt::{self, Write};
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TreeNode {
+    /// `Move::NULL` at the root.
+    pub mv: Move,
+    /// The window the move was searched with, from the side that played it.
+    pub alpha: i32,
+    pub beta: i32,
+    /// The move's value for the side that played it.
+    pub value: i32,
+    /// Whether the move failed high, so its siblings after it weren't searched.
+    pub cutoff: bool,
+    /// The replies in the order they were searched, the quiescence search's aren't recorded.
+    pub children: Vec<TreeNode>,
+}
+
+impl TreeNode {
+    pub(crate) fn new(mv: Move, alpha: i32, beta: i32) -> Self {
+        Self {
+            mv,
+            alpha,
+            beta,
+            value: 0,
+            cutoff: false,
+            children: Vec::new(),
+        }
+    }
+
+    /// The number of nodes in the tree.
+    pub fn size(&self) -> usize {
+        1 + self.children.iter().map(TreeNode::size).sum::<usize>()
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        self.write_json(&mut json);
+        json
+    }
+
+    fn write_json(&self, json: &mut String) {
+        json.push_str("{\"move\":");
+        if self.mv.is_null() {
+            json.push_str("null");
+        } else {
+            write!(json, "\"{}\"", self.mv).unwrap();
+        }
+        write!(
+            json,
+            ",\"alpha\":{},\"beta\":{},\"value\":{},\"cutoff\":{},\"children\":[",
+            self.alpha, self.beta, self.value, self.cutoff
+        )
+        .unwrap();
+        for (i, child) in self.children.iter().enumerate() {
+            if i != 0 {
+                json.push(',');
+            }
+            child.write_json(json);
+        }
+        json.push_str("]}");
+    }
+
+    fn fmt_indented(&self, f: &mut fmt::Formatter, indent: usize) -> fmt::Result {
+        write!(f, "{:indent$}", "", indent = 2 * indent)?;
+        if self.mv.is_null() {
+            write!(f, "root")?;
+        } else {
+            write!(f, "{}", self.mv)?;
+        }
+        write!(f, " [{}, {}] {}", self.alpha, self.beta, self.value)?;
+        if self.cutoff {
+            write!(f, " cutoff")?;
+        }
+        writeln!(f)?;
+        for child in &self.children {
+            child.fmt_indented(f, indent + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for TreeNode {
+    /// One move per line, indented by its depth, e.g. "  e7e5 [-30, 50] 12 cutoff".
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+/// Builds the tree along the search, up to `limit` nodes.
+pub(crate) struct Recorder {
+    /// The nodes on the path to the current one, the root first.
+    stack: Vec<TreeNode>,
+    len: usize,
+    limit: usize,
+}
+
+impl Recorder {
+    pub(crate) fn new(root: TreeNode, limit: usize) -> Self {
+        Self {
+            stack: vec![root],
+            len: 1,
+            limit,
+        }
+    }
+
+    /// Starts recording a move of the current node, unless the tree is full. Returns whether it
+    /// did, `exit()` has to follow only if it did.
+    pub(crate) fn enter(&mut self, mv: Move, alpha: i32, beta: i32) -> bool {
+        if self.limit <= self.len {
+            return false;
+        }
+        self.len += 1;
+        self.stack.push(TreeNode::new(mv, alpha, beta));
+        true
+    }
+
+    pub(crate) fn exit(&mut self, value: i32) {
+        let mut node = self.stack.pop().unwrap();
+        node.value = value;
+        node.cutoff = node.beta <= value;
+        self.stack.last_mut().unwrap().children.push(node);
+    }
+
+    pub(crate) fn finish(mut self) -> TreeNode {
+        self.stack.swap_remove(0)
+    }
+}
@@ -0,0 +1,389 @@
+// Games between two versions of the bot on the clock, to see how it fares at a time control and
+// whether a change made it stronger. Any `Engine` can play, e.g. a baseline as the sparring
+// partner.
+
+use crate::{clock::Clock, Color, Engine, Game, GameStatus, Score, SearchLimits};
+use std::{fmt, time::Duration};
+
+/// The time control when none is given, a minute and 0.1s per move.
+pub const DEFAULT_BASE: Duration = Duration::from_secs(60);
+pub const DEFAULT_INCREMENT: Duration = Duration::from_millis(100);
+pub const DEFAULT_GAMES: u32 = 2;
+/// How deep the bots may deepen, the clock stops them long before.
+pub const MAX_DEPTH: u32 = 64;
+
+/// When to end games whose result is clear before the rules end them, from the bots' own values
+/// in centipawns. A count of 0 disables the rule.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Adjudication {
+    /// A side resigns after valuing its position at `-resign_score` or less for `resign_moves`
+    /// of its moves in a row.
+    pub resign_score: i32,
+    pub resign_moves: u32,
+    /// The game is drawn once both sides valued the position within `draw_score` of 0 for
+    /// `draw_moves` moves each in a row, from move `draw_from` on.
+    pub draw_score: i32,
+    pub draw_moves: u32,
+    pub draw_from: usize,
+}
+
+impl Adjudication {
+    /// Plays every game out.
+    pub const NONE: Self = Self {
+        resign_score: 0,
+        resign_moves: 0,
+        draw_score: 0,
+        draw_moves: 0,
+        draw_from: 0,
+    };
+
+    /// Parses a rule like "600/4": the score and the number of moves.
+    pub fn parse_rule(s: &str) -> Option<(i32, u32)> {
+        let (score, moves) = s.split_once('/')?;
+        Some((
+            score.parse().ok().filter(|&score| 0 <= score)?,
+            moves.parse().ok()?,
+        ))
+    }
+}
+
+impl Default for Adjudication {
+    fn default() -> Self {
+        Self {
+            resign_score: 600,
+            resign_moves: 4,
+            draw_score: 10,
+            draw_moves: 8,
+            draw_from: 40,
+        }
+    }
+}
+
+/// One side's own verdicts by the rules of an `Adjudication`: whether it resigns, and whether it
+/// offers or accepts a draw, from its values of its moves.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PlayerAdjudication {
+    rules: Adjudication,
+    /// The side's moves in a row that count towards resigning and towards a draw.
+    losing: u32,
+    drawish: u32,
+}
+
+impl PlayerAdjudication {
+    pub fn new(rules: Adjudication) -> Self {
+        Self {
+            rules,
+            losing: 0,
+            drawish: 0,
+        }
+    }
+
+    /// Records the side's value of its move in centipawns. A forced move has none, and it doesn't
+    /// break the streaks.
+    pub fn record(&mut self, value: Option<i32>) {
+        if let Some(value) = value {
+            self.losing = if value <= -self.rules.resign_score {
+                self.losing + 1
+            } else {
+                0
+            };
+            self.drawish = if value.abs() <= self.rules.draw_score {
+                self.drawish + 1
+            } else {
+                0
+            };
+        }
+    }
+
+    pub fn resigns(&self) -> bool {
+        self.rules.resign_moves != 0 && self.losing >= self.rules.resign_moves
+    }
+
+    /// Whether the side offers a draw, or accepts one, at move `move_number`.
+    pub fn wants_draw(&self, move_number: usize) -> bool {
+        self.rules.draw_moves != 0
+            && self.drawish >= self.rules.draw_moves
+            && move_number >= self.rules.draw_from
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Termination {
+    Checkmate,
+    Stalemate,
+    Repetition,
+    FiftyMoves,
+    InsufficientMaterial,
+    Time,
+    Resignation,
+    /// Drawn by `Adjudication`.
+    Adjudication,
+    /// Won by a rule of the variant, see `Board::variant_winner()`.
+    VariantWin,
+    /// Lost by an engine that didn't move, e.g. an external engine that crashed.
+    Forfeit,
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Checkmate => "checkmate",
+            Self::Stalemate => "stalemate",
+            Self::Repetition => "threefold repetition",
+            Self::FiftyMoves => "fifty move rule",
+            Self::InsufficientMaterial => "insufficient material",
+            Self::Time => "time forfeit",
+            Self::Resignation => "resignation",
+            Self::Adjudication => "adjudicated draw",
+            Self::VariantWin => "variant win",
+            Self::Forfeit => "forfeit",
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GameResult {
+    /// `None` for a draw.
+    pub winner: Option<Color>,
+    pub termination: Termination,
+    pub game: Game,
+}
+
+impl GameResult {
+    /// "1-0", "0-1" or "1/2-1/2".
+    pub fn score(&self) -> &'static str {
+        match self.winner {
+            Some(Color::White) => "1-0",
+            Some(Color::Black) => "0-1",
+            None => "1/2-1/2",
+        }
+    }
+}
+
+/// Continues `opening` until the game ends, `engines` indexed by `Color as usize`.
+pub fn play_game(
+    engines: [&mut dyn Engine; 2],
+    opening: &Game,
+    mut clock: Clock,
+    adjudication: &Adjudication,
+) -> GameResult {
+    let mut game = opening.clone();
+    // The half moves since the last capture or pawn move.
+    let mut reversible = 0;
+    let mut players = [PlayerAdjudication::new(*adjudication); 2];
+
+    let (winner, termination) = loop {
+        let color = game.turn();
+        let board = *game.board();
+        match game.status() {
+            GameStatus::Checkmate(winner) => break (Some(winner), Termination::Checkmate),
+            GameStatus::Stalemate => break (None, Termination::Stalemate),
+            GameStatus::VariantWin(winner) => break (Some(winner), Termination::VariantWin),
+            GameStatus::Ongoing => {}
+        }
+        if board.is_insufficient_material() {
+            break (None, Termination::InsufficientMaterial);
+        }
+        let key = board.position_key(color);
+        if game.history().iter().filter(|&&prev| prev == key).count() >= 2 {
+            break (None, Termination::Repetition);
+        }
+        if reversible >= 100 {
+            break (None, Termination::FiftyMoves);
+        }
+
+        clock.start(color);
+        let limits = SearchLimits {
+            time: Some(clock.time_manager(color)),
+            history: game.history(),
+            ..SearchLimits::default()
+        };
+        let result = engines[color as usize].best_move(&board, color, &limits);
+        clock.stop();
+        if clock.flag_fell(color) {
+            break (Some(color.inv()), Termination::Time);
+        }
+        let mv = match result.best_move {
+            Some(mv) => mv,
+            None => break (Some(color.inv()), Termination::Forfeit),
+        };
+
+        players[color as usize].record(result.score.map(Score::centipawns));
+        if players[color as usize].resigns() {
+            break (Some(color.inv()), Termination::Resignation);
+        }
+        let move_number = game.moves().len() / 2 + 1;
+        if players.iter().all(|player| player.wants_draw(move_number)) {
+            break (None, Termination::Adjudication);
+        }
+
+        let irreversible = board.get_at(1 << mv.to).is_some()
+            || board.get_pieces(color).pawns() & 1 << mv.from != 0;
+        reversible = if irreversible { 0 } else { reversible + 1 };
+        game.push_move(mv);
+    };
+
+    GameResult {
+        winner,
+        termination,
+        game,
+    }
+}
+
+/// A sequential probability ratio test of whether one engine is `elo1` rather than `elo0` Elo
+/// stronger than another, wrongly accepting `elo1` with probability `alpha` and `elo0` with
+/// `beta`. The games decide it as they come instead of after a fixed number.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl Sprt {
+    pub fn new(elo0: f64, elo1: f64) -> Self {
+        Self {
+            elo0,
+            elo1,
+            alpha: 0.05,
+            beta: 0.05,
+        }
+    }
+
+    /// Parses "elo0/elo1" or "elo0/elo1/alpha/beta", e.g. "0/5" or "0/5/0.05/0.05".
+    pub fn parse(s: &str) -> Option<Self> {
+        let values = s
+            .split('/')
+            .map(|value| value.parse().ok().filter(|value: &f64| value.is_finite()))
+            .collect::<Option<Vec<_>>>()?;
+        let sprt = match values[..] {
+            [elo0, elo1] => Self::new(elo0, elo1),
+            [elo0, elo1, alpha, beta] => Self {
+                elo0,
+                elo1,
+                alpha,
+                beta,
+            },
+            _ => return None,
+        };
+        let probability = |p: f64| 0.0 < p && p < 0.5;
+        Some(sprt).filter(|sprt| {
+            sprt.elo0 < sprt.elo1 && probability(sprt.alpha) && probability(sprt.beta)
+        })
+    }
+
+    /// The log-likelihood ratio of `elo1` to `elo0` after the tested engine's `wins`, `draws`
+    /// and `losses`, in the normal approximation of the score per game.
+    pub fn llr(&self, wins: u32, draws: u32, losses: u32) -> f64 {
+        let games = (wins + draws + losses) as f64;
+        if games == 0.0 {
+            return 0.0;
+        }
+        let (wins, draws, losses) = (wins as f64, draws as f64, losses as f64);
+        let score = (wins + draws / 2.0) / games;
+        let variance =
+            (wins * (1.0 - score).powi(2) + draws * (0.5 - score).powi(2) + losses * score.powi(2))
+                / games;
+        if variance == 0.0 {
+            return 0.0;
+        }
+        let expected = |elo: f64| 1.0 / (1.0 + 10f64.powf(-elo / 400.0));
+        let (score0, score1) = (expected(self.elo0), expected(self.elo1));
+        games * (score1 - score0) * (2.0 * score - score0 - score1) / (2.0 * variance)
+    }
+
+    /// The LLRs at which `elo0` and `elo1` are accepted.
+    pub fn bounds(&self) -> (f64, f64) {
+        (
+            (self.beta / (1.0 - self.alpha)).ln(),
+            ((1.0 - self.beta) / self.alpha).ln(),
+        )
+    }
+
+    /// `Some(true)` once `elo1` is accepted, `Some(false)` once `elo0` is and `None` while
+    /// it's undecided.
+    pub fn decision(&self, llr: f64) -> Option<bool> {
+        let (lower, upper) = self.bounds();
+        if upper <= llr {
+            Some(true)
+        } else if llr <= lower {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+/// Plays up to `games` games on `clock` between `engines`, the base and the tested engine, and
+/// prints the results. The games go through `openings` in order, each is played twice with the
+/// colors reversed. The tested engine's results decide `sprt`, which ends the match early.
+pub fn run(
+    engines: [&mut dyn Engine; 2],
+    openings: &[Game],
+    games: u32,
+    clock: Clock,
+    adjudication: &Adjudication,
+    sprt: Option<&Sprt>,
+) {
+    let [base, test] = engines;
+    let mut scores = [0; 3];
+    // The tested engine's wins, draws and losses.
+    let mut results = [0; 3];
+    for i in 0..games {
+        let test_color = if i % 2 == 0 {
+            Color::White
+        } else {
+            Color::Black
+        };
+        let players: [&mut dyn Engine; 2] = match test_color {
+            Color::White => [&mut *test, &mut *base],
+            Color::Black => [&mut *base, &mut *test],
+        };
+        let opening = &openings[i as usize / 2 % openings.len()];
+        let result = play_game(players, opening, clock, adjudication);
+        scores[match result.winner {
+            Some(Color::White) => 0,
+            None => 1,
+            Some(Color::Black) => 2,
+        }] += 1;
+        results[match result.winner {
+            Some(winner) if winner == test_color => 0,
+            None => 1,
+            Some(_) => 2,
+        }] += 1;
+        println!(
+            "Game {}: {} ({}) after {} moves, the tested engine played {:?}",
+            i + 1,
+            result.score(),
+            result.termination,
+            result.game.moves().len().div_ceil(2),
+            test_color
+        );
+
+        if let Some(sprt) = sprt {
+            let llr = sprt.llr(results[0], results[1], results[2]);
+            let (lower, upper) = sprt.bounds();
+            println!("LLR {:.2} ({:.2}, {:.2})", llr, lower, upper);
+            match sprt.decision(llr) {
+                Some(true) => {
+                    println!("H1 accepted: elo >= {}", sprt.elo1);
+                    break;
+                }
+                Some(false) => {
+                    println!("H0 accepted: elo <= {}", sprt.elo0);
+                    break;
+                }
+                None => {}
+            }
+        }
+    }
+    println!(
+        "White {} wins, {} draws, Black {} wins",
+        scores[0], scores[1], scores[2]
+    );
+    println!(
+        "Tested engine {} wins, {} draws, {} losses",
+        results[0], results[1], results[2]
+    );
+}
@@ -0,0 +1,363 @@
+// A tiny HTTP/1.1 JSON API over `Game`:
+//
+//   POST /game                          -> creates a game, returns its state
+//   GET  /game/{id}                     -> the game's state
+//   POST /game/{id}/move  {"move": ..}  -> plays a move like "e2e4" or "e7e8q"
+//   GET  /game/{id}/bestmove?depth=N    -> the bot's move for the side to move
+//   GET  /game/{id}/svg?flip=1          -> the board as an SVG image, the last move highlighted
+
+use crate::{
+    game::{Game, GameStatus},
+    render::{self, Perspective, SvgOptions},
+    Bot, Color, Square,
+};
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+pub const DEFAULT_ADDR: &str = "127.0.0.1:8080";
+
+// Deeper searches can take minutes, don't let a single request hog the server.
+const MAX_DEPTH: u32 = 8;
+
+// The longest body a request may have. A move is a few dozen bytes, the limit keeps a client from
+// having the server allocate whatever its Content-Length claims.
+const MAX_BODY: usize = 4096;
+
+// The longest request line and headers together. They're read before the body's limit is known.
+const MAX_HEAD: u64 = 8192;
+
+// How long a read may wait for the client, so that a silent client doesn't keep its connection's
+// thread forever.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Each connection gets a thread of its own, past this many the new ones are turned away.
+const MAX_CONNECTIONS: usize = 64;
+
+// The games are kept until the server stops, past this many no new ones are created.
+const MAX_GAMES: usize = 1024;
+
+#[derive(Default)]
+struct Games {
+    next_id: u64,
+    games: HashMap<u64, Game>,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: String,
+    body: String,
+}
+
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn ok(body: String) -> Self {
+        Self {
+            status: "200 OK",
+            content_type: "application/json",
+            body,
+        }
+    }
+
+    fn svg(body: String) -> Self {
+        Self {
+            status: "200 OK",
+            content_type: "image/svg+xml",
+            body,
+        }
+    }
+
+    fn error(status: &'static str, msg: &str) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: format!("{{\"error\":\"{}\"}}", msg),
+        }
+    }
+}
+
+/// A connection's place among the `MAX_CONNECTIONS`, given back when the connection's thread ends,
+/// even if it panics.
+struct Slot(Arc<AtomicUsize>);
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Serves the API on `addr` until the listener fails.
+pub fn run(addr: &str) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    println!("Listening on http://{}", listener.local_addr()?);
+
+    let games = Arc::new(Mutex::new(Games::default()));
+    let connections = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let stream = stream?;
+        if connections.fetch_add(1, Ordering::AcqRel) >= MAX_CONNECTIONS {
+            connections.fetch_sub(1, Ordering::AcqRel);
+            let response = Response::error("503 Service Unavailable", "too many connections");
+            if let Err(err) = write_response(stream, &response) {
+                log::warn!("Connection error: {}", err);
+            }
+            continue;
+        }
+
+        let games = games.clone();
+        let slot = Slot(connections.clone());
+        thread::spawn(move || {
+            let _slot = slot;
+            if let Err(err) = handle_connection(stream, &games) {
+                log::warn!("Connection error: {}", err);
+            }
+        });
+    }
+
+    Ok(())
+}
+
+fn handle_connection(stream: TcpStream, games: &Mutex<Games>) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let response = match read_request(&mut reader)? {
+        Ok(req) => route(&req, games),
+        Err(response) => response,
+    };
+    write_response(stream, &response)
+}
+
+fn write_response(mut stream: TcpStream, response: &Response) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.content_type,
+        response.body.len(),
+        response.body,
+    )?;
+    stream.flush()
+}
+
+/// The request, or the error response to a malformed or too large one.
+fn read_request(reader: &mut impl BufRead) -> io::Result<Result<Request, Response>> {
+    let malformed = || Response::error("400 Bad Request", "malformed request");
+
+    let mut head = reader.by_ref().take(MAX_HEAD);
+    let mut line = String::new();
+    head.read_line(&mut line)?;
+
+    let mut parts = line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method.to_owned(), target.to_owned()),
+        _ => return Ok(Err(malformed())),
+    };
+
+    let mut content_length = 0;
+    loop {
+        line.clear();
+        if head.read_line(&mut line)? == 0 {
+            if head.limit() == 0 {
+                return Ok(Err(Response::error(
+                    "431 Request Header Fields Too Large",
+                    "request head too large",
+                )));
+            }
+            break;
+        }
+        let header = line.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = match value.trim().parse() {
+                    Ok(len) => len,
+                    Err(_) => return Ok(Err(malformed())),
+                };
+            }
+        }
+    }
+
+    if content_length > MAX_BODY {
+        return Ok(Err(Response::error(
+            "413 Payload Too Large",
+            "request body too large",
+        )));
+    }
+    let mut body = Vec::with_capacity(content_length);
+    reader.take(content_length as u64).read_to_end(&mut body)?;
+    if body.len() < content_length {
+        return Ok(Err(malformed()));
+    }
+
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path.to_owned(), query.to_owned()),
+        None => (target, String::new()),
+    };
+
+    Ok(Ok(Request {
+        method,
+        path,
+        query,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    }))
+}
+
+fn route(req: &Request, games: &Mutex<Games>) -> Response {
+    let segments: Vec<_> = req.path.trim_matches('/').split('/').collect();
+
+    match (req.method.as_str(), segments.as_slice()) {
+        ("POST", ["game"]) => {
+            let mut games = games.lock().unwrap();
+            if games.games.len() >= MAX_GAMES {
+                return Response::error("503 Service Unavailable", "too many games");
+            }
+            let id = games.next_id;
+            games.next_id += 1;
+
+            let game = Game::new();
+            let body = game_json(id, &game);
+            games.games.insert(id, game);
+
+            Response::ok(body)
+        }
+        ("GET", ["game", id]) => with_game(games, id, |id, game| Response::ok(game_json(id, game)))
+            .unwrap_or_else(|response| response),
+        ("POST", ["game", id, "move"]) => with_game(games, id, |id, game| {
+            let mv = match json_str_field(&req.body, "move") {
+                Some(mv) => mv,
+                None => return Response::error("400 Bad Request", "missing move"),
+            };
+
+            match game.board().parse_move(game.turn(), mv) {
+                Some(mv) if game.push_move(mv) => Response::ok(game_json(id, game)),
+                _ => Response::error("422 Unprocessable Entity", "illegal move"),
+            }
+        })
+        .unwrap_or_else(|response| response),
+        ("GET", ["game", id, "bestmove"]) => {
+            let depth = match query_param(&req.query, "depth").map(str::parse::<u32>) {
+                None => Bot::DEFAULT_DEPTH,
+                Some(Ok(depth)) if (1..=MAX_DEPTH).contains(&depth) => depth,
+                Some(_) => return Response::error("400 Bad Request", "bad depth"),
+            };
+
+            // Search on a copy, so the other games aren't locked during the search.
+            let game = match with_game(games, id, |_, game| game.clone()) {
+                Ok(game) => game,
+                Err(response) => return response,
+            };
+
+            match Bot::with_depth(depth).choose_move_with_history(
+                game.board(),
+                game.turn(),
+                game.history(),
+            ) {
+                Some(mv) => Response::ok(format!("{{\"move\":\"{}\"}}", mv)),
+                None => Response::ok("{\"move\":null}".to_owned()),
+            }
+        }
+        ("GET", ["game", id, "svg"]) => with_game(games, id, |_, game| {
+            let options = SvgOptions {
+                perspective: match query_param(&req.query, "flip") {
+                    Some("1") | Some("true") => Perspective::Black,
+                    Some("auto") => Perspective::Auto,
+                    _ => Perspective::White,
+                },
+                to_move: game.turn(),
+                last_move: game.moves().last().and_then(|mv| {
+                    Some((Square::from_index(mv.from)?, Square::from_index(mv.to)?))
+                }),
+                ..SvgOptions::default()
+            };
+            Response::svg(render::to_svg(game.board(), &options))
+        })
+        .unwrap_or_else(|response| response),
+        ("GET", _) | ("POST", _) => Response::error("404 Not Found", "not found"),
+        _ => Response::error("405 Method Not Allowed", "method not allowed"),
+    }
+}
+
+/// Runs `f` on the game with the given id, or fails with a 404 response.
+fn with_game<T>(
+    games: &Mutex<Games>,
+    id: &str,
+    f: impl FnOnce(u64, &mut Game) -> T,
+) -> Result<T, Response> {
+    let not_found = || Response::error("404 Not Found", "no such game");
+
+    let id = id.parse().map_err(|_| not_found())?;
+    let mut games = games.lock().unwrap();
+    let game = games.games.get_mut(&id).ok_or_else(not_found)?;
+
+    Ok(f(id, game))
+}
+
+fn game_json(id: u64, game: &Game) -> String {
+    let moves: Vec<_> = game
+        .moves()
+        .iter()
+        .map(|mv| format!("\"{}\"", mv))
+        .collect();
+    let legal_moves: Vec<_> = game
+        .legal_moves()
+        .iter()
+        .map(|mv| format!("\"{}\"", mv))
+        .collect();
+
+    format!(
+        "{{\"id\":{},\"turn\":\"{}\",\"status\":\"{}\",\"moves\":[{}],\"legal_moves\":[{}]}}",
+        id,
+        color_str(game.turn()),
+        match game.status() {
+            GameStatus::Ongoing => "ongoing".to_owned(),
+            GameStatus::Stalemate => "stalemate".to_owned(),
+            GameStatus::Checkmate(winner) | GameStatus::VariantWin(winner) => {
+                format!("{} wins", color_str(winner))
+            }
+        },
+        moves.join(","),
+        legal_moves.join(","),
+    )
+}
+
+fn color_str(color: Color) -> &'static str {
+    match color {
+        Color::White => "white",
+        Color::Black => "black",
+    }
+}
+
+fn query_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|&(key, _)| key == name)
+        .map(|(_, value)| value)
+}
+
+// Just enough JSON to read a flat string field, e.g. `{"move": "e2e4"}`.
+fn json_str_field<'a>(json: &'a str, name: &str) -> Option<&'a str> {
+    let key = format!("\"{}\"", name);
+    let rest = json[json.find(&key)? + key.len()..].trim_start();
+    let rest = rest.strip_prefix(':')?.trim_start();
+    let rest = rest.strip_prefix('"')?;
+
+    Some(&rest[..rest.find('"')?])
+}
@@ -0,0 +1,137 @@
+// The squares of the board by name. Internally a square is the `u8` index of its bit in the
+// bitboards, 8 * rank + file from a1 = 0 to h8 = 63, which `Square` wraps for code that handles
+// squares by their names and coordinates.
+
+use core::{fmt, str::FromStr};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub struct Square(u8);
+
+macro_rules! squares {
+    ($($rank:literal: $a:ident $b:ident $c:ident $d:ident $e:ident $f:ident $g:ident $h:ident;)*) => {
+        impl Square {
+            $(
+                pub const $a: Self = Self(8 * $rank);
+                pub const $b: Self = Self(8 * $rank + 1);
+                pub const $c: Self = Self(8 * $rank + 2);
+                pub const $d: Self = Self(8 * $rank + 3);
+                pub const $e: Self = Self(8 * $rank + 4);
+                pub const $f: Self = Self(8 * $rank + 5);
+                pub const $g: Self = Self(8 * $rank + 6);
+                pub const $h: Self = Self(8 * $rank + 7);
+            )*
+        }
+    };
+}
+
+squares! {
+    0: A1 B1 C1 D1 E1 F1 G1 H1;
+    1: A2 B2 C2 D2 E2 F2 G2 H2;
+    2: A3 B3 C3 D3 E3 F3 G3 H3;
+    3: A4 B4 C4 D4 E4 F4 G4 H4;
+    4: A5 B5 C5 D5 E5 F5 G5 H5;
+    5: A6 B6 C6 D6 E6 F6 G6 H6;
+    6: A7 B7 C7 D7 E7 F7 G7 H7;
+    7: A8 B8 C8 D8 E8 F8 G8 H8;
+}
+
+impl Square {
+    /// The square on `file` (0 for the a-file) and `rank` (0 for the first rank), `None` off
+    /// the board.
+    pub const fn new(file: u8, rank: u8) -> Option<Self> {
+        if file < 8 && rank < 8 {
+            Some(Self(8 * rank + file))
+        } else {
+            None
+        }
+    }
+
+    /// The square of the bit `index` of a bitboard, like `Move::from` and `Move::to`.
+    pub const fn from_index(index: u8) -> Option<Self> {
+        if index < 64 {
+            Some(Self(index))
+        } else {
+            None
+        }
+    }
+
+    /// The square of a bit index known to be on the board, e.g. a legal move's.
+    pub(crate) const fn of(index: u8) -> Self {
+        debug_assert!(index < 64);
+        Self(index)
+    }
+
+    /// Parses a name like "e4" from bytes, see `FromStr`.
+    pub fn from_ascii(name: &[u8]) -> Option<Self> {
+        match *name {
+            [file @ b'a'..=b'h', rank @ b'1'..=b'8'] => Self::new(file - b'a', rank - b'1'),
+            _ => None,
+        }
+    }
+
+    /// The index of the square's bit in a bitboard.
+    pub const fn index(self) -> u8 {
+        self.0
+    }
+
+    /// The square's bitboard.
+    pub const fn bit(self) -> u64 {
+        1 << self.0
+    }
+
+    /// 0 for the a-file to 7 for the h-file.
+    pub const fn file(self) -> u8 {
+        self.0 & 7
+    }
+
+    /// 0 for the first rank to 7 for the eighth.
+    pub const fn rank(self) -> u8 {
+        self.0 / 8
+    }
+
+    /// The number of king moves between the squares.
+    pub fn distance(self, other: Self) -> u8 {
+        let files = (self.file() as i8 - other.file() as i8).unsigned_abs();
+        let ranks = (self.rank() as i8 - other.rank() as i8).unsigned_abs();
+        files.max(ranks)
+    }
+}
+
+impl From<Square> for u8 {
+    fn from(square: Square) -> Self {
+        square.0
+    }
+}
+
+impl fmt::Display for Square {
+    /// The square's name, like "e4".
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            (b'a' + self.file()) as char,
+            (b'1' + self.rank()) as char
+        )
+    }
+}
+
+/// The text isn't a square's name.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ParseSquareError;
+
+impl fmt::Display for ParseSquareError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Bad square")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseSquareError {}
+
+impl FromStr for Square {
+    type Err = ParseSquareError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_ascii(s.as_bytes()).ok_or(ParseSquareError)
+    }
+}
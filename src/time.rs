@@ -0,0 +1,126 @@
+// Time management: how long to think about a move given the clock, and a token to stop a search
+// that's running out of time (or that the GUI wants to stop).
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// The moves the remaining time is spread over when the GUI doesn't say.
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+/// Kept in reserve for the communication with the GUI.
+const MOVE_OVERHEAD: Duration = Duration::from_millis(50);
+/// With less time than this left, after the overhead, the move is played right after the first
+/// iteration: a shallow move beats a flag fall.
+const LOW_TIME: Duration = Duration::from_secs(1);
+/// The limits of a search without a time limit, a century.
+const UNLIMITED: Duration = Duration::from_secs(100 * 365 * 24 * 60 * 60);
+
+/// Stops a running search once `stop()` is called, shared between the search and its controller.
+#[derive(Clone, Default, Debug)]
+pub struct StopToken(Arc<AtomicBool>);
+
+impl StopToken {
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
+
+/// The time limits of a single move. The search stops deepening past the soft limit and is
+/// aborted at the hard limit.
+#[derive(Clone, Copy, Debug)]
+pub struct TimeManager {
+    start: Instant,
+    soft: Duration,
+    hard: Duration,
+}
+
+impl TimeManager {
+    /// Limits for the side to move's clock, as in UCI's wtime/winc/movestogo. Without movestogo
+    /// it's sudden death, or sudden death with an increment, and the time is spread over
+    /// `DEFAULT_MOVES_TO_GO` moves.
+    pub fn for_clock(time_left: Duration, increment: Duration, moves_to_go: Option<u32>) -> Self {
+        let moves_to_go = moves_to_go.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+        let available = time_left.saturating_sub(MOVE_OVERHEAD.min(time_left / 10));
+        if available < LOW_TIME {
+            log::debug!("Low on time, {:?} left", available);
+            return Self {
+                start: Instant::now(),
+                soft: Duration::ZERO,
+                hard: available / 10,
+            };
+        }
+
+        let soft = (available / moves_to_go + increment * 3 / 4).min(available / 2);
+        let hard = (soft * 4).min(available * 3 / 4);
+        log::debug!("Soft limit {:?}, hard limit {:?}", soft, hard);
+        Self {
+            start: Instant::now(),
+            soft,
+            hard,
+        }
+    }
+
+    /// Exactly `time` for the move, as in UCI's movetime.
+    pub fn fixed(time: Duration) -> Self {
+        Self {
+            start: Instant::now(),
+            soft: time,
+            hard: time,
+        }
+    }
+
+    /// No time limit, for the searches that something else stops, e.g. UCI's nodes or stop.
+    pub fn unlimited() -> Self {
+        Self {
+            start: Instant::now(),
+            soft: UNLIMITED,
+            hard: UNLIMITED,
+        }
+    }
+
+    pub fn soft_limit(&self) -> Duration {
+        self.soft
+    }
+
+    pub fn hard_limit(&self) -> Duration {
+        self.hard
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Whether the clock is so low that the move is played right after the first iteration.
+    pub fn is_low_time(&self) -> bool {
+        self.soft.is_zero()
+    }
+
+    /// When the search has to be aborted.
+    pub fn deadline(&self) -> Instant {
+        self.start + self.hard
+    }
+
+    /// Gives the move more time, up to the hard limit, when the best move failed low or changed.
+    pub fn extend(&mut self) {
+        self.soft = (self.soft * 3 / 2).min(self.hard);
+        log::debug!("Soft limit extended to {:?}", self.soft);
+    }
+
+    /// Whether another iteration is likely to finish before the soft limit. Every iteration takes
+    /// a few times longer than the last, so it isn't started past half of it.
+    pub fn should_start_iteration(&self) -> bool {
+        self.elapsed() < self.soft / 2
+    }
+}
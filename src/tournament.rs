@@ -0,0 +1,184 @@
+// Tournaments between any number of engines, the bot's versions and external UCI engines alike:
+// every engine plays every other, or the first engine plays all the rest in a gauntlet. The games
+// are played one after the other by `selfplay::play_game()`, so the engines don't share the
+// cores.
+
+use crate::{
+    clock::Clock,
+    pgn::PgnGame,
+    selfplay::{play_game, Adjudication},
+    Color, Engine, Game,
+};
+use std::fmt;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Format {
+    /// Every engine plays every other.
+    RoundRobin,
+    /// The first engine plays every other, who don't play each other.
+    Gauntlet,
+}
+
+pub struct Player {
+    pub name: String,
+    pub engine: Box<dyn Engine>,
+}
+
+impl Player {
+    pub fn new(name: impl Into<String>, engine: Box<dyn Engine>) -> Self {
+        Self {
+            name: name.into(),
+            engine,
+        }
+    }
+}
+
+/// The results of a finished tournament.
+#[derive(Clone, Debug)]
+pub struct Tournament {
+    pub names: Vec<String>,
+    /// `results[i][j]` is the wins, draws and losses of engine `i` against engine `j`.
+    pub results: Vec<Vec<[u32; 3]>>,
+    /// All the games in the order they were played, with their players and results.
+    pub games: Vec<PgnGame>,
+}
+
+impl Tournament {
+    /// The points of engine `i`, a win is a point and a draw half of one.
+    pub fn points(&self, i: usize) -> f64 {
+        self.results[i]
+            .iter()
+            .map(|&[wins, draws, _]| wins as f64 + draws as f64 / 2.0)
+            .sum()
+    }
+
+    /// The games engine `i` played.
+    pub fn games_of(&self, i: usize) -> u32 {
+        self.results[i].iter().flatten().sum()
+    }
+
+    /// The engines from the most points to the least.
+    pub fn ranking(&self) -> Vec<usize> {
+        let mut ranking: Vec<_> = (0..self.names.len()).collect();
+        ranking.sort_by(|&a, &b| self.points(b).total_cmp(&self.points(a)));
+        ranking
+    }
+
+    /// All the games in PGN, separated by blank lines.
+    pub fn pgn(&self) -> String {
+        self.games
+            .iter()
+            .map(|game| game.to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl fmt::Display for Tournament {
+    /// The cross-table, with the engines ranked by points and their scores against each other.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ranking = self.ranking();
+        let width = self.names.iter().map(|name| name.len()).max().unwrap_or(0);
+        write!(f, " #  {:width$}  Points  Games", "Engine", width = width)?;
+        for rank in 1..=ranking.len() {
+            write!(f, "  {:>5}", rank)?;
+        }
+        writeln!(f)?;
+        for (rank, &i) in ranking.iter().enumerate() {
+            write!(
+                f,
+                "{:2}  {:width$}  {:6.1}  {:5}",
+                rank + 1,
+                self.names[i],
+                self.points(i),
+                self.games_of(i),
+                width = width
+            )?;
+            for &j in &ranking {
+                let [wins, draws, losses] = self.results[i][j];
+                if i == j || wins + draws + losses == 0 {
+                    write!(f, "  {:>5}", "-")?;
+                } else {
+                    write!(f, "  {:5.1}", wins as f64 + draws as f64 / 2.0)?;
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// The pairs of players that meet in `format`, by their indices.
+fn pairings(format: Format, players: usize) -> Vec<(usize, usize)> {
+    match format {
+        Format::RoundRobin => (0..players)
+            .flat_map(|i| (i + 1..players).map(move |j| (i, j)))
+            .collect(),
+        Format::Gauntlet => (1..players).map(|j| (0, j)).collect(),
+    }
+}
+
+/// Plays `games` games on `clock` between every pair of `players` that meets in `format`, and
+/// prints the results as they come. Like `selfplay::run()`, every pair goes through `openings` in
+/// order and plays each one twice with the colors reversed.
+pub fn run(
+    players: &mut [Player],
+    format: Format,
+    games: u32,
+    openings: &[Game],
+    clock: Clock,
+    adjudication: &Adjudication,
+) -> Tournament {
+    let mut tournament = Tournament {
+        names: players.iter().map(|player| player.name.clone()).collect(),
+        results: vec![vec![[0; 3]; players.len()]; players.len()],
+        games: Vec::new(),
+    };
+    for (a, b) in pairings(format, players.len()) {
+        for i in 0..games {
+            let (white, black) = if i % 2 == 0 { (a, b) } else { (b, a) };
+            let (first, rest) = players.split_at_mut(b);
+            let (player_a, player_b) = (&mut first[a], &mut rest[0]);
+            let engines: [&mut dyn Engine; 2] = if white == a {
+                [&mut *player_a.engine, &mut *player_b.engine]
+            } else {
+                [&mut *player_b.engine, &mut *player_a.engine]
+            };
+            let opening = &openings[i as usize / 2 % openings.len()];
+            let result = play_game(engines, opening, clock, adjudication);
+
+            let (white_result, black_result) = match result.winner {
+                Some(Color::White) => (0, 2),
+                None => (1, 1),
+                Some(Color::Black) => (2, 0),
+            };
+            tournament.results[white][black][white_result] += 1;
+            tournament.results[black][white][black_result] += 1;
+
+            let round = tournament.games.len() + 1;
+            println!(
+                "Game {}: {} - {} {} ({}) after {} moves",
+                round,
+                tournament.names[white],
+                tournament.names[black],
+                result.score(),
+                result.termination,
+                result.game.moves().len().div_ceil(2)
+            );
+            let mut pgn = PgnGame::from_game(&result.game);
+            let mut tags = vec![
+                ("Event".to_owned(), "mxchess tournament".to_owned()),
+                ("Round".to_owned(), round.to_string()),
+                ("White".to_owned(), tournament.names[white].clone()),
+                ("Black".to_owned(), tournament.names[black].clone()),
+                ("Result".to_owned(), result.score().to_owned()),
+                ("Termination".to_owned(), result.termination.to_string()),
+            ];
+            tags.append(&mut pgn.tags);
+            pgn.tags = tags;
+            pgn.result = result.score().to_owned();
+            tournament.games.push(pgn);
+        }
+    }
+    tournament
+}
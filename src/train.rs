@@ -0,0 +1,166 @@
+// Opening repertoire training: the trainer plays the other side's repertoire moves and quizzes
+// the user on theirs.
+
+use crate::{
+    pgn::{self, PgnMove},
+    rng::Rng,
+    Board, Color, Game, Move,
+};
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+};
+
+/// The repertoire's moves in every position it reaches.
+#[derive(Clone, Default)]
+pub struct Repertoire {
+    moves: HashMap<(Board, Color), Vec<Move>>,
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+impl Repertoire {
+    /// Collects the moves of all the games in `pgn`, including their variations.
+    pub fn from_pgn(pgn: &str) -> io::Result<Self> {
+        let mut repertoire = Self::default();
+        for game in pgn::parse(pgn).map_err(|err| invalid_data(err.to_string()))? {
+            repertoire.add_line(Game::new(), &game.moves)?;
+        }
+        Ok(repertoire)
+    }
+
+    fn add_line(&mut self, mut game: Game, line: &[PgnMove]) -> io::Result<()> {
+        for mv in line {
+            for variation in &mv.variations {
+                self.add_line(game.clone(), variation)?;
+            }
+
+            let parsed = game
+                .board()
+                .parse_san(game.turn(), &mv.san)
+                .ok_or_else(|| {
+                    invalid_data(format!("Illegal move in the repertoire: {}", mv.san))
+                })?;
+
+            let moves = self.moves.entry((*game.board(), game.turn())).or_default();
+            if !moves.contains(&parsed) {
+                moves.push(parsed);
+            }
+            game.push_move(parsed);
+        }
+        Ok(())
+    }
+
+    /// The repertoire's moves in the game's current position.
+    pub fn moves(&self, game: &Game) -> &[Move] {
+        self.moves
+            .get(&(*game.board(), game.turn()))
+            .map_or(&[], |moves| moves.as_slice())
+    }
+}
+
+/// Quizzes the user, who plays `side`, on the repertoire until they quit.
+/// The opponent's replies are picked by `rng`.
+pub fn run(
+    repertoire: &Repertoire,
+    side: Color,
+    rng: &mut Rng,
+    mut input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<()> {
+    let (mut correct, mut total) = (0, 0);
+    let mut buf = String::new();
+
+    'lines: loop {
+        writeln!(output)?;
+        writeln!(output, "New line")?;
+        writeln!(output, "--------")?;
+
+        let mut game = Game::new();
+        loop {
+            let candidates = repertoire.moves(&game);
+            if candidates.is_empty() {
+                break;
+            }
+
+            let mv = if game.turn() == side {
+                writeln!(output)?;
+                write!(output, "{}", game.board().render(side))?;
+                write!(output, "Your move: ")?;
+                output.flush()?;
+
+                buf.clear();
+                if input.read_line(&mut buf)? == 0 || buf.trim() == "quit" {
+                    break 'lines;
+                }
+                let board = game.board();
+                let mv = match crate::input::parse_move(board, side, &buf) {
+                    Ok(mv) => mv,
+                    Err(err) => {
+                        writeln!(output, "Illegal move: {}", err)?;
+                        continue;
+                    }
+                };
+
+                total += 1;
+                if candidates.contains(&mv) {
+                    correct += 1;
+                    writeln!(output, "Correct!")?;
+                    mv
+                } else {
+                    let expected: Vec<_> = candidates
+                        .iter()
+                        .map(|&mv| board.to_san(side, mv))
+                        .collect();
+                    writeln!(
+                        output,
+                        "Wrong, the repertoire plays {}",
+                        expected.join(" or ")
+                    )?;
+                    candidates[0]
+                }
+            } else {
+                let mv = candidates[rng.below(candidates.len())];
+                writeln!(
+                    output,
+                    "Opponent plays {}",
+                    game.board().to_san(game.turn(), mv)
+                )?;
+                mv
+            };
+
+            game.push_move(mv);
+        }
+
+        writeln!(output)?;
+        writeln!(
+            output,
+            "End of the line, score so far: {}/{}",
+            correct, total
+        )?;
+        write!(output, "Another line? [Y/n] ")?;
+        output.flush()?;
+
+        buf.clear();
+        if input.read_line(&mut buf)? == 0 || buf.trim().eq_ignore_ascii_case("n") {
+            break;
+        }
+    }
+
+    writeln!(output)?;
+    if total == 0 {
+        writeln!(output, "No moves were played")?;
+    } else {
+        writeln!(
+            output,
+            "Accuracy: {}/{} ({:.0}%)",
+            correct,
+            total,
+            100.0 * correct as f64 / total as f64
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,75 @@
+// A transposition table that any number of threads share without locks: the threads of a search,
+// a ponder search and an analysis alike. Every entry is the key xor the data and the data, so an
+// entry that two threads wrote at once doesn't match any key and reads as empty.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Maps position keys (`Board::position_key()`) to 64 bits of data, the last entry stored in a
+/// slot replaces the one before.
+pub struct TranspositionTable {
+    entries: Vec<[AtomicU64; 2]>,
+    bits: u32,
+}
+
+impl TranspositionTable {
+    /// The largest power of two of entries that fits in `hash_mb` MiB, at least one.
+    pub fn new(hash_mb: usize) -> Self {
+        let len = (hash_mb << 20) / core::mem::size_of::<[AtomicU64; 2]>();
+        let bits = usize::BITS - 1 - len.max(1).leading_zeros();
+        Self {
+            entries: (0..1usize << bits)
+                .map(|_| [AtomicU64::new(0), AtomicU64::new(0)])
+                .collect(),
+            bits,
+        }
+    }
+
+    /// The number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn entry(&self, key: u64) -> &[AtomicU64; 2] {
+        // `checked_shr` since a single entry would shift by 64.
+        let i = key.checked_shr(64 - self.bits).unwrap_or(0);
+        &self.entries[i as usize]
+    }
+
+    /// The data stored last for `key`, unless another key took its slot since.
+    pub fn probe(&self, key: u64) -> Option<u64> {
+        let [check, data] = self.entry(key);
+        let (check, data) = (check.load(Ordering::Relaxed), data.load(Ordering::Relaxed));
+        (check ^ data == key).then_some(data)
+    }
+
+    pub fn store(&self, key: u64, data: u64) {
+        let [check, entry] = self.entry(key);
+        check.store(key ^ data, Ordering::Relaxed);
+        entry.store(data, Ordering::Relaxed);
+    }
+
+    /// How full the table is in permille, as in UCI's hashfull, from a sample of the first
+    /// thousand entries.
+    pub fn hashfull(&self) -> u32 {
+        let sample = &self.entries[..self.entries.len().min(1000)];
+        let used = sample
+            .iter()
+            .filter(|[check, data]| {
+                check.load(Ordering::Relaxed) | data.load(Ordering::Relaxed) != 0
+            })
+            .count();
+        (used * 1000 / sample.len()) as u32
+    }
+
+    /// Empties the table, e.g. for a new game. Searches running meanwhile may still find entries.
+    pub fn clear(&self) {
+        for [check, data] in &self.entries {
+            check.store(0, Ordering::Relaxed);
+            data.store(0, Ordering::Relaxed);
+        }
+    }
+}
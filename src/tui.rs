@@ -0,0 +1,346 @@
+// A terminal UI for playing the bot and analyzing positions: the board, the moves played, the
+// engine's evaluation as a bar with its line and the clock, redrawn as the game goes on instead
+// of printed one position after another.
+
+use crate::{
+    clock::Clock,
+    render::{Annotations, Brush},
+    Board, Color, Engine, Game, GameStatus, Move, Score, SearchLimits,
+};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout, Rect},
+    style::{Color as TermColor, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Gauge, Paragraph},
+    Frame,
+};
+use std::{io, time::Duration};
+
+/// How often the clock is redrawn while waiting for a key.
+const TICK: Duration = Duration::from_millis(100);
+
+const LIGHT_SQUARE: TermColor = TermColor::Rgb(240, 217, 181);
+const DARK_SQUARE: TermColor = TermColor::Rgb(181, 136, 99);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Mode {
+    /// The player is White and the engine answers as Black.
+    Play,
+    /// The player plays both sides and the engine searches every position.
+    Analyze,
+}
+
+/// What the terminal UI shows, and the move being typed.
+pub struct App {
+    pub mode: Mode,
+    pub game: Game,
+    pub clock: Option<Clock>,
+    /// The engine's last score and line, the score from White's side.
+    pub score: Option<Score>,
+    pub pv: Vec<Move>,
+    /// The insights of the engine's last search, see `Annotations::from_analysis()`, the marked
+    /// squares take the color of the mark.
+    pub annotations: Annotations,
+    /// The board, the side to move and the move number that `pv` starts from.
+    pv_start: (Board, Color, usize),
+    pub input: String,
+    /// The last complaint or the result.
+    pub message: String,
+    pub finished: bool,
+}
+
+/// `score` of `color`'s move from White's side.
+fn white_score(score: Score, color: Color) -> Score {
+    match (color, score) {
+        (Color::White, score) => score,
+        (Color::Black, Score::Cp(cp)) => Score::Cp(-cp),
+        (Color::Black, Score::Mate(moves)) => Score::Mate(-moves),
+    }
+}
+
+fn brush_color(brush: Brush) -> TermColor {
+    let (r, g, b) = brush.rgb();
+    TermColor::Rgb(r, g, b)
+}
+
+/// How much of the evaluation bar is White's, from 0 to 1, a pawn's worth is about 0.57.
+pub fn eval_ratio(score: Score) -> f64 {
+    match score {
+        Score::Cp(cp) => 1.0 / (1.0 + 10f64.powf(-cp as f64 / 400.0)),
+        Score::Mate(moves) if moves > 0 => 1.0,
+        Score::Mate(_) => 0.0,
+    }
+}
+
+/// The moves in SAN, played from `board` with `color` to move, numbered like "1. e4 e5".
+fn san_lines(mut board: Board, mut color: Color, moves: &[Move], mut number: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    if color == Color::Black && !moves.is_empty() {
+        lines.push(format!("{}...", number));
+    }
+    for &mv in moves {
+        let san = board.to_san(color, mv);
+        match color {
+            Color::White => lines.push(format!("{}. {}", number, san)),
+            Color::Black => {
+                let line = lines.last_mut().unwrap();
+                line.push(' ');
+                line.push_str(&san);
+                number += 1;
+            }
+        }
+        board.perform_move_unchecked(mv);
+        color = color.inv();
+    }
+    lines
+}
+
+impl App {
+    pub fn new(mode: Mode, game: Game, clock: Option<Clock>) -> Self {
+        Self {
+            mode,
+            pv_start: (*game.board(), game.turn(), game.moves().len() / 2 + 1),
+            game,
+            clock,
+            score: None,
+            pv: Vec::new(),
+            annotations: Annotations::default(),
+            input: String::new(),
+            message: String::new(),
+            finished: false,
+        }
+    }
+
+    fn draw_board(&self, frame: &mut Frame, area: Rect) {
+        let board = self.game.board();
+        let mut lines = Vec::new();
+        for rank in (0..8).rev() {
+            let mut spans = vec![Span::raw(format!("{} ", rank + 1))];
+            for file in 0..8 {
+                let square = 8 * rank + file;
+                let ch = board
+                    .get_at(1 << square)
+                    .map_or(' ', |piece| piece.to_char());
+                let mark = self
+                    .annotations
+                    .marks
+                    .iter()
+                    .rev()
+                    .find(|mark| mark.from.index() == square || mark.to.index() == square);
+                let bg = match mark {
+                    Some(mark) => brush_color(mark.brush),
+                    None if (rank + file) % 2 == 0 => DARK_SQUARE,
+                    None => LIGHT_SQUARE,
+                };
+                spans.push(Span::styled(
+                    format!(" {} ", ch),
+                    Style::new().bg(bg).fg(TermColor::Black),
+                ));
+            }
+            lines.push(Line::from(spans));
+        }
+        lines.push(Line::raw(
+            ('a'..='h').fold("  ".to_owned(), |s, ch| s + &format!(" {} ", ch)),
+        ));
+        frame.render_widget(Paragraph::new(lines).block(Block::bordered()), area);
+    }
+
+    fn draw_moves(&self, frame: &mut Frame, area: Rect) {
+        let (board, color) = self.game.start();
+        let lines = san_lines(board, color, self.game.moves(), 1);
+        // The latest moves stay in sight.
+        let scroll = lines
+            .len()
+            .saturating_sub(area.height.saturating_sub(2) as usize);
+        let text: Vec<_> = lines.into_iter().map(Line::raw).collect();
+        frame.render_widget(
+            Paragraph::new(text)
+                .scroll((scroll as u16, 0))
+                .block(Block::bordered().title("Moves")),
+            area,
+        );
+    }
+
+    /// Draws the whole UI.
+    pub fn draw(&self, frame: &mut Frame) {
+        let [main, input] =
+            Layout::vertical([Constraint::Min(11), Constraint::Length(3)]).areas(frame.area());
+        let [board, side] =
+            Layout::horizontal([Constraint::Length(28), Constraint::Min(20)]).areas(main);
+        let [clock, eval, line, moves] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Min(3),
+        ])
+        .areas(side);
+
+        self.draw_board(frame, board);
+        let clock_text = self
+            .clock
+            .map_or_else(|| "No clock".to_owned(), |clock| clock.to_string());
+        frame.render_widget(
+            Paragraph::new(clock_text).block(Block::bordered().title("Clock")),
+            clock,
+        );
+        let (ratio, label) = self.score.map_or((0.5, "-".to_owned()), |score| {
+            (eval_ratio(score), score.to_string())
+        });
+        frame.render_widget(
+            Gauge::default()
+                .ratio(ratio)
+                .label(label)
+                .gauge_style(Style::new().white().on_black())
+                .block(Block::bordered().title("Evaluation")),
+            eval,
+        );
+        let (start, color, number) = self.pv_start;
+        let pv = san_lines(start, color, &self.pv, number).join(" ");
+        frame.render_widget(
+            Paragraph::new(pv).block(Block::bordered().title("Line")),
+            line,
+        );
+        self.draw_moves(frame, moves);
+
+        let title = match self.mode {
+            Mode::Play => "Your move (Esc quits)",
+            Mode::Analyze => "Move (Esc quits)",
+        };
+        let text = if self.message.is_empty() {
+            self.input.clone()
+        } else {
+            format!("{}  {}", self.input, self.message)
+        };
+        frame.render_widget(
+            Paragraph::new(text).block(Block::bordered().title(title)),
+            input,
+        );
+    }
+
+    /// Ends the game if it's over, returns whether it is.
+    fn check_finished(&mut self) -> bool {
+        let result = match self.game.status() {
+            GameStatus::Ongoing => match &self.clock {
+                Some(clock) if clock.flag_fell(self.game.turn()) => {
+                    format!("{:?} lost on time", self.game.turn())
+                }
+                _ => return false,
+            },
+            GameStatus::Checkmate(winner) => format!("Checkmate, {:?} wins", winner),
+            GameStatus::Stalemate => "Stalemate".to_owned(),
+            GameStatus::VariantWin(winner) => {
+                format!("{:?} wins by the {} rules", winner, self.game.variant())
+            }
+        };
+        self.message = result;
+        self.finished = true;
+        true
+    }
+
+    /// Searches the position for the side to move, and plays the best move in `Mode::Play`.
+    pub fn search(&mut self, engine: &mut dyn Engine) {
+        let (board, color) = (*self.game.board(), self.game.turn());
+        let limits = SearchLimits {
+            time: self.clock.map(|clock| clock.time_manager(color)),
+            history: self.game.history(),
+            ..SearchLimits::default()
+        };
+        let result = engine.best_move(&board, color, &limits);
+        self.score = result.score.map(|score| white_score(score, color));
+        self.pv = result.pv;
+        self.pv_start = (board, color, self.game.moves().len() / 2 + 1);
+        match (self.mode, result.best_move) {
+            // The player sees the threats and the pins, but not the best move.
+            (Mode::Play, Some(mv)) => {
+                self.play(mv);
+                self.annotations =
+                    Annotations::from_analysis(self.game.board(), self.game.turn(), None);
+            }
+            (_, best_move) => {
+                self.annotations = Annotations::from_analysis(&board, color, best_move);
+            }
+        }
+    }
+
+    fn play(&mut self, mv: Move) {
+        if let Some(clock) = &mut self.clock {
+            clock.stop();
+        }
+        self.game.push_move(mv);
+        if let Some(clock) = &mut self.clock {
+            clock.start(self.game.turn());
+        }
+    }
+
+    /// Plays the typed move, SAN or squares like "e2e4" or "e2 e4", returns whether it was legal.
+    pub fn submit(&mut self) -> bool {
+        let input = std::mem::take(&mut self.input);
+        match crate::input::parse_move(self.game.board(), self.game.turn(), &input) {
+            Ok(mv) => {
+                self.message.clear();
+                self.play(mv);
+                true
+            }
+            Err(err) => {
+                self.message = format!("{}: {}", input.trim(), err);
+                false
+            }
+        }
+    }
+}
+
+/// Runs the UI until Esc or Ctrl-C, the terminal is restored after.
+pub fn run(
+    mode: Mode,
+    game: Game,
+    clock: Option<Clock>,
+    engine: &mut dyn Engine,
+) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, App::new(mode, game, clock), engine);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(
+    terminal: &mut ratatui::DefaultTerminal,
+    mut app: App,
+    engine: &mut dyn Engine,
+) -> io::Result<()> {
+    if let Some(clock) = &mut app.clock {
+        clock.start(app.game.turn());
+    }
+    if app.mode == Mode::Analyze && !app.check_finished() {
+        app.search(engine);
+    }
+    loop {
+        if !app.finished {
+            app.check_finished();
+        }
+        terminal.draw(|frame| app.draw(frame))?;
+        if !event::poll(TICK)? {
+            continue;
+        }
+        let key = match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+        match key.code {
+            KeyCode::Esc => return Ok(()),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(()),
+            _ if app.finished => {}
+            KeyCode::Char(ch) => app.input.push(ch),
+            KeyCode::Backspace => {
+                app.input.pop();
+            }
+            KeyCode::Enter if app.submit() && !app.check_finished() => {
+                app.message = "Thinking...".to_owned();
+                terminal.draw(|frame| app.draw(frame))?;
+                app.search(engine);
+                app.message.clear();
+            }
+            _ => {}
+        }
+    }
+}
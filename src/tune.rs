@@ -0,0 +1,178 @@
+// Texel tuning: fits `EvalParams` to game results by minimizing the squared error between the
+// results and the evaluation mapped to a win probability.
+//
+// Positions come from EPD-like lines with a result ("<fen> 1-0", "<fen> [0.5]", or
+// `c9 "1/2-1/2";`), or from a PGN file, where every position is labeled with the game's result.
+
+use crate::{eval::EvalParams, pgn, Board, Game};
+use std::io::{self, Write};
+
+pub const DEFAULT_ITERATIONS: u32 = 500;
+
+// The opening's positions say little about the result.
+const PGN_SKIP_PLIES: usize = 8;
+
+struct Position {
+    features: Vec<(usize, f64)>,
+    /// 1 for a white win, 0.5 for a draw and 0 for a black win.
+    result: f64,
+}
+
+fn invalid_data(msg: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+fn parse_result(s: &str) -> Option<f64> {
+    let s = s.trim_matches(|ch| matches!(ch, '"' | ';' | '[' | ']'));
+    match s {
+        "1-0" | "1.0" | "1" => Some(1.0),
+        "0-1" | "0.0" | "0" => Some(0.0),
+        "1/2-1/2" | "0.5" => Some(0.5),
+        _ => None,
+    }
+}
+
+fn load(text: &str) -> io::Result<Vec<Position>> {
+    let mut positions = Vec::new();
+
+    if text.trim_start().starts_with('[') {
+        for game in pgn::parse(text).map_err(invalid_data)? {
+            let result = match parse_result(&game.result) {
+                Some(result) => result,
+                None => continue,
+            };
+            let mut replay = Game::new();
+            for mv in &game.moves {
+                let parsed = match replay.board().parse_san(replay.turn(), &mv.san) {
+                    Some(parsed) => parsed,
+                    None => break,
+                };
+                replay.push_move(parsed);
+                if PGN_SKIP_PLIES < replay.moves().len() {
+                    positions.push(Position {
+                        features: EvalParams::features(replay.board()),
+                        result,
+                    });
+                }
+            }
+        }
+        return Ok(positions);
+    }
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<_> = line.split_whitespace().collect();
+        let result = fields
+            .iter()
+            .skip(4)
+            .find_map(|field| parse_result(field))
+            .ok_or_else(|| invalid_data(format!("Line {}: no result", i + 1)))?;
+        let (board, _) = Board::from_fen(&fields[..4.min(fields.len())].join(" "))
+            .map_err(|err| invalid_data(format!("Line {}: {}", i + 1, err)))?;
+
+        positions.push(Position {
+            features: EvalParams::features(&board),
+            result,
+        });
+    }
+    Ok(positions)
+}
+
+/// The win probability of an evaluation, `k` scales centipawns.
+fn sigmoid(k: f64, eval: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-k * eval / 400.0))
+}
+
+fn eval(weights: &[f64], position: &Position) -> f64 {
+    position
+        .features
+        .iter()
+        .map(|&(i, value)| weights[i] * value)
+        .sum()
+}
+
+fn error(k: f64, weights: &[f64], positions: &[Position]) -> f64 {
+    let sum: f64 = positions
+        .iter()
+        .map(|position| (position.result - sigmoid(k, eval(weights, position))).powi(2))
+        .sum();
+    sum / positions.len() as f64
+}
+
+/// Finds the `k` that fits the current parameters best, by a ternary search.
+fn fit_k(weights: &[f64], positions: &[Position]) -> f64 {
+    let (mut low, mut high) = (0.0, 3.0);
+    for _ in 0..50 {
+        let a = low + (high - low) / 3.0;
+        let b = high - (high - low) / 3.0;
+        if error(a, weights, positions) < error(b, weights, positions) {
+            high = b;
+        } else {
+            low = a;
+        }
+    }
+    (low + high) / 2.0
+}
+
+/// Tunes `params` to the positions of `text` with `iterations` steps of Adam.
+pub fn tune(
+    text: &str,
+    params: &EvalParams,
+    iterations: u32,
+    mut log: impl Write,
+) -> io::Result<EvalParams> {
+    const LEARNING_RATE: f64 = 1.0;
+    const BETA1: f64 = 0.9;
+    const BETA2: f64 = 0.999;
+
+    let positions = load(text)?;
+    if positions.is_empty() {
+        return Err(invalid_data("No positions to tune with"));
+    }
+    writeln!(log, "Loaded {} positions", positions.len())?;
+
+    let mut weights = params.to_weights();
+    let k = fit_k(&weights, &positions);
+    writeln!(
+        log,
+        "K = {:.3}, error = {:.6}",
+        k,
+        error(k, &weights, &positions)
+    )?;
+
+    let (mut m, mut v) = (vec![0.0; weights.len()], vec![0.0; weights.len()]);
+    for t in 1..=iterations {
+        let mut gradient = vec![0.0; weights.len()];
+        for position in &positions {
+            let p = sigmoid(k, eval(&weights, position));
+            // d/dw (result - p)^2, up to a constant factor.
+            let g = (p - position.result) * p * (1.0 - p);
+            for &(i, value) in &position.features {
+                gradient[i] += g * value;
+            }
+        }
+
+        for i in 0..weights.len() {
+            m[i] = BETA1 * m[i] + (1.0 - BETA1) * gradient[i];
+            v[i] = BETA2 * v[i] + (1.0 - BETA2) * gradient[i] * gradient[i];
+            let m_hat = m[i] / (1.0 - BETA1.powi(t as i32));
+            let v_hat = v[i] / (1.0 - BETA2.powi(t as i32));
+            weights[i] -= LEARNING_RATE * m_hat / (v_hat.sqrt() + 1e-8);
+        }
+
+        if t % 50 == 0 || t == iterations {
+            writeln!(
+                log,
+                "Iteration {}: error = {:.6}",
+                t,
+                error(k, &weights, &positions)
+            )?;
+        }
+    }
+
+    Ok(EvalParams::from_weights(&weights))
+}
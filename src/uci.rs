@@ -0,0 +1,309 @@
+// The Universal Chess Interface, see https://www.shredderchess.com/chess-features/uci-universal-chess-interface.html
+
+use crate::{
+    mcts, options::EngineOptions, time::TimeManager, Board, Bot, Color, Engine, Game, MctsBot,
+    Move, Score, SearchLimits, SearchResult, Variant,
+};
+use std::{
+    io::{self, BufRead, Write},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+
+// The iterative deepening of a timed search stops here at the latest.
+const MAX_DEPTH: u32 = 64;
+// The root moves are reported once a search takes this long, quick ones would only flood the GUI.
+const CURRMOVE_DELAY: Duration = Duration::from_secs(1);
+
+/// The search used by `go`, chosen by the "Search" option.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Search {
+    AlphaBeta,
+    Mcts,
+}
+
+/// Talks UCI over `input` and `output` until "quit" or the end of the input, `options` are the
+/// defaults the GUI can change.
+pub fn run(
+    input: impl BufRead,
+    mut output: impl Write,
+    mut options: EngineOptions,
+) -> io::Result<()> {
+    let mut game = Game::with_variant(options.variant);
+    let mut search = Search::AlphaBeta;
+    let mut mcts_iterations = mcts::DEFAULT_ITERATIONS;
+
+    for line in input.lines() {
+        let line = line?;
+        log::trace!("< {}", line);
+        let mut args = line.split_whitespace();
+
+        match args.next() {
+            Some("uci") => {
+                writeln!(output, "id name MxChess")?;
+                writeln!(output, "id author PizzasBear")?;
+                writeln!(
+                    output,
+                    "option name Search type combo default AlphaBeta var AlphaBeta var MCTS"
+                )?;
+                writeln!(
+                    output,
+                    "option name MCTSIterations type spin default {} min 1 max 100000000",
+                    mcts::DEFAULT_ITERATIONS
+                )?;
+                for option in options.uci_options() {
+                    writeln!(output, "{}", option)?;
+                }
+                writeln!(output, "uciok")?;
+            }
+            Some("isready") => writeln!(output, "readyok")?,
+            Some("ucinewgame") => {
+                game = Game::with_variant(options.variant);
+                options.table().clear();
+            }
+            Some("position") => match parse_position(args, options.variant) {
+                Some(new_game) => game = new_game,
+                None => writeln!(output, "info string bad position: {}", line)?,
+            },
+            Some("setoption") => match parse_option(args) {
+                Some((name, value)) => match (name.as_str(), value.as_str()) {
+                    ("Search", "AlphaBeta") => search = Search::AlphaBeta,
+                    ("Search", "MCTS") => search = Search::Mcts,
+                    ("MCTSIterations", value) => match value.parse() {
+                        Ok(iterations) => mcts_iterations = iterations,
+                        Err(_) => writeln!(output, "info string bad option: {}", line)?,
+                    },
+                    (name, value) => {
+                        if let Err(err) = options.set(name, value) {
+                            writeln!(output, "info string {}", err)?;
+                        }
+                    }
+                },
+                None => writeln!(output, "info string bad option: {}", line)?,
+            },
+            Some("go") => {
+                let (limits, moves) = parse_go(args, &game);
+                // Book moves are played right away.
+                if let Some(mv) = options
+                    .book_moves(&game)
+                    .iter()
+                    .find(|mv| moves.contains(mv))
+                {
+                    writeln!(output, "bestmove {}", mv)?;
+                    output.flush()?;
+                    continue;
+                }
+                let (board, turn, history) = (game.board(), game.turn(), game.history());
+                let options = &options;
+                // The search runs on its own thread, so that its info lines get out while it runs.
+                let (sender, receiver) = mpsc::channel();
+                let mv = thread::scope(|scope| {
+                    let handle = scope.spawn(move || {
+                        let start = Instant::now();
+                        let mut engine: Box<dyn Engine + Send> = match search {
+                            Search::AlphaBeta => {
+                                let mut bot = options.bot(MAX_DEPTH);
+                                let sender = sender.clone();
+                                bot.on_root_move = Some(Box::new(move |depth, mv, number| {
+                                    if CURRMOVE_DELAY <= start.elapsed() {
+                                        let info = format!(
+                                            "info depth {} currmove {} currmovenumber {}",
+                                            depth, mv, number
+                                        );
+                                        // Only fails once writing the output did.
+                                        let _ = sender.send(info);
+                                    }
+                                }));
+                                Box::new(bot)
+                            }
+                            Search::Mcts => {
+                                let mut bot = MctsBot::new();
+                                bot.iterations = mcts_iterations;
+                                Box::new(bot)
+                            }
+                        };
+                        let limits = SearchLimits {
+                            history,
+                            moves: &moves,
+                            ..limits
+                        };
+                        let result = options.install(|| engine.best_move(board, turn, &limits));
+                        if let Some(info) = result_info(&result) {
+                            // Only fails once writing the output did.
+                            let _ = sender.send(info);
+                        }
+                        result.best_move
+                    });
+                    // Ends with the search, which drops the senders.
+                    for info in receiver {
+                        writeln!(output, "{}", info)?;
+                        output.flush()?;
+                    }
+                    Ok::<_, io::Error>(handle.join().unwrap())
+                })?;
+                match mv {
+                    Some(mv) => writeln!(output, "bestmove {}", mv)?,
+                    None => writeln!(output, "bestmove 0000")?,
+                }
+            }
+            Some("quit") => break,
+            _ => {}
+        }
+        output.flush()?;
+    }
+
+    Ok(())
+}
+
+/// The info line of a finished search with a score, e.g.
+/// "info depth 6 score cp 30 nodes 51234 nps 63096 hashfull 12 time 812 pv e2e4 e7e5".
+fn result_info(result: &SearchResult) -> Option<String> {
+    let score = match result.score? {
+        Score::Cp(cp) => format!("cp {}", cp),
+        Score::Mate(moves) => format!("mate {}", moves),
+    };
+    let mut info = format!(
+        "info depth {} score {} nodes {} nps {}",
+        result.depth,
+        score,
+        result.nodes,
+        result.nps()
+    );
+    if let Some(hashfull) = result.hashfull {
+        info.push_str(&format!(" hashfull {}", hashfull));
+    }
+    info.push_str(&format!(" time {}", result.time.as_millis()));
+    if !result.pv.is_empty() {
+        info.push_str(" pv");
+        for mv in &result.pv {
+            info.push_str(&format!(" {}", mv));
+        }
+    }
+    Some(info)
+}
+
+// setoption name <id> [value <x>], the name and the value may have spaces, e.g. "Skill Level".
+fn parse_option<'a>(mut args: impl Iterator<Item = &'a str>) -> Option<(String, String)> {
+    if args.next()? != "name" {
+        return None;
+    }
+    let mut name = Vec::new();
+    for word in args.by_ref() {
+        if word == "value" {
+            break;
+        }
+        name.push(word);
+    }
+    let value: Vec<_> = args.collect();
+    Some((name.join(" "), value.join(" "))).filter(|(name, _)| !name.is_empty())
+}
+
+// position (startpos | fen <fen>) [moves <move>...], the start position is `variant`'s.
+fn parse_position<'a>(args: impl Iterator<Item = &'a str>, variant: Variant) -> Option<Game> {
+    let mut args = args.peekable();
+    let mut game = match args.next()? {
+        "startpos" => Game::with_variant(variant),
+        "fen" => {
+            let mut fen = Vec::new();
+            while let Some(field) = args.next_if(|&field| field != "moves") {
+                fen.push(field);
+            }
+            let (mut board, turn) = Board::from_fen(&fen.join(" ")).ok()?;
+            // The FEN only tells Crazyhouse and Horde apart from standard chess.
+            if board.variant == Variant::Standard {
+                board.variant = variant;
+            }
+            Game::from_position(board, turn)
+        }
+        _ => return None,
+    };
+
+    match args.next() {
+        None => return Some(game),
+        Some("moves") => {}
+        Some(_) => return None,
+    }
+    for mv in args {
+        let mv = game.board().parse_move(game.turn(), mv)?;
+        if !game.push_move(mv) {
+            return None;
+        }
+    }
+
+    Some(game)
+}
+
+// go [searchmoves <move>...] [depth <n>] [nodes <n>] [wtime <ms>] [btime <ms>] [winc <ms>]
+//    [binc <ms>] [movestogo <n>] [movetime <ms>] ...
+// Returns the depth or the time to search, the nodes, and the root moves to search, all of them
+// unless searchmoves restricts them.
+fn parse_go<'a>(
+    args: impl Iterator<Item = &'a str>,
+    game: &Game,
+) -> (SearchLimits<'static>, Vec<Move>) {
+    let turn = game.turn();
+    let mut depth = None;
+    let mut nodes = None;
+    let mut move_time = None;
+    let (mut time_left, mut increment, mut moves_to_go) = (None, 0, None);
+    let mut searchmoves = Vec::new();
+
+    let mut args = args.peekable();
+    while let Some(arg) = args.next() {
+        match arg {
+            // Flags without values.
+            "infinite" | "ponder" => {}
+            // Moves until the next parameter, which can't be parsed as a move.
+            "searchmoves" => {
+                while let Some(mv) = args.peek().and_then(|mv| game.board().parse_move(turn, mv)) {
+                    searchmoves.push(mv);
+                    args.next();
+                }
+            }
+            _ => {
+                let value = args.next().and_then(|value| value.parse::<u64>().ok());
+                match (arg, turn) {
+                    ("depth", _) => depth = value.map(|depth| depth as u32),
+                    ("nodes", _) => nodes = value,
+                    ("movetime", _) => move_time = value,
+                    ("movestogo", _) => moves_to_go = value.map(|n| n as u32),
+                    ("wtime", Color::White) | ("btime", Color::Black) => time_left = value,
+                    ("winc", Color::White) | ("binc", Color::Black) => {
+                        increment = value.unwrap_or(0)
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    if searchmoves.is_empty() {
+        searchmoves = game.legal_moves();
+    }
+
+    let (depth, time) = match (depth, move_time, time_left) {
+        (Some(depth), _, _) => (Some(depth), None),
+        (None, Some(move_time), _) => (
+            None,
+            Some(TimeManager::fixed(Duration::from_millis(move_time))),
+        ),
+        (None, None, Some(time_left)) => (
+            None,
+            Some(TimeManager::for_clock(
+                Duration::from_millis(time_left),
+                Duration::from_millis(increment),
+                moves_to_go,
+            )),
+        ),
+        // The nodes alone limit the search.
+        (None, None, None) if nodes.is_some() => (None, None),
+        (None, None, None) => (Some(Bot::DEFAULT_DEPTH), None),
+    };
+    let limits = SearchLimits {
+        depth,
+        time,
+        nodes,
+        ..SearchLimits::default()
+    };
+    (limits, searchmoves)
+}
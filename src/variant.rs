@@ -0,0 +1,96 @@
+// The rule sets the board can play by. They're named like in UCI's `UCI_Variant` and in lowercase
+// PGN `Variant` tags, e.g. "crazyhouse".
+
+use crate::{
+    masks::{CENTER, FILE_B, FILE_C, FILE_F, FILE_G, RANK_1, RANK_2, RANK_3, RANK_4, RANK_5},
+    Board, Color,
+};
+use core::{fmt, str::FromStr};
+
+/// d4, e4, d5 and e5, the squares King of the Hill's kings race to.
+pub const HILL: u64 = CENTER;
+/// Horde's white pawns: the first four ranks and b5, c5, f5 and g5.
+pub const HORDE_PAWNS: u64 =
+    RANK_1 | RANK_2 | RANK_3 | RANK_4 | (FILE_B | FILE_C | FILE_F | FILE_G) & RANK_5;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub enum Variant {
+    #[default]
+    Standard,
+    /// Captured pieces go to the captor's reserve and can be dropped back on an empty square
+    /// instead of moving (pawns not on the first or last rank). Captured promoted pieces are
+    /// dropped as pawns.
+    Crazyhouse,
+    /// A king that reaches the hill (`HILL`) wins, on top of the usual rules.
+    KingOfTheHill,
+    /// White has 36 pawns (`HORDE_PAWNS`) and no king against Black's usual army. White's pawns
+    /// can leap from the first rank too, though not to be taken en passant. Black wins by taking
+    /// all of them.
+    Horde,
+}
+
+impl Variant {
+    pub const ALL: [Self; 4] = [
+        Self::Standard,
+        Self::Crazyhouse,
+        Self::KingOfTheHill,
+        Self::Horde,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::Crazyhouse => "crazyhouse",
+            Self::KingOfTheHill => "kingofthehill",
+            Self::Horde => "horde",
+        }
+    }
+}
+
+impl fmt::Display for Variant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The name isn't a known variant's.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct UnknownVariant;
+
+impl fmt::Display for UnknownVariant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unknown variant")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnknownVariant {}
+
+impl FromStr for Variant {
+    type Err = UnknownVariant;
+
+    /// Case insensitive, "chess" and "normal" are standard chess too.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "standard" | "chess" | "normal" => Ok(Self::Standard),
+            "crazyhouse" => Ok(Self::Crazyhouse),
+            "kingofthehill" | "king of the hill" | "koth" => Ok(Self::KingOfTheHill),
+            "horde" => Ok(Self::Horde),
+            _ => Err(UnknownVariant),
+        }
+    }
+}
+
+impl Board {
+    /// The side that won by the variant's own rules, e.g. a king on the hill in King of the Hill
+    /// or a Horde without pieces left.
+    /// Checkmates and stalemates are up to the side to move's moves.
+    pub fn variant_winner(&self) -> Option<Color> {
+        match self.variant {
+            Variant::KingOfTheHill if self.white_pieces.king() & HILL != 0 => Some(Color::White),
+            Variant::KingOfTheHill if self.black_pieces.king() & HILL != 0 => Some(Color::Black),
+            Variant::Horde if self.white_pieces.all == 0 => Some(Color::Black),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use crate::{Bot, Color, Game, Square};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+pub struct WasmGame {
+    game: Game,
+}
+
+#[wasm_bindgen]
+pub fn new_game() -> WasmGame {
+    WasmGame { game: Game::new() }
+}
+
+#[wasm_bindgen]
+impl WasmGame {
+    /// `true` if it's white's turn.
+    pub fn white_to_move(&self) -> bool {
+        self.game.turn() == Color::White
+    }
+
+    /// The board as text, from the perspective of the side to move.
+    pub fn render(&self) -> String {
+        self.game.board().render(self.game.turn())
+    }
+
+    /// The legal moves of the piece on `from` (e.g. "e2"), formatted like "e2e4".
+    pub fn legal_moves(&self, from: &str) -> Vec<String> {
+        let from = match from.parse::<Square>() {
+            Ok(from) => from.index(),
+            Err(_) => return Vec::new(),
+        };
+
+        self.game
+            .legal_moves()
+            .into_iter()
+            .filter(|mv| mv.from == from && mv.ty.dropped().is_none())
+            .map(|mv| mv.to_string())
+            .collect()
+    }
+
+    /// Plays a move like "e2e4" or "e7e8q", returns `false` if it's illegal.
+    pub fn push_move(&mut self, mv: &str) -> bool {
+        match self.game.board().parse_move(self.game.turn(), mv) {
+            Some(mv) => self.game.push_move(mv),
+            None => false,
+        }
+    }
+
+    /// The bot's move for the side to move, or `None` if there are no legal moves.
+    pub fn best_move(&self, depth: u32) -> Option<String> {
+        Bot::with_depth(depth)
+            .choose_move_with_history(self.game.board(), self.game.turn(), self.game.history())
+            .map(|mv| mv.to_string())
+    }
+}
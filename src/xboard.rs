@@ -0,0 +1,91 @@
+// The Chess Engine Communication Protocol used by XBoard/WinBoard,
+// see https://www.gnu.org/software/xboard/engine-intf.html
+
+use crate::{Bot, Color, Game, GameStatus};
+use std::io::{self, BufRead, Write};
+
+/// Talks CECP over `input` and `output` until "quit" or the end of the input.
+pub fn run(input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    let mut game = Game::new();
+    // The color the engine plays, `None` in force mode.
+    let mut engine = Some(Color::Black);
+    let mut depth = None;
+    let mut time_left = None;
+
+    for line in input.lines() {
+        let line = line?;
+        log::trace!("< {}", line);
+        let mut args = line.split_whitespace();
+
+        match args.next() {
+            Some("protover") => writeln!(
+                output,
+                "feature myname=\"MxChess\" usermove=1 ping=1 setboard=0 colors=0 sigint=0 sigterm=0 done=1"
+            )?,
+            Some("new") => {
+                game = Game::new();
+                engine = Some(Color::Black);
+                depth = None;
+            }
+            Some("force") | Some("result") => engine = None,
+            Some("go") => engine = Some(game.turn()),
+            Some("playother") => engine = Some(game.turn().inv()),
+            Some("sd") => depth = args.next().and_then(|depth| depth.parse().ok()),
+            // The engine's clock, in centiseconds.
+            Some("time") => {
+                time_left = args
+                    .next()
+                    .and_then(|time| time.parse::<u64>().ok())
+                    .map(|time| 10 * time)
+            }
+            Some("ping") => writeln!(output, "pong {}", args.next().unwrap_or_default())?,
+            Some("quit") => break,
+            Some("usermove") => {
+                let mv = args.next().unwrap_or_default();
+                user_move(&mut game, mv, &mut output)?;
+            }
+            // Without the usermove feature moves come bare.
+            Some(cmd) if game.board().parse_move(game.turn(), cmd).is_some() => {
+                user_move(&mut game, cmd, &mut output)?;
+            }
+            Some(_) | None => {}
+        }
+
+        if engine == Some(game.turn()) && game.status() == GameStatus::Ongoing {
+            let depth = depth
+                .or_else(|| time_left.map(Bot::depth_for_clock))
+                .unwrap_or(Bot::DEFAULT_DEPTH);
+
+            if let Some(mv) = Bot::with_depth(depth).choose_move_with_history(
+                game.board(),
+                game.turn(),
+                game.history(),
+            ) {
+                game.push_move(mv);
+                writeln!(output, "move {}", mv)?;
+                write_result(&game, &mut output)?;
+            }
+        }
+        output.flush()?;
+    }
+
+    Ok(())
+}
+
+fn user_move(game: &mut Game, mv: &str, output: &mut impl Write) -> io::Result<()> {
+    match game.board().parse_move(game.turn(), mv) {
+        Some(parsed) if game.push_move(parsed) => write_result(game, output),
+        _ => writeln!(output, "Illegal move: {}", mv),
+    }
+}
+
+fn write_result(game: &Game, output: &mut impl Write) -> io::Result<()> {
+    match game.status() {
+        GameStatus::Ongoing => Ok(()),
+        GameStatus::Stalemate => writeln!(output, "1/2-1/2 {{Stalemate}}"),
+        GameStatus::Checkmate(Color::White) => writeln!(output, "1-0 {{White mates}}"),
+        GameStatus::Checkmate(Color::Black) => writeln!(output, "0-1 {{Black mates}}"),
+        GameStatus::VariantWin(Color::White) => writeln!(output, "1-0 {{White wins}}"),
+        GameStatus::VariantWin(Color::Black) => writeln!(output, "0-1 {{Black wins}}"),
+    }
+}
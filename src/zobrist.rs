@@ -0,0 +1,123 @@
+// Zobrist hashing, the keys are generated at compile time by splitmix64.
+
+use crate::{
+    board::MAX_RESERVE, masks::LINE, rng::splitmix64, Board, Color, Move, MoveType, PieceType,
+};
+
+const fn keys<const N: usize>(seed: u64) -> [u64; N] {
+    let mut keys = [0; N];
+    let mut state = seed;
+    let mut i = 0;
+    while i < N {
+        let (next, key) = splitmix64(state);
+        state = next;
+        keys[i] = key;
+        i += 1;
+    }
+    keys
+}
+
+/// Indexed by `color * 6 * 64 + piece type * 64 + square`.
+const PIECES: [u64; 2 * 6 * 64] = keys(1);
+/// Indexed by the castling flags' bits.
+const CASTLING: [u64; 16] = keys(2);
+/// Indexed by the file of the pawn that can be taken en passant.
+const EN_PASSANT: [u64; 8] = keys(3);
+const BLACK_TO_MOVE: u64 = keys::<1>(4)[0];
+/// The counts a reserve of a piece type can have.
+const COUNTS: usize = MAX_RESERVE as usize + 1;
+/// Indexed by `color * 6 * COUNTS + piece type * COUNTS + count` for the Crazyhouse reserves.
+const RESERVES: [u64; 2 * 6 * COUNTS] = keys(5);
+/// Indexed by the square of a Crazyhouse piece that was a pawn, it goes back to the reserve as one.
+const PROMOTED: [u64; 64] = keys(6);
+
+impl Board {
+    /// The Zobrist hash of the position with `color` to move. It counts the en passant file
+    /// after every pawn leap, see `position_key()`.
+    pub fn zobrist(&self, color: Color) -> u64 {
+        let mut hash = CASTLING[self.flags.bits() as usize];
+        if color == Color::Black {
+            hash ^= BLACK_TO_MOVE;
+        }
+        if self.prev_move.ty == MoveType::PawnLeap {
+            hash ^= EN_PASSANT[(self.prev_move.to & 7) as usize];
+        }
+
+        for (i, reserve) in self.reserves.iter().enumerate() {
+            for (ty, &count) in reserve.iter().enumerate() {
+                if count != 0 {
+                    let count = count.min(MAX_RESERVE) as usize;
+                    hash ^= RESERVES[i * 6 * COUNTS + ty * COUNTS + count];
+                }
+            }
+        }
+
+        for bit in crate::BitIterator(self.promoted) {
+            hash ^= PROMOTED[bit.trailing_zeros() as usize];
+        }
+
+        for (i, pieces) in [&self.white_pieces, &self.black_pieces].iter().enumerate() {
+            for ty in 0..6 {
+                for bit in crate::BitIterator(pieces.get(PieceType::ALL[ty])) {
+                    hash ^= PIECES[i * 6 * 64 + ty * 64 + bit.trailing_zeros() as usize];
+                }
+            }
+        }
+
+        hash
+    }
+
+    /// Like `zobrist()`, but the en passant file only counts when the capture is legal, so the
+    /// positions that are the same by the FIDE rules (the pieces, the side to move and the
+    /// castling and en passant rights) get the same key whatever move led to them.
+    pub fn position_key(&self, color: Color) -> u64 {
+        let mut hash = self.zobrist(color);
+        if self.prev_move.ty == MoveType::PawnLeap && !self.can_capture_en_passant(color) {
+            hash ^= EN_PASSANT[(self.prev_move.to & 7) as usize];
+        }
+        hash
+    }
+
+    /// Whether `color` can take the pawn that just leaped en passant. It runs at every node of
+    /// the search through `position_key()`, so the capture is only played out on a copy of the
+    /// board in the rare case that it could expose the king.
+    pub fn can_capture_en_passant(&self, color: Color) -> bool {
+        let leap = self.prev_move;
+        if leap.ty != MoveType::PawnLeap {
+            return false;
+        }
+        let pieces = self.get_pieces(color);
+        let other = self.get_pieces(color.inv());
+        let to = (leap.from + leap.to) / 2;
+        let left = Some(leap.to.wrapping_sub(1)).filter(|_| leap.to & 7 != 0);
+        let right = Some(leap.to + 1).filter(|_| leap.to & 7 != 7);
+
+        let king = pieces.king();
+        let sliders = other.queens() | other.rooks() | other.bishops();
+        left.into_iter()
+            .chain(right)
+            .filter(|&from| pieces.pawns() & 1 << from != 0)
+            .any(|from| {
+                if king == 0 {
+                    return true;
+                }
+                // The capture empties its pawn's square and the leaped pawn's, the king can only
+                // be exposed along the lines through them, or already be in check.
+                let king_square = king.trailing_zeros() as usize;
+                let exposed = (LINE[king_square][from as usize]
+                    | LINE[king_square][leap.to as usize])
+                    & sliders;
+                if exposed == 0 && !self.in_check(color) {
+                    return true;
+                }
+                self.is_legal(
+                    color,
+                    Move {
+                        from,
+                        to,
+                        ty: MoveType::PawnEnPassant,
+                    },
+                )
+            })
+    }
+}
@@ -0,0 +1,609 @@
+// The board and its move generation: the rules of the variants, the bitboard tables, pins,
+// checks and castling, and the hashing of positions.
+
+mod common;
+
+use common::{after, PERFT};
+use mxchess::{
+    masks::{self, CENTER, FILE_A, FILE_H, LIGHT_SQUARES, RANK_1, RANK_8},
+    Board, Bot, ChessFlags, Color, Game, GameStatus, Move, MoveType, MxChessError, Piece,
+    PieceType, Square, Variant,
+};
+use std::convert::TryFrom;
+
+#[test]
+fn crazyhouse_drops_the_captured_pieces() {
+    // From Fairy-Stockfish, the captures on the third ply can be dropped back on the fifth.
+    let board = Board::with_variant(Variant::Crazyhouse);
+    assert_eq!(board.perft_fast(Color::White, 5), 4_888_832);
+
+    let fen = "r1bqk1nr/pppp1ppp/2n5/4p3/4P3/6N1/PPPP1PPP/RNBQKB1r~[BNbp] w Qkq - 0 1";
+    let (mut board, color) = Board::from_fen(fen).unwrap();
+    assert_eq!(board.to_fen(color), fen);
+    assert_eq!(board.reserve(Color::Black, PieceType::Bishop), 1);
+    assert!(board.parse_move(color, "B@b5").is_some());
+    assert!(board.parse_move(color, "P@e6").is_none());
+    assert!(board.parse_move(color, "N@e8").is_none());
+
+    // The promoted rook goes back to the reserve as a pawn.
+    board.perform_move_unchecked(board.parse_san(color, "Nxh1").unwrap());
+    assert_eq!(board.reserve(Color::White, PieceType::Pawn), 1);
+    assert_eq!(board.reserve(Color::White, PieceType::Rook), 0);
+}
+
+#[test]
+fn kings_race_to_the_hill() {
+    let (mut board, color) = Board::from_fen("7k/8/8/8/8/2K5/8/8 w - - 0 1").unwrap();
+    board.variant = Variant::KingOfTheHill;
+    let mv = Bot::with_depth(2).choose_move(&board, color).unwrap();
+    assert_eq!(mv.to_string(), "c3d4");
+
+    let mut game = Game::from_position(board, color);
+    assert!(game.push_move(mv));
+    assert_eq!(game.status(), GameStatus::VariantWin(Color::White));
+}
+
+#[test]
+fn horde_pawns_leap_from_the_first_rank() {
+    // From python-chess' Horde perft tests.
+    let board = Board::with_variant(Variant::Horde);
+    assert_eq!(board.perft(Color::White, 4), 23_310);
+    let fen = "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1";
+    assert_eq!(Board::from_fen(fen).unwrap(), (board, Color::White));
+    assert_eq!(board.to_fen(Color::White), fen);
+
+    // The leap to a3 passes the black pawn on b2, but it can't be taken en passant.
+    let (board, color) = Board::from_fen("4k3/8/8/8/8/8/1p6/P7 w - - 0 1").unwrap();
+    let leap = board.parse_move(color, "a1a3").unwrap();
+    assert_eq!(leap.ty, MoveType::PawnLeap);
+    assert!(after(&board, leap)
+        .moves(color.inv())
+        .iter()
+        .all(|mv| mv.ty != MoveType::PawnEnPassant));
+
+    // Black wins by taking the last pawn.
+    let mut game = Game::from_position(board, color.inv());
+    assert!(game.push_move(board.parse_move(color.inv(), "b2a1").unwrap()));
+    assert_eq!(game.status(), GameStatus::VariantWin(Color::Black));
+}
+
+#[test]
+fn en_passant_out_of_a_pin_is_illegal() {
+    // Taking on c6 would leave the king in the rook's line.
+    let (board, color) = Board::from_diagram(
+        "
+        . . . . . . . k
+        . . . . . . . .
+        . . . . . . . .
+        K P p . . . . r
+        . . . . . . . .
+        . . . . . . . .
+        . . . . . . . .
+        . . . . . . . .
+        w - c6
+        ",
+    )
+    .unwrap();
+    assert_eq!(board.prev_move.ty, MoveType::PawnLeap);
+    assert!(board
+        .moves(color)
+        .iter()
+        .all(|mv| mv.ty != MoveType::PawnEnPassant));
+}
+
+#[test]
+fn boards_without_a_king_are_refused() {
+    let (mut board, color) = Board::from_fen("4k3/8/8/8/8/8/4P3/R3K2R w KQ - 0 1").unwrap();
+    board.set(1 << 0o04, None);
+    assert!(board.validate().is_err());
+    assert!(board
+        .moves(color)
+        .iter()
+        .all(|mv| mv.ty != MoveType::Castle));
+    assert_eq!(Bot::with_depth(3).choose_move(&board, color), None);
+    assert!(Board::from_fen("8/8/8/8/8/8/4P3/R6R w - - 0 1").is_err());
+}
+
+#[test]
+fn diffs_list_the_changed_squares() {
+    let board = Board::new();
+    let mut after = board;
+    after.perform_move_unchecked(board.parse_move(Color::White, "e2e4").unwrap());
+    let pawn = Some(Piece {
+        color: Color::White,
+        ty: PieceType::Pawn,
+    });
+    assert_eq!(
+        board.diff(&after),
+        [(Square::E2, pawn, None), (Square::E4, None, pawn)]
+    );
+    assert_eq!(
+        after.diff(&board),
+        [(Square::E2, None, pawn), (Square::E4, pawn, None)]
+    );
+    assert!(board.diff(&board).is_empty());
+
+    let (castling, color) = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    let mut castled = castling;
+    castled.perform_move_unchecked(castling.parse_move(color, "e1g1").unwrap());
+    let squares: Vec<_> = castling
+        .diff(&castled)
+        .iter()
+        .map(|&(sq, _, _)| sq)
+        .collect();
+    assert_eq!(squares, [Square::E1, Square::F1, Square::G1, Square::H1]);
+}
+
+#[test]
+fn legal_destinations_of_a_square() {
+    let board = Board::new();
+    assert_eq!(
+        board.legal_destinations(Square::G1),
+        Square::F3.bit() | Square::H3.bit()
+    );
+    assert_eq!(
+        board.legal_destinations(Square::E7),
+        Square::E6.bit() | Square::E5.bit()
+    );
+    assert_eq!(board.legal_destinations(Square::A1), 0);
+    assert_eq!(board.legal_destinations(Square::E4), 0);
+
+    // The e2 knight is pinned to the king by the rook.
+    let (board, _) = Board::from_fen("4r1k1/8/8/8/8/8/4N3/4K3 w - - 0 1").unwrap();
+    assert_eq!(board.legal_destinations(Square::E2), 0);
+    assert_eq!(
+        board.legal_destinations(Square::E1),
+        Square::D1.bit() | Square::F1.bit() | Square::D2.bit() | Square::F2.bit()
+    );
+}
+
+#[test]
+fn batches_of_moves_are_filtered_like_one_by_one() {
+    // The d2 bishop is pinned, White can castle only kingside.
+    let (board, color) = Board::from_fen("2r1k3/1P6/8/b7/8/8/3B4/4K2R w K - 0 1").unwrap();
+    let candidates: Vec<_> = [
+        (0o61, 0o71, MoveType::PawnKnightPromotion),
+        (0o61, 0o72, MoveType::PawnRookPromotion),
+        (0o61, 0o71, MoveType::PawnQueenPromotion),
+        (0o13, 0o24, MoveType::Bishop),
+        (0o13, 0o22, MoveType::Bishop),
+        (0o04, 0o06, MoveType::Castle),
+        (0o04, 0o02, MoveType::Castle),
+        (0o07, 0o77, MoveType::Rook),
+        (0o07, 0o00, MoveType::Rook),
+    ]
+    .iter()
+    .map(|&(from, to, ty)| Move { from, to, ty })
+    .collect();
+    let one_by_one: Vec<_> = candidates
+        .iter()
+        .copied()
+        .filter(|&mv| board.is_legal(color, mv))
+        .collect();
+    assert_eq!(board.filter_legal(color, &candidates), one_by_one);
+    assert_eq!(one_by_one.len(), 6);
+
+    for &(fen, _, _) in &PERFT {
+        let (board, color) = Board::from_fen(fen).unwrap();
+        let moves = board.moves(color);
+        assert_eq!(board.filter_legal(color, &moves), moves, "{}", fen);
+    }
+}
+
+#[test]
+fn attacked_squares_match_the_attack_maps() {
+    for &(fen, _, _) in &PERFT {
+        let (board, color) = Board::from_fen(fen).unwrap();
+        assert_eq!(
+            board.in_check(color),
+            board.check_attack(color.inv()) & board.get_pieces(color).king() != 0
+        );
+        for by in [Color::White, Color::Black] {
+            // `check_attack()` sees through the other king.
+            let mut board = board;
+            board.set(board.get_pieces(by.inv()).king(), None);
+            let attack = board.check_attack(by);
+            for square in 0..64 {
+                assert_eq!(
+                    board.is_attacked(Square::from_index(square).unwrap(), by),
+                    attack >> square & 1 != 0,
+                    "{} {:o}",
+                    fen,
+                    square
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn squares_by_name_and_coordinates() {
+    assert_eq!(Square::E4.index(), 0o34);
+    assert_eq!(Square::new(4, 3), Some(Square::E4));
+    assert_eq!(Square::new(8, 0), None);
+    assert_eq!((Square::E4.file(), Square::E4.rank()), (4, 3));
+    assert_eq!(Square::from_index(0o77), Some(Square::H8));
+    assert_eq!(Square::from_index(64), None);
+    assert_eq!(Square::H8.bit(), 1 << 63);
+    assert_eq!(Square::A1.distance(Square::H8), 7);
+    assert_eq!(Square::E4.distance(Square::F6), 2);
+    assert_eq!(Square::E4.to_string(), "e4");
+    assert_eq!("h1".parse(), Ok(Square::H1));
+    assert!("i1".parse::<Square>().is_err());
+    assert!("e44".parse::<Square>().is_err());
+    for index in 0..64 {
+        let square = Square::from_index(index).unwrap();
+        assert_eq!(square.to_string().parse(), Ok(square));
+    }
+}
+
+#[test]
+fn masks_of_the_regions_and_lines() {
+    assert_eq!(FILE_A | FILE_H, 0x8181_8181_8181_8181);
+    assert_eq!(RANK_1 | RANK_8, 0xff00_0000_0000_00ff);
+    assert_ne!(LIGHT_SQUARES & Square::H1.bit(), 0);
+    assert_eq!(LIGHT_SQUARES & Square::A1.bit(), 0);
+    assert_eq!(CENTER.count_ones(), 4);
+    let (a1, e1, h1, h8) = (
+        Square::A1.index(),
+        Square::E1.index(),
+        Square::H1.index(),
+        Square::H8.index(),
+    );
+    assert_eq!(
+        masks::between(a1, e1),
+        Square::B1.bit() | Square::C1.bit() | Square::D1.bit()
+    );
+    assert_eq!(masks::between(e1, a1), masks::between(a1, e1));
+    assert_eq!(masks::between(a1, Square::B1.index()), 0);
+    assert_eq!(masks::between(a1, Square::B3.index()), 0);
+    assert_eq!(
+        masks::line(Square::C3.index(), Square::E5.index()),
+        masks::line(a1, h8)
+    );
+    assert_eq!(masks::line(a1, h8).count_ones(), 8);
+    assert_eq!(masks::line(e1, h1), RANK_1);
+    assert_eq!(masks::line(a1, Square::B3.index()), 0);
+}
+
+#[test]
+fn occupancy_of_the_starting_position() {
+    let board = Board::new();
+    assert_eq!(board.occupied_by(Color::White), RANK_1 | RANK_1 << 0o10);
+    assert_eq!(board.occupied_by(Color::Black), RANK_8 | RANK_8 >> 0o10);
+    assert_eq!(
+        board.occupied(),
+        board.occupied_by(Color::White) | board.occupied_by(Color::Black)
+    );
+    assert_eq!(board.empty().count_ones(), 32);
+    assert_eq!(board.empty() & board.occupied(), 0);
+}
+
+#[test]
+fn material_counts_and_signatures() {
+    let (board, _) = Board::from_fen("8/8/4k3/4r3/8/2R1P3/4K3/8 w - - 0 1").unwrap();
+    let white = board.material_count(Color::White);
+    assert_eq!(white.get(PieceType::Rook), 1);
+    assert_eq!((white.total(), white.non_pawns()), (3, 1));
+    assert!(!white.is_bare());
+    let signature = board.material_signature();
+    assert_eq!(signature.to_string(), "KRPvKR");
+    assert_eq!(signature.mirror().to_string(), "KRvKRP");
+    assert_eq!(signature.pieces(), 5);
+    assert_ne!(signature.key(), signature.mirror().key());
+    assert_eq!(
+        Board::new().material_signature().to_string(),
+        "KQRRBBNNPPPPPPPPvKQRRBBNNPPPPPPPP"
+    );
+}
+
+#[test]
+fn pins_and_checkers_from_the_line_tables() {
+    // The h4 bishop checks the king, the e2 knight is pinned by the e8 rook so it can't block
+    // while the h1 knight and the a3 rook can.
+    let (board, color) = Board::from_fen("4r2k/8/8/8/7b/R7/4N3/4K2N w - - 0 1").unwrap();
+    assert_eq!(board.find_pins(color), Square::E2.bit());
+    assert_eq!(board.checkers(color), Square::H4.bit());
+    assert_eq!(
+        masks::BETWEEN[Square::E1.index() as usize][Square::H4.index() as usize],
+        Square::F2.bit() | Square::G3.bit()
+    );
+    let mut moves: Vec<_> = board.moves(color).iter().map(|mv| mv.to_string()).collect();
+    moves.sort();
+    assert_eq!(moves, ["a3g3", "e1d1", "e1d2", "e1f1", "h1f2", "h1g3"]);
+}
+
+#[test]
+fn xrays_see_through_the_first_blockers() {
+    let (board, _) = Board::from_fen("3q3k/8/3p4/8/8/3R4/8/B2RK3 w - - 0 1").unwrap();
+    let occupied = board.get_pieces(Color::White).all | board.get_pieces(Color::Black).all;
+    // Through the d3 rook, the d1 rook is backed up to the d6 pawn.
+    assert_eq!(
+        Board::xray_rook_attacks(Square::D1.index(), occupied, Square::D3.bit()),
+        Square::D4.bit() | Square::D5.bit() | Square::D6.bit()
+    );
+    // Blockers that aren't first on a ray change nothing.
+    assert_eq!(
+        Board::xray_rook_attacks(Square::D1.index(), occupied, Square::D6.bit()),
+        0
+    );
+    assert_eq!(
+        Board::xray_bishop_attacks(Square::A1.index(), occupied, 0),
+        0
+    );
+    assert_eq!(
+        Board::xray_rook_attacks(Square::E1.index(), occupied, Square::D1.bit()),
+        Square::C1.bit() | Square::B1.bit() | Square::A1.bit()
+    );
+}
+
+#[test]
+fn discovered_and_double_checks() {
+    // The d3 bishop blocks the d1 rook.
+    let (board, color) = Board::from_fen("8/3k4/8/8/8/3B4/8/3RK3 w - - 0 1").unwrap();
+    let bishop_move = |to: Square| Move {
+        from: Square::D3.index(),
+        to: to.index(),
+        ty: MoveType::Bishop,
+    };
+    assert!(board.move_gives_discovered_check(bishop_move(Square::E4)));
+    let king_move = Move {
+        from: Square::E1.index(),
+        to: Square::E2.index(),
+        ty: MoveType::King,
+    };
+    assert!(!board.move_gives_discovered_check(king_move));
+
+    // Bd3-b5 checks with both the bishop and the rook.
+    let mut next = after(&board, bishop_move(Square::B5));
+    assert_eq!(
+        next.checkers(color.inv()),
+        Square::B5.bit() | Square::D1.bit()
+    );
+    assert!(next.in_double_check(color.inv()));
+    assert!(next
+        .moves(color.inv())
+        .iter()
+        .all(|mv| mv.ty == MoveType::King));
+    next.perform_move_unchecked(Move {
+        from: Square::D7.index(),
+        to: Square::E7.index(),
+        ty: MoveType::King,
+    });
+    assert!(!next.in_double_check(color.inv()));
+}
+
+#[test]
+fn all_four_castles_need_empty_and_safe_squares() {
+    let castles = |fen: &str| {
+        let (board, color) = Board::from_fen(fen).unwrap();
+        let mut castles: Vec<_> = board
+            .moves(color)
+            .iter()
+            .filter(|mv| mv.ty == MoveType::Castle)
+            .map(|mv| mv.to_string())
+            .collect();
+        castles.sort();
+        castles
+    };
+    assert_eq!(
+        castles("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1"),
+        ["e1c1", "e1g1"]
+    );
+    assert_eq!(
+        castles("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1"),
+        ["e8c8", "e8g8"]
+    );
+    // Blocked by a piece of either color, b1 and b8 included.
+    assert_eq!(
+        castles("rn2k1Nr/8/8/8/8/8/8/Rn2K1nR w KQkq - 0 1"),
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        castles("rN2k1Nr/8/8/8/8/8/8/RN2K1NR b KQkq - 0 1"),
+        Vec::<String>::new()
+    );
+    // The king can't pass attacked squares, the rook can.
+    assert_eq!(
+        castles("r3k2r/8/8/8/8/8/4p3/R3K2R w KQkq - 0 1"),
+        Vec::<String>::new()
+    );
+    assert_eq!(castles("r3k2r/6P1/8/8/8/8/8/R3K2R b KQkq - 0 1"), ["e8c8"]);
+    assert_eq!(
+        castles("1r2k2r/8/8/8/8/8/8/R3K2R w KQk - 0 1"),
+        ["e1c1", "e1g1"]
+    );
+    assert_eq!(
+        castles("r3k2r/8/8/8/8/8/8/1R2K2R b KQkq - 0 1"),
+        ["e8c8", "e8g8"]
+    );
+    // Without the rights.
+    assert_eq!(castles("r3k2r/8/8/8/8/8/8/R3K2R w Kq - 0 1"), ["e1g1"]);
+    assert_eq!(castles("r3k2r/8/8/8/8/8/8/R3K2R b Kq - 0 1"), ["e8c8"]);
+
+    // Moving a rook gives up its castle, moving the king both.
+    let (board, color) = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    let rook = after(
+        &board,
+        Move {
+            from: Square::H1.index(),
+            to: Square::H2.index(),
+            ty: MoveType::Rook,
+        },
+    );
+    assert_eq!(
+        rook.flags,
+        ChessFlags::WHITE_QUEENS_CASTLE
+            | ChessFlags::BLACK_KINGS_CASTLE
+            | ChessFlags::BLACK_QUEENS_CASTLE
+    );
+    let castled = after(&board, board.parse_move(color, "e1c1").unwrap());
+    assert_eq!(
+        castled.get_at(Square::D1.bit()).map(|piece| piece.ty),
+        Some(PieceType::Rook)
+    );
+    assert_eq!(
+        castled.flags,
+        ChessFlags::BLACK_KINGS_CASTLE | ChessFlags::BLACK_QUEENS_CASTLE
+    );
+}
+
+#[test]
+fn errors_convert_into_the_crate_error() {
+    fn first_move(fen: &str, san: &str) -> Result<Move, MxChessError> {
+        let (board, color) = Board::from_fen(fen)?;
+        board.check_valid()?;
+        board.try_parse_san(color, san)
+    }
+
+    assert!(first_move(mxchess::fen::STARTPOS, "e4").is_ok());
+    assert!(matches!(
+        first_move("not a fen", "e4"),
+        Err(MxChessError::Fen(_))
+    ));
+    assert_eq!(
+        first_move(mxchess::fen::STARTPOS, "e5"),
+        Err(MxChessError::San("e5".to_string()))
+    );
+    let (mut board, color) = Board::from_fen(mxchess::fen::STARTPOS).unwrap();
+    board.set(Square::E1.bit(), None);
+    assert!(matches!(
+        board.check_valid(),
+        Err(MxChessError::InvalidPosition(_))
+    ));
+    assert!(matches!(
+        board.try_parse_move(color, "e2e5"),
+        Err(MxChessError::Uci(_))
+    ));
+    assert_eq!(PieceType::try_from(4), Ok(PieceType::Knight));
+    assert_eq!(PieceType::try_from(6), Err(MxChessError::PieceType(6)));
+}
+
+#[test]
+fn captures_include_the_queen_promotions() {
+    // The b7 pawn can promote on b8 or take on a8 or c8, the g2 pawn only push.
+    let (board, color) = Board::from_fen("r1n4k/1P6/8/8/8/8/6p1/4K3 w - - 0 1").unwrap();
+    let mut captures: Vec<_> = board
+        .capture_moves(color)
+        .iter()
+        .map(|mv| mv.to_string())
+        .collect();
+    captures.sort();
+    assert_eq!(captures, ["b7a8q", "b7b8q", "b7c8q"]);
+    let black: Vec<_> = board
+        .capture_moves(color.inv())
+        .iter()
+        .map(|mv| mv.to_string())
+        .collect();
+    assert_eq!(black, ["g2g1q"]);
+}
+
+#[test]
+fn crazyhouse_positions_with_hundreds_of_moves() {
+    let fen = "4k3/8/8/8/8/8/8/4K3[QRBNPqrbnp] w - - 0 1";
+    let (board, color) = Board::from_fen(fen).unwrap();
+    let moves = board.moves(color);
+    assert!(moves.len() > mxchess::MoveList::CAPACITY);
+    assert_eq!(board.filter_legal(color, &moves), moves);
+    assert!(board.has_moves(color, board.check_attack(color.inv())));
+    assert_eq!(board.legal_destinations(Square::E1).count_ones(), 5);
+
+    let mut game = Game::from_position(board, color);
+    assert_eq!(game.status(), GameStatus::Ongoing);
+    let check = board.parse_move(color, "Q@e7").unwrap();
+    assert_eq!(board.to_san(color, check), "Q@e7+");
+    assert!(game.push_move(check));
+    assert_eq!(game.status(), GameStatus::Ongoing);
+}
+
+#[test]
+fn crazyhouse_reserves_are_capped() {
+    let full = "4k3/8/8/8/8/8/8/4K3[PPPPPPPPPPPPPPPP] w - - 0 1";
+    let (board, _) = Board::from_fen(full).unwrap();
+    assert_eq!(board.reserve(Color::White, PieceType::Pawn), 16);
+    let overfull = format!("4k3/8/8/8/8/8/8/4K3[{}] w - - 0 1", "p".repeat(17));
+    assert!(Board::from_fen(&overfull).is_err());
+    let wrapping = format!("4k3/8/8/8/8/8/8/4K3[{}] w - - 0 1", "Q".repeat(256));
+    assert!(Board::from_fen(&wrapping).is_err());
+}
+
+#[test]
+fn promoted_crazyhouse_pieces_change_the_key() {
+    let (queen, color) = Board::from_fen("4k3/8/8/8/8/8/8/Q3K3[] w - - 0 1").unwrap();
+    let (promoted, _) = Board::from_fen("4k3/8/8/8/8/8/8/Q~3K3[] w - - 0 1").unwrap();
+    assert_ne!(queen, promoted);
+    assert_ne!(queen.position_key(color), promoted.position_key(color));
+    assert_ne!(queen.zobrist(color), promoted.zobrist(color));
+}
+
+#[test]
+fn move_lists_tell_when_moves_are_left_out() {
+    use mxchess::MoveList;
+
+    let board = Board::new();
+    let mut list = MoveList::new();
+    board.moves_into(Color::White, board.check_attack(Color::Black), &mut list);
+    assert!(!list.overflowed());
+    assert_eq!(list[..], board.moves(Color::White)[..]);
+
+    let fen = "4k3/8/8/8/8/8/8/4K3[QRBNPqrbnp] w - - 0 1";
+    let (board, color) = Board::from_fen(fen).unwrap();
+    let moves = board.moves(color);
+    list.clear();
+    board.moves_into(color, board.check_attack(color.inv()), &mut list);
+    assert!(list.overflowed());
+    assert_eq!(list[..], moves[..MoveList::CAPACITY]);
+    list.clear();
+    assert!(!list.overflowed() && list.is_empty());
+}
+
+#[test]
+fn passing_clears_en_passant() {
+    // Black's d-pawn just leaped next to White's e5 pawn.
+    let fen = "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1";
+    let (mut board, color) = Board::from_fen(fen).unwrap();
+    let before = board;
+    assert!(board.can_capture_en_passant(color));
+
+    // White passes, and by its next turn the right to take en passant is gone.
+    let (turn, undo) = board.make_null_move(color);
+    assert_eq!(turn, Color::Black);
+    assert!(!board.can_capture_en_passant(color));
+    assert!(!board
+        .moves(color)
+        .iter()
+        .any(|mv| mv.ty == MoveType::PawnEnPassant));
+    let (passed, _) = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 b - - 0 1").unwrap();
+    assert_eq!(board.zobrist(turn), passed.zobrist(turn));
+    assert_eq!(board.position_key(turn), passed.position_key(turn));
+
+    board.undo_null_move(undo);
+    assert_eq!(board, before);
+}
+
+#[test]
+fn en_passant_rights_match_the_legal_moves() {
+    for fen in [
+        // Free to take.
+        "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1",
+        // Both pawns leave the king's rank to the rook.
+        "8/8/8/K2pP2r/8/8/8/4k3 w - d6 0 1",
+        // The taken pawn blocked the bishop's diagonal to the king.
+        "8/8/8/3pP3/8/1b6/8/5K1k w - d6 0 1",
+        "8/8/8/3pP3/2K5/8/8/6bk w - d6 0 1",
+        // The capturing pawn is pinned along a diagonal.
+        "8/8/8/3pP3/8/8/1K6/7k w - d6 0 1",
+        "4k3/b7/8/3pP3/4K3/8/8/8 w - d6 0 1",
+        // In check from the leaped pawn, which the capture answers.
+        "8/8/8/3pP3/4K3/8/8/7k w - d6 0 1",
+        // In check from a knight, which it doesn't.
+        "8/8/5n2/3pP3/4K3/8/8/7k w - d6 0 1",
+    ] {
+        let (board, color) = Board::from_fen(fen).unwrap();
+        let legal = board
+            .moves(color)
+            .iter()
+            .any(|mv| mv.ty == MoveType::PawnEnPassant);
+        assert_eq!(board.can_capture_en_passant(color), legal, "{}", fen);
+    }
+}
@@ -0,0 +1,55 @@
+// The positions the tests share: the standard perft positions and the mate puzzles.
+
+#![allow(dead_code)]
+
+use mxchess::{Board, Move};
+
+/// (FEN, depth, nodes) from https://www.chessprogramming.org/Perft_Results
+pub const PERFT: [(&str, u32, u64); 6] = [
+    (
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        4,
+        197_281,
+    ),
+    (
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        3,
+        97_862,
+    ),
+    ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 4, 43_238),
+    (
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        3,
+        9_467,
+    ),
+    (
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        3,
+        62_379,
+    ),
+    (
+        "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+        3,
+        89_890,
+    ),
+];
+
+/// (FEN, moves to mate)
+pub const MATES: [(&str, u32); 5] = [
+    // Morphy: 1. Ra6 bxa6 2. b7#
+    ("kbK5/pp6/1P6/8/8/8/8/R7 w - - 0 1", 2),
+    // 1. Nf6+ gxf6 2. Bxf7#
+    (
+        "r2qkb1r/pp2nppp/3p4/2pNN1B1/2BnP3/3P4/PPP2PPP/R2bK2R w KQkq - 1 1",
+        2,
+    ),
+    ("1r6/4b2k/1q1pNrpp/p2Pp3/4P3/1P1R3Q/5PPP/5RK1 w - - 1 1", 2),
+    ("6k1/pp4p1/2p5/2bp4/8/P5Pb/1P3rrP/2BRRN1K b - - 0 1", 2),
+    ("r5rk/5p1p/5R2/4B3/8/8/7P/7K w - - 0 1", 3),
+];
+
+pub fn after(board: &Board, mv: Move) -> Board {
+    let mut board = *board;
+    board.perform_move_unchecked(mv);
+    board
+}
@@ -0,0 +1,165 @@
+// The evaluation of positions: the handcrafted terms, the NNUE and the cache.
+
+mod common;
+
+use common::PERFT;
+use mxchess::{
+    eval::{phase, EvalParams, PhaseParams},
+    tt::TranspositionTable,
+    Board, Bot,
+};
+use std::sync::Arc;
+
+#[test]
+fn the_eval_cache_doesnt_change_the_values() {
+    let cache = Arc::new(TranspositionTable::new(1));
+    for &(fen, _, _) in &PERFT[..3] {
+        let (board, color) = Board::from_fen(fen).unwrap();
+        let bot = Bot::with_depth(3);
+        bot.choose_move(&board, color);
+        let mut cached = Bot::with_depth(3);
+        cached.eval_cache = Some(cache.clone());
+        // The second search reads the first one's evaluations.
+        for _ in 0..2 {
+            cached.choose_move(&board, color);
+            assert_eq!(cached.value(), bot.value(), "{}", fen);
+        }
+    }
+}
+
+#[test]
+fn the_nnue_accumulator_follows_the_moves() {
+    use mxchess::{
+        eval::Evaluator,
+        nnue::{Nnue, HIDDEN},
+    };
+
+    // A network of arbitrary weights, each input's column of them distinct.
+    let mut bytes = b"MXNN".to_vec();
+    bytes.extend_from_slice(&(HIDDEN as u32).to_le_bytes());
+    let mut seed = 1u32;
+    for _ in 0..(768 + 2) * HIDDEN {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        bytes.extend_from_slice(&((seed >> 16) as i16 % 200).to_le_bytes());
+    }
+    bytes.extend_from_slice(&7i32.to_le_bytes());
+    let nnue = Nnue::from_bytes(&bytes).unwrap();
+    assert_eq!(nnue.to_bytes(), bytes);
+
+    // Castles, en passant, promotions and drops.
+    let crazyhouse = "r1bqk1nr/pppp1ppp/2n5/4p3/4P3/6N1/PPPP1PPP/RNBQKB1r~[BNbp] w Qkq - 0 1";
+    for fen in PERFT.iter().map(|&(fen, _, _)| fen).chain([crazyhouse]) {
+        let (board, color) = Board::from_fen(fen).unwrap();
+        let state = nnue.init(&board);
+        for mv in board.moves(color) {
+            let mut next = board;
+            next.perform_move_unchecked(mv);
+            let mut next_state = state;
+            nnue.update(&mut next_state, &board, &next);
+            assert_eq!(next_state, nnue.init(&next), "{} {}", fen, mv);
+        }
+    }
+}
+
+/// Material and `terms` in both phases, without the default passed pawn terms.
+fn with_terms(terms: PhaseParams) -> EvalParams {
+    let terms = PhaseParams {
+        passed_pawn: [0; 8],
+        free_passer: 0,
+        rook_behind_passer: 0,
+        passer_king_distance: 0,
+        ..terms
+    };
+    EvalParams {
+        middlegame: terms.clone(),
+        endgame: terms,
+    }
+}
+
+fn evaluate(params: &EvalParams, fen: &str) -> i32 {
+    params.evaluate(&Board::from_fen(fen).unwrap().0)
+}
+
+#[test]
+fn the_phase_picks_the_weights() {
+    let pawns = "4k3/pp6/8/8/8/8/PPP5/4K3 w - - 0 1";
+    let full = "rnbqkbnr/ppp1pppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+    assert_eq!(phase(&Board::from_fen(pawns).unwrap().0), 0);
+    assert_eq!(phase(&Board::from_fen(full).unwrap().0), 24);
+
+    let other = PhaseParams {
+        material: [0, 1800, 1000, 600, 600, 200],
+        passed_pawn: [0, 50, 50, 50, 50, 50, 50, 0],
+        ..PhaseParams::default()
+    };
+    let default = EvalParams::default();
+    let middlegame = EvalParams {
+        middlegame: other.clone(),
+        ..EvalParams::default()
+    };
+    let endgame = EvalParams {
+        endgame: other,
+        ..EvalParams::default()
+    };
+    assert_eq!(evaluate(&middlegame, pawns), evaluate(&default, pawns));
+    assert_ne!(evaluate(&endgame, pawns), evaluate(&default, pawns));
+    assert_eq!(evaluate(&endgame, full), evaluate(&default, full));
+    assert_ne!(evaluate(&middlegame, full), evaluate(&default, full));
+}
+
+#[test]
+fn passed_pawns_want_the_king_in_front() {
+    let params = EvalParams::default();
+    let escorted = evaluate(&params, "8/p7/3K4/4P3/8/8/P7/7k w - - 0 1");
+    let behind = evaluate(&params, "8/p7/8/4P3/8/8/P3K3/7k w - - 0 1");
+    assert!(escorted > behind, "{} {}", escorted, behind);
+}
+
+#[test]
+fn rooks_want_open_files() {
+    let params = with_terms(PhaseParams {
+        rook_open_file: 25,
+        rook_semi_open_file: 10,
+        ..PhaseParams::default()
+    });
+    let open = evaluate(&params, "4k3/4p3/8/8/8/8/4P3/3RK3 w - - 0 1");
+    let semi_open = evaluate(&params, "4k3/3p4/8/8/8/8/4P3/3RK3 w - - 0 1");
+    let closed = evaluate(&params, "4k3/3p4/8/8/8/8/3P4/3RK3 w - - 0 1");
+    assert_eq!(open - semi_open, 15);
+    assert_eq!(semi_open - closed, 10);
+}
+
+#[test]
+fn the_bishop_pair_and_knight_outposts() {
+    let params = with_terms(PhaseParams {
+        bishop_pair: 50,
+        knight_outpost: 20,
+        ..PhaseParams::default()
+    });
+    let pair = evaluate(&params, "4k3/p7/8/8/8/8/7P/2B1KB2 w - - 0 1");
+    let mixed = evaluate(&params, "4k3/p7/8/8/8/8/7P/2N1KB2 w - - 0 1");
+    assert_eq!(pair - mixed, 50);
+
+    // The knight on d5 is protected by the c-pawn, and only a pawn on c7 could chase it.
+    let outpost = evaluate(&params, "4k3/p7/8/3N4/2P5/8/8/4K3 w - - 0 1");
+    let chased = evaluate(&params, "4k3/2p5/8/3N4/2P5/8/8/4K3 w - - 0 1");
+    let unprotected = evaluate(&params, "4k3/p7/8/3N4/8/2P5/8/4K3 w - - 0 1");
+    assert_eq!(outpost - chased, 20);
+    assert_eq!(outpost - unprotected, 20);
+}
+
+#[test]
+fn the_side_to_move_gets_the_tempo() {
+    // Without captures, the search ends in quiet positions with White to move again.
+    let (board, color) = Board::from_fen("4k3/p7/8/8/8/8/7P/4K3 w - - 0 1").unwrap();
+    let values = |tempo| {
+        let mut bot = Bot::with_depth(1);
+        bot.options.tempo = tempo;
+        bot.score_all_moves(&board, color, 1)
+    };
+    let (with, without) = (values(30), values(0));
+    assert_eq!(with.len(), without.len());
+    for (&(mv, value), &(_, other)) in with.iter().zip(&without) {
+        assert_eq!(value, other + 30, "{}", mv);
+    }
+}
@@ -0,0 +1,280 @@
+// Chess in and out of the board: FEN diagrams, PGN, typed moves, opening names and the text,
+// SVG and GIF renderings.
+
+mod common;
+
+use common::PERFT;
+use mxchess::{eco::EcoCode, pgn::PgnGame, Board, Color, Game, MoveType, Odds, Square};
+
+#[test]
+fn rook_odds_are_recorded_in_the_pgn() {
+    let mut game = Game::with_odds(Odds::RookOdds);
+    assert_eq!(
+        game.board().to_fen(Color::White),
+        "1nbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQk - 0 1"
+    );
+    assert!(game.push_move(game.board().parse_san(Color::White, "e4").unwrap()));
+
+    let pgn = PgnGame::from_game(&game);
+    assert_eq!(pgn.tag("Odds"), Some("rook"));
+    let replayed = pgn.mainline().unwrap();
+    assert_eq!(replayed.odds(), Some(Odds::RookOdds));
+    assert_eq!(replayed.board(), game.board());
+    assert_eq!(Game::new().odds(), None);
+}
+
+#[test]
+fn diagrams_read_rendered_boards() {
+    for &(fen, _, _) in &PERFT {
+        let (board, _) = Board::from_fen(fen).unwrap();
+        for color in [Color::White, Color::Black] {
+            let (diagram, _) = Board::from_diagram(&board.render(color)).unwrap();
+            assert_eq!(
+                (diagram.white_pieces, diagram.black_pieces),
+                (board.white_pieces, board.black_pieces),
+                "{}",
+                fen
+            );
+        }
+    }
+}
+
+#[test]
+fn openings_are_named_through_transpositions() {
+    let play = |moves: &str| {
+        let mut game = Game::new();
+        for san in moves.split_whitespace() {
+            assert!(game.push_move(game.board().parse_san(game.turn(), san).unwrap()));
+        }
+        game
+    };
+
+    let game = play("e4 e5 Nf3 Nc6 Bc4 Bc5 c3");
+    let (code, name) = game.opening().unwrap();
+    assert_eq!(
+        (code.to_string().as_str(), name),
+        ("C50", "Italian Game: Giuoco Piano")
+    );
+    let pgn = PgnGame::from_game(&game);
+    assert_eq!(pgn.tag("ECO"), Some("C50"));
+    assert_eq!(pgn.tag("Opening"), Some("Italian Game: Giuoco Piano"));
+
+    assert_eq!(
+        play("Nf3 Nc6 e4 e5").opening(),
+        play("e4 e5 Nf3 Nc6").opening()
+    );
+    assert_eq!(play("a3 a6").opening(), None);
+    assert_eq!("E60".parse::<EcoCode>().map(EcoCode::number), Ok(60));
+    assert!("F00".parse::<EcoCode>().is_err());
+}
+
+#[test]
+fn boards_render_as_svg() {
+    use mxchess::render::{to_svg, Annotations, Brush, Mark, Perspective, SvgOptions};
+
+    let svg = to_svg(&Board::new(), &SvgOptions::default());
+    assert!(svg.starts_with("<svg ") && svg.ends_with("</svg>"));
+    assert!(svg.contains("width=\"360\""));
+    assert_eq!(svg.matches("<rect ").count(), 64);
+    // The pieces and the 16 coordinates.
+    assert_eq!(svg.matches("<text ").count(), 32 + 16);
+    assert!(!svg.contains("<line "));
+
+    let options = SvgOptions {
+        square_size: 10,
+        perspective: Perspective::Black,
+        to_move: Color::White,
+        coordinates: false,
+        last_move: Some((Square::E2, Square::E4)),
+        annotations: Annotations {
+            marks: vec![Mark {
+                from: Square::G1,
+                to: Square::F3,
+                brush: Brush::Green,
+            }],
+        },
+    };
+    let svg = to_svg(&Board::new(), &options);
+    assert_eq!(svg.matches("<text ").count(), 32);
+    assert_eq!(svg.matches("fill=\"#cdd26a\"").count(), 2);
+    assert_eq!(svg.matches("<line ").count(), 1);
+    // From Black's side a1 is in the top right corner, and its rook is drawn there.
+    assert!(svg.contains("<rect x=\"70\" y=\"0\""));
+    assert!(svg.contains("x=\"75\" y=\"8\""));
+}
+
+#[cfg(feature = "animation")]
+#[test]
+fn games_animate_as_gifs() {
+    use mxchess::render::AnimationOptions;
+    use std::time::Duration;
+
+    let mut game = Game::new();
+    for mv in ["e2e4", "e7e5", "g1f3"] {
+        let mv = game.board().parse_move(game.turn(), mv).unwrap();
+        assert!(game.push_move(mv));
+    }
+    let path = std::env::temp_dir().join("mxchess-animation-test.gif");
+    let options = AnimationOptions {
+        delay: Duration::from_millis(500),
+        ..AnimationOptions::default()
+    };
+    game.to_animation(&path, &options).unwrap();
+
+    let mut decoder = gif::DecodeOptions::new()
+        .read_info(std::fs::File::open(&path).unwrap())
+        .unwrap();
+    assert_eq!((decoder.width(), decoder.height()), (360, 360));
+    let mut delays = Vec::new();
+    while let Some(frame) = decoder.read_next_frame().unwrap() {
+        delays.push(frame.delay);
+    }
+    assert_eq!(delays, [50, 50, 50, 300]);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn boards_are_drawn_from_either_side() {
+    use mxchess::render::{to_svg, Perspective, SvgOptions};
+
+    // From Black's side rank 1 is at the top and the h-file on the left, labels included.
+    let (board, _) = Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w Q - 0 1").unwrap();
+    let drawn = board.render(Color::Black);
+    let lines: Vec<_> = drawn.lines().collect();
+    assert!(lines[0].starts_with("1 \u{25FB} \u{25FC} \u{25FB} \u{2654}"));
+    assert!(lines[0].ends_with("\u{2656}"));
+    assert_eq!(lines[8].split_whitespace().collect::<String>(), "hgfedcba");
+    let drawn = board.render(Color::White);
+    assert_eq!(
+        drawn
+            .lines()
+            .nth(8)
+            .unwrap()
+            .split_whitespace()
+            .collect::<String>(),
+        "abcdefgh"
+    );
+    assert_eq!(
+        Board::from_diagram(&board.render(Color::Black))
+            .unwrap()
+            .0
+            .white_pieces,
+        board.white_pieces
+    );
+    let control: Vec<_> = board
+        .render_control_map(Color::Black)
+        .lines()
+        .map(String::from)
+        .collect();
+    assert_eq!(
+        control[8].split_whitespace().collect::<String>(),
+        "hgfedcba"
+    );
+
+    // The side to move decides with `Perspective::Auto`, and only then.
+    assert_eq!(Perspective::Auto.side(Color::Black), Color::Black);
+    assert_eq!(Perspective::White.side(Color::Black), Color::White);
+    assert_eq!(Perspective::from(Color::Black), Perspective::Black);
+    let svg = |perspective, to_move| {
+        let options = SvgOptions {
+            perspective,
+            to_move,
+            ..SvgOptions::default()
+        };
+        (options.flipped(), to_svg(&board, &options))
+    };
+    let (flipped, black) = svg(Perspective::Black, Color::White);
+    assert!(flipped);
+    assert_eq!(svg(Perspective::Auto, Color::Black), (true, black));
+    let (flipped, white) = svg(Perspective::Auto, Color::White);
+    assert!(!flipped);
+    assert_eq!(svg(Perspective::White, Color::Black), (false, white));
+}
+
+#[test]
+fn typed_moves_are_read_in_any_style_and_refused_with_a_reason() {
+    use mxchess::{
+        input::{parse_move, LineSource, MoveInputError, MoveSource},
+        IllegalMoveReason,
+    };
+
+    let parse = |fen: &str, line: &str| {
+        let (board, color) = Board::from_fen(fen).unwrap();
+        parse_move(&board, color, line)
+    };
+    let reason = |fen: &str, line: &str| match parse(fen, line) {
+        Err(MoveInputError::Illegal(reason)) => reason.to_string(),
+        other => panic!("{}: {:?}", line, other),
+    };
+    const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    let e4 = parse(START, "e2e4").unwrap();
+    for line in ["e2 e4", "e2-e4", " e4 ", "e2xe4"] {
+        assert_eq!(parse(START, line), Ok(e4), "{}", line);
+    }
+    assert_eq!(reason(START, "e3e4"), "no piece on e3");
+    assert_eq!(reason(START, "e7e5"), "the piece on e7 is your opponent's");
+    assert_eq!(reason(START, "a1a2"), "your own piece is on a2");
+    assert_eq!(reason(START, "b1b3"), "the knight on b1 can't move to b3");
+    assert_eq!(reason(START, "Bc4"), "no bishop of yours can move to c4");
+    assert_eq!(
+        reason(START, "e2e4q"),
+        IllegalMoveReason::BadPromotion.to_string()
+    );
+    assert_eq!(parse(START, "hello"), Err(MoveInputError::Unreadable));
+
+    // The bishop pins the knight.
+    const PIN: &str = "4k3/8/8/b7/8/8/3N4/4K3 w - - 0 1";
+    for line in ["Nf3", "d2f3", "d2 f3", "d2-f3"] {
+        assert_eq!(reason(PIN, line), "that knight is pinned", "{}", line);
+    }
+    const CHECK: &str = "4r1k1/8/8/8/8/8/8/1N2K3 w - - 0 1";
+    assert_eq!(
+        reason(CHECK, "Nc3"),
+        "that doesn't get your king out of check"
+    );
+    assert_eq!(reason(CHECK, "e1e2"), "the king would be in check on e2");
+    const CASTLE: &str = "5rk1/8/8/8/8/8/8/4K2R w K - 0 1";
+    assert_eq!(
+        reason(CASTLE, "O-O"),
+        "the king can't castle out of, through or into check"
+    );
+    assert_eq!(
+        reason(CASTLE, "e1g1"),
+        "the king can't castle out of, through or into check"
+    );
+    assert_eq!(
+        reason("6k1/8/8/8/8/8/8/4K2R w - - 0 1", "O-O"),
+        "you can't castle that way"
+    );
+
+    const KNIGHTS: &str = "4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1";
+    assert_eq!(parse(KNIGHTS, "Nd2"), Err(MoveInputError::Ambiguous));
+    assert_eq!(parse(KNIGHTS, "Nbd2").unwrap().from, Square::B1.index());
+    assert_eq!(parse(KNIGHTS, "Nf1d2").unwrap().from, Square::F1.index());
+
+    const PROMOTION: &str = "4k3/P7/8/8/8/8/8/4K3 w - - 0 1";
+    for (line, ty) in [
+        ("a8", MoveType::PawnQueenPromotion),
+        ("a7a8n", MoveType::PawnKnightPromotion),
+        ("a7-a8=R", MoveType::PawnRookPromotion),
+        ("a8=B", MoveType::PawnBishopPromotion),
+    ] {
+        assert_eq!(parse(PROMOTION, line).unwrap().ty, ty, "{}", line);
+    }
+
+    // The terminal tells the reason and asks again, and for the promotion of typed squares.
+    let (board, color) = Board::from_fen(PIN).unwrap();
+    let mut output = Vec::new();
+    let mut source = LineSource::new(&b"Nf3\ne1-f2\n"[..], &mut output);
+    let mv = source.next_move(&board, color).unwrap().unwrap();
+    assert_eq!((mv.from, mv.to), (Square::E1.index(), Square::F2.index()));
+    drop(source);
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("Illegal move: that knight is pinned"));
+
+    let (board, color) = Board::from_fen(PROMOTION).unwrap();
+    let mut source = LineSource::new(&b"a7 a8\nn\n"[..], Vec::new());
+    let mv = source.next_move(&board, color).unwrap().unwrap();
+    assert_eq!(mv.ty, MoveType::PawnKnightPromotion);
+}
@@ -0,0 +1,283 @@
+// Compares the bitboard move generation against a slow mailbox generator that's written to be
+// obviously correct, on positions reached by random legal play.
+
+use mxchess::{Board, ChessFlags, Color, Move, MoveType, Piece, PieceType};
+use proptest::prelude::*;
+
+const KNIGHT_STEPS: [(i8, i8); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+const KING_STEPS: [(i8, i8); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+const STRAIGHT: [(i8, i8); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+const DIAGONAL: [(i8, i8); 4] = [(1, 1), (-1, 1), (-1, -1), (1, -1)];
+
+type Mailbox = [Option<Piece>; 64];
+
+fn mailbox(board: &Board) -> Mailbox {
+    let mut squares = [None; 64];
+    for (square, piece) in squares.iter_mut().enumerate() {
+        *piece = board.get_at(1 << square);
+    }
+    squares
+}
+
+/// The square `(file, rank)` steps away from `square`, if it's on the board.
+fn step(square: u8, (file, rank): (i8, i8)) -> Option<u8> {
+    let file = (square & 7) as i8 + file;
+    let rank = (square >> 3) as i8 + rank;
+    if (0..8).contains(&file) && (0..8).contains(&rank) {
+        Some((8 * rank + file) as u8)
+    } else {
+        None
+    }
+}
+
+fn is(squares: &Mailbox, square: u8, color: Color, ty: PieceType) -> bool {
+    squares[square as usize] == Some(Piece { color, ty })
+}
+
+fn attacked(squares: &Mailbox, square: u8, by: Color) -> bool {
+    let forward = match by {
+        Color::White => 1,
+        Color::Black => -1,
+    };
+    let pawn = [(-1, -forward), (1, -forward)]
+        .iter()
+        .filter_map(|&d| step(square, d))
+        .any(|from| is(squares, from, by, PieceType::Pawn));
+    let knight = KNIGHT_STEPS
+        .iter()
+        .filter_map(|&d| step(square, d))
+        .any(|from| is(squares, from, by, PieceType::Knight));
+    let king = KING_STEPS
+        .iter()
+        .filter_map(|&d| step(square, d))
+        .any(|from| is(squares, from, by, PieceType::King));
+
+    let slider = |directions: &[(i8, i8)], ty: PieceType| {
+        directions.iter().any(|&d| {
+            let mut at = square;
+            while let Some(next) = step(at, d) {
+                match squares[next as usize] {
+                    None => at = next,
+                    Some(piece) => {
+                        return piece.color == by
+                            && (piece.ty == ty || piece.ty == PieceType::Queen)
+                    }
+                }
+            }
+            false
+        })
+    };
+
+    pawn || knight
+        || king
+        || slider(&STRAIGHT, PieceType::Rook)
+        || slider(&DIAGONAL, PieceType::Bishop)
+}
+
+/// The mailbox after `mv`.
+fn play(squares: &Mailbox, mv: Move, color: Color) -> Mailbox {
+    let mut squares = *squares;
+    let piece = squares[mv.from as usize].take();
+    squares[mv.to as usize] = match mv.ty {
+        MoveType::PawnQueenPromotion => Some(Piece {
+            color,
+            ty: PieceType::Queen,
+        }),
+        _ => piece,
+    };
+    match mv.ty {
+        MoveType::PawnEnPassant => squares[((mv.from & 0o70) | (mv.to & 7)) as usize] = None,
+        MoveType::Castle => {
+            let (rook_from, rook_to) = match mv.to & 7 {
+                2 => (mv.to - 2, mv.to + 1),
+                _ => (mv.to + 1, mv.to - 1),
+            };
+            squares[rook_to as usize] = squares[rook_from as usize].take();
+        }
+        _ => {}
+    }
+    squares
+}
+
+/// The legal moves by the rules, promotions are to a queen only like `Board::moves()`.
+fn reference_moves(board: &Board, color: Color) -> Vec<Move> {
+    let squares = mailbox(board);
+    let mut moves = Vec::new();
+    let own = |square: u8| squares[square as usize].is_some_and(|piece| piece.color == color);
+    let enemy = |square: u8| squares[square as usize].is_some_and(|piece| piece.color != color);
+
+    for from in 0..64u8 {
+        let piece = match squares[from as usize] {
+            Some(piece) if piece.color == color => piece,
+            _ => continue,
+        };
+        let mut add = |to: u8, ty: MoveType| moves.push(Move { from, to, ty });
+
+        match piece.ty {
+            PieceType::Pawn => {
+                let (forward, start_rank, last_rank) = match color {
+                    Color::White => (1, 1, 7),
+                    Color::Black => (-1, 6, 0),
+                };
+                let push_type = |to: u8| {
+                    if to >> 3 == last_rank {
+                        MoveType::PawnQueenPromotion
+                    } else {
+                        MoveType::Pawn
+                    }
+                };
+                if let Some(to) =
+                    step(from, (0, forward)).filter(|&to| squares[to as usize].is_none())
+                {
+                    add(to, push_type(to));
+                    if from >> 3 == start_rank {
+                        let leap = step(to, (0, forward)).unwrap();
+                        if squares[leap as usize].is_none() {
+                            add(leap, MoveType::PawnLeap);
+                        }
+                    }
+                }
+                for file in [-1, 1] {
+                    if let Some(to) = step(from, (file, forward)) {
+                        if enemy(to) {
+                            add(to, push_type(to));
+                        }
+                        // En passant, behind a pawn that just leaped next to this one.
+                        let prev = board.prev_move;
+                        if prev.ty == MoveType::PawnLeap
+                            && prev.to == (from & 0o70 | to & 7)
+                            && squares[to as usize].is_none()
+                        {
+                            add(to, MoveType::PawnEnPassant);
+                        }
+                    }
+                }
+            }
+            PieceType::Knight | PieceType::King => {
+                let (steps, ty) = match piece.ty {
+                    PieceType::Knight => (&KNIGHT_STEPS, MoveType::Knight),
+                    _ => (&KING_STEPS, MoveType::King),
+                };
+                for &d in steps {
+                    if let Some(to) = step(from, d).filter(|&to| !own(to)) {
+                        add(to, ty);
+                    }
+                }
+            }
+            PieceType::Queen | PieceType::Rook | PieceType::Bishop => {
+                let (directions, ty): (Vec<_>, _) = match piece.ty {
+                    PieceType::Queen => {
+                        (STRAIGHT.iter().chain(&DIAGONAL).collect(), MoveType::Queen)
+                    }
+                    PieceType::Rook => (STRAIGHT.iter().collect(), MoveType::Rook),
+                    _ => (DIAGONAL.iter().collect(), MoveType::Bishop),
+                };
+                for &d in directions {
+                    let mut at = from;
+                    while let Some(to) = step(at, d).filter(|&to| !own(to)) {
+                        add(to, ty);
+                        if enemy(to) {
+                            break;
+                        }
+                        at = to;
+                    }
+                }
+            }
+        }
+    }
+
+    // Castling: the right, the rook in its corner, empty squares between them and a king that
+    // neither is in check nor passes or lands on an attacked square.
+    let (rank, king_side, queen_side) = match color {
+        Color::White => (
+            0,
+            ChessFlags::WHITE_KINGS_CASTLE,
+            ChessFlags::WHITE_QUEENS_CASTLE,
+        ),
+        Color::Black => (
+            0o70,
+            ChessFlags::BLACK_KINGS_CASTLE,
+            ChessFlags::BLACK_QUEENS_CASTLE,
+        ),
+    };
+    let king = rank + 4;
+    if is(&squares, king, color, PieceType::King) && !attacked(&squares, king, color.inv()) {
+        for (flag, rook, empty, passed) in [
+            (king_side, rank + 7, &[5, 6][..], [5, 6]),
+            (queen_side, rank, &[1, 2, 3][..], [3, 2]),
+        ] {
+            if board.flags.contains(flag)
+                && is(&squares, rook, color, PieceType::Rook)
+                && empty
+                    .iter()
+                    .all(|&file| squares[(rank + file) as usize].is_none())
+                && passed
+                    .iter()
+                    .all(|&file| !attacked(&squares, rank + file, color.inv()))
+            {
+                moves.push(Move {
+                    from: king,
+                    to: rank + passed[1],
+                    ty: MoveType::Castle,
+                });
+            }
+        }
+    }
+
+    // Only the moves that don't leave the king in check.
+    moves.retain(|&mv| {
+        let after = play(&squares, mv, color);
+        let king = (0..64).find(|&square| is(&after, square, color, PieceType::King));
+        king.is_some_and(|king| !attacked(&after, king, color.inv()))
+    });
+    moves
+}
+
+fn sorted(mut moves: Vec<Move>) -> Vec<Move> {
+    moves.sort_by_key(|mv| (mv.from, mv.to, mv.ty as u8));
+    moves
+}
+
+const STARTS: [&str; 4] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+];
+
+proptest! {
+    #[test]
+    fn moves_match_the_reference(start in 0..STARTS.len(), choices in prop::collection::vec(any::<u32>(), 0..100)) {
+        let (mut board, mut color) = Board::from_fen(STARTS[start]).unwrap();
+
+        for choice in choices {
+            let moves = sorted(board.moves(color));
+            let expected = sorted(reference_moves(&board, color));
+            prop_assert_eq!(&moves, &expected, "{}", board.to_fen(color));
+            if moves.is_empty() {
+                break;
+            }
+
+            board.perform_move_unchecked(moves[choice as usize % moves.len()]);
+            color = color.inv();
+        }
+    }
+}
@@ -0,0 +1,62 @@
+// The frontends' protocols, UCI and XBoard, talked to through scripted input.
+
+use mxchess::{options::EngineOptions, uci, xboard, Board, Color};
+
+fn lines(output: Vec<u8>) -> Vec<String> {
+    String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(str::to_owned)
+        .collect()
+}
+
+#[test]
+fn xboard_answers_features_moves_and_pings() {
+    let script = "protover 2\nnew\nsd 2\nusermove e2e5\nusermove e2e4\nping 1\nquit\n";
+    let mut output = Vec::new();
+    xboard::run(script.as_bytes(), &mut output).unwrap();
+    let lines = lines(output);
+
+    assert_eq!(lines.len(), 4, "{:?}", lines);
+    assert!(lines[0].starts_with("feature myname=\"MxChess\" usermove=1 ping=1"));
+    assert!(lines[0].ends_with("done=1"));
+    assert_eq!(lines[1], "Illegal move: e2e5");
+    let (board, _) =
+        Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+    let reply = lines[2].strip_prefix("move ").unwrap();
+    assert!(board.parse_move(Color::Black, reply).is_some(), "{}", reply);
+    assert_eq!(lines[3], "pong 1");
+}
+
+#[test]
+fn uci_reports_the_root_moves_of_long_searches() {
+    let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    let run = |go: &str| {
+        let script = format!("position fen {}\n{}\nquit\n", fen, go);
+        let mut output = Vec::new();
+        uci::run(script.as_bytes(), &mut output, EngineOptions::default()).unwrap();
+        lines(output)
+    };
+    let (board, color) = Board::from_fen(fen).unwrap();
+    let currmove = |line: &String| line.contains(" currmove ");
+
+    let quick = run("go depth 2");
+    assert!(!quick.iter().any(currmove), "{:?}", quick);
+    assert!(quick.last().unwrap().starts_with("bestmove "));
+
+    // The root moves are reported from a second into the search on.
+    let long = run("go movetime 2500");
+    let reported: Vec<_> = long.iter().filter(|line| currmove(line)).collect();
+    assert!(!reported.is_empty(), "{:?}", long);
+    for line in reported {
+        let words: Vec<_> = line.split_whitespace().collect();
+        assert_eq!(words.len(), 7, "{}", line);
+        assert_eq!(
+            [words[0], words[1], words[3], words[5]],
+            ["info", "depth", "currmove", "currmovenumber"]
+        );
+        assert!(board.parse_move(color, words[4]).is_some(), "{}", line);
+        assert!(words[6].parse::<u32>().unwrap() >= 1);
+    }
+    assert!(long.last().unwrap().starts_with("bestmove "));
+}
@@ -0,0 +1,86 @@
+// Known perft values of the standard test positions and mate puzzles the bot has to solve, to
+// catch regressions in the move generation and the search.
+
+mod common;
+
+use common::{after, MATES, PERFT};
+use mxchess::{tt::TranspositionTable, Board, Bot, Color};
+use std::sync::Arc;
+
+/// Whether `color`, to move, is checkmated within `n` more moves of its opponent, whatever it
+/// plays.
+fn mated_within(board: &Board, color: Color, n: u32) -> bool {
+    let moves = board.moves(color);
+    if moves.is_empty() {
+        return board.in_check(color);
+    }
+    n > 0
+        && moves.into_iter().all(|mv| {
+            let board = after(board, mv);
+            board
+                .moves(color.inv())
+                .into_iter()
+                .any(|reply| mated_within(&after(&board, reply), color, n - 1))
+        })
+}
+
+#[test]
+fn perft_matches_the_known_values() {
+    for &(fen, depth, nodes) in &PERFT {
+        let (board, color) = Board::from_fen(fen).unwrap();
+        assert_eq!(board.perft(color, depth), nodes, "{}", fen);
+        assert_eq!(board.perft_fast(color, depth), nodes, "{}", fen);
+    }
+}
+
+#[test]
+fn bot_finds_the_mates() {
+    for &(fen, n) in &MATES {
+        let (board, color) = Board::from_fen(fen).unwrap();
+        let mv = Bot::with_depth(2 * n - 1)
+            .choose_move(&board, color)
+            .unwrap();
+        assert!(
+            mated_within(&after(&board, mv), color.inv(), n - 1),
+            "{}: {} doesn't mate in {}",
+            fen,
+            mv,
+            n
+        );
+    }
+}
+
+#[test]
+fn bots_sharing_a_table_still_find_the_mates() {
+    let table = Arc::new(TranspositionTable::new(1));
+    let threads: Vec<_> = MATES
+        .iter()
+        .filter(|&&(_, n)| n == 2)
+        .map(|&(fen, n)| {
+            let table = table.clone();
+            std::thread::spawn(move || {
+                let (board, color) = Board::from_fen(fen).unwrap();
+                let mut bot = Bot::with_depth(2 * n - 1);
+                bot.table = Some(table);
+                // The second search starts from the first one's moves.
+                for _ in 0..2 {
+                    let mv = bot.choose_move(&board, color).unwrap();
+                    assert!(
+                        mated_within(&after(&board, mv), color.inv(), n - 1),
+                        "{}",
+                        fen
+                    );
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    table.store(42, 7);
+    assert_eq!(table.probe(42), Some(7));
+    assert_eq!(table.probe(43), None);
+    table.clear();
+    assert_eq!(table.probe(42), None);
+}
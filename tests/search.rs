@@ -0,0 +1,425 @@
+// The bot's search and the other engines: the limits, what the searches report, the move
+// ordering and the engine options.
+
+mod common;
+
+use common::{MATES, PERFT};
+use mxchess::{
+    baseline::{GreedyBot, RandomBot},
+    bot::DefaultOrderer,
+    experience::Experience,
+    human::HumanBot,
+    mcts::Rollout,
+    options::EngineOptions,
+    picker::MovePicker,
+    time::TimeManager,
+    tt::TranspositionTable,
+    Board, Bot, Color, Engine, MctsBot, Move, MoveType, PieceType, Score, SearchLimits, Square,
+};
+use std::{sync::Arc, time::Duration};
+
+#[test]
+fn baselines_play_legal_moves() {
+    let (board, color) = Board::from_fen("4k3/8/8/3q4/8/8/8/3RK3 w - - 0 1").unwrap();
+    let mv = GreedyBot::new(0).choose_move(&board, color).unwrap();
+    assert_eq!(mv.to_string(), "d1d5");
+
+    let mut random = RandomBot::new(0);
+    for _ in 0..10 {
+        let mv = random.choose_move(&board, color).unwrap();
+        assert!(board.moves(color).contains(&mv));
+    }
+
+    // Restricted to a move that loses the rook, like UCI's searchmoves.
+    let blunder = board.parse_move(color, "d1d4").unwrap();
+    let limits = SearchLimits {
+        moves: &[blunder],
+        ..SearchLimits::default()
+    };
+    let result = GreedyBot::new(0).best_move(&board, color, &limits);
+    assert_eq!(result.best_move, Some(blunder));
+    assert_eq!(result.score, Some(Score::Cp(0)));
+
+    let (mated, color) = Board::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+    assert_eq!(GreedyBot::new(0).choose_move(&mated, color), None);
+    assert_eq!(random.choose_move(&mated, color), None);
+}
+
+#[test]
+fn experience_steers_the_bot_away_from_lost_moves() {
+    let board = Board::new();
+    let key = board.position_key(Color::White);
+    let mut bot = Bot::with_depth(2);
+    let first = bot.choose_move(&board, Color::White).unwrap();
+    let value = bot.value().unwrap();
+
+    let mut experience = Experience::new();
+    for _ in 0..3 {
+        experience.learn(&[(key, Color::White, first, value)], Some(Color::Black));
+    }
+    let experience: Experience = experience.to_string().parse().unwrap();
+    assert_eq!(experience.entries(key).len(), 1);
+    assert_eq!(experience.entries(key)[0].losses, 3);
+    assert!(experience.blend(key, first, value) < value);
+
+    bot.experience = Some(experience.into());
+    assert_ne!(bot.choose_move(&board, Color::White), Some(first));
+}
+
+#[test]
+fn the_piece_values_are_engine_options() {
+    let mut options = EngineOptions::default();
+    assert!(options
+        .uci_options()
+        .contains(&"option name KnightValue type spin default 300 min 0 max 10000".to_string()));
+    options.set("KnightValue", "325").unwrap();
+    assert_eq!(
+        options.eval().middlegame.material[PieceType::Knight as usize],
+        325
+    );
+    assert_eq!(
+        options.eval().endgame.material[PieceType::Knight as usize],
+        325
+    );
+    assert_eq!(options.bot(1).evaluator, *options.eval());
+    assert!(options.set("PawnValue", "-1").is_err());
+    assert!(options.set("KingValue", "0").is_err());
+}
+
+#[test]
+fn all_the_moves_are_scored_best_first() {
+    // Only Qd8 mates, the other queen moves leave the king a way out.
+    let (board, color) = Board::from_fen("6k1/5ppp/8/8/8/8/5PPP/3Q2K1 w - - 0 1").unwrap();
+    let scored = Bot::new().score_all_moves(&board, color, 1);
+    assert_eq!(scored.len(), board.moves(color).len());
+    assert_eq!(scored[0].0.to_string(), "d1d8");
+    assert!(scored.windows(2).all(|pair| pair[1].1 <= pair[0].1));
+    assert!(scored[1].1 < scored[0].1);
+}
+
+#[test]
+fn low_time_plays_after_the_first_iteration() {
+    let normal = TimeManager::for_clock(Duration::from_secs(60), Duration::from_secs(1), None);
+    assert!(!normal.is_low_time());
+    assert!(Duration::from_secs(2) < normal.soft_limit());
+    assert!(normal.hard_limit() < Duration::from_secs(45));
+
+    let low = TimeManager::for_clock(Duration::from_millis(500), Duration::ZERO, None);
+    assert!(low.is_low_time());
+    assert!(!low.should_start_iteration());
+    assert!(low.hard_limit() <= Duration::from_millis(50));
+
+    let (board, color) = Board::from_fen(PERFT[1].0).unwrap();
+    let mut time = low;
+    let bot = Bot::with_depth(64);
+    let moves = board.moves(color);
+    assert!(bot
+        .choose_move_timed(&board, color, &[], &moves, &mut time)
+        .is_some());
+}
+
+#[test]
+fn search_results_carry_the_score_and_the_line() {
+    assert_eq!(Score::from_value(i32::MAX - 1), Score::Mate(1));
+    assert_eq!(Score::from_value(-(i32::MAX - 4)), Score::Mate(-2));
+    assert_eq!(Score::from_value(130), Score::Cp(130));
+    assert_eq!(Score::Cp(130).to_string(), "+1.30");
+    assert_eq!(Score::Cp(-25).to_string(), "-0.25");
+    assert_eq!(Score::Mate(4).to_string(), "M4");
+    assert_eq!(Score::Mate(-3).to_string(), "-M3");
+    assert!(Score::Mate(2).centipawns() > Score::Mate(3).centipawns());
+    assert!(Score::Mate(-2).centipawns() < Score::Mate(-3).centipawns());
+    assert!(Score::Mate(-3).centipawns() < Score::Cp(-5000).centipawns());
+
+    // Morphy's mate in 2.
+    let (board, color) = Board::from_fen(MATES[0].0).unwrap();
+    let mut bot = Bot::with_depth(3);
+    bot.table = Some(Arc::new(TranspositionTable::new(1)));
+    bot.options.easy_move_depth = 0;
+    let result = bot.best_move(&board, color, &SearchLimits::default());
+    assert_eq!(result.score, Some(Score::Mate(2)));
+    assert_eq!(result.depth, 3);
+    assert_eq!(result.pv.first().copied(), result.best_move);
+    let mut replay = board;
+    let mut turn = color;
+    for &mv in &result.pv {
+        assert!(replay.is_legal(turn, mv));
+        replay.perform_move_unchecked(mv);
+        turn = turn.inv();
+    }
+}
+
+#[test]
+fn the_node_limit_stops_a_deep_search() {
+    let (board, color) = Board::from_fen(PERFT[0].0).unwrap();
+    let mut bot = Bot::with_depth(64);
+    let limits = SearchLimits {
+        nodes: Some(20_000),
+        ..SearchLimits::default()
+    };
+    let result = bot.best_move(&board, color, &limits);
+    assert!(result.best_move.is_some());
+    // It's checked every so many nodes per thread.
+    assert!(result.nodes < 100_000, "{}", result.nodes);
+    assert_eq!(bot.node_limit, None);
+}
+
+#[test]
+fn the_picker_yields_every_legal_move_once() {
+    for &(fen, _, _) in &PERFT {
+        let (board, color) = Board::from_fen(fen).unwrap();
+        let attack = board.check_attack(color.inv());
+        let mut moves = board.moves(color);
+        let first = moves.last().copied();
+        // An illegal countermove is skipped.
+        let countermove = Move {
+            from: 0,
+            to: 0o77,
+            ty: MoveType::Queen,
+        };
+        let mut picked: Vec<_> =
+            MovePicker::new(&board, color, attack, &DefaultOrderer, first, countermove).collect();
+        assert_eq!(picked.first().copied(), first, "{}", fen);
+        let key = |mv: &Move| (mv.from, mv.to, mv.ty as u8);
+        moves.sort_by_key(key);
+        picked.sort_by_key(key);
+        assert_eq!(picked, moves, "{}", fen);
+    }
+}
+
+#[test]
+fn the_orderer_scores_king_moves_and_castles() {
+    use mxchess::bot::MoveOrderer;
+
+    // The king can take the knight, step aside or castle long.
+    let (board, color) = Board::from_fen("4k3/8/8/8/8/8/3n4/R3K2R w KQ - 0 1").unwrap();
+    let attack = board.check_attack(color.inv());
+    let moves = board.moves(color);
+    assert!(moves.iter().any(|mv| mv.ty == MoveType::Castle));
+    let score = |mv: &Move| DefaultOrderer.score(mv, &board, attack, Move::NULL);
+    for mv in moves.iter().chain(board.capture_moves(color).iter()) {
+        score(mv);
+    }
+    // Taking the knight beats stepping aside.
+    let king_to = |to: Square| {
+        let mv = moves
+            .iter()
+            .find(|mv| mv.ty == MoveType::King && mv.to == to.index());
+        score(mv.unwrap())
+    };
+    assert!(king_to(Square::D2) > king_to(Square::F2));
+}
+
+#[test]
+fn the_human_bot_plays_good_moves_at_random() {
+    // Every seed takes the hanging queen, and without randomness it's the bot's best move.
+    let (board, color) = Board::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+    let best = Bot::with_depth(2).score_all_moves(&board, color, 2)[0];
+    for seed in 0..8 {
+        let mut human = HumanBot::new(Bot::with_depth(2), seed);
+        human.oversight = 0;
+        let result = human.best_move(&board, color, &SearchLimits::default());
+        assert_eq!(result.best_move.unwrap().to_string(), "d2d5");
+        assert_eq!(result.pv.len(), 1);
+    }
+    let mut human = HumanBot::new(Bot::with_depth(2), 0);
+    human.temperature = 0;
+    human.oversight = 0;
+    let result = human.best_move(&board, color, &SearchLimits::default());
+    assert_eq!(result.best_move, Some(best.0));
+    assert_eq!(result.score, Some(Score::from_value(best.1)));
+
+    // The opening has many moves about as good.
+    let board = Board::new();
+    let played: std::collections::HashSet<_> = (0..8)
+        .map(|seed| {
+            let mut human = HumanBot::new(Bot::with_depth(2), seed);
+            human.choose_move(&board, Color::White).unwrap()
+        })
+        .collect();
+    assert!(played.len() > 1);
+}
+
+#[test]
+fn searches_report_nodes_per_second_and_hashfull() {
+    let table = Arc::new(TranspositionTable::new(1));
+    assert_eq!(table.hashfull(), 0);
+
+    let mut bot = Bot::with_depth(4);
+    bot.table = Some(table.clone());
+    let result = bot.best_move(&Board::new(), Color::White, &SearchLimits::default());
+    assert!(result.nodes > 0);
+    assert_eq!(result.hashfull, Some(table.hashfull()));
+    assert!(table.hashfull() > 0);
+    if !result.time.is_zero() {
+        assert!(result.nps() > 0);
+    }
+    table.clear();
+    assert_eq!(table.hashfull(), 0);
+
+    // Without a table there's nothing to report.
+    let result =
+        Bot::with_depth(2).best_move(&Board::new(), Color::White, &SearchLimits::default());
+    assert_eq!(result.hashfull, None);
+}
+
+#[test]
+fn the_bench_signature_is_reproducible() {
+    let mut bot = Bot::with_depth(2);
+    let signature = mxchess::bench::signature(&mut bot);
+    assert!(signature > 0);
+    assert_eq!(mxchess::bench::signature(&mut bot), signature);
+
+    // The table is left out of the signature, and given back after.
+    let table = Arc::new(TranspositionTable::new(1));
+    bot.table = Some(table.clone());
+    assert_eq!(mxchess::bench::signature(&mut bot), signature);
+    assert!(bot.table.is_some());
+    assert_eq!(table.hashfull(), 0);
+}
+
+#[test]
+fn a_node_limited_bot_searches_every_move_afresh() {
+    let board = Board::new();
+    let mut bot = Bot::new();
+    bot.depth = 8;
+    bot.node_limit = Some(5000);
+
+    for _ in 0..2 {
+        let mv = bot.choose_move(&board, Color::White).unwrap();
+        assert!(board.moves(Color::White).contains(&mv));
+        // The search ran up to the limit rather than returning at once on a stale stop.
+        assert!(bot.nodes() >= 5000);
+        assert_eq!(bot.value(), None);
+    }
+
+    // Nor does a later search without the limit see the stop.
+    bot.depth = 2;
+    bot.node_limit = None;
+    assert_eq!(
+        bot.score_all_moves(&board, Color::White, 2),
+        Bot::new().score_all_moves(&board, Color::White, 2)
+    );
+}
+
+#[test]
+fn the_countermove_is_ordered_between_the_good_captures_and_the_quiet_moves() {
+    use mxchess::bot::MoveOrderer;
+
+    // After 1.d4 e5, dxe5 is a good capture and Bh6 a quiet move to an attacked square.
+    let (board, color) =
+        Board::from_fen("rnbqkbnr/pppp1ppp/8/4p3/3P4/8/PPP1PPPP/RNBQKBNR w KQkq e6 0 2").unwrap();
+    let attack = board.check_attack(color.inv());
+    let moves = board.moves(color);
+    let find = |from: Square, to: Square| {
+        *moves
+            .iter()
+            .find(|mv| (mv.from, mv.to) == (from.index(), to.index()))
+            .unwrap()
+    };
+    let (capture, countermove) = (find(Square::D4, Square::E5), find(Square::C1, Square::H6));
+
+    let ordered = |countermove: Move| {
+        let mut moves = moves.clone();
+        moves.sort_by_key(|mv| -DefaultOrderer.score(mv, &board, attack, countermove));
+        moves
+    };
+    let without = ordered(Move::NULL);
+    assert_eq!(without[0], capture);
+    assert!(without.iter().position(|&mv| mv == countermove).unwrap() > 1);
+    let with = ordered(countermove);
+    assert_eq!(with[..2], [capture, countermove]);
+}
+
+#[test]
+fn the_engine_options_refuse_what_they_cant_honour() {
+    let mut options = EngineOptions::default();
+    assert!(options.set("Hash", "0").is_err());
+    options.set("Hash", "2").unwrap();
+    assert_eq!(options.hash_mb, 2);
+    assert!(options.set("SyzygyPath", "/").is_err());
+    assert!(!options
+        .uci_options()
+        .iter()
+        .any(|option| option.contains("SyzygyPath")));
+
+    // The searches reuse the pool of the threads.
+    options.set("Threads", "2").unwrap();
+    for _ in 0..2 {
+        assert_eq!(options.install(|| 1 + 1), 2);
+    }
+}
+
+#[test]
+fn perpetual_check_saves_the_side_behind() {
+    // Three rooks against a queen, but Qg5+ Kh8 Qf6+ Kg8 repeats forever.
+    let fen = "5rk1/5p1p/8/8/8/8/rr3PPP/2Q3K1 w - - 0 1";
+    let (board, color) = Board::from_fen(fen).unwrap();
+    let bot = Bot::with_depth(4);
+    let mv = bot.choose_move(&board, color).unwrap();
+    assert_eq!(mv.to_string(), "c1g5");
+    assert_eq!(bot.value(), Some(0));
+}
+
+#[test]
+fn the_side_ahead_avoids_repeating_with_contempt() {
+    // A pawn up, White could repeat the checks of the position above with Qg5+.
+    let fen = "5rk1/5p1p/5Q2/8/2P5/8/r4PPP/6K1 w - - 0 1";
+    let (board, color) = Board::from_fen(fen).unwrap();
+    let mut repeated = board;
+    repeated.perform_move_unchecked(board.parse_move(color, "f6g5").unwrap());
+    let history = [repeated.position_key(color.inv())];
+
+    let mut bot = Bot::with_depth(4);
+    bot.options.contempt = 50;
+    let mv = bot
+        .choose_move_with_history(&board, color, &history)
+        .unwrap();
+    assert_ne!(mv.to_string(), "f6g5");
+    assert!(bot.value().unwrap() > 0);
+}
+
+#[test]
+fn mcts_finds_the_mate_in_one() {
+    let (board, color) = Board::from_fen("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1").unwrap();
+    let mate = board.parse_move(color, "a1a8").unwrap();
+    for rollout in [Rollout::Evaluation, Rollout::Playout { plies: 20 }] {
+        let bot = MctsBot {
+            iterations: 2_000,
+            rollout,
+            ..MctsBot::new()
+        };
+        assert_eq!(bot.choose_move(&board, color), Some(mate), "{:?}", rollout);
+    }
+}
+
+#[test]
+fn mcts_has_no_move_without_a_legal_one() {
+    let bot = MctsBot {
+        iterations: 100,
+        ..MctsBot::new()
+    };
+    for fen in [
+        "R5k1/5ppp/8/8/8/8/5PPP/6K1 b - - 0 1",
+        "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1",
+    ] {
+        let (board, color) = Board::from_fen(fen).unwrap();
+        assert_eq!(bot.choose_move(&board, color), None, "{}", fen);
+    }
+}
+
+#[test]
+fn mcts_is_deterministic_for_a_seed() {
+    let (board, color) = Board::from_fen(PERFT[1].0).unwrap();
+    let bot = |seed| MctsBot {
+        iterations: 300,
+        rollout: Rollout::Playout { plies: 16 },
+        seed,
+        ..MctsBot::new()
+    };
+    for seed in 0..4 {
+        let first = bot(seed).choose_move(&board, color);
+        assert!(first.is_some());
+        assert_eq!(bot(seed).choose_move(&board, color), first);
+    }
+}
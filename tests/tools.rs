@@ -0,0 +1,408 @@
+// The tools around the engine: tournaments, self-play, profiles, the analysis of games and the
+// frontends.
+
+use mxchess::{
+    baseline::{GreedyBot, RandomBot},
+    clock::Clock,
+    profile::{Profile, START_RATING},
+    selfplay::{self, Adjudication, PlayerAdjudication, Sprt, Termination},
+    tournament::{self, Format, Player},
+    Board, Bot, Color, Engine, Game, Score, SearchLimits, SearchResult, Square,
+};
+use std::time::Duration;
+
+#[test]
+fn profiles_rate_the_player_by_the_results() {
+    let mut profile = Profile::default();
+    profile.record(20, 1.0);
+    profile.record(20, 0.5);
+    profile.record(0, 0.0);
+    assert!(profile.rating > START_RATING);
+    assert_eq!(profile.total().games(), 3);
+    assert_eq!(profile.levels[&20].draws, 1);
+
+    let json = profile.to_json();
+    let read = Profile::from_json(&json).unwrap();
+    assert_eq!(read.levels, profile.levels);
+    assert!((read.rating - profile.rating).abs() < 0.1);
+    assert_eq!(Profile::from_json("{}"), None);
+}
+
+#[test]
+fn tournaments_pair_every_engine_and_keep_the_games() {
+    let players = || {
+        vec![
+            Player::new("random", Box::new(RandomBot::new(1))),
+            Player::new("greedy", Box::new(GreedyBot::new(2))),
+            Player::new("random2", Box::new(RandomBot::new(3))),
+        ]
+    };
+    let clock = Clock::new(Duration::from_secs(10), Duration::ZERO);
+    let openings = [Game::new()];
+
+    let mut round_robin = players();
+    let t = tournament::run(
+        &mut round_robin,
+        Format::RoundRobin,
+        2,
+        &openings,
+        clock,
+        &Adjudication::NONE,
+    );
+    assert_eq!(t.games.len(), 6);
+    assert_eq!(t.points(0) + t.points(1) + t.points(2), 6.0);
+    for i in 0..3 {
+        assert_eq!(t.games_of(i), 4);
+        assert_eq!(t.results[i][i], [0; 3]);
+        for j in 0..3 {
+            let [wins, draws, losses] = t.results[i][j];
+            assert_eq!(t.results[j][i], [losses, draws, wins]);
+        }
+    }
+    let first = &t.games[0];
+    assert_eq!(first.tag("White"), Some("random"));
+    assert_eq!(first.tag("Black"), Some("greedy"));
+    assert_eq!(first.tag("Result"), Some(first.result.as_str()));
+    assert_eq!(t.games[1].tag("White"), Some("greedy"));
+    assert_eq!(mxchess::pgn::parse(&t.pgn()).unwrap().len(), 6);
+    assert!(t.to_string().contains("greedy"));
+
+    let mut gauntlet = players();
+    let t = tournament::run(
+        &mut gauntlet,
+        Format::Gauntlet,
+        2,
+        &openings,
+        clock,
+        &Adjudication::NONE,
+    );
+    assert_eq!(t.games.len(), 4);
+    assert_eq!(t.games_of(0), 4);
+    assert_eq!(t.results[1][2], [0; 3]);
+}
+
+#[test]
+fn databases_are_analyzed_in_order_with_the_losses() {
+    let pgn = "1. e4 e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0\n\n1. d4 d5 2. c4 *\n";
+    let games: Vec<_> = mxchess::pgn::parse(pgn)
+        .unwrap()
+        .iter()
+        .map(|game| game.mainline().unwrap())
+        .collect();
+    let analyzed = mxchess::annotate::analyze_games(&games, 1, 2);
+    assert_eq!(analyzed.len(), 2);
+    for ((pgn, [white, black]), game) in analyzed.iter().zip(&games) {
+        assert_eq!(pgn.moves.len(), game.moves().len());
+        assert_eq!(white.moves + black.moves, game.moves().len() as u32);
+    }
+    // 3...Nf6 allows the mate.
+    let [_, black] = analyzed[0].1;
+    assert!(0 < black.total && black.average().unwrap() <= 1000.0);
+}
+
+#[test]
+fn the_blunder_check_finds_the_move_allowing_mate() {
+    let pgn = "1. e4 e5 2. Bc4 Nc6 3. Qh5 Nf6 4. Qxf7# 1-0\n";
+    let game = mxchess::pgn::parse(pgn).unwrap()[0].mainline().unwrap();
+    let blunders = game.blunder_check(2, 1000);
+    assert_eq!(blunders.len(), 1);
+    let blunder = &blunders[0];
+    assert_eq!(blunder.ply, 5);
+    assert_eq!(blunder.played, game.moves()[5]);
+    assert_ne!(blunder.best, blunder.played);
+    assert!(1000 <= blunder.loss);
+    assert_eq!(
+        blunder.fen,
+        "r1bqkbnr/pppp1ppp/2n5/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR b KQkq - 0 1"
+    );
+}
+
+#[test]
+fn annotations_carry_the_evaluations() {
+    // Qh5 lets Black mate at once, after Qh4 Black has.
+    let (board, color) = Board::from_fen(mxchess::fen::STARTPOS).unwrap();
+    let mut game = Game::from_position(board, color);
+    for mv in ["f3", "e5", "g4", "Qh4#"] {
+        let mv = game.board().parse_san(game.turn(), mv).unwrap();
+        game.push_move(mv);
+    }
+    let analysis = game.analyze(2);
+    assert_eq!(analysis[2].mate, Some(-1));
+    assert_eq!(analysis[2].eval_comment(Color::White), "[%eval #-1]");
+    assert_eq!(analysis[3].mate, Some(1));
+    assert_eq!(analysis[3].eval_comment(Color::Black), "[%eval #-1]");
+    assert!(analysis[0]
+        .eval_comment(Color::White)
+        .starts_with("[%eval "));
+
+    let pgn = game.annotate(2).to_string();
+    assert!(pgn.contains("g4 $4 {[%eval #-1] "), "{}", pgn);
+    let parsed = &mxchess::pgn::parse(&pgn).unwrap()[0];
+    assert_eq!(parsed.moves[2].nags, [4]);
+    assert!(parsed.moves[3]
+        .comment
+        .as_deref()
+        .is_some_and(|comment| comment.starts_with("[%eval #-1]")));
+}
+
+#[test]
+fn the_bot_resigns_and_offers_draws_by_its_values() {
+    let rules = Adjudication {
+        resign_score: 600,
+        resign_moves: 3,
+        draw_score: 10,
+        draw_moves: 2,
+        draw_from: 30,
+    };
+    let mut player = PlayerAdjudication::new(rules);
+    // A forced move doesn't break the streak, a better value does.
+    for value in [
+        Some(-700),
+        None,
+        Some(-600),
+        Some(-100),
+        Some(-800),
+        Some(-900),
+    ] {
+        player.record(value);
+        assert!(!player.resigns());
+    }
+    player.record(Some(-650));
+    assert!(player.resigns());
+
+    let mut player = PlayerAdjudication::new(rules);
+    player.record(Some(5));
+    player.record(Some(-10));
+    assert!(!player.wants_draw(29));
+    assert!(player.wants_draw(30));
+    player.record(Some(50));
+    assert!(!player.wants_draw(30));
+
+    let mut player = PlayerAdjudication::new(Adjudication::NONE);
+    for _ in 0..10 {
+        player.record(Some(-10_000));
+    }
+    assert!(!player.resigns() && !player.wants_draw(100));
+}
+
+/// Plays the first legal move and always gives it the same score.
+struct Scripted(Score);
+
+impl Engine for Scripted {
+    fn best_move(&mut self, board: &Board, color: Color, _: &SearchLimits) -> SearchResult {
+        SearchResult {
+            best_move: board.moves(color).into_iter().next(),
+            score: Some(self.0),
+            ..SearchResult::default()
+        }
+    }
+}
+
+#[test]
+fn games_end_by_the_adjudication_rules() {
+    assert_eq!(Adjudication::parse_rule("600/4"), Some((600, 4)));
+    assert_eq!(Adjudication::parse_rule("0/0"), Some((0, 0)));
+    for rule in ["-600/4", "600", "600/-4", "a/4", "600/4/2"] {
+        assert_eq!(Adjudication::parse_rule(rule), None, "{}", rule);
+    }
+
+    let clock = Clock::new(Duration::from_secs(60), Duration::ZERO);
+    let rules = Adjudication {
+        resign_score: 600,
+        resign_moves: 3,
+        draw_score: 10,
+        draw_moves: 2,
+        draw_from: 3,
+    };
+    // White resigns instead of playing its third move valued at -7.00.
+    let (mut white, mut black) = (Scripted(Score::Cp(-700)), Scripted(Score::Cp(700)));
+    let result = selfplay::play_game([&mut white, &mut black], &Game::new(), clock, &rules);
+    assert_eq!(result.winner, Some(Color::Black));
+    assert_eq!(result.termination, Termination::Resignation);
+    assert_eq!(result.game.moves().len(), 4);
+
+    // Both sides valued two moves each as level when White is about to play move 3.
+    let (mut white, mut black) = (Scripted(Score::Cp(5)), Scripted(Score::Cp(-10)));
+    let result = selfplay::play_game([&mut white, &mut black], &Game::new(), clock, &rules);
+    assert_eq!(result.winner, None);
+    assert_eq!(result.termination, Termination::Adjudication);
+    assert_eq!(result.game.moves().len(), 4);
+
+    // A won position isn't adjudicated, nor a game without the rules.
+    let (mut white, mut black) = (Scripted(Score::Mate(3)), Scripted(Score::Cp(0)));
+    let result = selfplay::play_game([&mut white, &mut black], &Game::new(), clock, &rules);
+    assert_ne!(result.termination, Termination::Resignation);
+    assert_ne!(result.termination, Termination::Adjudication);
+    let (mut white, mut black) = (Scripted(Score::Cp(-700)), Scripted(Score::Cp(0)));
+    let result = selfplay::play_game(
+        [&mut white, &mut black],
+        &Game::new(),
+        clock,
+        &Adjudication::NONE,
+    );
+    assert_ne!(result.termination, Termination::Resignation);
+    assert_ne!(result.termination, Termination::Adjudication);
+}
+
+#[test]
+fn blindfold_games_and_the_coordinate_drill() {
+    let drawn = mxchess::blindfold::render_square(Square::A1, Color::White);
+    assert_eq!(drawn.lines().count(), 8);
+    assert_eq!(
+        drawn.lines().last().unwrap().chars().nth(1),
+        Some('\u{25CF}')
+    );
+    assert_eq!(drawn.matches('\u{25CF}').count(), 1);
+    let drawn = mxchess::blindfold::render_square(Square::A1, Color::Black);
+    assert_eq!(
+        drawn.lines().next().unwrap().chars().nth(15),
+        Some('\u{25CF}')
+    );
+
+    // The drill asks the same squares again with the same seed.
+    let ask = |answers: &str| {
+        let mut output = Vec::new();
+        let mut rng = mxchess::rng::Rng::new(7);
+        mxchess::blindfold::drill(3, &mut rng, answers.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    };
+    let asked = ask("?\nquit\n");
+    assert!(asked.contains("0/1 right"));
+    let square = &asked.split("No, it's ").nth(1).unwrap()[..2];
+    assert!(ask(&format!("{}\n", square)).contains("1/1 right"));
+
+    // The moves go in and come out in SAN, the board shows only when asked for.
+    let mut output = Vec::new();
+    let mut bot = Bot::with_depth(1);
+    mxchess::blindfold::play(&mut bot, "e4\nKe5\nboard\n".as_bytes(), &mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("1... "));
+    assert!(output.contains("Not a legal move in SAN: Ke5"));
+    assert!(output.contains("You resign"));
+    assert!(output.contains("Looks at the board: 1"));
+}
+
+#[cfg(feature = "tui")]
+#[test]
+fn the_terminal_ui_draws_the_game() {
+    use mxchess::tui::{eval_ratio, App, Mode};
+    use ratatui::{backend::TestBackend, Terminal};
+
+    assert_eq!(eval_ratio(Score::Cp(0)), 0.5);
+    assert!(eval_ratio(Score::Cp(100)) > 0.5);
+    assert_eq!(eval_ratio(Score::Mate(-2)), 0.0);
+
+    let mut app = App::new(Mode::Play, Game::new(), None);
+    app.input = "e5".to_owned();
+    assert!(!app.submit());
+    app.input = "e4".to_owned();
+    assert!(app.submit());
+    app.search(&mut Bot::with_depth(2));
+    assert_eq!(app.game.moves().len(), 2);
+    assert!(app.score.is_some() && !app.pv.is_empty());
+
+    let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+    terminal.draw(|frame| app.draw(frame)).unwrap();
+    let screen: String = terminal
+        .backend()
+        .buffer()
+        .content()
+        .iter()
+        .map(|cell| cell.symbol())
+        .collect();
+    assert!(screen.contains("1. e4 "));
+    assert!(screen.contains("Evaluation"));
+    assert!(screen.contains("No clock"));
+}
+
+#[test]
+fn the_analysis_annotates_threats_pins_and_the_best_move() {
+    use mxchess::render::{to_svg, Annotations, Brush, Mark, SvgOptions};
+
+    // The knight on g6 attacks the undefended queen, the bishop pins the defended knight.
+    let (board, color) = Board::from_fen("4k3/8/6n1/8/1b5Q/8/3N4/4K3 w - - 0 1").unwrap();
+    let best = board.parse_move(color, "h4b4").unwrap();
+    let annotations = Annotations::from_analysis(&board, color, Some(best));
+    let mark = |from, to, brush| Mark { from, to, brush };
+    assert_eq!(
+        annotations.marks,
+        [
+            mark(Square::G6, Square::H4, Brush::Red),
+            mark(Square::D2, Square::D2, Brush::Blue),
+            mark(Square::H4, Square::B4, Brush::Green),
+        ]
+    );
+
+    let svg = to_svg(
+        &board,
+        &SvgOptions {
+            annotations,
+            ..SvgOptions::default()
+        },
+    );
+    assert_eq!(svg.matches("<circle ").count(), 1);
+    assert_eq!(svg.matches("<line ").count(), 2);
+    assert!(svg.contains("url(#arrowhead-red)"));
+}
+
+#[test]
+fn the_board_editor_sets_up_playable_positions() {
+    use mxchess::editor::{edit, Finish};
+
+    let run = |commands: &str| {
+        let mut output = Vec::new();
+        let edited = edit(Board::new(), Color::White, commands.as_bytes(), &mut output).unwrap();
+        (edited, String::from_utf8(output).unwrap())
+    };
+
+    let (edited, output) =
+        run("clear\nplay\nKe1\nke8\nRh1\npe7\n-e7\nturn b\ncastle Kq\nbogus\nanalyze\n");
+    let (board, color, finish) = edited.unwrap();
+    assert_eq!(finish, Finish::Analyze);
+    assert_eq!(color, Color::Black);
+    assert_eq!(board.to_fen(color), "4k3/8/8/8/8/8/8/4K2R b K - 0 1");
+    assert!(output.contains("Not a playable position: each side needs exactly one king"));
+    assert!(output.contains("Dropped the rights"));
+    assert!(output.contains("Unknown command"));
+
+    // Nothing is let go before it's playable, e.g. with the side that isn't to move in check.
+    let (edited, output) = run("fen 4k3/8/8/8/8/8/8/4R1K1 b - - 0 1\nturn w\nplay\n");
+    assert_eq!(edited, None);
+    assert!(output.contains("the side that isn't to move is in check"));
+    let (edited, _) = run("start\nfen nonsense\nplay\n");
+    assert_eq!(edited, Some((Board::new(), Color::White, Finish::Play)));
+    assert_eq!(run("quit\nplay\n").0, None);
+}
+
+#[test]
+fn the_sprt_matches_the_reference_llrs() {
+    let close = |a: f64, b: f64| (a - b).abs() < 1e-3;
+    let sprt = Sprt::new(0.0, 5.0);
+    assert!(close(sprt.llr(100, 200, 80), 0.5276));
+    assert!(close(sprt.llr(1000, 2000, 1000), -0.8283));
+    assert_eq!(sprt.llr(0, 0, 0), 0.0);
+    assert!(close(Sprt::new(0.0, 10.0).llr(600, 1000, 400), 10.0549));
+    assert!(close(Sprt::new(-5.0, 5.0).llr(50, 0, 100), -1.6189));
+
+    let (lower, upper) = sprt.bounds();
+    assert!(close(lower, -2.9444) && close(upper, 2.9444));
+    assert_eq!(sprt.decision(0.5276), None);
+    assert_eq!(sprt.decision(3.0), Some(true));
+    assert_eq!(sprt.decision(-3.0), Some(false));
+
+    let sprt = Sprt::parse("0/5/0.05/0.1").unwrap();
+    let (lower, upper) = sprt.bounds();
+    assert!(close(lower, -2.2513) && close(upper, 2.8904));
+    assert_eq!(Sprt::parse("0/5"), Some(Sprt::new(0.0, 5.0)));
+    for s in [
+        "5/5",
+        "5/0",
+        "0/5/0/0.05",
+        "0/5/0.5/0.05",
+        "0/5/0.05/-0.1",
+        "0/5/0.05",
+        "0/inf",
+    ] {
+        assert_eq!(Sprt::parse(s), None, "{}", s);
+    }
+}